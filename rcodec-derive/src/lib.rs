@@ -0,0 +1,106 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DataStruct, DeriveInput, Field, Fields};
+
+/// Derives an implementation of `rcodec::codec::DefaultCodec` for a struct with named fields, which
+/// encodes each field in order using the default codec for its type.
+///
+/// The struct must also support `HList` conversions (e.g. using the `HListSupport` derive).  For
+/// generic structs, the implementation requires each field type to implement `DefaultCodec`, and
+/// the struct to support `HList` conversions for those field types.
+#[proc_macro_derive(DefaultCodec)]
+pub fn default_codec_derive(input: TokenStream) -> TokenStream {
+    // Parse the input tokens into a syntax tree
+    let input = parse_macro_input!(input as DeriveInput);
+
+    // Check that the input type is a struct
+    let data_struct: DataStruct;
+    if let Data::Struct(s) = input.data {
+        data_struct = s
+    } else {
+        panic!("`DefaultCodec` may only be applied to structs")
+    }
+
+    // Check that the struct has named fields, since that's the only
+    // type we support at the moment
+    let fields: Fields;
+    if let Fields::Named(_) = data_struct.fields {
+        fields = data_struct.fields
+    } else {
+        panic!("`DefaultCodec` may only be applied to structs with named fields")
+    }
+
+    // Extract the struct name, and for a generic struct, require a default codec for each field
+    // type along with HList conversions for those types
+    let struct_name = &input.ident;
+    let mut generics = input.generics.clone();
+    if !generics.params.is_empty() {
+        let (_, ty_generics, _) = input.generics.split_for_impl();
+        let hlist_type = hlist_type(fields.iter());
+        let where_clause = generics.make_where_clause();
+        for field in fields.iter() {
+            let ty = &field.ty;
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: ::rcodec::codec::DefaultCodec));
+        }
+        where_clause.predicates.push(parse_quote!(
+            #struct_name #ty_generics: ::pl_hlist::FromHList<#hlist_type> + ::pl_hlist::ToHList<#hlist_type>
+        ));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Build the HList codec
+    let hlist_codec = hlist_codec(fields.iter());
+
+    // Build the output
+    let expanded = quote! {
+        impl #impl_generics ::rcodec::codec::DefaultCodec for #struct_name #ty_generics #where_clause {
+            fn codec() -> impl ::rcodec::codec::Codec<Value = Self> {
+                ::rcodec::codec::struct_codec::<_, Self, _>(#hlist_codec)
+            }
+        }
+    };
+
+    // Hand the output tokens back to the compiler
+    TokenStream::from(expanded)
+}
+
+/// Recursive function that builds up an HList type from the types of a series of Fields.
+fn hlist_type(mut fields: syn::punctuated::Iter<Field>) -> proc_macro2::TokenStream {
+    match fields.next() {
+        Some(field) => {
+            let ty = &field.ty;
+            let rhs = hlist_type(fields);
+            quote!(::pl_hlist::HCons<#ty, #rhs>)
+        }
+        None => quote!(::pl_hlist::HNil),
+    }
+}
+
+/// Recursive function that builds up an HList codec using the default codecs for the types from a
+/// series of Fields, with each field codec wrapped in a context containing the field name.
+fn hlist_codec(mut fields: syn::punctuated::Iter<Field>) -> proc_macro2::TokenStream {
+    match fields.next() {
+        Some(field) => {
+            let name = field.ident.as_ref().unwrap().to_string();
+            let ty = &field.ty;
+            let rhs = hlist_codec(fields);
+            quote!(::rcodec::codec::hlist_prepend_codec(
+                ::rcodec::codec::with_context(
+                    #name,
+                    <#ty as ::rcodec::codec::DefaultCodec>::codec()
+                ),
+                #rhs
+            ))
+        }
+        None => quote!(::rcodec::codec::hnil_codec()),
+    }
+}
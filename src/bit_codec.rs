@@ -0,0 +1,947 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+//! Codecs that operate at bit granularity, for packed formats whose fields do not occupy whole
+//! bytes.
+
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+use num_traits::{FromPrimitive, PrimInt, Signed, ToPrimitive, Unsigned};
+
+use crate::bit_vector;
+use crate::bit_vector::{BitVector, PadSide};
+use crate::byte_vector;
+use crate::byte_vector::ByteVector;
+use crate::codec::{unsigned_to_u64, Codec, DecodeResult, DecoderResult, EncodeResult, SizeBound};
+use crate::error::{lazy_description, Error};
+
+/// Implements encoding and decoding of values of type `Value` at bit granularity.
+pub trait BitCodec {
+    /// The value type.
+    type Value;
+
+    /// Attempts to encode a value of type `Value` into a `BitVector`.
+    fn encode(&self, value: &Self::Value) -> Result<BitVector, Error>;
+
+    /// Attempts to decode a value of type `Value` from the given `BitVector`.
+    fn decode(&self, bits: &BitVector) -> BitDecodeResult<Self::Value>;
+
+    /// Returns bounds on the number of bits produced by `encode` (and consumed by `decode`).
+    ///
+    /// The default implementation returns `SizeBound::unknown()`.
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::unknown()
+    }
+}
+
+/// A result type returned by `BitCodec::decode`.
+pub type BitDecodeResult<T> = Result<BitDecoderResult<T>, Error>;
+
+/// A result type, consisting of a decoded value and any unconsumed bits, returned by
+/// `BitCodec::decode`.
+#[derive(Debug)]
+pub struct BitDecoderResult<T> {
+    /// The decoded value.
+    pub value: T,
+
+    /// The bits that were not consumed by the decoder.
+    pub remainder: BitVector,
+}
+
+// Automatically provides implementation of `BitCodec` trait for all `Box<BitCodec>`.
+impl<C: BitCodec + ?Sized> BitCodec for Box<C> {
+    type Value = C::Value;
+
+    fn size_bound(&self) -> SizeBound {
+        (**self).size_bound()
+    }
+
+    fn encode(&self, value: &Self::Value) -> Result<BitVector, Error> {
+        (**self).encode(value)
+    }
+
+    fn decode(&self, bits: &BitVector) -> BitDecodeResult<Self::Value> {
+        (**self).decode(bits)
+    }
+}
+
+//
+// Tuple codecs
+//
+
+// Provides implementations of the `BitCodec` trait for tuples of bit codecs, where each element is
+// encoded/decoded in turn.
+macro_rules! tuple_bit_codec_impl {
+    { $($type:ident $idx:tt $v:ident),+ } => {
+        impl<$($type: BitCodec),+> BitCodec for ($($type,)+) {
+            type Value = ($($type::Value,)+);
+
+            fn size_bound(&self) -> SizeBound {
+                SizeBound::exact(0) $(+ self.$idx.size_bound())+
+            }
+
+            fn encode(&self, value: &Self::Value) -> Result<BitVector, Error> {
+                let mut bits = bit_vector::empty();
+                $(
+                    bits = bit_vector::append(&bits, &self.$idx.encode(&value.$idx)?)?;
+                )+
+                Ok(bits)
+            }
+
+            fn decode(&self, bits: &BitVector) -> BitDecodeResult<Self::Value> {
+                let remainder = bits.clone();
+                $(
+                    let decoded = self.$idx.decode(&remainder)?;
+                    let $v = decoded.value;
+                    let remainder = decoded.remainder;
+                )+
+                Ok(BitDecoderResult { value: ($($v,)+), remainder })
+            }
+        }
+    };
+}
+
+tuple_bit_codec_impl! { A 0 a, B 1 b }
+tuple_bit_codec_impl! { A 0 a, B 1 b, C 2 c }
+tuple_bit_codec_impl! { A 0 a, B 1 b, C 2 c, D 3 d }
+tuple_bit_codec_impl! { A 0 a, B 1 b, C 2 c, D 3 d, E 4 e }
+tuple_bit_codec_impl! { A 0 a, B 1 b, C 2 c, D 3 d, E 4 e, F 5 f }
+tuple_bit_codec_impl! { A 0 a, B 1 b, C 2 c, D 3 d, E 4 e, F 5 f, G 6 g }
+tuple_bit_codec_impl! { A 0 a, B 1 b, C 2 c, D 3 d, E 4 e, F 5 f, G 6 g, H 7 h }
+
+//
+// Bit ordering
+//
+
+/// Bit ordering used when encoding/decoding multi-bit values, and when packing bits into bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitOrdering {
+    /// Most significant bit first (as in network protocol headers).
+    MsbFirst,
+
+    /// Least significant bit first (as in DEFLATE and many radio protocols).
+    LsbFirst,
+}
+
+impl BitOrdering {
+    /// Converts the low `bits` bits of `raw` between this ordering and most significant bit first.
+    fn reorder(self, raw: u64, bits: usize) -> u64 {
+        match self {
+            BitOrdering::MsbFirst => raw,
+            BitOrdering::LsbFirst => raw.reverse_bits() >> (64 - bits),
+        }
+    }
+
+    /// Converts whole bytes between this ordering and most significant bit first.
+    fn reorder_bytes(self, bytes: &mut [u8]) {
+        if self == BitOrdering::LsbFirst {
+            bytes.iter_mut().for_each(|b| *b = b.reverse_bits());
+        }
+    }
+}
+
+//
+// Integral codecs
+//
+
+/// Codec for unsigned integers that occupy `bits` bits, most significant bit first (e.g. the 4-bit
+/// version and 13-bit fragment offset fields of an IPv4 header).
+///
+///   - Encodes the low `bits` bits of the value; fails if the value does not fit in `bits` bits.
+///   - Decodes by taking `bits` bits and zero-extending them to the value type.
+///
+/// Panics if `bits` is zero or exceeds the width of the value type.
+#[inline(always)]
+pub fn uint_bits<T>(bits: usize) -> impl BitCodec<Value = T>
+where
+    T: PrimInt + Unsigned + FromPrimitive + ToPrimitive,
+{
+    uint_bits_with(bits, BitOrdering::MsbFirst)
+}
+
+/// Codec for unsigned integers that occupy `bits` bits, with the given bit ordering.  See
+/// `uint_bits` for details.
+///
+/// Panics if `bits` is zero or exceeds the width of the value type.
+#[inline(always)]
+pub fn uint_bits_with<T>(bits: usize, ordering: BitOrdering) -> impl BitCodec<Value = T>
+where
+    T: PrimInt + Unsigned + FromPrimitive + ToPrimitive,
+{
+    assert!(
+        bits > 0 && bits <= size_of::<T>() * 8,
+        "Bit width must be between 1 and the width of the value type"
+    );
+    UIntBitsCodec {
+        bits,
+        ordering,
+        _marker: PhantomData::<T>,
+    }
+}
+
+struct UIntBitsCodec<T> {
+    bits: usize,
+    ordering: BitOrdering,
+    _marker: PhantomData<T>,
+}
+
+impl<T> BitCodec for UIntBitsCodec<T>
+where
+    T: PrimInt + Unsigned + FromPrimitive + ToPrimitive,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(self.bits as u64)
+    }
+
+    fn encode(&self, value: &T) -> Result<BitVector, Error> {
+        let raw = unsigned_to_u64(value)?;
+        if self.bits < 64 && raw >> self.bits != 0 {
+            return Err(Error::new(lazy_description(|| {
+                format!("Value {} does not fit in {} bits", raw, self.bits)
+            })));
+        }
+        Ok(bit_vector::from_u64(
+            self.ordering.reorder(raw, self.bits),
+            self.bits,
+        ))
+    }
+
+    fn decode(&self, bits: &BitVector) -> BitDecodeResult<T> {
+        let raw = self
+            .ordering
+            .reorder(bits.get_bits(0, self.bits)?, self.bits);
+        bits.drop(self.bits).map(|remainder| BitDecoderResult {
+            value: T::from_u64(raw).unwrap(),
+            remainder,
+        })
+    }
+}
+
+/// Codec for signed integers stored in two's complement form in `bits` bits, most significant bit
+/// first (e.g. the signed deltas of packed audio samples).
+///
+///   - Encodes the low `bits` bits of the value; fails if the value does not fit in `bits` bits.
+///   - Decodes by taking `bits` bits and sign-extending them to the value type.
+///
+/// Panics if `bits` is zero or exceeds the width of the value type.
+#[inline(always)]
+pub fn int_bits<T>(bits: usize) -> impl BitCodec<Value = T>
+where
+    T: PrimInt + Signed + BitField,
+{
+    int_bits_with(bits, BitOrdering::MsbFirst)
+}
+
+/// Codec for signed integers stored in two's complement form in `bits` bits, with the given bit
+/// ordering.  See `int_bits` for details.
+///
+/// Panics if `bits` is zero or exceeds the width of the value type.
+#[inline(always)]
+pub fn int_bits_with<T>(bits: usize, ordering: BitOrdering) -> impl BitCodec<Value = T>
+where
+    T: PrimInt + Signed + BitField,
+{
+    bit_field_with(bits, ordering)
+}
+
+/// Types that can be stored in a field of an arbitrary number of bits, as used by `bit_field` and
+/// by the fields of structs defined using `bitfield_struct!`.
+///
+/// Unsigned integers are zero-extended, signed integers are stored in two's complement form and
+/// sign-extended, and `bool` values occupy a single bit.
+pub trait BitField: Sized {
+    /// The maximum number of bits that a field of this type may occupy.
+    const MAX_BITS: usize;
+
+    /// Returns the raw bits representing this value in a field of `bits` bits, or an error if the
+    /// value does not fit.
+    fn to_bits(&self, bits: usize) -> Result<u64, Error>;
+
+    /// Returns the value represented by the raw bits of a field of `bits` bits.
+    fn from_bits(raw: u64, bits: usize) -> Self;
+}
+
+// Generates a `BitField` implementation for an unsigned integer type.
+macro_rules! unsigned_bit_field_impl {
+    { $($t:ty),+ } => {
+        $(
+            impl BitField for $t {
+                const MAX_BITS: usize = size_of::<$t>() * 8;
+
+                fn to_bits(&self, bits: usize) -> Result<u64, Error> {
+                    let raw = *self as u64;
+                    if bits < 64 && raw >> bits != 0 {
+                        return Err(Error::new(lazy_description(|| format!(
+                            "Value {} does not fit in {} bits",
+                            raw, bits
+                        ))));
+                    }
+                    Ok(raw)
+                }
+
+                fn from_bits(raw: u64, _bits: usize) -> $t {
+                    raw as $t
+                }
+            }
+        )+
+    };
+}
+
+// Generates a `BitField` implementation for a signed integer type.
+macro_rules! signed_bit_field_impl {
+    { $($t:ty),+ } => {
+        $(
+            impl BitField for $t {
+                const MAX_BITS: usize = size_of::<$t>() * 8;
+
+                fn to_bits(&self, bits: usize) -> Result<u64, Error> {
+                    let value = *self as i64;
+                    let limit = 1i128 << (bits - 1);
+                    if (value as i128) < -limit || (value as i128) >= limit {
+                        return Err(Error::new(lazy_description(|| format!(
+                            "Value {} does not fit in {} bits",
+                            value, bits
+                        ))));
+                    }
+                    Ok(((value as u64) << (64 - bits)) >> (64 - bits))
+                }
+
+                fn from_bits(raw: u64, bits: usize) -> $t {
+                    (((raw << (64 - bits)) as i64) >> (64 - bits)) as $t
+                }
+            }
+        )+
+    };
+}
+
+unsigned_bit_field_impl!(u8, u16, u32, u64);
+signed_bit_field_impl!(i8, i16, i32, i64);
+
+impl BitField for bool {
+    const MAX_BITS: usize = 1;
+
+    fn to_bits(&self, _bits: usize) -> Result<u64, Error> {
+        Ok(*self as u64)
+    }
+
+    fn from_bits(raw: u64, _bits: usize) -> bool {
+        raw != 0
+    }
+}
+
+/// Codec for values of any `BitField` type that occupy `bits` bits, most significant bit first.
+///
+/// Panics if `bits` is zero or exceeds `T::MAX_BITS`.
+#[inline(always)]
+pub fn bit_field<T: BitField>(bits: usize) -> impl BitCodec<Value = T> {
+    bit_field_with(bits, BitOrdering::MsbFirst)
+}
+
+/// Codec for values of any `BitField` type that occupy `bits` bits, with the given bit ordering.
+///
+/// Panics if `bits` is zero or exceeds `T::MAX_BITS`.
+#[inline(always)]
+pub fn bit_field_with<T: BitField>(bits: usize, ordering: BitOrdering) -> impl BitCodec<Value = T> {
+    assert!(
+        bits > 0 && bits <= T::MAX_BITS,
+        "Bit width must be between 1 and the width of the value type"
+    );
+    BitFieldCodec {
+        bits,
+        ordering,
+        _marker: PhantomData::<T>,
+    }
+}
+
+struct BitFieldCodec<T> {
+    bits: usize,
+    ordering: BitOrdering,
+    _marker: PhantomData<T>,
+}
+
+impl<T: BitField> BitCodec for BitFieldCodec<T> {
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(self.bits as u64)
+    }
+
+    fn encode(&self, value: &T) -> Result<BitVector, Error> {
+        value
+            .to_bits(self.bits)
+            .map(|raw| bit_vector::from_u64(self.ordering.reorder(raw, self.bits), self.bits))
+    }
+
+    fn decode(&self, bits: &BitVector) -> BitDecodeResult<T> {
+        let raw = self
+            .ordering
+            .reorder(bits.get_bits(0, self.bits)?, self.bits);
+        bits.drop(self.bits).map(|remainder| BitDecoderResult {
+            value: T::from_bits(raw, self.bits),
+            remainder,
+        })
+    }
+}
+
+//
+// Ignore and constant codecs
+//
+
+/// Codec that encodes `len` low bits and decodes by discarding `len` bits, for reserved regions
+/// within packed structures.
+#[inline(always)]
+pub fn ignore_bits(len: usize) -> impl BitCodec<Value = ()> {
+    IgnoreBitsCodec { len }
+}
+
+struct IgnoreBitsCodec {
+    len: usize,
+}
+
+impl BitCodec for IgnoreBitsCodec {
+    type Value = ();
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(self.len as u64)
+    }
+
+    fn encode(&self, _value: &()) -> Result<BitVector, Error> {
+        Ok(bit_vector::fill(false, self.len))
+    }
+
+    fn decode(&self, bits: &BitVector) -> BitDecodeResult<()> {
+        bits.drop(self.len).map(|remainder| BitDecoderResult {
+            value: (),
+            remainder,
+        })
+    }
+}
+
+/// Codec that always encodes the given bits, and decodes by returning a unit result if the actual
+/// bits match the given bits or an error otherwise.
+///
+/// Panics if the given bits cannot be read.
+#[inline(always)]
+pub fn constant_bits(bits: BitVector) -> impl BitCodec<Value = ()> {
+    // Keep a plain copy of the bits so that the codec can be shared between threads
+    ConstantBitsCodec {
+        bytes: bits.to_vec().expect("Failed to read constant bits"),
+        len: bits.length(),
+    }
+}
+
+struct ConstantBitsCodec {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl ConstantBitsCodec {
+    fn bits(&self) -> BitVector {
+        bit_vector::from_vec_bits(self.bytes.clone(), self.len)
+    }
+}
+
+impl BitCodec for ConstantBitsCodec {
+    type Value = ();
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(self.len as u64)
+    }
+
+    fn encode(&self, _value: &()) -> Result<BitVector, Error> {
+        Ok(self.bits())
+    }
+
+    fn decode(&self, bits: &BitVector) -> BitDecodeResult<()> {
+        let len = self.len;
+        let taken = bits.take(len)?;
+        if taken.to_vec()? != self.bytes {
+            return Err(Error::new(lazy_description(|| {
+                format!(
+                    "Expected constant bits {:?} but got {:?}",
+                    self.bits(),
+                    taken
+                )
+            })));
+        }
+        bits.drop(len).map(|remainder| BitDecoderResult {
+            value: (),
+            remainder,
+        })
+    }
+}
+
+/// Codec that always encodes the given pattern, and decodes by returning a unit result if the
+/// actual bits match the pattern at each position where the corresponding bit of `mask` is set, or
+/// an error otherwise.  Bits outside of the mask are ignored when decoding, which allows for
+/// matching sync words that share some bits with neighboring fields (e.g. MPEG audio frame sync).
+///
+/// Panics if `pattern` and `mask` differ in length, or if either cannot be read.
+#[inline(always)]
+pub fn masked_constant_bits(pattern: BitVector, mask: BitVector) -> impl BitCodec<Value = ()> {
+    assert!(
+        pattern.length() == mask.length(),
+        "Pattern and mask must have the same length"
+    );
+    // Precompute the left-aligned bytes once rather than on every decode
+    MaskedConstantBitsCodec {
+        pattern: pattern.to_vec().expect("Failed to read pattern bits"),
+        mask: mask.to_vec().expect("Failed to read mask bits"),
+        len: pattern.length(),
+    }
+}
+
+struct MaskedConstantBitsCodec {
+    pattern: Vec<u8>,
+    mask: Vec<u8>,
+    len: usize,
+}
+
+impl BitCodec for MaskedConstantBitsCodec {
+    type Value = ();
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(self.len as u64)
+    }
+
+    fn encode(&self, _value: &()) -> Result<BitVector, Error> {
+        Ok(bit_vector::from_vec_bits(self.pattern.clone(), self.len))
+    }
+
+    fn decode(&self, bits: &BitVector) -> BitDecodeResult<()> {
+        let len = self.len;
+        let taken = bits.take(len)?;
+        let actual = taken.to_vec()?;
+        let matches = actual
+            .iter()
+            .zip(self.pattern.iter().zip(self.mask.iter()))
+            .all(|(a, (p, m))| (a ^ p) & m == 0);
+        if !matches {
+            return Err(Error::new(lazy_description(|| {
+                format!(
+                    "Expected bits {:?} under mask {:?} but got {:?}",
+                    bit_vector::from_vec_bits(self.pattern.clone(), len),
+                    bit_vector::from_vec_bits(self.mask.clone(), len),
+                    taken
+                )
+            })));
+        }
+        bits.drop(len).map(|remainder| BitDecoderResult {
+            value: (),
+            remainder,
+        })
+    }
+}
+
+//
+// Byte alignment
+//
+
+/// Codec that adapts the given bit codec for use within byte-oriented codecs (such as those
+/// produced by `hcodec!`), by padding its encoding to a whole number of bytes.
+///
+///   - Encodes using `bit_codec`, followed by low bits up to the next byte boundary.
+///   - Decodes using `bit_codec`, and then discards any remaining bits up to the next byte
+///     boundary.
+#[inline(always)]
+pub fn byte_aligned<T, C>(bit_codec: C) -> impl Codec<Value = T>
+where
+    C: BitCodec<Value = T>,
+{
+    byte_aligned_with(bit_codec, BitOrdering::MsbFirst)
+}
+
+/// Codec that adapts the given bit codec for use within byte-oriented codecs, where bits are
+/// packed into each byte with the given ordering.  See `byte_aligned` for details.
+///
+/// With `BitOrdering::LsbFirst`, the first bit of the encoding occupies the least significant bit
+/// of the first byte, and padding occupies the high bits of the final byte.
+#[inline(always)]
+pub fn byte_aligned_with<T, C>(bit_codec: C, ordering: BitOrdering) -> impl Codec<Value = T>
+where
+    C: BitCodec<Value = T>,
+{
+    ByteAlignedCodec {
+        bit_codec,
+        ordering,
+    }
+}
+
+struct ByteAlignedCodec<C> {
+    bit_codec: C,
+    ordering: BitOrdering,
+}
+
+impl<T, C> Codec for ByteAlignedCodec<C>
+where
+    C: BitCodec<Value = T>,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        let bits = self.bit_codec.size_bound();
+        SizeBound {
+            lower: bits.lower.div_ceil(8),
+            upper: bits.upper.map(|upper| upper.div_ceil(8)),
+        }
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        let bits = self.bit_codec.encode(value)?;
+        match self.ordering {
+            BitOrdering::MsbFirst => bits.to_byte_vector(PadSide::Right),
+            BitOrdering::LsbFirst => {
+                let mut bytes = bits.to_vec()?;
+                self.ordering.reorder_bytes(&mut bytes);
+                Ok(byte_vector::from_vec(bytes))
+            }
+        }
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        let bits = match self.ordering {
+            BitOrdering::MsbFirst => bv.bits(),
+            // The bits of each byte are reordered as the bit codec reads them
+            BitOrdering::LsbFirst => bit_vector::from_byte_vector_lsb_first(bv.clone()),
+        };
+        let decoded = self.bit_codec.decode(&bits)?;
+        let consumed = bits.length() - decoded.remainder.length();
+        bv.drop(consumed.div_ceil(8))
+            .map(|remainder| DecoderResult {
+                value: decoded.value,
+                remainder,
+            })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        // Only copy the bytes that the bit codec is able to consume
+        let len = match self.size_bound().upper {
+            Some(upper) => input.len().min(upper as usize),
+            None => input.len(),
+        };
+        let decoded = self.decode(&byte_vector::from_slice_copy(&input[..len]))?;
+        Ok((decoded.value, len - decoded.remainder.length()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::*;
+    use crate::testing::assert_round_trip;
+    use pl_hlist::*;
+
+    fn bits(digits: &str) -> BitVector {
+        bit_vector::from_bin(digits).unwrap()
+    }
+
+    #[test]
+    fn a_uint_bits_codec_should_round_trip() {
+        let codec = uint_bits::<u16>(13);
+        let encoded = codec.encode(&0x1234).unwrap();
+        assert_eq!(encoded, bits("1_0010_0011_0100"));
+        let decoded = codec.decode(&encoded).unwrap();
+        assert_eq!(decoded.value, 0x1234);
+        assert_eq!(decoded.remainder, bit_vector::empty());
+        assert_eq!(codec.size_bound(), SizeBound::exact(13));
+
+        let codec = uint_bits::<u64>(64);
+        let decoded = codec.decode(&codec.encode(&u64::MAX).unwrap()).unwrap();
+        assert_eq!(decoded.value, u64::MAX);
+    }
+
+    #[test]
+    fn a_uint_bits_codec_should_leave_remaining_bits() {
+        let decoded = uint_bits::<u8>(3).decode(&bits("10110")).unwrap();
+        assert_eq!(decoded.value, 5);
+        assert_eq!(decoded.remainder, bits("10"));
+    }
+
+    #[test]
+    fn a_uint_bits_codec_should_fail_on_out_of_range_values() {
+        assert_eq!(
+            uint_bits::<u8>(3).encode(&8).unwrap_err().message(),
+            "Value 8 does not fit in 3 bits"
+        );
+        assert!(uint_bits::<u128>(8).encode(&(1u128 << 70)).is_err());
+        assert_eq!(
+            uint_bits::<u8>(3)
+                .decode(&bits("10"))
+                .unwrap_err()
+                .message(),
+            "Requested read offset of 0 and length 3 bits exceeds vector length of 2"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit width must be between 1 and the width of the value type")]
+    fn a_uint_bits_codec_should_reject_widths_larger_than_the_value_type() {
+        uint_bits::<u8>(9);
+    }
+
+    #[test]
+    fn an_int_bits_codec_should_sign_extend() {
+        let codec = int_bits::<i16>(12);
+        let encoded = codec.encode(&-1000).unwrap();
+        assert_eq!(encoded, bits("1100_0001_1000"));
+        assert_eq!(codec.decode(&encoded).unwrap().value, -1000);
+        assert_eq!(codec.decode(&bits("0111_1111_1111")).unwrap().value, 2047);
+        assert_eq!(codec.size_bound(), SizeBound::exact(12));
+
+        let decoded = int_bits::<i8>(3).decode(&bits("10011")).unwrap();
+        assert_eq!(decoded.value, -4);
+        assert_eq!(decoded.remainder, bits("11"));
+
+        let codec = int_bits::<i64>(64);
+        for value in [i64::MIN, -1, 0, i64::MAX] {
+            assert_eq!(
+                codec.decode(&codec.encode(&value).unwrap()).unwrap().value,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn an_int_bits_codec_should_fail_on_out_of_range_values() {
+        assert_eq!(
+            int_bits::<i32>(4).encode(&8).unwrap_err().message(),
+            "Value 8 does not fit in 4 bits"
+        );
+        assert_eq!(
+            int_bits::<i32>(4).encode(&-9).unwrap_err().message(),
+            "Value -9 does not fit in 4 bits"
+        );
+        assert!(int_bits::<i32>(4).encode(&-8).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit width must be between 1 and the width of the value type")]
+    fn an_int_bits_codec_should_reject_widths_larger_than_the_value_type() {
+        int_bits::<i16>(17);
+    }
+
+    #[test]
+    fn a_tuple_of_bit_codecs_should_round_trip() {
+        // IPv4 version/IHL, followed by flags and fragment offset
+        let codec = (
+            uint_bits::<u8>(4),
+            uint_bits::<u8>(4),
+            uint_bits::<u8>(3),
+            uint_bits::<u16>(13),
+        );
+        let value = (4, 5, 0b010, 0x0123);
+        let encoded = codec.encode(&value).unwrap();
+        assert_eq!(encoded, bit_vector::from_vec(vec![0x45, 0x41, 0x23]));
+        let decoded = codec.decode(&encoded).unwrap();
+        assert_eq!(decoded.value, value);
+        assert_eq!(decoded.remainder, bit_vector::empty());
+        assert_eq!(codec.size_bound(), SizeBound::exact(24));
+    }
+
+    #[test]
+    fn a_bit_field_codec_should_handle_each_field_type() {
+        let codec = (
+            bit_field::<i8>(4),
+            bit_field::<bool>(1),
+            bit_field::<u16>(11),
+        );
+        let value = (-3, true, 0x123);
+        let encoded = codec.encode(&value).unwrap();
+        assert_eq!(encoded, bits("1101_1001_0010_0011"));
+        assert_eq!(codec.decode(&encoded).unwrap().value, value);
+
+        assert_eq!(
+            bit_field::<i8>(4).encode(&8).unwrap_err().message(),
+            "Value 8 does not fit in 4 bits"
+        );
+        assert!(bit_field::<i8>(4).encode(&-9).is_err());
+        assert_eq!(
+            bit_field::<i64>(64).encode(&i64::MIN).unwrap(),
+            bit_vector::from_u64(1 << 63, 64)
+        );
+    }
+
+    bitfield_struct!(
+        /// The first two bytes of a TCP header's data offset and flags.
+        pub(crate) TestTcpFlags,
+        2,
+        data_offset: u8: 4,
+        reserved: u8: 3,
+        ns: bool: 1,
+        flags: u8: 8,
+    );
+
+    bitfield_struct!(TestSignedFields, 1, delta: i8: 5, scale: u8: 3);
+
+    #[test]
+    fn a_bitfield_struct_should_round_trip() {
+        let value = TestTcpFlags {
+            data_offset: 5,
+            reserved: 0,
+            ns: true,
+            flags: 0x18,
+        };
+        assert_round_trip(
+            TestTcpFlags::codec(),
+            &value,
+            &Some(byte_vector!(0x51, 0x18)),
+        );
+        assert_eq!(TestTcpFlags::codec().size_bound(), SizeBound::exact(2));
+
+        assert_round_trip(
+            TestSignedFields::codec(),
+            &TestSignedFields {
+                delta: -2,
+                scale: 5,
+            },
+            &Some(byte_vector!(0b1111_0101)),
+        );
+        assert_eq!(
+            TestSignedFields::codec()
+                .encode(&TestSignedFields {
+                    delta: 16,
+                    scale: 0
+                })
+                .unwrap_err()
+                .message(),
+            "delta: Value 16 does not fit in 5 bits"
+        );
+    }
+
+    #[test]
+    fn bit_codecs_should_support_lsb_first_ordering() {
+        let codec = uint_bits_with::<u8>(3, BitOrdering::LsbFirst);
+        let encoded = codec.encode(&0b110).unwrap();
+        assert_eq!(encoded, bits("011"));
+        assert_eq!(codec.decode(&encoded).unwrap().value, 0b110);
+
+        let codec = bit_field_with::<i8>(4, BitOrdering::LsbFirst);
+        assert_eq!(codec.encode(&-2).unwrap(), bits("0111"));
+        assert_eq!(codec.decode(&bits("0111")).unwrap().value, -2);
+    }
+
+    #[test]
+    fn a_byte_aligned_codec_should_support_lsb_first_packing() {
+        // DEFLATE block header: BFINAL (1 bit) followed by BTYPE (2 bits), packed LSB-first
+        let codec = || {
+            byte_aligned_with(
+                (
+                    uint_bits_with::<u8>(1, BitOrdering::LsbFirst),
+                    uint_bits_with::<u8>(2, BitOrdering::LsbFirst),
+                ),
+                BitOrdering::LsbFirst,
+            )
+        };
+        assert_round_trip(codec(), &(1, 2), &Some(byte_vector!(0b0000_0101)));
+        assert_round_trip(codec(), &(0, 1), &Some(byte_vector!(0b0000_0010)));
+
+        let decoded = codec().decode(&byte_vector!(0x03, 0xff)).unwrap();
+        assert_eq!(decoded.value, (1, 1));
+        assert_eq!(decoded.remainder, byte_vector!(0xff));
+    }
+
+    #[test]
+    fn an_ignore_bits_codec_should_skip_reserved_bits() {
+        let codec = (uint_bits::<u8>(2), ignore_bits(3), uint_bits::<u8>(3));
+        let encoded = codec.encode(&(3, (), 5)).unwrap();
+        assert_eq!(encoded, bits("1100_0101"));
+        let decoded = codec.decode(&bits("1111_1101")).unwrap();
+        assert_eq!(decoded.value, (3, (), 5));
+        assert!(ignore_bits(4).decode(&bits("101")).is_err());
+    }
+
+    #[test]
+    fn a_constant_bits_codec_should_match_its_pattern() {
+        let codec = constant_bits(bits("1011"));
+        assert_eq!(codec.encode(&()).unwrap(), bits("1011"));
+        assert_eq!(codec.size_bound(), SizeBound::exact(4));
+        let decoded = codec.decode(&bits("1011_01")).unwrap();
+        assert_eq!(decoded.remainder, bits("01"));
+        assert_eq!(
+            codec.decode(&bits("1001_01")).unwrap_err().message(),
+            "Expected constant bits 1011 but got 1001"
+        );
+        assert!(codec.decode(&bits("101")).is_err());
+    }
+
+    #[test]
+    fn a_constant_bits_codec_should_be_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        assert_send_sync(&constant_bits(bits("1011")));
+    }
+
+    #[test]
+    fn a_masked_constant_bits_codec_should_match_only_masked_bits() {
+        // MPEG audio frame sync (11 bits) followed by a variable version ID (2 bits)
+        let codec = masked_constant_bits(bits("1111_1111_1110_0"), bits("1111_1111_1110_0"));
+        assert_eq!(codec.encode(&()).unwrap(), bits("1111_1111_1110_0"));
+        assert_eq!(codec.size_bound(), SizeBound::exact(13));
+        for input in ["1111_1111_1110_0101", "1111_1111_1111_1"] {
+            let decoded = codec.decode(&bits(input)).unwrap();
+            assert_eq!(decoded.remainder, bits(input).drop(13).unwrap());
+        }
+        assert_eq!(
+            codec
+                .decode(&bits("1111_1111_1010_0"))
+                .unwrap_err()
+                .message(),
+            "Expected bits 1111111111100 under mask 1111111111100 but got 1111111110100"
+        );
+        assert!(codec.decode(&bits("1111")).is_err());
+    }
+
+    #[test]
+    fn a_masked_constant_bits_codec_should_be_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        assert_send_sync(&masked_constant_bits(bits("1110"), bits("1100")));
+    }
+
+    #[test]
+    #[should_panic(expected = "Pattern and mask must have the same length")]
+    fn a_masked_constant_bits_codec_should_reject_mismatched_masks() {
+        masked_constant_bits(bits("101"), bits("11"));
+    }
+
+    #[test]
+    fn a_byte_aligned_codec_should_pad_to_a_byte_boundary() {
+        let codec = || byte_aligned(uint_bits::<u8>(3));
+        assert_round_trip(codec(), &5, &Some(byte_vector!(0b1010_0000)));
+        assert_eq!(codec().size_bound(), SizeBound::exact(1));
+
+        // Padding bits are ignored when decoding
+        let decoded = codec().decode(&byte_vector!(0b1011_1111, 7)).unwrap();
+        assert_eq!(decoded.value, 5);
+        assert_eq!(decoded.remainder, byte_vector!(7));
+    }
+
+    #[test]
+    fn a_byte_aligned_codec_should_be_usable_within_hcodec() {
+        let codec = || {
+            hcodec!(
+                { "type"  => uint8 } ::
+                { "flags" => byte_aligned((uint_bits::<u8>(3), uint_bits::<u16>(13))) } ::
+                { "len"   => uint8 }
+            )
+        };
+        assert_round_trip(
+            codec(),
+            &hlist!(1, (0b101, 0x0123), 9),
+            &Some(byte_vector!(1, 0xa1, 0x23, 9)),
+        );
+        assert_eq!(
+            codec()
+                .decode(&byte_vector!(1, 0xa1))
+                .unwrap_err()
+                .message(),
+            "flags: Requested read offset of 0 and length 13 bits exceeds vector length of 5"
+        );
+    }
+}
@@ -0,0 +1,541 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+use std::fmt::{Debug, Formatter};
+
+use crate::byte_vector;
+use crate::byte_vector::ByteVector;
+use crate::error::{lazy_description, Error};
+
+/// An immutable vector of bits, ordered from the most significant bit of each byte to the least.
+///
+/// The bits are backed by a `ByteVector`, so taking and dropping bits shares the underlying
+/// storage rather than copying it, as does appending two vectors that meet at a byte boundary.
+#[derive(Clone)]
+pub struct BitVector {
+    /// The bytes containing the bits of this vector.
+    bytes: ByteVector,
+
+    /// The number of leading bits in `bytes` that precede this vector's contents (always less
+    /// than 8).
+    offset: usize,
+
+    /// The length, in bits.
+    len: usize,
+
+    /// Whether the bits of each byte in `bytes` are stored least significant bit first, in which
+    /// case they are reversed as they are read.
+    lsb_first: bool,
+}
+
+impl BitVector {
+    /// Returns the length, in bits.
+    pub fn length(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the bit at `index`, or an error if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Result<bool, Error> {
+        if index >= self.len {
+            return Err(Error::insufficient_data(lazy_description(|| {
+                format!(
+                    "Requested bit index of {} exceeds vector length of {}",
+                    index, self.len
+                )
+            })));
+        }
+        let pos = self.offset + index;
+        let byte = self.reorder(self.bytes.get_u8(pos / 8)?);
+        Ok((byte >> (7 - pos % 8)) & 1 == 1)
+    }
+
+    /// Returns the `len` bits at `offset` as an unsigned integer (most significant bit first), or
+    /// an error if insufficient data is available.  At most 64 bits may be read at a time.
+    pub fn get_bits(&self, offset: usize, len: usize) -> Result<u64, Error> {
+        if len > 64 {
+            return Err(Error::new(lazy_description(|| {
+                format!("Requested length of {} bits exceeds maximum of 64", len)
+            })));
+        }
+        if len > self.len || offset > self.len - len {
+            return Err(Error::insufficient_data(lazy_description(|| {
+                format!(
+                    "Requested read offset of {} and length {} bits exceeds vector length of {}",
+                    offset, len, self.len
+                )
+            })));
+        }
+        if len == 0 {
+            return Ok(0);
+        }
+
+        // Read the (at most 9) bytes that contain the requested bits, then shift off the bits
+        // that follow them
+        let first = self.offset + offset;
+        let byte_start = first / 8;
+        let byte_count = (first % 8 + len).div_ceil(8);
+        let mut buf = [0u8; 9];
+        self.bytes
+            .read(&mut buf[..byte_count], byte_start, byte_count)?;
+        let raw = buf[..byte_count]
+            .iter()
+            .fold(0u128, |acc, byte| (acc << 8) | self.reorder(*byte) as u128);
+        let trailing = byte_count * 8 - first % 8 - len;
+        Ok(((raw >> trailing) & low_mask(len)) as u64)
+    }
+
+    /// Returns a new bit vector containing exactly `len` bits from the start of this bit vector,
+    /// or an error if insufficient data is available.
+    pub fn take(&self, len: usize) -> Result<BitVector, Error> {
+        if len > self.len {
+            return Err(Error::insufficient_data(lazy_description(|| {
+                format!(
+                    "Requested length of {} bits exceeds vector length of {}",
+                    len, self.len
+                )
+            })));
+        }
+        self.bytes
+            .take((self.offset + len).div_ceil(8))
+            .map(|bytes| BitVector {
+                bytes,
+                offset: self.offset,
+                len,
+                lsb_first: self.lsb_first,
+            })
+    }
+
+    /// Returns a new bit vector containing all but the first `len` bits of this bit vector, or an
+    /// error if dropping `len` bits would overrun the end of this bit vector.
+    pub fn drop(&self, len: usize) -> Result<BitVector, Error> {
+        if len > self.len {
+            return Err(Error::insufficient_data(lazy_description(|| {
+                format!(
+                    "Requested length of {} bits exceeds vector length of {}",
+                    len, self.len
+                )
+            })));
+        }
+        let start = self.offset + len;
+        self.bytes.drop(start / 8).map(|bytes| BitVector {
+            bytes,
+            offset: start % 8,
+            len: self.len - len,
+            lsb_first: self.lsb_first,
+        })
+    }
+
+    /// Returns the contents of this bit vector as a byte vector, with low bits added on the given
+    /// side to make up a whole number of bytes.
+    ///
+    /// If this vector starts and ends on byte boundaries, no padding is needed and the result
+    /// shares this vector's storage; otherwise, the bits are copied.
+    pub fn to_byte_vector(&self, pad: PadSide) -> Result<ByteVector, Error> {
+        let trailing_len = self.len % 8;
+        if self.offset == 0 && trailing_len == 0 && !self.lsb_first {
+            return self.bytes.take(self.len / 8);
+        }
+        let bits = match pad {
+            PadSide::Left => append(&fill(false, (8 - trailing_len) % 8), self)?,
+            PadSide::Right => self.clone(),
+        };
+        bits.to_vec().map(byte_vector::from_vec)
+    }
+
+    /// Returns the contents of this bit vector as bytes, where the bits following the end of the
+    /// vector in the final byte (if any) are set to zero.
+    pub fn to_vec(&self) -> Result<Vec<u8>, Error> {
+        if self.len == 0 {
+            return Ok(Vec::new());
+        }
+
+        let byte_len = self.bytes.length();
+        let mut raw = vec![0u8; byte_len];
+        self.bytes.read(&mut raw, 0, byte_len)?;
+        if self.lsb_first {
+            raw.iter_mut().for_each(|b| *b = b.reverse_bits());
+        }
+        let mut out: Vec<u8> = if self.offset == 0 {
+            raw
+        } else {
+            (0..raw.len())
+                .map(|i| {
+                    let next = raw.get(i + 1).copied().unwrap_or(0);
+                    (raw[i] << self.offset) | (next >> (8 - self.offset))
+                })
+                .collect()
+        };
+        out.truncate(self.len.div_ceil(8));
+        let trailing_len = self.len % 8;
+        if trailing_len != 0 {
+            let last = out.len() - 1;
+            out[last] &= 0xff << (8 - trailing_len);
+        }
+        Ok(out)
+    }
+
+    /// Returns the given byte of storage with its bits in most significant bit first order.
+    fn reorder(&self, byte: u8) -> u8 {
+        if self.lsb_first {
+            byte.reverse_bits()
+        } else {
+            byte
+        }
+    }
+}
+
+impl PartialEq for BitVector {
+    fn eq(&self, other: &BitVector) -> bool {
+        if self.len != other.len {
+            return false;
+        }
+        match (self.to_vec(), other.to_vec()) {
+            (Ok(lhs), Ok(rhs)) => lhs == rhs,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for BitVector {}
+
+impl Debug for BitVector {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let bytes = self.to_vec().map_err(|_| std::fmt::Error)?;
+        let digits: String = (0..self.len)
+            .map(|i| {
+                if (bytes[i / 8] >> (7 - i % 8)) & 1 == 1 {
+                    '1'
+                } else {
+                    '0'
+                }
+            })
+            .collect();
+        f.write_str(&digits)
+    }
+}
+
+/// The side of a bit vector on which padding bits are added when converting it to bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PadSide {
+    /// Padding precedes the contents, preserving their value as an unsigned integer (e.g. the
+    /// bits `101` become the byte `0x05`).
+    Left,
+
+    /// Padding follows the contents (e.g. the bits `101` become the byte `0xa0`).
+    Right,
+}
+
+/// Returns a mask with the low `len` bits set.
+fn low_mask(len: usize) -> u128 {
+    (1u128 << len) - 1
+}
+
+/// Returns an empty bit vector.
+pub fn empty() -> BitVector {
+    BitVector {
+        bytes: byte_vector::empty(),
+        offset: 0,
+        len: 0,
+        lsb_first: false,
+    }
+}
+
+/// Returns a bit vector that consumes the given bytes, containing all of their bits.
+pub fn from_vec(bytes: Vec<u8>) -> BitVector {
+    let len = bytes.len() * 8;
+    BitVector {
+        bytes: byte_vector::from_vec(bytes),
+        offset: 0,
+        len,
+        lsb_first: false,
+    }
+}
+
+/// Returns a bit vector containing the first `len` bits of the given bytes.
+///
+/// Panics if `len` exceeds the number of bits in `bytes`.
+pub(crate) fn from_vec_bits(bytes: Vec<u8>, len: usize) -> BitVector {
+    assert!(len <= bytes.len() * 8, "Bit length exceeds byte length");
+    BitVector {
+        bytes: byte_vector::from_vec(bytes),
+        offset: 0,
+        len,
+        lsb_first: false,
+    }
+}
+
+/// Returns a bit vector containing all of the bits of the given byte vector, sharing its storage.
+pub(crate) fn from_byte_vector(bytes: ByteVector) -> BitVector {
+    let len = bytes.length() * 8;
+    BitVector {
+        bytes,
+        offset: 0,
+        len,
+        lsb_first: false,
+    }
+}
+
+/// Returns a bit vector containing all of the bits of the given byte vector, where the bits of
+/// each byte are ordered least significant bit first, sharing its storage.  The bits are reordered
+/// as they are read, so only the bytes that are actually read are visited.
+pub(crate) fn from_byte_vector_lsb_first(bytes: ByteVector) -> BitVector {
+    BitVector {
+        lsb_first: true,
+        ..from_byte_vector(bytes)
+    }
+}
+
+/// Returns a bit vector containing the low `len` bits of `value`, most significant bit first.
+///
+/// Panics if `len` is greater than 64.
+pub fn from_u64(value: u64, len: usize) -> BitVector {
+    assert!(len <= 64, "Bit length must not exceed 64");
+    let aligned = ((value as u128) & low_mask(len))
+        .checked_shl(128 - len as u32)
+        .unwrap_or(0);
+    let bytes = aligned.to_be_bytes()[..len.div_ceil(8)].to_vec();
+    BitVector {
+        bytes: byte_vector::from_vec(bytes),
+        offset: 0,
+        len,
+        lsb_first: false,
+    }
+}
+
+/// Returns a bit vector containing the bits given as a string of binary digits (e.g. `"1011"`),
+/// or an error if the string contains any other characters.  Underscores may be used to separate
+/// groups of digits and are ignored.
+pub fn from_bin(digits: &str) -> Result<BitVector, Error> {
+    let mut bytes = Vec::with_capacity(digits.len().div_ceil(8));
+    let mut len = 0;
+    for c in digits.chars().filter(|c| *c != '_') {
+        let bit = match c {
+            '0' => 0u8,
+            '1' => 1u8,
+            _ => {
+                return Err(Error::new(lazy_description(|| {
+                    format!("Invalid binary digit {:?} in {:?}", c, digits)
+                })))
+            }
+        };
+        if len % 8 == 0 {
+            bytes.push(0);
+        }
+        bytes[len / 8] |= bit << (7 - len % 8);
+        len += 1;
+    }
+    from_vec(bytes).take(len)
+}
+
+/// Returns a bit vector containing `value` repeated `count` times.
+pub fn fill(value: bool, count: usize) -> BitVector {
+    let byte = if value { 0xff } else { 0 };
+    BitVector {
+        bytes: byte_vector::fill(byte, count.div_ceil(8)),
+        offset: 0,
+        len: count,
+        lsb_first: false,
+    }
+}
+
+/// Returns a bit vector that contains the contents of `lhs` followed by the contents of `rhs`.
+///
+/// If `lhs` ends and `rhs` begins on a byte boundary, the result shares the storage of both
+/// vectors; otherwise, the bits of `rhs` are copied, and an error is returned if they cannot be
+/// read.
+pub fn append(lhs: &BitVector, rhs: &BitVector) -> Result<BitVector, Error> {
+    if lhs.len == 0 {
+        return Ok(rhs.clone());
+    }
+    if rhs.len == 0 {
+        return Ok(lhs.clone());
+    }
+    let lhs_end = (lhs.offset + lhs.len) % 8;
+    if lhs_end == 0 && rhs.offset == 0 && lhs.lsb_first == rhs.lsb_first {
+        return Ok(BitVector {
+            bytes: byte_vector::append(&lhs.bytes, &rhs.bytes),
+            offset: lhs.offset,
+            len: lhs.len + rhs.len,
+            lsb_first: lhs.lsb_first,
+        });
+    }
+
+    // Shift the bits of `rhs` into place following the last bit of `lhs`
+    let mut bytes = lhs.to_vec()?;
+    let shift = lhs.len % 8;
+    for byte in rhs.to_vec()? {
+        if shift == 0 {
+            bytes.push(byte);
+        } else {
+            let last = bytes.len() - 1;
+            bytes[last] |= byte >> shift;
+            bytes.push(byte << (8 - shift));
+        }
+    }
+    from_vec(bytes).take(lhs.len + rhs.len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::byte_vector;
+
+    #[test]
+    fn from_bin_should_work() {
+        let bv = from_bin("1011_0").unwrap();
+        assert_eq!(bv.length(), 5);
+        assert_eq!(bv.to_vec().unwrap(), vec![0b1011_0000]);
+        assert_eq!(format!("{:?}", bv), "10110");
+        assert_eq!(
+            from_bin("102").unwrap_err().message(),
+            "Invalid binary digit '2' in \"102\""
+        );
+    }
+
+    #[test]
+    fn from_u64_should_work() {
+        assert_eq!(from_u64(5, 3), from_bin("101").unwrap());
+        assert_eq!(from_u64(0x1ff, 4), from_bin("1111").unwrap());
+        assert_eq!(from_u64(u64::MAX, 64), fill(true, 64));
+        assert_eq!(from_u64(0, 0), empty());
+    }
+
+    #[test]
+    fn get_should_work() {
+        let bv = from_vec(vec![0b1010_0000]).drop(2).unwrap();
+        assert!(bv.get(0).unwrap());
+        assert!(!bv.get(1).unwrap());
+        assert_eq!(
+            bv.get(6).unwrap_err().message(),
+            "Requested bit index of 6 exceeds vector length of 6"
+        );
+    }
+
+    #[test]
+    fn get_bits_should_work() {
+        let bv = from_vec(vec![0x12, 0x34, 0x56]);
+        assert_eq!(bv.get_bits(0, 8).unwrap(), 0x12);
+        assert_eq!(bv.get_bits(4, 12).unwrap(), 0x234);
+        assert_eq!(bv.get_bits(3, 0).unwrap(), 0);
+        assert_eq!(bv.drop(1).unwrap().get_bits(3, 5).unwrap(), 0b00100);
+
+        let wide = from_vec(vec![0xff; 10]).drop(7).unwrap();
+        assert_eq!(wide.get_bits(0, 64).unwrap(), u64::MAX);
+        assert_eq!(
+            bv.get_bits(20, 5).unwrap_err().message(),
+            "Requested read offset of 20 and length 5 bits exceeds vector length of 24"
+        );
+        assert!(wide.get_bits(0, 65).is_err());
+    }
+
+    #[test]
+    fn take_and_drop_should_work() {
+        let bv = from_vec(vec![0b1100_1010, 0b0111_0001]);
+        assert_eq!(bv.take(3).unwrap(), from_bin("110").unwrap());
+        assert_eq!(bv.drop(3).unwrap(), from_bin("0101_0011_1000_1").unwrap());
+        assert_eq!(
+            bv.drop(5).unwrap().take(6).unwrap(),
+            from_bin("010011").unwrap()
+        );
+        assert_eq!(bv.drop(16).unwrap(), empty());
+        assert_eq!(
+            bv.take(17).unwrap_err().message(),
+            "Requested length of 17 bits exceeds vector length of 16"
+        );
+        assert!(bv.drop(17).is_err());
+    }
+
+    #[test]
+    fn lsb_first_vectors_should_reorder_bits_as_they_are_read() {
+        let bytes = byte_vector!(0b0000_0101, 0b1000_0000);
+        let bv = from_byte_vector_lsb_first(bytes.clone());
+        assert_eq!(bv, from_bin("1010_0000_0000_0001").unwrap());
+        assert!(bv.get(0).unwrap());
+        assert!(!bv.get(1).unwrap());
+        assert_eq!(bv.get_bits(2, 14).unwrap(), 0b10_0000_0000_0001);
+        assert_eq!(bv.drop(8).unwrap().to_vec().unwrap(), vec![0b0000_0001]);
+        assert_eq!(
+            bv.to_byte_vector(PadSide::Right).unwrap(),
+            byte_vector!(0b1010_0000, 0b0000_0001)
+        );
+
+        // Appending to a vector in the other ordering reorders the bits
+        let appended = append(&from_byte_vector(bytes), &bv).unwrap();
+        assert_eq!(
+            appended.to_vec().unwrap(),
+            vec![0b0000_0101, 0b1000_0000, 0b1010_0000, 0b0000_0001]
+        );
+    }
+
+    #[test]
+    fn to_vec_should_clear_trailing_bits() {
+        let bv = from_vec(vec![0xff, 0xff])
+            .drop(3)
+            .unwrap()
+            .take(10)
+            .unwrap();
+        assert_eq!(bv.to_vec().unwrap(), vec![0xff, 0b1100_0000]);
+        assert_eq!(empty().to_vec().unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn to_byte_vector_should_pad_on_the_given_side() {
+        let bv = from_bin("1_0010_0011").unwrap();
+        assert_eq!(
+            bv.to_byte_vector(PadSide::Left).unwrap(),
+            byte_vector!(0x01, 0x23)
+        );
+        assert_eq!(
+            bv.to_byte_vector(PadSide::Right).unwrap(),
+            byte_vector!(0x91, 0x80)
+        );
+
+        // Unaligned vectors of whole bytes are shifted without padding
+        let shifted = from_vec(vec![0x0a, 0xbc]).drop(4).unwrap().take(8).unwrap();
+        assert_eq!(
+            shifted.to_byte_vector(PadSide::Left).unwrap(),
+            byte_vector!(0xab)
+        );
+
+        // Aligned vectors share their storage with the original byte vector
+        let bytes = byte_vector!(1, 2, 3, 4);
+        let bits = bytes.bits().drop(8).unwrap().take(16).unwrap();
+        assert_eq!(bits.length(), 16);
+        assert_eq!(
+            bits.to_byte_vector(PadSide::Right).unwrap(),
+            byte_vector!(2, 3)
+        );
+        assert_eq!(
+            empty().to_byte_vector(PadSide::Left).unwrap(),
+            byte_vector::empty()
+        );
+    }
+
+    #[test]
+    fn append_should_work() {
+        let lhs = from_bin("101").unwrap();
+        let rhs = from_bin("0111_0000_1").unwrap();
+        let appended = append(&lhs, &rhs).unwrap();
+        assert_eq!(appended, from_bin("1010_1110_0001").unwrap());
+        assert_eq!(append(&empty(), &rhs).unwrap(), rhs);
+        assert_eq!(append(&lhs, &empty()).unwrap(), lhs);
+
+        // Vectors that meet at a byte boundary are appended without copying
+        let aligned = append(&from_vec(vec![0xab]), &from_vec(vec![0xcd])).unwrap();
+        assert_eq!(aligned, from_vec(vec![0xab, 0xcd]));
+        assert_eq!(aligned.drop(4).unwrap().get_bits(0, 8).unwrap(), 0xbc);
+    }
+
+    #[test]
+    fn equality_should_ignore_storage_layout() {
+        let lhs = from_vec(vec![0b0001_0110]).drop(3).unwrap();
+        let rhs = from_vec(vec![0b1011_0111]).take(5).unwrap();
+        assert_eq!(lhs, rhs);
+        assert_ne!(lhs, rhs.take(4).unwrap());
+    }
+}
@@ -7,32 +7,251 @@
 //
 
 use core::fmt;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::fmt::{Debug, Formatter};
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::vec::Vec;
 
-use crate::error::Error;
+use crate::bit_vector;
+use crate::bit_vector::BitVector;
+use crate::error::{lazy_description, Error};
+
+// Generates an accessor that reads a primitive integer value at a given offset.
+macro_rules! get_int {
+    { $fname:ident, $itype:ty, $conv:ident, $desc:expr } => {
+        #[doc = $desc]
+        #[doc = " integer accessor; returns the value at `offset`, or an error if insufficient data is available."]
+        pub fn $fname(&self, offset: usize) -> Result<$itype, Error> {
+            let mut buf = [0u8; std::mem::size_of::<$itype>()];
+            let len = buf.len();
+            self.read(&mut buf, offset, len).map(|_| <$itype>::$conv(buf))
+        }
+    };
+}
 
 /// An immutable vector of bytes.
+///
+/// A byte vector is a window of `len` bytes at `offset` within its (shared) storage, so that taking
+/// or dropping bytes (as every codec does for the remainder after each decoded value) only adjusts
+/// the window rather than allocating a new view of the storage.
 #[derive(Clone)]
 pub struct ByteVector {
     /// The underlying storage type.
     storage: Rc<StorageType>,
+
+    /// The offset of this vector's first byte within the storage.
+    offset: usize,
+
+    /// The length of this vector, in bytes.
+    len: usize,
+}
+
+/// The maximum number of storage nodes retained for reuse by `with_storage_pool`.
+const STORAGE_POOL_LIMIT: usize = 256;
+
+/// The number of `with_storage_pool` calls in progress across all threads, which lets byte vectors
+/// skip the thread-local lookups entirely when no storage pool is active anywhere.
+static ACTIVE_POOLS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// The storage nodes available for reuse, or `None` if no storage pool is active on this thread.
+    static STORAGE_POOL: RefCell<Option<Vec<Rc<StorageType>>>> = const { RefCell::new(None) };
+
+    /// Whether a storage pool is active on this thread, which avoids borrowing `STORAGE_POOL` on
+    /// the common path.
+    static POOLING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Calls `f` with a pool of storage nodes active on the current thread, releasing the pool
+/// wholesale afterwards.
+///
+/// While the pool is active, the bookkeeping nodes of byte vectors that are no longer referenced
+/// (such as the nodes created by `append` and by views that span appended storage, as well as
+/// small direct values) are retained for reuse by new byte vectors rather than being returned to
+/// the allocator, which reduces allocator traffic for high-rate encoders and decoders that create
+/// many short-lived vectors.  Nodes that own heap buffers or files are never retained.
+///
+/// # Examples
+///
+/// ```
+/// use rcodec::byte_vector;
+/// use rcodec::codec::*;
+///
+/// let codec = vector_of_n(uint8, uint16);
+/// let values = byte_vector::with_storage_pool(|| {
+///     (0..100u16)
+///         .map(|i| codec.encode(&vec![i, i + 1]).and_then(|bv| codec.decode(&bv)))
+///         .collect::<Result<Vec<_>, _>>()
+/// })
+/// .unwrap();
+/// assert_eq!(values[99].value, vec![99, 100]);
+/// ```
+pub fn with_storage_pool<R, F>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    // Restore the previous pool (releasing ours) even if `f` panics
+    struct Restore(Option<Vec<Rc<StorageType>>>, bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let pool = STORAGE_POOL.with(|p| p.replace(self.0.take()));
+            POOLING.with(|p| p.set(self.1));
+            drop(pool);
+            ACTIVE_POOLS.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    ACTIVE_POOLS.fetch_add(1, Ordering::Relaxed);
+    let _restore = Restore(
+        STORAGE_POOL.with(|p| p.replace(Some(Vec::new()))),
+        POOLING.with(|p| p.replace(true)),
+    );
+    f()
+}
+
+/// Returns true if a storage pool is active on the current thread.
+#[inline]
+fn pooling() -> bool {
+    // Only pay for the thread-local lookup if some thread has a storage pool active
+    ACTIVE_POOLS.load(Ordering::Relaxed) != 0 && POOLING.try_with(Cell::get).unwrap_or(false)
+}
+
+/// Returns a new storage node containing `storage`, reusing a pooled node if one is available.
+fn new_node(storage: StorageType) -> Rc<StorageType> {
+    if pooling() {
+        let node = STORAGE_POOL.with(|p| p.borrow_mut().as_mut().and_then(Vec::pop));
+        if let Some(mut node) = node {
+            if let Some(slot) = Rc::get_mut(&mut node) {
+                *slot = storage;
+                return node;
+            }
+        }
+    }
+    Rc::new(storage)
+}
+
+/// Retains `node` for reuse if a storage pool is active and `node` is about to be released.
+///
+/// The contents of a retained node are released first, so that the pool does not keep the storage
+/// it referred to alive.
+fn recycle_node(node: &mut Rc<StorageType>) {
+    if !pooling() {
+        return;
+    }
+    match Rc::get_mut(node) {
+        Some(
+            slot @ (StorageType::DirectValue { .. }
+            | StorageType::View { .. }
+            | StorageType::Append { .. }),
+        ) => *slot = StorageType::Empty,
+        _ => return,
+    }
+    let _ = STORAGE_POOL.try_with(|p| {
+        if let Some(pool) = p.borrow_mut().as_mut() {
+            if pool.len() < STORAGE_POOL_LIMIT {
+                pool.push(node.clone());
+            }
+        }
+    });
+}
+
+impl Drop for ByteVector {
+    fn drop(&mut self) {
+        recycle_node(&mut self.storage);
+    }
 }
 
 impl ByteVector {
+    /// Returns a byte vector containing all of the given storage.
+    fn from_storage(storage: Rc<StorageType>) -> ByteVector {
+        let len = storage.length();
+        ByteVector {
+            storage,
+            offset: 0,
+            len,
+        }
+    }
+
+    /// Returns true if this vector covers all of its storage.
+    fn is_whole(&self) -> bool {
+        self.offset == 0 && self.len == self.storage.length()
+    }
+
+    /// Returns the storage of this vector's contents, creating a view of the underlying storage if
+    /// this vector does not cover all of it.
+    fn shared_storage(&self) -> Rc<StorageType> {
+        if self.is_whole() {
+            self.storage.clone()
+        } else {
+            ByteVector::view(&self.storage, self.offset, self.len).unwrap()
+        }
+    }
+
+    /// Returns a byte vector sharing this vector's storage that contains `len` bytes at `offset`.
+    fn window(&self, offset: usize, len: usize) -> Result<ByteVector, Error> {
+        check_range("view", offset, len, self.len)?;
+        Ok(ByteVector {
+            storage: self.storage.clone(),
+            offset: self.offset + offset,
+            len,
+        })
+    }
+
+    /// Returns the byte at `index`, panicking if it is out of bounds.
+    fn unsafe_get(&self, index: usize) -> u8 {
+        self.storage.unsafe_get(self.offset + index)
+    }
+
     /// Returns the length, in bytes.
     pub fn length(&self) -> usize {
-        self.storage.length()
+        self.len
     }
 
     /// Reads up to a maximum of `len` bytes at `offset` from this byte vector into the given buffer.
     pub fn read(&self, buf: &mut [u8], offset: usize, len: usize) -> Result<usize, Error> {
-        self.storage.read(buf, offset, len)
+        check_range("read", offset, len, self.len)?;
+        self.storage.read(buf, self.offset + offset, len)
+    }
+
+    /// Returns a borrowed slice of this byte vector's contents if the underlying storage is contiguous
+    /// in memory (i.e., a single heap or direct value buffer, or a view over one), or `None` otherwise.
+    /// This allows for zero-copy access in the common case where the vector has not been assembled
+    /// from multiple pieces.
+    pub fn as_slice(&self) -> Option<&[u8]> {
+        self.storage.slice(self.offset, self.len)
+    }
+
+    /// Returns a mutable slice of this byte vector's contents, allowing for in-place edits.
+    ///
+    /// If this byte vector is the sole owner of a contiguous buffer, the buffer is modified directly.
+    /// Otherwise, the contents are first copied into a new buffer that is owned solely by this byte
+    /// vector, so that changes are never visible through other vectors that share the original storage.
+    ///
+    /// Panics if the contents need to be copied but cannot be read (which is only possible for
+    /// file-backed storage).
+    pub fn make_mut(&mut self) -> &mut [u8] {
+        let unique = self.is_whole()
+            && matches!(
+                Rc::get_mut(&mut self.storage),
+                Some(StorageType::Heap { .. }) | Some(StorageType::DirectValue { .. })
+            );
+        if !unique {
+            let bytes = self.to_vec().expect("Failed to read byte vector contents");
+            *self = from_vec(bytes);
+        }
+
+        match Rc::get_mut(&mut self.storage) {
+            Some(StorageType::Heap { ref mut bytes }) => &mut bytes[..],
+            Some(StorageType::DirectValue {
+                ref mut bytes,
+                ref length,
+            }) => &mut bytes[..*length],
+            _ => unreachable!(),
+        }
     }
 
     /// Converts this byte vector to a `Vec<u8>` instance. Note that this will copy all of the underlying
@@ -46,10 +265,51 @@ impl ByteVector {
         self.read(&mut vec[..], 0, self.length()).map(|_res| vec)
     }
 
+    /// Returns a bit vector containing all of the bits of this byte vector, sharing its storage.
+    pub fn bits(&self) -> BitVector {
+        bit_vector::from_byte_vector(self.clone())
+    }
+
+    /// Writes the contents of this byte vector to `out`.  The storage is written out piece by
+    /// piece, so this does not require the full contents to be held in memory at once.
+    pub fn write_to(&self, out: &mut dyn Write) -> Result<(), Error> {
+        self.storage
+            .write(out, self.offset, self.len)
+            .map_err(|io_err| Error::new(format!("Failed to write bytes: {}", io_err)))
+    }
+
+    /// Writes the contents of this byte vector to a newly created file at the given path, replacing
+    /// any existing file. The storage is written out piece by piece, so unlike `to_vec` this does
+    /// not require the full contents to be held in memory at once.
+    pub fn to_file(&self, path: &Path) -> Result<(), Error> {
+        let file = File::create(path)
+            .map_err(|io_err| Error::new(format!("Failed to create file: {}", io_err)))?;
+        // Pieces may be small, so buffer them; flush explicitly, since errors are lost on drop
+        let mut out = BufWriter::new(file);
+        self.storage
+            .write(&mut out, self.offset, self.len)
+            .and_then(|_| out.flush())
+            .map_err(|io_err| Error::new(format!("Failed to write file: {}", io_err)))
+    }
+
     /// Returns a new byte vector containing exactly `len` bytes from this byte vector, or an
     /// error if insufficient data is available.
     pub fn take(&self, len: usize) -> Result<ByteVector, Error> {
-        ByteVector::view(&self.storage, 0, len).map(|storage| ByteVector { storage })
+        self.window(0, len)
+    }
+
+    /// Returns a new byte vector containing up to `len` bytes from the start of this byte vector.
+    /// Unlike `take`, this does not fail if fewer than `len` bytes are available; instead, the
+    /// entire contents are returned.
+    pub fn take_max(&self, len: usize) -> ByteVector {
+        let len = std::cmp::min(len, self.length());
+        self.window(0, len).unwrap()
+    }
+
+    /// Returns this byte vector shortened to at most `len` bytes, analogous to `Vec::truncate`.
+    /// If the vector is already `len` bytes or shorter, it is returned unmodified.
+    pub fn truncate(&self, len: usize) -> ByteVector {
+        self.take_max(len)
     }
 
     /// Returns a new byte vector containing all but the first `len` bytes of this byte vector,
@@ -57,29 +317,36 @@ impl ByteVector {
     pub fn drop(&self, len: usize) -> Result<ByteVector, Error> {
         let storage_len = self.length();
         if len > storage_len {
-            return Err(Error::new(format!(
-                "Requested length of {len} bytes exceeds vector length of {vlen}",
-                len = len,
-                vlen = storage_len
-            )));
+            return Err(Error::short_input(
+                lazy_description(|| {
+                    format!(
+                        "Requested length of {len} bytes exceeds vector length of {vlen}",
+                        len = len,
+                        vlen = storage_len
+                    )
+                }),
+                storage_len,
+                len,
+            ));
         }
 
-        ByteVector::view(&self.storage, len, storage_len - len)
-            .map(|remainder| ByteVector { storage: remainder })
+        self.window(len, storage_len - len)
     }
 
     /// Returns a new vector of length `len` containing zero or more low bytes followed by this byte vector's contents.
     /// If this vector is longer than `len` bytes, an error will be returned.
     pub fn pad_left(&self, len: usize) -> Result<ByteVector, Error> {
-        #![allow(clippy::unknown_clippy_lints, clippy::comparison_chain)]
+        #![allow(unknown_lints, clippy::comparison_chain)]
 
         let storage_len = self.length();
         if len < storage_len {
-            Err(Error::new(format!(
+            Err(Error::new(lazy_description(|| {
+                format!(
                 "Requested padded length of {len} bytes is smaller than vector length of {vlen}",
                 len = len,
                 vlen = storage_len
-            )))
+            )
+            })))
         } else if len == storage_len {
             Ok((*self).clone())
         } else {
@@ -90,15 +357,17 @@ impl ByteVector {
     /// Returns a new vector of length `len` containing this byte vector's contents followed by zero or more low bytes.
     /// If this vector is longer than `len` bytes, an error will be returned.
     pub fn pad_right(&self, len: usize) -> Result<ByteVector, Error> {
-        #![allow(clippy::unknown_clippy_lints, clippy::comparison_chain)]
+        #![allow(unknown_lints, clippy::comparison_chain)]
 
         let storage_len = self.length();
         if len < storage_len {
-            Err(Error::new(format!(
+            Err(Error::new(lazy_description(|| {
+                format!(
                 "Requested padded length of {len} bytes is smaller than vector length of {vlen}",
                 len = len,
                 vlen = storage_len
-            )))
+            )
+            })))
         } else if len == storage_len {
             Ok((*self).clone())
         } else {
@@ -106,31 +375,147 @@ impl ByteVector {
         }
     }
 
+    /// Returns the byte at `offset`, or an error if `offset` is out of bounds.
+    pub fn get_u8(&self, offset: usize) -> Result<u8, Error> {
+        let mut buf = [0u8; 1];
+        self.read(&mut buf, offset, 1).map(|_| buf[0])
+    }
+
+    /// Returns the byte at `offset` interpreted as a signed value, or an error if `offset` is out of bounds.
+    pub fn get_i8(&self, offset: usize) -> Result<i8, Error> {
+        self.get_u8(offset).map(|b| b as i8)
+    }
+
+    get_int!(get_u16_be, u16, from_be_bytes, "Big-endian unsigned 16-bit");
+    get_int!(
+        get_u16_le,
+        u16,
+        from_le_bytes,
+        "Little-endian unsigned 16-bit"
+    );
+    get_int!(get_i16_be, i16, from_be_bytes, "Big-endian signed 16-bit");
+    get_int!(
+        get_i16_le,
+        i16,
+        from_le_bytes,
+        "Little-endian signed 16-bit"
+    );
+    get_int!(get_u32_be, u32, from_be_bytes, "Big-endian unsigned 32-bit");
+    get_int!(
+        get_u32_le,
+        u32,
+        from_le_bytes,
+        "Little-endian unsigned 32-bit"
+    );
+    get_int!(get_i32_be, i32, from_be_bytes, "Big-endian signed 32-bit");
+    get_int!(
+        get_i32_le,
+        i32,
+        from_le_bytes,
+        "Little-endian signed 32-bit"
+    );
+    get_int!(get_u64_be, u64, from_be_bytes, "Big-endian unsigned 64-bit");
+    get_int!(
+        get_u64_le,
+        u64,
+        from_le_bytes,
+        "Little-endian unsigned 64-bit"
+    );
+    get_int!(get_i64_be, i64, from_be_bytes, "Big-endian signed 64-bit");
+    get_int!(
+        get_i64_le,
+        i64,
+        from_le_bytes,
+        "Little-endian signed 64-bit"
+    );
+
+    /// Returns an iterator over the views between occurrences of the given `delimiter` byte.
+    ///
+    /// As with `slice::split`, adjacent delimiters (or a delimiter at either end) produce empty views,
+    /// and the delimiters themselves are not included in the resulting views.  If the underlying
+    /// storage cannot be read while searching for a delimiter, the iterator yields the error and
+    /// then ends.
+    pub fn split(&self, delimiter: u8) -> Split {
+        Split {
+            remaining: Some(self.clone()),
+            delimiter: vec![delimiter],
+        }
+    }
+
+    /// Returns an iterator over the views between occurrences of the given `delimiter` byte sequence,
+    /// or an error if the delimiter cannot be read.  If `delimiter` is empty, the iterator yields this
+    /// byte vector as a single view.  See `split` for details.
+    pub fn split_on_slice(&self, delimiter: &ByteVector) -> Result<Split, Error> {
+        delimiter.to_vec().map(|delimiter| Split {
+            remaining: Some(self.clone()),
+            delimiter,
+        })
+    }
+
+    /// Returns an iterator over all overlapping views of length `size`, analogous to `slice::windows`.
+    /// If this vector is shorter than `size` bytes, the iterator yields no views.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn windows(&self, size: usize) -> Windows {
+        assert!(size != 0, "window size must be non-zero");
+        Windows {
+            bv: self.clone(),
+            size,
+            offset: 0,
+        }
+    }
+
+    /// Returns the offset of the first occurrence of `pattern` at or after `from`, if any, or an
+    /// error if the underlying storage cannot be read.
+    pub(crate) fn index_of_slice(
+        &self,
+        pattern: &[u8],
+        from: usize,
+    ) -> Result<Option<usize>, Error> {
+        let len = self.length();
+        let plen = pattern.len();
+        if plen == 0 || from > len || len - from < plen {
+            return Ok(None);
+        }
+
+        // Search the slice directly if the vector is contiguous
+        if let Some(bytes) = self.as_slice() {
+            return Ok(bytes[from..]
+                .windows(plen)
+                .position(|w| w == pattern)
+                .map(|index| from + index));
+        }
+
+        // Read the vector in chunks, overlapping each chunk with the previous one by `plen - 1` bytes
+        // so that matches that straddle a chunk boundary are not missed
+        let mut buf = vec![0u8; std::cmp::max(SEARCH_CHUNK_SIZE, plen)];
+        let mut offset = from;
+        while len - offset >= plen {
+            let count = std::cmp::min(buf.len(), len - offset);
+            self.read(&mut buf[..count], offset, count)?;
+            if let Some(index) = buf[..count].windows(plen).position(|w| w == pattern) {
+                return Ok(Some(offset + index));
+            }
+            if offset + count == len {
+                break;
+            }
+            offset += count - (plen - 1);
+        }
+
+        Ok(None)
+    }
+
     /// Returns a projection at `offset` with `len` bytes within the given storage.
     fn view(
         storage: &Rc<StorageType>,
         offset: usize,
         len: usize,
     ) -> Result<Rc<StorageType>, Error> {
-        // Verify that offset is within our storage bounds
+        // Verify that the view is within our storage bounds
         let storage_len = storage.length();
-        if offset > storage_len {
-            return Err(Error::new(format!(
-                "Requested view offset of {off} bytes exceeds vector length of {vlen}",
-                off = offset,
-                vlen = storage_len
-            )));
-        }
-
-        // Verify that offset + len will not overflow
-        if std::usize::MAX - offset < len {
-            return Err(Error::new(format!("Requested view offset of {off} and length {len} bytes would overflow maximum value of usize", off = offset, len = len)));
-        }
-
-        // Verify that offset + len is within our storage bounds
-        if offset + len > storage_len {
-            return Err(Error::new(format!("Requested view offset of {off} and length {len} bytes exceeds vector length of {vlen}", off = offset, len = len, vlen = storage_len)));
-        }
+        check_range("view", offset, len, storage_len)?;
 
         // Return storage unmodified if the requested length equals the storage length
         if len == storage_len {
@@ -138,13 +523,13 @@ impl ByteVector {
         }
 
         match **storage {
-            StorageType::Empty => Err(Error::new(
-                "Cannot create view for empty vector".to_string(),
-            )),
+            StorageType::Empty => Err(Error::new(lazy_description(|| {
+                "Cannot create view for empty vector".to_string()
+            }))),
 
             StorageType::DirectValue { .. } => {
                 // Create a new view around the value storage
-                Ok(Rc::new(StorageType::View {
+                Ok(new_node(StorageType::View {
                     vstorage: (*storage).clone(),
                     voffset: offset,
                     vlen: len,
@@ -153,7 +538,7 @@ impl ByteVector {
 
             StorageType::Heap { .. } => {
                 // Create a new view around this heap storage
-                Ok(Rc::new(StorageType::View {
+                Ok(new_node(StorageType::View {
                     vstorage: (*storage).clone(),
                     voffset: offset,
                     vlen: len,
@@ -169,20 +554,20 @@ impl ByteVector {
                 let lhs_len = lhs.length();
                 if offset + len < lhs_len {
                     // Drop the entire rhs
-                    ByteVector::view(&lhs, offset, len)
+                    ByteVector::view(lhs, offset, len)
                 } else if offset >= lhs_len {
                     // Drop the entire lhs
                     let rhs_offset = offset - lhs_len;
-                    ByteVector::view(&rhs, rhs_offset, len)
+                    ByteVector::view(rhs, rhs_offset, len)
                 } else {
                     // Create a new Append that spans portions of lhs and rhs
                     let lhs_view_len = lhs_len - offset;
                     let rhs_view_len = len - lhs_view_len;
                     forcomp!({
-                        lhs_view <- ByteVector::view(&lhs, offset, lhs_view_len);
-                        rhs_view <- ByteVector::view(&rhs, 0, rhs_view_len);
+                        lhs_view <- ByteVector::view(lhs, offset, lhs_view_len);
+                        rhs_view <- ByteVector::view(rhs, 0, rhs_view_len);
                     } yield {
-                        Rc::new(StorageType::Append { lhs: lhs_view, rhs: rhs_view, len: lhs_view_len + rhs_view_len })
+                        new_node(StorageType::Append { lhs: lhs_view, rhs: rhs_view, len: lhs_view_len + rhs_view_len })
                     })
                 }
             }
@@ -193,15 +578,17 @@ impl ByteVector {
                 ..
             } => {
                 // Verify that voffset + offset will not overflow
-                if std::usize::MAX - offset < *voffset {
-                    return Err(Error::new(format!("Requested view offset of {off} plus storage offset {voff} would overflow maximum value of usize", off = offset, voff = *voffset)));
+                if usize::MAX - offset < *voffset {
+                    return Err(Error::new(lazy_description(|| {
+                        format!("Requested view offset of {off} plus storage offset {voff} would overflow maximum value of usize", off = offset, voff = *voffset)
+                    })));
                 }
                 ByteVector::view(vstorage, *voffset + offset, len)
             }
 
             StorageType::File { .. } => {
                 // Create a new view around the file storage
-                Ok(Rc::new(StorageType::View {
+                Ok(new_node(StorageType::View {
                     vstorage: (*storage).clone(),
                     voffset: offset,
                     vlen: len,
@@ -211,17 +598,128 @@ impl ByteVector {
     }
 }
 
+/// Verifies that the range of `len` bytes at `offset` lies within a vector of `vlen` bytes,
+/// returning an error that describes the attempted `kind` of access (e.g. "read") otherwise.
+fn check_range(kind: &'static str, offset: usize, len: usize, vlen: usize) -> Result<(), Error> {
+    // Verify that offset is within bounds
+    if offset > vlen {
+        return Err(Error::short_input(
+            lazy_description(|| {
+                format!(
+                    "Requested {kind} offset of {off} bytes exceeds vector length of {vlen}",
+                    kind = kind,
+                    off = offset,
+                    vlen = vlen
+                )
+            }),
+            vlen,
+            offset,
+        ));
+    }
+
+    // Verify that offset + len will not overflow
+    if usize::MAX - offset < len {
+        return Err(Error::new(lazy_description(|| {
+            format!("Requested {kind} offset of {off} and length {len} bytes would overflow maximum value of usize", kind = kind, off = offset, len = len)
+        })));
+    }
+
+    // Verify that offset + len is within bounds
+    if offset + len > vlen {
+        return Err(Error::short_input(
+            lazy_description(|| {
+                format!("Requested {kind} offset of {off} and length {len} bytes exceeds vector length of {vlen}", kind = kind, off = offset, len = len, vlen = vlen)
+            }),
+            vlen,
+            offset + len,
+        ));
+    }
+
+    Ok(())
+}
+
+/// The size of the buffer used when writing file-backed storage.
+const WRITE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The size of the buffer used when searching a byte vector.
+const SEARCH_CHUNK_SIZE: usize = 4096;
+
+/// An iterator over the views of a `ByteVector` separated by a delimiter.
+///
+/// This struct is created by the `split` and `split_on_slice` methods on `ByteVector`.
+pub struct Split {
+    /// The portion of the vector that has yet to be split, or `None` if iteration is complete.
+    remaining: Option<ByteVector>,
+
+    /// The delimiter bytes.
+    delimiter: Vec<u8>,
+}
+
+impl Iterator for Split {
+    type Item = Result<ByteVector, Error>;
+
+    fn next(&mut self) -> Option<Result<ByteVector, Error>> {
+        let remaining = self.remaining.take()?;
+        Some(match remaining.index_of_slice(&self.delimiter, 0) {
+            Ok(Some(index)) => {
+                self.remaining = remaining.drop(index + self.delimiter.len()).ok();
+                remaining.take(index)
+            }
+            Ok(None) => Ok(remaining),
+            Err(e) => Err(e),
+        })
+    }
+}
+
+/// An iterator over overlapping views of a `ByteVector`.
+///
+/// This struct is created by the `windows` method on `ByteVector`.
+pub struct Windows {
+    /// The vector being iterated.
+    bv: ByteVector,
+
+    /// The length of each window.
+    size: usize,
+
+    /// The offset of the next window.
+    offset: usize,
+}
+
+impl Iterator for Windows {
+    type Item = ByteVector;
+
+    fn next(&mut self) -> Option<ByteVector> {
+        if self.bv.length() - self.offset < self.size {
+            return None;
+        }
+
+        let window = self.bv.window(self.offset, self.size).ok();
+        self.offset += 1;
+        window
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = (self.bv.length() - self.offset + 1).saturating_sub(self.size);
+        (count, Some(count))
+    }
+}
+
 impl PartialEq for ByteVector {
     fn eq(&self, other: &ByteVector) -> bool {
         if self.length() != other.length() {
             return false;
         }
 
+        // Compare the slices directly if both vectors are contiguous
+        if let (Some(lhs), Some(rhs)) = (self.as_slice(), other.as_slice()) {
+            return lhs == rhs;
+        }
+
         // This is a pretty inefficient implementation that reads a single byte at a time
         let len = self.length();
         for i in 0..len {
-            let lhs = self.storage.unsafe_get(i);
-            let rhs = other.storage.unsafe_get(i);
+            let lhs = self.unsafe_get(i);
+            let rhs = other.unsafe_get(i);
             if lhs != rhs {
                 return false;
             }
@@ -233,6 +731,108 @@ impl PartialEq for ByteVector {
 
 impl Eq for ByteVector {}
 
+impl ByteVector {
+    /// Returns true if the contents of this byte vector are equal to the given bytes.
+    fn eq_slice(&self, other: &[u8]) -> bool {
+        if self.length() != other.len() {
+            return false;
+        }
+
+        // Compare the slices directly if this vector is contiguous
+        if let Some(bytes) = self.as_slice() {
+            return bytes == other;
+        }
+
+        // Otherwise, compare one chunk at a time
+        let mut buf = [0u8; SEARCH_CHUNK_SIZE];
+        let mut offset = 0;
+        while offset < other.len() {
+            let count = std::cmp::min(buf.len(), other.len() - offset);
+            if self.read(&mut buf[..count], offset, count).is_err()
+                || buf[..count] != other[offset..offset + count]
+            {
+                return false;
+            }
+            offset += count;
+        }
+
+        true
+    }
+}
+
+impl PartialEq<[u8]> for ByteVector {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.eq_slice(other)
+    }
+}
+
+impl<'a> PartialEq<&'a [u8]> for ByteVector {
+    fn eq(&self, other: &&'a [u8]) -> bool {
+        self.eq_slice(other)
+    }
+}
+
+impl PartialEq<Vec<u8>> for ByteVector {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        self.eq_slice(other)
+    }
+}
+
+impl PartialEq<ByteVector> for [u8] {
+    fn eq(&self, other: &ByteVector) -> bool {
+        other.eq_slice(self)
+    }
+}
+
+impl PartialEq<ByteVector> for &[u8] {
+    fn eq(&self, other: &ByteVector) -> bool {
+        other.eq_slice(self)
+    }
+}
+
+impl PartialEq<ByteVector> for Vec<u8> {
+    fn eq(&self, other: &ByteVector) -> bool {
+        other.eq_slice(self)
+    }
+}
+
+impl From<Vec<u8>> for ByteVector {
+    fn from(bytes: Vec<u8>) -> ByteVector {
+        from_vec(bytes)
+    }
+}
+
+impl From<&[u8]> for ByteVector {
+    fn from(bytes: &[u8]) -> ByteVector {
+        from_slice_copy(bytes)
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for ByteVector {
+    fn from(bytes: [u8; N]) -> ByteVector {
+        from_slice_copy(&bytes)
+    }
+}
+
+impl<const N: usize> From<&[u8; N]> for ByteVector {
+    fn from(bytes: &[u8; N]) -> ByteVector {
+        from_slice_copy(bytes)
+    }
+}
+
+impl From<&str> for ByteVector {
+    /// Returns a byte vector containing the UTF-8 encoding of the given string.
+    fn from(s: &str) -> ByteVector {
+        from_slice_copy(s.as_bytes())
+    }
+}
+
+impl From<&ByteVector> for ByteVector {
+    fn from(bv: &ByteVector) -> ByteVector {
+        bv.clone()
+    }
+}
+
 const CHARS: &[u8] = b"0123456789abcdef";
 
 impl Debug for ByteVector {
@@ -240,15 +840,12 @@ impl Debug for ByteVector {
         let len = self.length();
         let mut v = Vec::with_capacity(len * 2);
         for i in 0..len {
-            let byte = self.storage.unsafe_get(i);
+            let byte = self.unsafe_get(i);
             v.push(CHARS[(byte >> 4) as usize]);
             v.push(CHARS[(byte & 0xf) as usize]);
         }
         unsafe {
-            let result = f.write_str(&String::from_utf8_unchecked(v));
-            if result.is_err() {
-                return result;
-            }
+            f.write_str(&String::from_utf8_unchecked(v))?;
         };
         Ok(())
     }
@@ -312,30 +909,44 @@ impl StorageType {
         }
     }
 
-    /// Reads up to a maximum of length bytes at offset from this byte vector into the given buffer.
-    fn read(&self, buf: &mut [u8], offset: usize, len: usize) -> Result<usize, Error> {
-        // Verify that offset is within our storage bounds
-        let storage_len = self.length();
-        if offset > storage_len {
-            return Err(Error::new(format!(
-                "Requested read offset of {off} bytes exceeds vector length of {vlen}",
-                off = offset,
-                vlen = storage_len
-            )));
-        }
-
-        // Verify that offset + len will not overflow
-        if std::usize::MAX - offset < len {
-            return Err(Error::new(format!("Requested read offset of {off} and length {len} bytes would overflow maximum value of usize", off = offset, len = len)));
+    /// Returns a borrowed slice of the `len` bytes at `offset` within this storage if they are
+    /// contiguous in memory. The caller is responsible for ensuring that the requested range is
+    /// within bounds.
+    fn slice(&self, offset: usize, len: usize) -> Option<&[u8]> {
+        match *self {
+            StorageType::Empty => Some(&[]),
+            StorageType::DirectValue { ref bytes, .. } => Some(&bytes[offset..offset + len]),
+            StorageType::Heap { ref bytes } => Some(&bytes[offset..offset + len]),
+            StorageType::View {
+                ref vstorage,
+                ref voffset,
+                ..
+            } => vstorage.slice(*voffset + offset, len),
+            StorageType::Append {
+                ref lhs, ref rhs, ..
+            } => {
+                // The range is contiguous only if it falls entirely within one side
+                let lhs_len = lhs.length();
+                if offset + len <= lhs_len {
+                    lhs.slice(offset, len)
+                } else if offset >= lhs_len {
+                    rhs.slice(offset - lhs_len, len)
+                } else {
+                    None
+                }
+            }
+            StorageType::File { .. } => None,
         }
+    }
 
-        // Verify that offset + len is within our storage bounds
-        if offset + len > storage_len {
-            return Err(Error::new(format!("Requested read offset of {off} and length {len} bytes exceeds vector length of {vlen}", off = offset, len = len, vlen = storage_len)));
-        }
+    /// Reads up to a maximum of length bytes at offset from this byte vector into the given buffer.
+    fn read(&self, buf: &mut [u8], offset: usize, len: usize) -> Result<usize, Error> {
+        // Verify that the read is within our storage bounds
+        check_range("read", offset, len, self.length())?;
 
         match *self {
-            StorageType::Empty => Err(Error::new("Cannot read from empty vector".to_string())),
+            // The range check above only admits zero-length reads
+            StorageType::Empty => Ok(0),
 
             StorageType::DirectValue {
                 ref bytes,
@@ -392,8 +1003,10 @@ impl StorageType {
                 ref vlen,
             } => {
                 // Verify that voffset + offset won't overflow
-                if std::usize::MAX - offset < *voffset {
-                    return Err(Error::new(format!("Requested read offset of {off} plus storage offset {voff} would overflow maximum value of usize", off = offset, voff = *voffset)));
+                if usize::MAX - offset < *voffset {
+                    return Err(Error::new(lazy_description(|| {
+                        format!("Requested read offset of {off} plus storage offset {voff} would overflow maximum value of usize", off = offset, voff = *voffset)
+                    })));
                 }
 
                 // Let the backing storage perform the read
@@ -431,6 +1044,59 @@ impl StorageType {
         }
     }
 
+    /// Writes `len` bytes at `offset` from this storage to the given writer. The caller is
+    /// responsible for ensuring that the requested range is within bounds.
+    fn write(&self, out: &mut dyn Write, offset: usize, len: usize) -> std::io::Result<()> {
+        if len == 0 {
+            return Ok(());
+        }
+
+        match *self {
+            StorageType::Empty => Ok(()),
+
+            StorageType::DirectValue { ref bytes, .. } => {
+                out.write_all(&bytes[offset..offset + len])
+            }
+
+            StorageType::Heap { ref bytes } => out.write_all(&bytes[offset..offset + len]),
+
+            StorageType::Append {
+                ref lhs, ref rhs, ..
+            } => {
+                // Write the portion that falls within lhs, followed by the portion that falls within rhs
+                let lhs_len = lhs.length();
+                if offset < lhs_len {
+                    let lcount = std::cmp::min(lhs_len - offset, len);
+                    lhs.write(out, offset, lcount)?;
+                    rhs.write(out, 0, len - lcount)
+                } else {
+                    rhs.write(out, offset - lhs_len, len)
+                }
+            }
+
+            StorageType::View {
+                ref vstorage,
+                ref voffset,
+                ..
+            } => vstorage.write(out, *voffset + offset, len),
+
+            StorageType::File { .. } => {
+                // Copy the file contents through a fixed-size buffer
+                let mut buf = vec![0u8; std::cmp::min(len, WRITE_CHUNK_SIZE)];
+                let mut pos = offset;
+                let end = offset + len;
+                while pos < end {
+                    let count = std::cmp::min(buf.len(), end - pos);
+                    self.read(&mut buf[..count], pos, count)
+                        .map_err(std::io::Error::from)?;
+                    out.write_all(&buf[..count])?;
+                    pos += count;
+                }
+                Ok(())
+            }
+        }
+    }
+
     /// Unsafe access by index.
     fn unsafe_get(&self, index: usize) -> u8 {
         let v: &mut [u8] = &mut [0];
@@ -452,17 +1118,13 @@ impl StorageType {
 // TODO: Statics can't refer to heap-allocated data, so we can't have a single instance here
 //pub static EMPTY: ByteVector = ByteVector { storage: Rc::new(StorageType::Empty) };
 pub fn empty() -> ByteVector {
-    ByteVector {
-        storage: Rc::new(StorageType::Empty),
-    }
+    ByteVector::from_storage(new_node(StorageType::Empty))
 }
 
 /// Returns a byte vector that consumes the contents of the given `Vec<u8>`.
 pub fn from_vec(bytes: Vec<u8>) -> ByteVector {
     let storage = StorageType::Heap { bytes };
-    ByteVector {
-        storage: Rc::new(storage),
-    }
+    ByteVector::from_storage(new_node(storage))
 }
 
 /// Returns a byte vector that stores a copy of the given bytes on the heap.
@@ -479,16 +1141,12 @@ pub fn from_slice_copy(bytes: &[u8]) -> ByteVector {
             bytes: bytes.to_owned(),
         }
     };
-    ByteVector {
-        storage: Rc::new(storage),
-    }
+    ByteVector::from_storage(new_node(storage))
 }
 
 /// Returns a byte vector that consumes the given slice, used to store primitive values directly.
 pub fn from_slice(bytes: [u8; DIRECT_VALUE_SIZE_LIMIT], length: usize) -> ByteVector {
-    ByteVector {
-        storage: Rc::new(StorageType::DirectValue { bytes, length }),
-    }
+    ByteVector::from_storage(new_node(StorageType::DirectValue { bytes, length }))
 }
 
 /// Returns a byte vector whose contents come from a file.
@@ -498,42 +1156,65 @@ pub fn file(path: &Path) -> Result<ByteVector, Error> {
         file <- File::open(path);
         metadata <- path.metadata();
     } yield {
-        ByteVector {
-            storage: Rc::new(StorageType::File {
-                file: WrappedFile {
-                    file: RefCell::new(file),
-                    path: format!("{}", path.display())
-                },
-                length: metadata.len() as usize
-            })
-        }
+        ByteVector::from_storage(new_node(StorageType::File {
+            file: WrappedFile {
+                file: RefCell::new(file),
+                path: format!("{}", path.display())
+            },
+            length: metadata.len() as usize
+        }))
     });
 
     // Wrap I/O error in an rcodec error, if needed
     result.map_err(|io_err| Error::new(format!("Failed to open file: {}", io_err)))
 }
 
+/// Returns a byte vector containing all bytes read from the given source until end-of-file.
+pub fn from_reader<R: Read>(reader: R) -> Result<ByteVector, Error> {
+    read_into_heap(reader, None)
+}
+
+/// Returns a byte vector containing all bytes read from the given source until end-of-file, or an
+/// error if the source produces more than `max` bytes.
+pub fn from_reader_limited<R: Read>(reader: R, max: usize) -> Result<ByteVector, Error> {
+    read_into_heap(reader, Some(max))
+}
+
+/// Reads from the given source into heap storage, failing if more than `max` bytes are available.
+fn read_into_heap<R: Read>(mut reader: R, max: Option<usize>) -> Result<ByteVector, Error> {
+    let mut bytes = Vec::new();
+    let result = match max {
+        // Read at most one byte more than the limit so that we can detect oversized input
+        Some(max) => reader
+            .take((max as u64).saturating_add(1))
+            .read_to_end(&mut bytes),
+        None => reader.read_to_end(&mut bytes),
+    };
+    result.map_err(|io_err| Error::new(format!("Failed to read from source: {}", io_err)))?;
+
+    match max {
+        Some(max) if bytes.len() > max => Err(Error::new(lazy_description(|| {
+            format!("Input exceeds maximum length of {max} bytes", max = max)
+        }))),
+        _ => Ok(from_vec(bytes)),
+    }
+}
+
 /// Returns a byte vector that contains the contents of `lhs` followed by the contents of `rhs`.
 pub fn append(lhs: &ByteVector, rhs: &ByteVector) -> ByteVector {
     if lhs.length() == 0 && rhs.length() == 0 {
         empty()
     } else if lhs.length() == 0 {
-        ByteVector {
-            storage: rhs.storage.clone(),
-        }
+        rhs.clone()
     } else if rhs.length() == 0 {
-        ByteVector {
-            storage: lhs.storage.clone(),
-        }
+        lhs.clone()
     } else {
         let storage = StorageType::Append {
-            lhs: lhs.storage.clone(),
-            rhs: rhs.storage.clone(),
-            len: lhs.storage.length() + rhs.storage.length(),
+            lhs: lhs.shared_storage(),
+            rhs: rhs.shared_storage(),
+            len: lhs.length() + rhs.length(),
         };
-        ByteVector {
-            storage: Rc::new(storage),
-        }
+        ByteVector::from_storage(new_node(storage))
     }
 }
 
@@ -542,9 +1223,7 @@ pub fn fill(value: u8, count: usize) -> ByteVector {
     let storage = StorageType::Heap {
         bytes: vec![value; count],
     };
-    ByteVector {
-        storage: Rc::new(storage),
-    }
+    ByteVector::from_storage(new_node(storage))
 }
 
 /// A replacement for the deprecated std::slice::bytes::copy_memory
@@ -575,6 +1254,32 @@ mod tests {
         assert_eq!(bv1, bv2);
     }
 
+    #[test]
+    fn conversions_should_work() {
+        let expected = byte_vector!(1, 2, 3);
+        let slice: &[u8] = &[1, 2, 3];
+        assert_eq!(ByteVector::from(vec![1, 2, 3]), expected);
+        assert_eq!(ByteVector::from(slice), expected);
+        assert_eq!(ByteVector::from([1, 2, 3]), expected);
+        assert_eq!(ByteVector::from(&[1, 2, 3]), expected);
+        assert_eq!(ByteVector::from("\u{1}\u{2}\u{3}"), expected);
+        assert_eq!(ByteVector::from(&expected), expected);
+    }
+
+    #[test]
+    fn comparison_with_slices_and_vecs_should_work() {
+        let bv = append(&byte_vector!(1, 2), &byte_vector!(3, 4));
+        let bytes: &[u8] = &[1, 2, 3, 4];
+        assert_eq!(bv, bytes);
+        assert_eq!(bv, *bytes);
+        assert_eq!(bv, vec![1u8, 2, 3, 4]);
+        assert_eq!(bytes, bv);
+        assert_eq!(*bytes, bv);
+        assert_eq!(vec![1u8, 2, 3, 4], bv);
+        assert_ne!(bv, vec![1u8, 2, 3]);
+        assert_ne!(bv, vec![1u8, 2, 3, 5]);
+    }
+
     #[test]
     fn debug_string_should_be_formatted_correctly() {
         assert_eq!("01020eff", format!("{:?}", byte_vector!(1, 2, 14, 255)))
@@ -696,6 +1401,121 @@ mod tests {
         // TODO: Also test overflow case
     }
 
+    #[test]
+    fn as_slice_should_work_for_contiguous_storage() {
+        assert_eq!(empty().as_slice(), Some(&[][..]));
+        assert_eq!(byte_vector!(1, 2, 3).as_slice(), Some(&[1, 2, 3][..]));
+
+        let direct = from_slice_copy(&[1, 2, 3, 4]);
+        assert_eq!(direct.drop(1).unwrap().as_slice(), Some(&[2, 3, 4][..]));
+
+        let heap = from_vec(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(heap.take(3).unwrap().as_slice(), Some(&[1, 2, 3][..]));
+    }
+
+    #[test]
+    fn as_slice_should_return_none_for_append_storage() {
+        let bv = append(&byte_vector!(1, 2), &byte_vector!(3, 4));
+        assert!(bv.as_slice().is_none());
+
+        // A view that falls entirely within one side is contiguous
+        assert_eq!(bv.drop(2).unwrap().as_slice(), Some(&[3, 4][..]));
+    }
+
+    #[test]
+    fn make_mut_should_modify_unique_storage_in_place() {
+        let mut bv = from_vec(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        let ptr = bv.as_slice().unwrap().as_ptr();
+        {
+            let bytes = bv.make_mut();
+            bytes[0] = 7;
+            assert_eq!(bytes.as_ptr(), ptr);
+        }
+        assert_eq!(bv, byte_vector!(7, 2, 3, 4, 5, 6, 7, 8, 9, 10));
+    }
+
+    #[test]
+    fn make_mut_should_not_affect_shared_storage() {
+        let original = append(&byte_vector!(1, 2), &byte_vector!(3, 4));
+        let mut bv = original.clone();
+        bv.make_mut()[3] = 9;
+        assert_eq!(bv, byte_vector!(1, 2, 3, 9));
+        assert_eq!(original, byte_vector!(1, 2, 3, 4));
+
+        let mut bv = empty();
+        assert!(bv.make_mut().is_empty());
+        assert_eq!(bv, empty());
+    }
+
+    #[test]
+    fn take_and_drop_should_share_storage_without_creating_views() {
+        let bv = from_vec((0..16).collect());
+        let remainder = bv.drop(3).unwrap().drop(4).unwrap();
+        let taken = remainder.take(5).unwrap();
+        assert!(Rc::ptr_eq(&bv.storage, &taken.storage));
+        assert_eq!(taken, byte_vector!(7, 8, 9, 10, 11));
+        assert_eq!(taken.as_slice(), Some(&[7, 8, 9, 10, 11][..]));
+        assert_eq!(taken.get_u8(4).unwrap(), 11);
+        assert_eq!(
+            taken.get_u16_be(4).unwrap_err().message(),
+            "Requested read offset of 4 and length 2 bytes exceeds vector length of 5"
+        );
+        assert!(taken.drop(6).is_err());
+
+        // Windows of a vector remain correct when appended or written out
+        let appended = append(&taken, &bv.take(2).unwrap());
+        assert_eq!(appended, byte_vector!(7, 8, 9, 10, 11, 0, 1));
+        let mut out = Vec::new();
+        taken.write_to(&mut out).unwrap();
+        assert_eq!(out, vec![7, 8, 9, 10, 11]);
+
+        // Modifying a window copies only its contents
+        let mut window = taken.clone();
+        window.make_mut()[0] = 0xff;
+        assert_eq!(window, byte_vector!(0xff, 8, 9, 10, 11));
+        assert_eq!(bv.get_u8(7).unwrap(), 7);
+    }
+
+    #[test]
+    fn with_storage_pool_should_reuse_released_nodes() {
+        let lhs = from_vec(vec![1, 2, 3]);
+        let rhs = from_vec(vec![4, 5, 6]);
+        with_storage_pool(|| {
+            let bv = append(&lhs, &rhs);
+            let node = Rc::as_ptr(&bv.storage);
+            drop(bv);
+
+            // The released node is reused for the next vector
+            let bv = append(&rhs, &lhs);
+            assert_eq!(Rc::as_ptr(&bv.storage), node);
+            assert_eq!(bv, byte_vector!(4, 5, 6, 1, 2, 3));
+
+            // Nodes that are still referenced are not reused
+            let shared = bv.clone();
+            drop(bv);
+            let other = append(&lhs, &lhs);
+            assert_ne!(Rc::as_ptr(&other.storage), node);
+            assert_eq!(shared, byte_vector!(4, 5, 6, 1, 2, 3));
+            assert_eq!(other, byte_vector!(1, 2, 3, 1, 2, 3));
+
+            // Nodes that own heap buffers are never retained
+            drop(from_vec(vec![7; 16]));
+            STORAGE_POOL.with(|p| assert!(p.borrow().as_ref().unwrap().is_empty()));
+
+            // Retained nodes do not keep the storage they referred to alive
+            let heap = from_vec(vec![8; 16]);
+            drop(append(&heap, &lhs));
+            assert_eq!(Rc::strong_count(&heap.storage), 1);
+            STORAGE_POOL.with(|p| assert_eq!(p.borrow().as_ref().unwrap().len(), 1));
+        });
+
+        // The pool is released afterwards
+        STORAGE_POOL.with(|p| assert!(p.borrow().is_none()));
+        assert!(!POOLING.with(Cell::get));
+        drop(append(&lhs, &rhs));
+        STORAGE_POOL.with(|p| assert!(p.borrow().is_none()));
+    }
+
     #[test]
     fn to_vec_should_work() {
         let input = vec![1, 2, 3, 4];
@@ -707,6 +1527,18 @@ mod tests {
         assert_eq!(result.unwrap(), vec!(1, 2, 3, 4, 1, 2, 3, 4));
     }
 
+    #[test]
+    fn to_vec_and_zero_length_reads_should_work_for_empty_vector() {
+        let bv = empty();
+        assert_eq!(bv.to_vec().unwrap(), Vec::<u8>::new());
+        assert_eq!(bv.read(&mut [], 0, 0).unwrap(), 0);
+        assert!(bv.read(&mut [0], 0, 1).is_err());
+        assert_eq!(
+            from_vec(vec![1, 2]).drop(2).unwrap().to_vec().unwrap(),
+            Vec::<u8>::new()
+        );
+    }
+
     #[test]
     fn take_should_fail_if_length_is_invalid() {
         let bv = byte_vector!(1, 2, 3, 4);
@@ -744,6 +1576,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn take_max_should_return_available_bytes() {
+        let bv = byte_vector!(1, 2, 3, 4);
+        assert_eq!(bv.take_max(2), byte_vector!(1, 2));
+        assert_eq!(bv.take_max(4), byte_vector!(1, 2, 3, 4));
+        assert_eq!(bv.take_max(10), byte_vector!(1, 2, 3, 4));
+        assert_eq!(empty().take_max(3), empty());
+    }
+
+    #[test]
+    fn truncate_should_work() {
+        let bv = append(&byte_vector!(1, 2), &byte_vector!(3, 4));
+        assert_eq!(bv.truncate(3), byte_vector!(1, 2, 3));
+        assert_eq!(bv.truncate(0), empty());
+        assert_eq!(bv.truncate(5), byte_vector!(1, 2, 3, 4));
+    }
+
     #[test]
     fn drop_should_fail_if_length_is_invalid() {
         let bv = byte_vector!(1, 2, 3, 4);
@@ -815,6 +1664,147 @@ mod tests {
         );
     }
 
+    #[test]
+    fn primitive_accessors_should_work() {
+        let lhs = from_slice_copy(&[0xff, 0x12, 0x34]);
+        let rhs = from_slice_copy(&[0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x01]);
+        let bv = append(&lhs, &rhs);
+        assert_eq!(bv.get_u8(1).unwrap(), 0x12);
+        assert_eq!(bv.get_i8(0).unwrap(), -1);
+        assert_eq!(bv.get_u16_be(1).unwrap(), 0x1234);
+        assert_eq!(bv.get_u16_le(1).unwrap(), 0x3412);
+        assert_eq!(bv.get_i16_be(0).unwrap(), -238);
+        assert_eq!(bv.get_u32_be(1).unwrap(), 0x1234_5678);
+        assert_eq!(bv.get_u32_le(1).unwrap(), 0x7856_3412);
+        assert_eq!(bv.get_u64_be(1).unwrap(), 0x1234_5678_9abc_def0);
+        assert_eq!(bv.get_i64_le(2).unwrap(), 0x01f0_debc_9a78_5634);
+    }
+
+    #[test]
+    fn primitive_accessors_should_fail_if_offset_is_out_of_bounds() {
+        let bv = byte_vector!(1, 2, 3, 4);
+        assert!(bv.get_u32_be(0).is_ok());
+        assert!(bv.get_u32_be(1).is_err());
+        assert!(bv.get_u8(4).is_err());
+    }
+
+    #[test]
+    fn windows_should_work() {
+        let bv = append(&byte_vector!(1, 2), &byte_vector!(3, 4));
+        let windows: Vec<ByteVector> = bv.windows(3).collect();
+        assert_eq!(windows, vec![byte_vector!(1, 2, 3), byte_vector!(2, 3, 4)]);
+        assert_eq!(bv.windows(1).count(), 4);
+        assert_eq!(bv.windows(4).count(), 1);
+        assert_eq!(bv.windows(5).count(), 0);
+        assert_eq!(bv.windows(2).size_hint(), (3, Some(3)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn windows_should_panic_if_size_is_zero() {
+        byte_vector!(1, 2).windows(0);
+    }
+
+    #[test]
+    fn to_file_should_work() {
+        let path = Path::new("/tmp/rcodec-test-to-file");
+
+        let lhs = from_slice_copy(&[1, 2, 3, 4]);
+        let rhs = from_vec(vec![5, 6, 7, 8, 9, 10, 11, 12, 13]);
+        let bv = append(&lhs, &rhs).drop(1).unwrap();
+        assert!(bv.to_file(path).is_ok());
+        assert_eq!(
+            fs::read(path).unwrap(),
+            vec![2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13]
+        );
+
+        let _ignore = fs::remove_file(path);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn to_file_should_report_errors_when_flushing() {
+        // Writes to /dev/full fail with ENOSPC, so the buffered bytes only fail once flushed
+        let err = byte_vector!(1, 2, 3)
+            .to_file(Path::new("/dev/full"))
+            .unwrap_err();
+        assert!(err.message().starts_with("Failed to write file: "));
+    }
+
+    #[test]
+    fn split_should_work() {
+        let bv = byte_vector!(1, 0, 2, 3, 0, 0, 4);
+        let parts: Vec<ByteVector> = bv.split(0).collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            parts,
+            vec![
+                byte_vector!(1),
+                byte_vector!(2, 3),
+                empty(),
+                byte_vector!(4)
+            ]
+        );
+
+        let trailing: Vec<ByteVector> = byte_vector!(1, 0)
+            .split(0)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(trailing, vec![byte_vector!(1), empty()]);
+
+        let none: Vec<ByteVector> = byte_vector!(1, 2)
+            .split(0)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(none, vec![byte_vector!(1, 2)]);
+    }
+
+    #[test]
+    fn split_on_slice_should_work_across_append_boundaries() {
+        let lhs = from_slice_copy(&[1, 2, 0x0d]);
+        let rhs = from_slice_copy(&[0x0a, 3, 0x0d, 0x0a, 4]);
+        let bv = append(&lhs, &rhs);
+        let parts: Vec<ByteVector> = bv
+            .split_on_slice(&byte_vector!(0x0d, 0x0a))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            parts,
+            vec![byte_vector!(1, 2), byte_vector!(3), byte_vector!(4)]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn split_should_yield_storage_read_errors() {
+        // Reading from a directory fails, which exercises the error path of the chunked search
+        let dir = file(Path::new(env!("CARGO_MANIFEST_DIR")).join("src").as_path()).unwrap();
+        let mut parts = dir.split(b'\n');
+        assert!(parts.next().unwrap().is_err());
+        assert!(parts.next().is_none());
+        assert!(byte_vector!(1).split_on_slice(&dir).is_err());
+    }
+
+    #[test]
+    fn from_reader_should_work() {
+        let input: &[u8] = &[1, 2, 3, 4, 5];
+        assert_eq!(from_reader(input).unwrap(), byte_vector!(1, 2, 3, 4, 5));
+        assert_eq!(from_reader(&[][..]).unwrap(), empty());
+    }
+
+    #[test]
+    fn from_reader_limited_should_fail_if_input_is_too_long() {
+        let input: &[u8] = &[1, 2, 3, 4, 5];
+        assert_eq!(
+            from_reader_limited(input, 5).unwrap(),
+            byte_vector!(1, 2, 3, 4, 5)
+        );
+        assert_eq!(
+            from_reader_limited(input, 4).unwrap_err().message(),
+            "Input exceeds maximum length of 4 bytes"
+        );
+    }
+
     #[test]
     fn file_should_work() {
         use std::io::Write;
@@ -839,6 +1829,6 @@ mod tests {
         assert!(dropped.is_ok());
         assert_eq!(dropped.unwrap(), byte_vector!(6, 7, 8, 9, 10));
 
-        let _ignore = fs::remove_file(&path);
+        let _ignore = fs::remove_file(path);
     }
 }
@@ -0,0 +1,168 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+/// A checksum algorithm that is fed bytes incrementally and then produces a checksum value.
+///
+/// Values of a `Checksum` type represent the initial state of the algorithm; they are cloned
+/// each time a new checksum is computed.
+pub trait Checksum: Clone {
+    /// The checksum value type.
+    type Output;
+
+    /// Feeds the given bytes into the checksum.
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Consumes the checksum state and returns the resulting checksum value.
+    fn finalize(self) -> Self::Output;
+
+    /// Returns the checksum of the given bytes, starting from a clone of this state.
+    fn checksum(&self, bytes: &[u8]) -> Self::Output {
+        let mut state = self.clone();
+        state.update(bytes);
+        state.finalize()
+    }
+}
+
+/// Simple 8-bit checksum that sums all bytes, modulo 256.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sum8 {
+    sum: u8,
+}
+
+impl Checksum for Sum8 {
+    type Output = u8;
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.sum = self.sum.wrapping_add(b);
+        }
+    }
+
+    fn finalize(self) -> u8 {
+        self.sum
+    }
+}
+
+/// Fletcher-16 checksum.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Fletcher16 {
+    sum1: u16,
+    sum2: u16,
+}
+
+impl Checksum for Fletcher16 {
+    type Output = u16;
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.sum1 = (self.sum1 + b as u16) % 255;
+            self.sum2 = (self.sum2 + self.sum1) % 255;
+        }
+    }
+
+    fn finalize(self) -> u16 {
+        (self.sum2 << 8) | self.sum1
+    }
+}
+
+/// Adler-32 checksum, as used by zlib.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+const ADLER_MODULUS: u32 = 65521;
+
+impl Default for Adler32 {
+    fn default() -> Adler32 {
+        Adler32 { a: 1, b: 0 }
+    }
+}
+
+impl Checksum for Adler32 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.a = (self.a + b as u32) % ADLER_MODULUS;
+            self.b = (self.b + self.a) % ADLER_MODULUS;
+        }
+    }
+
+    fn finalize(self) -> u32 {
+        (self.b << 16) | self.a
+    }
+}
+
+/// Internet checksum (RFC 1071), as used in IPv4, ICMP, TCP, and UDP headers: the ones' complement
+/// of the ones' complement sum of all 16-bit big-endian words, with odd-length input padded with a
+/// zero byte.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InternetChecksum {
+    sum: u64,
+    pending: Option<u8>,
+}
+
+impl Checksum for InternetChecksum {
+    type Output = u16;
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            match self.pending.take() {
+                Some(high) => self.sum += ((high as u64) << 8) | b as u64,
+                None => self.pending = Some(b),
+            }
+        }
+    }
+
+    fn finalize(self) -> u16 {
+        let mut sum = self.sum + self.pending.map_or(0, |high| (high as u64) << 8);
+        while sum >> 16 != 0 {
+            sum = (sum & 0xffff) + (sum >> 16);
+        }
+        !(sum as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums_should_produce_known_values() {
+        assert_eq!(Sum8::default().checksum(&[0xff, 0x02, 0x03]), 0x04);
+        assert_eq!(Fletcher16::default().checksum(b"abcde"), 0xc8f0);
+        assert_eq!(Fletcher16::default().checksum(b"abcdef"), 0x2057);
+        assert_eq!(Adler32::default().checksum(b"Wikipedia"), 0x11e6_0398);
+
+        // IPv4 header with the checksum field omitted
+        let header = [
+            0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0xc0, 0xa8, 0x00, 0x01,
+            0xc0, 0xa8, 0x00, 0xc7,
+        ];
+        assert_eq!(InternetChecksum::default().checksum(&header), 0xb861);
+    }
+
+    #[test]
+    fn incremental_updates_should_match_single_update() {
+        let mut adler = Adler32::default();
+        adler.update(b"Wiki");
+        adler.update(b"pedia");
+        assert_eq!(adler.finalize(), Adler32::default().checksum(b"Wikipedia"));
+
+        // Words may be split across updates
+        let mut internet = InternetChecksum::default();
+        internet.update(&[0x12]);
+        internet.update(&[0x34, 0x56]);
+        assert_eq!(
+            internet.finalize(),
+            InternetChecksum::default().checksum(&[0x12, 0x34, 0x56])
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+use crate::error::Error;
+
+/// A reversible transformation between plaintext and ciphertext bytes.
+pub trait Cipher {
+    /// Attempts to encrypt the given plaintext.
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// Attempts to decrypt the given ciphertext.
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+/// Cipher that XORs each byte with the corresponding byte of a repeating key.
+///
+/// This provides no real security, but is commonly used for obfuscation in file formats.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Xor {
+    key: Vec<u8>,
+}
+
+impl Xor {
+    /// Returns a cipher that uses the given key, which must not be empty.
+    pub fn new(key: &[u8]) -> Result<Xor, Error> {
+        if key.is_empty() {
+            Err(Error::new("XOR key must not be empty".to_string()))
+        } else {
+            Ok(Xor { key: key.to_vec() })
+        }
+    }
+
+    fn apply(&self, bytes: &[u8]) -> Vec<u8> {
+        bytes
+            .iter()
+            .zip(self.key.iter().cycle())
+            .map(|(b, k)| b ^ k)
+            .collect()
+    }
+}
+
+impl Cipher for Xor {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(self.apply(plaintext))
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(self.apply(ciphertext))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xor_cipher_should_be_reversible() {
+        let cipher = Xor::new(&[0x0f, 0xf0]).unwrap();
+        let encrypted = cipher.encrypt(&[1, 2, 3]).unwrap();
+        assert_eq!(encrypted, vec![0x0e, 0xf2, 0x0c]);
+        assert_eq!(cipher.decrypt(&encrypted).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn xor_cipher_should_reject_empty_key() {
+        assert!(Xor::new(&[]).is_err());
+    }
+}
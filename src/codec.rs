@@ -9,19 +9,36 @@
 // The following allows for non-uppercase constants (e.g. uint32_l vs UINT32_L).
 #![allow(non_upper_case_globals)]
 
-use std::fmt::Display;
+use std::any::{type_name, Any};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+use std::io::Write;
+use std::iter;
 use std::marker::PhantomData;
 use std::mem::size_of;
+use std::ops::{Range, RangeBounds};
 use std::ptr;
+use std::rc::Rc;
 use std::slice;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use num_traits::{FromPrimitive, PrimInt, Unsigned};
+use num_traits::{FromPrimitive, PrimInt, Signed, ToPrimitive, Unsigned};
 
 use pl_hlist::*;
 
 use crate::byte_vector;
 use crate::byte_vector::ByteVector;
-use crate::error::Error;
+use crate::checksum::Checksum;
+use crate::cipher::Cipher;
+use crate::crc::{self, Crc};
+use crate::cursor;
+use crate::error::{self, lazy_description, Error};
+use crate::limits::{self, DecodeLimits, DepthGuard};
+use crate::metrics::{Event, MetricsSink, Operation};
+use crate::validation;
 
 /// Implements encoding and decoding of values of type `Value`.
 pub trait Codec {
@@ -33,6 +50,245 @@ pub trait Codec {
 
     /// Attempts to decode a value of type `Value` from the given `ByteVector`.
     fn decode(&self, bv: &ByteVector) -> DecodeResult<Self::Value>;
+
+    /// Attempts to encode a value of type `Value`, appending the encoded bytes to `out`.  If
+    /// encoding fails, `out` may contain a partial encoding.
+    ///
+    /// The default implementation calls `encode` and copies the result into `out`.  Built-in codecs
+    /// override this to write directly into `out`, which avoids constructing intermediate
+    /// `ByteVector`s for each field when encoding large structures.
+    fn encode_into(&self, value: &Self::Value, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.encode(value)
+            .and_then(|encoded| append_to_vec(&encoded, out))
+    }
+
+    /// Attempts to encode a value of type `Value`, writing the encoded bytes to `out` (e.g. a file
+    /// or socket).  If encoding fails, a partial encoding may already have been written.
+    ///
+    /// The default implementation calls `encode_into` with a temporary buffer and writes the
+    /// buffer to `out`.  Built-in codecs for compound values (tuples, HLists, structs and
+    /// collections) override this to write each part as soon as it is encoded, so that the
+    /// complete encoding never needs to be held in memory at once.
+    fn encode_to(&self, value: &Self::Value, out: &mut dyn Write) -> Result<(), Error> {
+        let mut buf = Vec::new();
+        self.encode_into(value, &mut buf)?;
+        write_bytes(&buf, out)
+    }
+
+    /// Returns the number of sections in the encoding of a value, each of which can be encoded
+    /// independently of the others using `encode_sections_into` (see `parallel::encode_parallel`).
+    ///
+    /// The default implementation returns 1.  Built-in codecs for HLists and structs override this
+    /// to return one section per field.
+    fn section_count(&self) -> usize {
+        1
+    }
+
+    /// Attempts to encode the sections of a value with indices in `range`, appending the encoded
+    /// bytes to `out`.  Encoding consecutive ranges and concatenating the results produces the same
+    /// bytes as `encode_into`.
+    ///
+    /// The default implementation calls `encode_into` if `range` contains the only section.
+    fn encode_sections_into(
+        &self,
+        value: &Self::Value,
+        range: Range<usize>,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        if range.contains(&0) {
+            self.encode_into(value, out)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Attempts to decode a value of type `Value` from the start of the given bytes, returning the
+    /// value along with the number of bytes that were consumed.
+    ///
+    /// The default implementation copies the bytes into a `ByteVector` and calls `decode`.  Built-in
+    /// codecs override this to read directly from the slice, which avoids constructing a
+    /// `ByteVector` view for each field.
+    fn decode_slice(&self, input: &[u8]) -> Result<(Self::Value, usize), Error> {
+        self.decode(&byte_vector::from_slice_copy(input))
+            .map(|decoded| (decoded.value, input.len() - decoded.remainder.length()))
+    }
+
+    /// Returns the number of bytes that this codec would decode verbatim as its value from input
+    /// with `available` bytes, if it decodes by taking a prefix of its input unchanged (as `bytes`
+    /// and `identity_bytes` do).  This allows wrappers such as `eager` to read those bytes
+    /// directly into their own buffer.
+    ///
+    /// The default implementation returns `None`.
+    fn verbatim_len(&self, _available: usize) -> Option<usize> {
+        None
+    }
+
+    /// Returns bounds on the number of bytes produced by `encode` (and consumed by `decode`).
+    ///
+    /// The default implementation returns `SizeBound::unknown()`.
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::unknown()
+    }
+
+    /// Returns a structured description of this codec's layout, for use by tooling such as schema
+    /// documentation generators and inspectors.
+    ///
+    /// The default implementation describes the codec using its type name and `size_bound`.
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new(type_name::<Self>(), self.size_bound())
+    }
+}
+
+/// Lower and (optional) upper bounds on the size in bytes of an encoded value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SizeBound {
+    /// The minimum size, in bytes.
+    pub lower: u64,
+
+    /// The maximum size, in bytes, or `None` if there is no known maximum.
+    pub upper: Option<u64>,
+}
+
+impl SizeBound {
+    /// Returns a bound for values that always occupy exactly `size` bytes.
+    pub fn exact(size: u64) -> SizeBound {
+        SizeBound {
+            lower: size,
+            upper: Some(size),
+        }
+    }
+
+    /// Returns a bound for values that occupy at least `lower` bytes.
+    pub fn at_least(lower: u64) -> SizeBound {
+        SizeBound { lower, upper: None }
+    }
+
+    /// Returns a bound for values that occupy between `lower` and `upper` bytes (inclusive).
+    pub fn bounded(lower: u64, upper: u64) -> SizeBound {
+        SizeBound {
+            lower,
+            upper: Some(upper),
+        }
+    }
+
+    /// Returns a bound for values whose size is not known in advance.
+    pub fn unknown() -> SizeBound {
+        SizeBound::at_least(0)
+    }
+
+    /// Returns the bound for a value that is described by either this bound or `other`.
+    pub fn or(self, other: SizeBound) -> SizeBound {
+        SizeBound {
+            lower: self.lower.min(other.lower),
+            upper: self.upper.and_then(|a| other.upper.map(|b| a.max(b))),
+        }
+    }
+
+    /// Returns the bound for `count` consecutive values described by this bound.
+    pub fn times(self, count: u64) -> SizeBound {
+        SizeBound {
+            lower: self.lower.saturating_mul(count),
+            upper: self.upper.and_then(|upper| upper.checked_mul(count)),
+        }
+    }
+}
+
+impl core::ops::Add for SizeBound {
+    type Output = SizeBound;
+
+    /// Returns the bound for a value described by this bound followed by one described by `rhs`.
+    fn add(self, rhs: SizeBound) -> SizeBound {
+        SizeBound {
+            lower: self.lower.saturating_add(rhs.lower),
+            upper: self
+                .upper
+                .and_then(|a| rhs.upper.and_then(|b| a.checked_add(b))),
+        }
+    }
+}
+
+/// A tree describing the layout of a codec, as returned by `Codec::describe`.
+///
+/// Two descriptions compare equal when the codecs they describe have the same structure, which
+/// can be used to check that both ends of a protocol agree on its layout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodecDescription {
+    /// The name of the codec, which is usually the name of the function that constructs it.
+    pub name: String,
+
+    /// Bounds on the size of values encoded by the codec.
+    pub size: SizeBound,
+
+    /// The byte ordering used by the codec, if it encodes multi-byte values in a fixed order.
+    pub byte_ordering: Option<ByteOrdering>,
+
+    /// Descriptions of the codecs that this codec is composed of, in encoding order.
+    pub children: Vec<CodecDescription>,
+}
+
+impl CodecDescription {
+    /// Returns a description of a codec with the given name and size bound and no children.
+    pub fn new<N: Into<String>>(name: N, size: SizeBound) -> CodecDescription {
+        CodecDescription {
+            name: name.into(),
+            size,
+            byte_ordering: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Returns this description with the given byte ordering.
+    pub fn with_byte_ordering(self, byte_ordering: Option<ByteOrdering>) -> CodecDescription {
+        CodecDescription {
+            byte_ordering,
+            ..self
+        }
+    }
+
+    /// Returns this description with the given child appended.
+    pub fn with_child(mut self, child: CodecDescription) -> CodecDescription {
+        self.children.push(child);
+        self
+    }
+
+    /// Returns this description with the given children appended.
+    pub fn with_children<I>(mut self, children: I) -> CodecDescription
+    where
+        I: IntoIterator<Item = CodecDescription>,
+    {
+        self.children.extend(children);
+        self
+    }
+
+    /// Returns the size of values encoded by the codec, if it is always the same.
+    pub fn fixed_size(&self) -> Option<u64> {
+        self.size.upper.filter(|upper| *upper == self.size.lower)
+    }
+
+    fn fmt_indented(&self, f: &mut Formatter, depth: usize) -> std::fmt::Result {
+        write!(f, "{:indent$}{}", "", self.name, indent = depth * 2)?;
+        match self.byte_ordering {
+            Some(ByteOrdering::BigEndian) => write!(f, " (big-endian)")?,
+            Some(ByteOrdering::LittleEndian) => write!(f, " (little-endian)")?,
+            None => {}
+        }
+        match (self.size.lower, self.size.upper) {
+            (lower, Some(upper)) if lower == upper => writeln!(f, ": {} bytes", lower)?,
+            (lower, Some(upper)) => writeln!(f, ": {}..={} bytes", lower, upper)?,
+            (0, None) => writeln!(f, ": variable size")?,
+            (lower, None) => writeln!(f, ": at least {} bytes", lower)?,
+        }
+        self.children
+            .iter()
+            .try_for_each(|child| child.fmt_indented(f, depth + 1))
+    }
+}
+
+/// Formats the description as an indented tree, with one line per codec.
+impl Display for CodecDescription {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        self.fmt_indented(f, 0)
+    }
 }
 
 /// A result type returned by `encode` operations.
@@ -51,42 +307,333 @@ pub struct DecoderResult<V> {
 /// A result type returned by `decode` operations.
 pub type DecodeResult<V> = Result<DecoderResult<V>, Error>;
 
-// Automatically provides implementation of `Codec` trait for all `Box<Codec>`.
-impl<C: Codec + ?Sized> Codec for Box<C> {
-    type Value = C::Value;
+/// Convenience methods for encoding to and decoding from plain byte slices, available on all codecs.
+pub trait CodecExt: Codec {
+    /// Attempts to decode a value of type `Value` from the given bytes, returning an error if any
+    /// bytes remain unconsumed.
+    fn decode_all(&self, bytes: &[u8]) -> Result<Self::Value, Error> {
+        let (value, consumed) = self.decode_slice(bytes)?;
+        check_complete(bytes.len() - consumed).map(|_| value)
+    }
 
-    #[inline(always)]
-    fn encode(&self, value: &Self::Value) -> EncodeResult {
-        (**self).encode(value)
+    /// Attempts to encode a value of type `Value` into a `Vec<u8>`.
+    fn encode_to_vec(&self, value: &Self::Value) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::new();
+        self.encode_into(value, &mut out).map(|_| out)
     }
 
-    #[inline(always)]
-    fn decode(&self, bv: &ByteVector) -> DecodeResult<Self::Value> {
-        (**self).decode(bv)
+    /// Injects additional context into errors produced by this codec; see `with_context`.
+    fn with_context(self, context: &'static str) -> impl Codec<Value = Self::Value>
+    where
+        Self: Sized,
+    {
+        with_context(context, self)
+    }
+
+    /// Reports metrics for each operation performed by this codec to `sink`; see `instrumented`.
+    fn instrumented<S>(self, name: &'static str, sink: S) -> impl Codec<Value = Self::Value>
+    where
+        Self: Sized,
+        S: MetricsSink,
+    {
+        instrumented(name, self, sink)
+    }
+
+    /// Decodes values using this codec with the given resource limits in effect; see `limited`.
+    fn limited(self, limits: DecodeLimits) -> impl Codec<Value = Self::Value>
+    where
+        Self: Sized,
+    {
+        limited(limits, self)
+    }
+
+    /// Encodes and decodes values using this codec with terse errors enabled; see `terse`.
+    fn terse(self) -> impl Codec<Value = Self::Value>
+    where
+        Self: Sized,
+    {
+        terse(self)
+    }
+
+    /// Converts this codec into a codec for values of type `B`; see `xmap`.
+    fn xmap<B, F, G>(self, f: F, g: G) -> impl Codec<Value = B>
+    where
+        Self: Sized,
+        F: Fn(Self::Value) -> B,
+        G: Fn(&B) -> Self::Value,
+    {
+        xmap(self, f, g)
+    }
+
+    /// Converts this codec into a codec for values of type `B` using partial conversions; see
+    /// `exmap`.
+    fn exmap<B, F, G>(self, f: F, g: G) -> impl Codec<Value = B>
+    where
+        Self: Sized,
+        F: Fn(Self::Value) -> Result<B, Error>,
+        G: Fn(&B) -> Result<Self::Value, Error>,
+    {
+        exmap(self, f, g)
+    }
+
+    /// Checks decoded values against `predicate`; see `validated`.
+    fn validated<P>(self, predicate: P, message: &'static str) -> impl Codec<Value = Self::Value>
+    where
+        Self: Sized,
+        P: Fn(&Self::Value) -> bool,
+    {
+        validated(self, predicate, message)
+    }
+
+    /// Limits this codec to exactly `len` bytes; see `fixed_size_bytes`.
+    fn fixed_size(self, len: usize) -> impl Codec<Value = Self::Value>
+    where
+        Self: Sized,
+    {
+        fixed_size_bytes(len, self)
+    }
+
+    /// Prefixes values encoded by this codec with their length, encoded using `len_codec`; see
+    /// `variable_size_bytes`.
+    fn framed_by<L, LC>(self, len_codec: LC) -> impl Codec<Value = Self::Value>
+    where
+        Self: Sized,
+        L: PrimInt + Unsigned + FromPrimitive + Display,
+        LC: Codec<Value = L>,
+    {
+        variable_size_bytes(len_codec, self)
+    }
+
+    /// Pairs values of this codec with values of a codec derived from them; see `flat_zip`.
+    fn flat_zip<B, BC, F>(self, f: F) -> impl Codec<Value = (Self::Value, B)>
+    where
+        Self: Sized,
+        BC: Codec<Value = B>,
+        F: Fn(&Self::Value) -> BC,
+    {
+        flat_zip(self, f)
+    }
+
+    /// Boxes this codec, erasing its concrete type.
+    fn boxed<'a>(self) -> Box<dyn Codec<Value = Self::Value> + 'a>
+    where
+        Self: Sized + 'a,
+    {
+        Box::new(self)
+    }
+
+    /// Wraps this codec in a reference-counted pointer, erasing its concrete type, so that it can
+    /// be cloned cheaply and shared between multiple owners (e.g. a registry and its decoders).
+    fn shared<'a>(self) -> Rc<dyn Codec<Value = Self::Value> + 'a>
+    where
+        Self: Sized + 'a,
+    {
+        Rc::new(self)
+    }
+}
+
+impl<C: Codec + ?Sized> CodecExt for C {}
+
+/// A type-erased codec that can be shared between threads.
+pub type SyncCodec<'a, T> = dyn Codec<Value = T> + Send + Sync + 'a;
+
+/// An owned, type-erased codec that can be sent between threads.
+pub type BoxedSyncCodec<'a, T> = Box<SyncCodec<'a, T>>;
+
+/// A reference-counted, type-erased codec that can be shared between threads.
+pub type SharedSyncCodec<'a, T> = Arc<SyncCodec<'a, T>>;
+
+/// Appends the contents of the given byte vector to `out`.
+fn append_to_vec(bv: &ByteVector, out: &mut Vec<u8>) -> Result<(), Error> {
+    let len = bv.length();
+    if len == 0 {
+        return Ok(());
+    }
+    let start = out.len();
+    out.resize(start + len, 0);
+    bv.read(&mut out[start..], 0, len).map(|_| ())
+}
+
+/// Writes the given bytes to `out`.
+fn write_bytes(bytes: &[u8], out: &mut dyn Write) -> Result<(), Error> {
+    out.write_all(bytes)
+        .map_err(|io_err| Error::new(format!("Failed to write bytes: {}", io_err)))
+}
+
+/// Returns the byte at the given index of the given slice, or an error if it is out of bounds.
+fn slice_u8(input: &[u8], index: usize) -> Result<u8, Error> {
+    slice_prefix(input, index + 1).map(|bytes| bytes[index])
+}
+
+/// Returns the first `len` bytes of the given slice, or an error if there are not enough bytes.
+fn slice_prefix(input: &[u8], len: usize) -> Result<&[u8], Error> {
+    input.get(..len).ok_or_else(|| {
+        Error::short_input(
+            lazy_description(|| {
+                format!(
+                    "Requested read offset of 0 and length {} bytes exceeds vector length of {}",
+                    len,
+                    input.len()
+                )
+            }),
+            input.len(),
+            len,
+        )
+    })
+}
+
+/// Encodes the given value by way of `encode_into`, for use by codecs that are composed of other
+/// codecs.
+fn encode_via_buffer<C: Codec + ?Sized>(codec: &C, value: &C::Value) -> EncodeResult {
+    let mut out = Vec::new();
+    codec
+        .encode_into(value, &mut out)
+        .map(|_| byte_vector::from_vec(out))
+}
+
+/// Converts a range of section indices for a compound value whose first section is its head into
+/// the corresponding range of section indices for the remainder of the value.
+fn tail_sections(range: Range<usize>) -> Range<usize> {
+    range.start.saturating_sub(1)..range.end.saturating_sub(1)
+}
+
+// Provides implementations of the `Codec` trait for smart pointers to codecs, which delegate to
+// the pointee.
+macro_rules! pointer_codec_impl {
+    { $ptr:ident } => {
+        impl<C: Codec + ?Sized> Codec for $ptr<C> {
+            type Value = C::Value;
+
+            fn size_bound(&self) -> SizeBound {
+                (**self).size_bound()
+            }
+
+            fn describe(&self) -> CodecDescription {
+                (**self).describe()
+            }
+
+            #[inline(always)]
+            fn encode(&self, value: &Self::Value) -> EncodeResult {
+                (**self).encode(value)
+            }
+
+            #[inline(always)]
+            fn encode_into(&self, value: &Self::Value, out: &mut Vec<u8>) -> Result<(), Error> {
+                (**self).encode_into(value, out)
+            }
+
+            #[inline(always)]
+            fn encode_to(&self, value: &Self::Value, out: &mut dyn Write) -> Result<(), Error> {
+                (**self).encode_to(value, out)
+            }
+
+            fn section_count(&self) -> usize {
+                (**self).section_count()
+            }
+
+            fn encode_sections_into(
+                &self,
+                value: &Self::Value,
+                range: Range<usize>,
+                out: &mut Vec<u8>,
+            ) -> Result<(), Error> {
+                (**self).encode_sections_into(value, range, out)
+            }
+
+            #[inline(always)]
+            fn decode(&self, bv: &ByteVector) -> DecodeResult<Self::Value> {
+                (**self).decode(bv)
+            }
+
+            #[inline(always)]
+            fn decode_slice(&self, input: &[u8]) -> Result<(Self::Value, usize), Error> {
+                (**self).decode_slice(input)
+            }
+
+            fn verbatim_len(&self, available: usize) -> Option<usize> {
+                (**self).verbatim_len(available)
+            }
+        }
     }
 }
 
+// Automatically provides implementation of `Codec` trait for all `Box<Codec>`, `Rc<Codec>` and
+// `Arc<Codec>`.
+pointer_codec_impl!(Box);
+pointer_codec_impl!(Rc);
+pointer_codec_impl!(Arc);
+
 // Automatically provides implementation of `Codec` trait for all `&'static Codec`.
 impl<C: Codec + ?Sized> Codec for &'static C {
     type Value = C::Value;
 
+    fn size_bound(&self) -> SizeBound {
+        (*self).size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        (*self).describe()
+    }
+
     #[inline(always)]
     fn encode(&self, value: &Self::Value) -> EncodeResult {
         (*self).encode(value)
     }
 
+    #[inline(always)]
+    fn encode_into(&self, value: &Self::Value, out: &mut Vec<u8>) -> Result<(), Error> {
+        (*self).encode_into(value, out)
+    }
+
+    #[inline(always)]
+    fn encode_to(&self, value: &Self::Value, out: &mut dyn Write) -> Result<(), Error> {
+        (*self).encode_to(value, out)
+    }
+
+    fn section_count(&self) -> usize {
+        (*self).section_count()
+    }
+
+    fn encode_sections_into(
+        &self,
+        value: &Self::Value,
+        range: Range<usize>,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        (*self).encode_sections_into(value, range, out)
+    }
+
     #[inline(always)]
     fn decode(&self, bv: &ByteVector) -> DecodeResult<Self::Value> {
         (*self).decode(bv)
     }
+
+    #[inline(always)]
+    fn decode_slice(&self, input: &[u8]) -> Result<(Self::Value, usize), Error> {
+        (*self).decode_slice(input)
+    }
+
+    fn verbatim_len(&self, available: usize) -> Option<usize> {
+        (*self).verbatim_len(available)
+    }
 }
 
 //
 // Integral codecs
 //
 
+/// Byte ordering used when encoding/decoding multi-byte values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOrdering {
+    /// Most significant byte first.
+    BigEndian,
+
+    /// Least significant byte first.
+    LittleEndian,
+}
+
 macro_rules! integral_codec {
-    { $structname:ident, $value:ident, $encswap:expr, $decswap:expr } => {
+    { $structname:ident, $value:ident, $encswap:expr, $decswap:expr, $ordering:expr } => {
         /// Codec for primitive integral types.
         struct $structname<T> {
             _marker: PhantomData<T>
@@ -97,6 +644,16 @@ macro_rules! integral_codec {
         {
             type Value = T;
 
+            fn size_bound(&self) -> SizeBound {
+                SizeBound::exact(size_of::<T>() as u64)
+            }
+
+            fn describe(&self) -> CodecDescription {
+                let signed = T::min_value() < T::zero();
+                CodecDescription::new(integral_name(signed, size_of::<T>(), $ordering), self.size_bound())
+                    .with_byte_ordering($ordering)
+            }
+
             fn encode(&self, $value: &T) -> EncodeResult {
                 let size = size_of::<T>();
                 let mut v = [0u8; byte_vector::DIRECT_VALUE_SIZE_LIMIT];
@@ -108,6 +665,19 @@ macro_rules! integral_codec {
                 Ok(byte_vector::from_slice(v, size))
             }
 
+            fn encode_into(&self, $value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+                let size = size_of::<T>();
+                let swapped: T = *$encswap;
+                let start = out.len();
+                out.resize(start + size, 0);
+                unsafe {
+                    let src_ptr: *const u8 = (&swapped as *const T) as *const u8;
+                    let dst_ptr: *mut u8 = out.as_mut_ptr().add(start);
+                    ptr::copy(src_ptr, dst_ptr, size);
+                }
+                Ok(())
+            }
+
             fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
                 let size = size_of::<T>();
                 let mut $value: T = T::zero();
@@ -121,1040 +691,9746 @@ macro_rules! integral_codec {
                     })
                 }
             }
+
+            fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+                let size = size_of::<T>();
+                let bytes = slice_prefix(input, size)?;
+                let mut $value: T = T::zero();
+                unsafe {
+                    let dst_ptr: *mut u8 = (&mut $value as *mut T) as *mut u8;
+                    ptr::copy(bytes.as_ptr(), dst_ptr, size);
+                }
+                Ok(($decswap, size))
+            }
         }
     }
 }
 
-integral_codec!(IntegralCodec, value, value, value);
-integral_codec!(IntegralBECodec, value, &(*value).to_be(), value.to_be());
-integral_codec!(IntegralLECodec, value, &(*value).to_le(), value.to_le());
+integral_codec!(IntegralCodec, value, value, value, None);
+integral_codec!(
+    IntegralBECodec,
+    value,
+    &(*value).to_be(),
+    value.to_be(),
+    Some(ByteOrdering::BigEndian)
+);
+integral_codec!(
+    IntegralLECodec,
+    value,
+    &(*value).to_le(),
+    value.to_le(),
+    Some(ByteOrdering::LittleEndian)
+);
+
+/// Converts the given unsigned integer to a `u64`, or returns an error if it is too large (which
+/// is only possible for 128-bit integers).
+pub(crate) fn unsigned_to_u64<T: ToPrimitive>(value: &T) -> Result<u64, Error> {
+    value.to_u64().ok_or_else(|| {
+        Error::new(lazy_description(|| {
+            format!(
+                "Value {} exceeds maximum supported value ({})",
+                value.to_u128().unwrap_or(u128::MAX),
+                u64::MAX
+            )
+        }))
+    })
+}
+
+/// Converts the given signed integer to an `i64`, or returns an error if it is out of range (which
+/// is only possible for 128-bit integers).
+fn signed_to_i64<T: ToPrimitive>(value: &T) -> Result<i64, Error> {
+    value.to_i64().ok_or_else(|| {
+        Error::new(lazy_description(|| {
+            format!(
+                "Value {} is outside the supported range ({}..={})",
+                value.to_i128().unwrap_or(i128::MAX),
+                i64::MIN,
+                i64::MAX
+            )
+        }))
+    })
+}
+
+/// Returns the name of the codec for integers of the given size in bytes, e.g. `uint16_l`.
+fn integral_name(signed: bool, size: usize, ordering: Option<ByteOrdering>) -> String {
+    format!(
+        "{}int{}{}",
+        if signed { "" } else { "u" },
+        size * 8,
+        if ordering == Some(ByteOrdering::LittleEndian) {
+            "_l"
+        } else {
+            ""
+        }
+    )
+}
 
 /// Unsigned 8-bit integer codec.    
-pub const uint8: &'static dyn Codec<Value = u8> = &IntegralCodec {
+pub const uint8: &'static (dyn Codec<Value = u8> + Send + Sync) = &IntegralCodec {
     _marker: PhantomData::<u8>,
 };
 
 /// Signed 8-bit integer codec.
-pub const int8: &'static dyn Codec<Value = i8> = &IntegralCodec {
+pub const int8: &'static (dyn Codec<Value = i8> + Send + Sync) = &IntegralCodec {
     _marker: PhantomData::<i8>,
 };
 
 /// Big-endian unsigned 16-bit integer codec.
-pub const uint16: &'static dyn Codec<Value = u16> = &IntegralBECodec {
+pub const uint16: &'static (dyn Codec<Value = u16> + Send + Sync) = &IntegralBECodec {
     _marker: PhantomData::<u16>,
 };
 
 /// Big-endian signed 16-bit integer codec.
-pub const int16: &'static dyn Codec<Value = i16> = &IntegralBECodec {
+pub const int16: &'static (dyn Codec<Value = i16> + Send + Sync) = &IntegralBECodec {
     _marker: PhantomData::<i16>,
 };
 
 /// Big-endian unsigned 32-bit integer codec.
-pub const uint32: &'static dyn Codec<Value = u32> = &IntegralBECodec {
+pub const uint32: &'static (dyn Codec<Value = u32> + Send + Sync) = &IntegralBECodec {
     _marker: PhantomData::<u32>,
 };
 
 /// Big-endian signed 32-bit integer codec.
-pub const int32: &'static dyn Codec<Value = i32> = &IntegralBECodec {
+pub const int32: &'static (dyn Codec<Value = i32> + Send + Sync) = &IntegralBECodec {
     _marker: PhantomData::<i32>,
 };
 
 /// Big-endian unsigned 64-bit integer codec.
-pub const uint64: &'static dyn Codec<Value = u64> = &IntegralBECodec {
+pub const uint64: &'static (dyn Codec<Value = u64> + Send + Sync) = &IntegralBECodec {
     _marker: PhantomData::<u64>,
 };
 
 /// Big-endian signed 64-bit integer codec.
-pub const int64: &'static dyn Codec<Value = i64> = &IntegralBECodec {
+pub const int64: &'static (dyn Codec<Value = i64> + Send + Sync) = &IntegralBECodec {
     _marker: PhantomData::<i64>,
 };
 
 /// Little-endian unsigned 16-bit integer codec.
-pub const uint16_l: &'static dyn Codec<Value = u16> = &IntegralLECodec {
+pub const uint16_l: &'static (dyn Codec<Value = u16> + Send + Sync) = &IntegralLECodec {
     _marker: PhantomData::<u16>,
 };
 
 /// Little-endian signed 16-bit integer codec.
-pub const int16_l: &'static dyn Codec<Value = i16> = &IntegralLECodec {
+pub const int16_l: &'static (dyn Codec<Value = i16> + Send + Sync) = &IntegralLECodec {
     _marker: PhantomData::<i16>,
 };
 
 /// Little-endian unsigned 32-bit integer codec.
-pub const uint32_l: &'static dyn Codec<Value = u32> = &IntegralLECodec {
+pub const uint32_l: &'static (dyn Codec<Value = u32> + Send + Sync) = &IntegralLECodec {
     _marker: PhantomData::<u32>,
 };
 
 /// Little-endian signed 32-bit integer codec.
-pub const int32_l: &'static dyn Codec<Value = i32> = &IntegralLECodec {
+pub const int32_l: &'static (dyn Codec<Value = i32> + Send + Sync) = &IntegralLECodec {
     _marker: PhantomData::<i32>,
 };
 
 /// Little-endian unsigned 64-bit integer codec.
-pub const uint64_l: &'static dyn Codec<Value = u64> = &IntegralLECodec {
+pub const uint64_l: &'static (dyn Codec<Value = u64> + Send + Sync) = &IntegralLECodec {
     _marker: PhantomData::<u64>,
 };
 
 /// Little-endian signed 64-bit integer codec.
-pub const int64_l: &'static dyn Codec<Value = i64> = &IntegralLECodec {
+pub const int64_l: &'static (dyn Codec<Value = i64> + Send + Sync) = &IntegralLECodec {
     _marker: PhantomData::<i64>,
 };
 
-//
-// Ignore codec
-//
-
-/// Codec that encodes `len` low bytes and decodes by discarding `len` bytes.
-#[inline(always)]
-pub fn ignore(len: usize) -> impl Codec<Value = ()> {
-    IgnoreCodec { len }
+/// Codec for unsigned integral values that occupy fewer bytes than their primitive type, such as
+/// 24-bit values stored in a `u32`.
+struct SizedIntegralCodec<T> {
+    size: usize,
+    big_endian: bool,
+    _marker: PhantomData<T>,
 }
 
-struct IgnoreCodec {
-    len: usize,
-}
+impl<T> SizedIntegralCodec<T>
+where
+    T: PrimInt + Unsigned,
+{
+    /// Returns the encoded bytes for the given value, of which the first `size` bytes are used.
+    fn encode_bytes(&self, value: &T) -> Result<[u8; byte_vector::DIRECT_VALUE_SIZE_LIMIT], Error> {
+        let bits = self.size * 8;
+        let v = unsigned_to_u64(value)?;
+        if v >> bits != 0 {
+            return Err(Error::new(lazy_description(|| {
+                format!(
+                    "Value {} is greater than maximum value ({}) of {}-bit integer",
+                    v,
+                    (1u64 << bits) - 1,
+                    bits
+                )
+            })));
+        }
 
-impl Codec for IgnoreCodec {
-    type Value = ();
+        let mut bytes = [0u8; byte_vector::DIRECT_VALUE_SIZE_LIMIT];
+        for (i, byte) in bytes.iter_mut().take(self.size).enumerate() {
+            let shift = if self.big_endian {
+                (self.size - 1 - i) * 8
+            } else {
+                i * 8
+            };
+            *byte = (v >> shift) as u8;
+        }
+        Ok(bytes)
+    }
 
-    fn encode(&self, _value: &()) -> EncodeResult {
-        Ok(byte_vector::fill(0, self.len))
+    /// Returns the byte ordering used by this codec.
+    fn ordering(&self) -> ByteOrdering {
+        if self.big_endian {
+            ByteOrdering::BigEndian
+        } else {
+            ByteOrdering::LittleEndian
+        }
     }
 
-    fn decode(&self, bv: &ByteVector) -> DecodeResult<()> {
-        bv.drop(self.len).map(|remainder| DecoderResult {
-            value: (),
-            remainder,
-        })
+    /// Returns the value represented by the given `size` bytes.
+    fn decode_bytes(&self, bytes: &[u8]) -> T {
+        let v = bytes.iter().enumerate().fold(0u64, |acc, (i, byte)| {
+            let shift = if self.big_endian {
+                (self.size - 1 - i) * 8
+            } else {
+                i * 8
+            };
+            acc | (u64::from(*byte) << shift)
+        });
+        T::from(v).unwrap()
     }
 }
 
-//
-// Constant codec
-//
+impl<T> Codec for SizedIntegralCodec<T>
+where
+    T: PrimInt + Unsigned,
+{
+    type Value = T;
 
-/// Codec that always encodes the given byte vector, and decodes by returning a unit result if the actual bytes match
-/// the given byte vector or an error otherwise.
-#[inline(always)]
-pub fn constant(bytes: &ByteVector) -> impl Codec<Value = ()> {
-    ConstantCodec {
-        bytes: (*bytes).clone(),
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(self.size as u64)
     }
-}
 
-struct ConstantCodec {
-    bytes: ByteVector,
-}
+    fn describe(&self) -> CodecDescription {
+        let ordering = self.ordering();
+        CodecDescription::new(
+            integral_name(false, self.size, Some(ordering)),
+            self.size_bound(),
+        )
+        .with_byte_ordering(Some(ordering))
+    }
 
-impl Codec for ConstantCodec {
-    type Value = ();
+    fn encode(&self, value: &T) -> EncodeResult {
+        self.encode_bytes(value)
+            .map(|bytes| byte_vector::from_slice(bytes, self.size))
+    }
 
-    fn encode(&self, _value: &()) -> EncodeResult {
-        Ok(self.bytes.clone())
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.encode_bytes(value)
+            .map(|bytes| out.extend_from_slice(&bytes[..self.size]))
     }
 
-    fn decode(&self, bv: &ByteVector) -> DecodeResult<()> {
-        bv.take(self.bytes.length()).and_then(|taken| {
-            if taken == self.bytes {
-                Ok(DecoderResult {
-                    value: (),
-                    remainder: bv.drop(self.bytes.length()).unwrap(),
-                })
-            } else {
-                Err(Error::new(format!(
-                    "Expected constant {:?} but got {:?}",
-                    self.bytes, taken
-                )))
-            }
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        let mut bytes = [0u8; byte_vector::DIRECT_VALUE_SIZE_LIMIT];
+        bv.read(&mut bytes[..self.size], 0, self.size)?;
+        Ok(DecoderResult {
+            value: self.decode_bytes(&bytes[..self.size]),
+            remainder: bv.drop(self.size)?,
         })
     }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        slice_prefix(input, self.size).map(|bytes| (self.decode_bytes(bytes), self.size))
+    }
 }
 
-//
-// Identity codec
-//
+/// Big-endian unsigned 24-bit integer codec.
+pub const uint24: &'static (dyn Codec<Value = u32> + Send + Sync) = &SizedIntegralCodec {
+    size: 3,
+    big_endian: true,
+    _marker: PhantomData::<u32>,
+};
 
-/// Identity byte vector codec.
-///
-///   - Encodes by returning the given byte vector.
-///   - Decodes by taking all remaining bytes from the given byte vector.
-#[inline(always)]
-pub fn identity_bytes() -> impl Codec<Value = ByteVector> {
-    IdentityCodec
-}
+/// Big-endian unsigned 40-bit integer codec.
+pub const uint40: &'static (dyn Codec<Value = u64> + Send + Sync) = &SizedIntegralCodec {
+    size: 5,
+    big_endian: true,
+    _marker: PhantomData::<u64>,
+};
 
-struct IdentityCodec;
+/// Big-endian unsigned 48-bit integer codec.
+pub const uint48: &'static (dyn Codec<Value = u64> + Send + Sync) = &SizedIntegralCodec {
+    size: 6,
+    big_endian: true,
+    _marker: PhantomData::<u64>,
+};
 
-impl Codec for IdentityCodec {
-    type Value = ByteVector;
+/// Big-endian unsigned 56-bit integer codec.
+pub const uint56: &'static (dyn Codec<Value = u64> + Send + Sync) = &SizedIntegralCodec {
+    size: 7,
+    big_endian: true,
+    _marker: PhantomData::<u64>,
+};
 
-    fn encode(&self, value: &ByteVector) -> EncodeResult {
-        Ok((*value).clone())
-    }
+/// Little-endian unsigned 24-bit integer codec.
+pub const uint24_l: &'static (dyn Codec<Value = u32> + Send + Sync) = &SizedIntegralCodec {
+    size: 3,
+    big_endian: false,
+    _marker: PhantomData::<u32>,
+};
 
-    fn decode(&self, bv: &ByteVector) -> DecodeResult<ByteVector> {
-        Ok(DecoderResult {
-            value: (*bv).clone(),
-            remainder: byte_vector::empty(),
-        })
-    }
-}
+/// Little-endian unsigned 40-bit integer codec.
+pub const uint40_l: &'static (dyn Codec<Value = u64> + Send + Sync) = &SizedIntegralCodec {
+    size: 5,
+    big_endian: false,
+    _marker: PhantomData::<u64>,
+};
+
+/// Little-endian unsigned 48-bit integer codec.
+pub const uint48_l: &'static (dyn Codec<Value = u64> + Send + Sync) = &SizedIntegralCodec {
+    size: 6,
+    big_endian: false,
+    _marker: PhantomData::<u64>,
+};
+
+/// Little-endian unsigned 56-bit integer codec.
+pub const uint56_l: &'static (dyn Codec<Value = u64> + Send + Sync) = &SizedIntegralCodec {
+    size: 7,
+    big_endian: false,
+    _marker: PhantomData::<u64>,
+};
 
 //
-// Bytes codec
+// Floating point codecs
 //
 
-/// Byte vector codec.
-///
-///   - Encodes by returning the given byte vector if its length is `len` bytes, otherwise returns an error.
-///   - Decodes by taking `len` bytes from the given byte vector.
-#[inline(always)]
-pub fn bytes(len: usize) -> impl Codec<Value = ByteVector> {
-    fixed_size_bytes(len, identity_bytes())
+macro_rules! float_codec {
+    { $structname:ident, $ftype:ty, $itype:ty, $name:expr } => {
+        /// Codec for IEEE-754 floating point types, implemented in terms of an integral codec
+        /// operating on the raw bit representation.
+        struct $structname {
+            bits_codec: &'static (dyn Codec<Value = $itype> + Send + Sync),
+        }
+
+        impl Codec for $structname {
+            type Value = $ftype;
+
+            fn size_bound(&self) -> SizeBound {
+                self.bits_codec.size_bound()
+            }
+
+            fn describe(&self) -> CodecDescription {
+                let ordering = self.bits_codec.describe().byte_ordering;
+                let suffix = if ordering == Some(ByteOrdering::LittleEndian) { "_l" } else { "" };
+                CodecDescription::new(format!("{}{}", $name, suffix), self.size_bound())
+                    .with_byte_ordering(ordering)
+            }
+
+            fn encode(&self, value: &$ftype) -> EncodeResult {
+                self.bits_codec.encode(&value.to_bits())
+            }
+
+            fn encode_into(&self, value: &$ftype, out: &mut Vec<u8>) -> Result<(), Error> {
+                self.bits_codec.encode_into(&value.to_bits(), out)
+            }
+
+            fn decode(&self, bv: &ByteVector) -> DecodeResult<$ftype> {
+                self.bits_codec.decode(bv).map(|decoded| DecoderResult {
+                    value: <$ftype>::from_bits(decoded.value),
+                    remainder: decoded.remainder,
+                })
+            }
+
+            fn decode_slice(&self, input: &[u8]) -> Result<($ftype, usize), Error> {
+                self.bits_codec
+                    .decode_slice(input)
+                    .map(|(bits, size)| (<$ftype>::from_bits(bits), size))
+            }
+        }
+    };
 }
 
+float_codec!(Float32Codec, f32, u32, "float32");
+float_codec!(Float64Codec, f64, u64, "float64");
+
+/// Big-endian 32-bit floating point codec.
+pub const float32: &'static (dyn Codec<Value = f32> + Send + Sync) =
+    &Float32Codec { bits_codec: uint32 };
+
+/// Big-endian 64-bit floating point codec.
+pub const float64: &'static (dyn Codec<Value = f64> + Send + Sync) =
+    &Float64Codec { bits_codec: uint64 };
+
+/// Little-endian 32-bit floating point codec.
+pub const float32_l: &'static (dyn Codec<Value = f32> + Send + Sync) = &Float32Codec {
+    bits_codec: uint32_l,
+};
+
+/// Little-endian 64-bit floating point codec.
+pub const float64_l: &'static (dyn Codec<Value = f64> + Send + Sync) = &Float64Codec {
+    bits_codec: uint64_l,
+};
+
 //
-// Fixed size bytes codec
+// Endianness-dependent codecs
 //
 
-/// Codec that limits the number of bytes that are available to the given `codec`.
-///
-/// When encoding, if the given `codec` encodes fewer than `len` bytes, the byte vector
-/// is right padded with low bytes.  If `codec` instead encodes more than `len` bytes,
-/// an error is returned.
-///
-/// When decoding, the given `codec` is only given `len` bytes.  If `codec` does
-/// not consume all `len` bytes, any remaining bytes are discarded.
+macro_rules! ordered_codec {
+    { $name:ident, $value:ty, $be:ident, $le:ident, $doc:expr } => {
+        #[doc = $doc]
+        #[inline(always)]
+        pub fn $name(ordering: ByteOrdering) -> &'static (dyn Codec<Value = $value> + Send + Sync) {
+            match ordering {
+                ByteOrdering::BigEndian => $be,
+                ByteOrdering::LittleEndian => $le,
+            }
+        }
+    };
+}
+
+ordered_codec! { uint16_with, u16, uint16, uint16_l, "Unsigned 16-bit integer codec with the given byte ordering." }
+ordered_codec! { int16_with, i16, int16, int16_l, "Signed 16-bit integer codec with the given byte ordering." }
+ordered_codec! { uint24_with, u32, uint24, uint24_l, "Unsigned 24-bit integer codec with the given byte ordering." }
+ordered_codec! { uint32_with, u32, uint32, uint32_l, "Unsigned 32-bit integer codec with the given byte ordering." }
+ordered_codec! { int32_with, i32, int32, int32_l, "Signed 32-bit integer codec with the given byte ordering." }
+ordered_codec! { uint64_with, u64, uint64, uint64_l, "Unsigned 64-bit integer codec with the given byte ordering." }
+ordered_codec! { int64_with, i64, int64, int64_l, "Signed 64-bit integer codec with the given byte ordering." }
+ordered_codec! { float32_with, f32, float32, float32_l, "32-bit floating point codec with the given byte ordering." }
+ordered_codec! { float64_with, f64, float64, float64_l, "64-bit floating point codec with the given byte ordering." }
+
+/// Codec that selects between a big-endian and a little-endian codec based on the given byte
+/// ordering, which is typically determined at runtime (e.g. from a byte order mark in a header).
 #[inline(always)]
-pub fn fixed_size_bytes<T, C>(len: usize, codec: C) -> impl Codec<Value = T>
+pub fn endianness_dependent<T, BC, LC>(
+    be_codec: BC,
+    le_codec: LC,
+    ordering: ByteOrdering,
+) -> impl Codec<Value = T>
 where
-    C: Codec<Value = T>,
+    BC: Codec<Value = T>,
+    LC: Codec<Value = T>,
 {
-    FixedSizeCodec { len, codec }
+    EndiannessDependentCodec {
+        be_codec,
+        le_codec,
+        ordering,
+    }
 }
 
-struct FixedSizeCodec<C> {
-    len: usize,
-    codec: C,
+struct EndiannessDependentCodec<BC, LC> {
+    be_codec: BC,
+    le_codec: LC,
+    ordering: ByteOrdering,
 }
 
-impl<T, C> Codec for FixedSizeCodec<C>
+impl<T, BC, LC> Codec for EndiannessDependentCodec<BC, LC>
 where
-    C: Codec<Value = T>,
+    BC: Codec<Value = T>,
+    LC: Codec<Value = T>,
 {
     type Value = T;
 
+    fn size_bound(&self) -> SizeBound {
+        match self.ordering {
+            ByteOrdering::BigEndian => self.be_codec.size_bound(),
+            ByteOrdering::LittleEndian => self.le_codec.size_bound(),
+        }
+    }
+
+    fn describe(&self) -> CodecDescription {
+        let selected = match self.ordering {
+            ByteOrdering::BigEndian => self.be_codec.describe(),
+            ByteOrdering::LittleEndian => self.le_codec.describe(),
+        };
+        CodecDescription::new("endianness_dependent", self.size_bound())
+            .with_byte_ordering(Some(self.ordering))
+            .with_child(selected)
+    }
+
     fn encode(&self, value: &T) -> EncodeResult {
-        self.codec.encode(value).and_then(|encoded| {
-            if encoded.length() > self.len {
-                Err(Error::new(format!(
-                    "Encoding requires {} bytes but codec is limited to fixed length of {}",
-                    encoded.length(),
-                    self.len
-                )))
-            } else {
-                encoded.pad_right(self.len)
-            }
-        })
+        match self.ordering {
+            ByteOrdering::BigEndian => self.be_codec.encode(value),
+            ByteOrdering::LittleEndian => self.le_codec.encode(value),
+        }
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        match self.ordering {
+            ByteOrdering::BigEndian => self.be_codec.encode_into(value, out),
+            ByteOrdering::LittleEndian => self.le_codec.encode_into(value, out),
+        }
     }
 
     fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
-        // Give `len` bytes to the decoder; if successful, return the result along with
-        // the remainder of `bv` after dropping `len` bytes from it
-        forcomp!({
-            taken <- bv.take(self.len);
-            decoded <- self.codec.decode(&taken);
-        } yield {
-            DecoderResult { value: decoded.value, remainder: bv.drop(self.len).unwrap() }
-        })
+        match self.ordering {
+            ByteOrdering::BigEndian => self.be_codec.decode(bv),
+            ByteOrdering::LittleEndian => self.le_codec.decode(bv),
+        }
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        match self.ordering {
+            ByteOrdering::BigEndian => self.be_codec.decode_slice(input),
+            ByteOrdering::LittleEndian => self.le_codec.decode_slice(input),
+        }
     }
 }
 
 //
-// Variable size bytes codec
+// Variable-length integer codecs
 //
 
-/// Codec for length-delimited values.
-///
-///   - Encodes by encoding the length (in bytes) of the value followed by the value itself.
-///   - Decodes by decoding the length and then attempting to decode the value that follows.
+/// Unsigned variable-length integer codec using the LEB128 encoding, in which each byte holds
+/// seven bits of the value (least significant group first) and the high bit is set on all but
+/// the final byte.
 #[inline(always)]
-pub fn variable_size_bytes<L, V, LC, VC>(len_codec: LC, val_codec: VC) -> impl Codec<Value = V>
+pub fn vuint<T>() -> impl Codec<Value = T>
 where
-    L: PrimInt + Unsigned + FromPrimitive + Display,
-    LC: Codec<Value = L>,
-    VC: Codec<Value = V>,
+    T: PrimInt + Unsigned + FromPrimitive,
 {
-    VariableSizeCodec {
-        len_codec,
-        val_codec,
+    VarUIntCodec {
+        _marker: PhantomData::<T>,
     }
 }
 
-struct VariableSizeCodec<LC, VC> {
-    len_codec: LC,
-    val_codec: VC,
+/// Signed variable-length integer codec that maps values to unsigned integers using the zigzag
+/// encoding (so that values of small magnitude have a short encoding) and then encodes them
+/// using LEB128, as in the Protocol Buffers `sint32`/`sint64` types.
+#[inline(always)]
+pub fn vint<T>() -> impl Codec<Value = T>
+where
+    T: PrimInt + Signed + FromPrimitive,
+{
+    VarIntCodec {
+        _marker: PhantomData::<T>,
+    }
 }
 
-impl<L, V, LC, VC> Codec for VariableSizeCodec<LC, VC>
+struct VarUIntCodec<T> {
+    _marker: PhantomData<T>,
+}
+
+impl<T> Codec for VarUIntCodec<T>
 where
-    L: PrimInt + Unsigned + FromPrimitive + Display,
-    LC: Codec<Value = L>,
-    VC: Codec<Value = V>,
+    T: PrimInt + Unsigned + FromPrimitive,
 {
-    type Value = V;
+    type Value = T;
 
-    fn encode(&self, value: &V) -> EncodeResult {
-        // Encode the value, then prepend the length of the encoded value
-        self.val_codec.encode(&value).and_then(|encoded_val| {
-            // Fail if length is too long to be encoded
-            match L::from_usize(encoded_val.length()) {
-                Some(len) => self.len_codec.encode(&len).map(|encoded_len| byte_vector::append(&encoded_len, &encoded_val)),
-                None => Err(Error::new(format!("Length of encoded value ({} bytes) is greater than maximum value ({}) of length type", encoded_val.length(), L::max_value())))
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::bounded(1, (size_of::<T>() * 8).div_ceil(7) as u64)
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("vuint", self.size_bound())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        unsigned_to_u64(value).map(encode_leb128)
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        write_leb128(unsigned_to_u64(value)?, out);
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        decode_leb128(|i| bv.get_u8(i), size_of::<T>()).and_then(|(raw, size)| {
+            match T::from_u64(raw) {
+                Some(value) => Ok(DecoderResult {
+                    value,
+                    remainder: bv.drop(size)?,
+                }),
+                None => Err(Error::new(lazy_description(|| {
+                    format!(
+                        "Varint value {} exceeds maximum value ({}) of target type",
+                        raw,
+                        T::max_value().to_u64().unwrap()
+                    )
+                }))),
             }
         })
     }
 
-    fn decode(&self, bv: &ByteVector) -> DecodeResult<V> {
-        // Decode the length, then decode the value
-        forcomp!({
-            decoded_len <- self.len_codec.decode(&bv);
-            remainder <- {
-                // TODO: Ideally we'd just use fixed_size_bytes() here, but not sure how to transfer ownership of val_decoder
-                let len = decoded_len.value.to_usize().unwrap();
-                decoded_len.remainder.take(len)
-            };
-            decoded_val <- self.val_codec.decode(&remainder);
-        } yield {
-            DecoderResult { value: decoded_val.value, remainder: bv.drop(remainder.length()).unwrap() }
-        })
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        let (raw, size) = decode_leb128(|i| slice_u8(input, i), size_of::<T>())?;
+        match T::from_u64(raw) {
+            Some(value) => Ok((value, size)),
+            None => Err(Error::new(lazy_description(|| {
+                format!(
+                    "Varint value {} exceeds maximum value ({}) of target type",
+                    raw,
+                    T::max_value().to_u64().unwrap()
+                )
+            }))),
+        }
     }
 }
 
-//
-// Eager bytes codec
-//
+struct VarIntCodec<T> {
+    _marker: PhantomData<T>,
+}
 
-/// Codec that encodes/decodes fully-realized `Vec<u8>` values.
-///
-///   - Encodes by first efficiently converting `Vec<u8>` values to a `ByteVector`.
-///   - Decodes by performing a fully-realized read on the backing `ByteVector`.
-#[inline(always)]
-pub fn eager<C>(bv_codec: C) -> impl Codec<Value = Vec<u8>>
+impl<T> Codec for VarIntCodec<T>
 where
-    C: Codec<Value = ByteVector>,
+    T: PrimInt + Signed + FromPrimitive,
 {
-    EagerCodec { bv_codec }
-}
+    type Value = T;
 
-struct EagerCodec<C> {
-    bv_codec: C,
-}
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::bounded(1, (size_of::<T>() * 8).div_ceil(7) as u64)
+    }
 
-impl<C> Codec for EagerCodec<C>
-where
-    C: Codec<Value = ByteVector>,
-{
-    type Value = Vec<u8>;
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("vint", self.size_bound())
+    }
 
-    fn encode(&self, value: &Vec<u8>) -> EncodeResult {
-        self.bv_codec.encode(&byte_vector::from_slice_copy(value))
+    fn encode(&self, value: &T) -> EncodeResult {
+        let v = signed_to_i64(value)?;
+        Ok(encode_leb128(((v << 1) ^ (v >> 63)) as u64))
     }
 
-    fn decode(&self, bv: &ByteVector) -> DecodeResult<Vec<u8>> {
-        forcomp!({
-            decoded <- self.bv_codec.decode(bv);
-            vec <- decoded.value.to_vec();
-        } yield {
-            DecoderResult { value: vec, remainder: decoded.remainder }
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        let v = signed_to_i64(value)?;
+        write_leb128(((v << 1) ^ (v >> 63)) as u64, out);
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        decode_leb128(|i| bv.get_u8(i), size_of::<T>()).and_then(|(raw, size)| {
+            let v = ((raw >> 1) as i64) ^ -((raw & 1) as i64);
+            match T::from_i64(v) {
+                Some(value) => Ok(DecoderResult {
+                    value,
+                    remainder: bv.drop(size)?,
+                }),
+                None => Err(Error::new(lazy_description(|| {
+                    format!("Varint value {} is outside the range of target type", v)
+                }))),
+            }
         })
     }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        let (raw, size) = decode_leb128(|i| slice_u8(input, i), size_of::<T>())?;
+        let v = ((raw >> 1) as i64) ^ -((raw & 1) as i64);
+        match T::from_i64(v) {
+            Some(value) => Ok((value, size)),
+            None => Err(Error::new(lazy_description(|| {
+                format!("Varint value {} is outside the range of target type", v)
+            }))),
+        }
+    }
+}
+
+/// Returns the LEB128 encoding of the given value.
+fn encode_leb128(value: u64) -> ByteVector {
+    let mut bytes = Vec::with_capacity(10);
+    write_leb128(value, &mut bytes);
+    byte_vector::from_slice_copy(&bytes)
+}
+
+/// Appends the LEB128 encoding of the given value to `out`.
+fn write_leb128(mut value: u64, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Decodes a LEB128 value that represents an integer of `width` bytes, returning the value along with
+/// the number of bytes consumed.
+fn decode_leb128<F>(get_u8: F, width: usize) -> Result<(u64, usize), Error>
+where
+    F: Fn(usize) -> Result<u8, Error>,
+{
+    let max_len = (width * 8).div_ceil(7);
+    let mut value = 0u64;
+    for i in 0..max_len {
+        let byte = get_u8(i)?;
+        let part = u64::from(byte & 0x7f);
+        let shift = i * 7;
+        if shift >= 64 || (part << shift) >> shift != part {
+            break;
+        }
+        value |= part << shift;
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    Err(Error::new(lazy_description(|| {
+        format!(
+            "Varint exceeds maximum length of {} bytes for target type",
+            max_len
+        )
+    })))
 }
 
 //
-// HList-related codecs
+// Ignore codec
 //
 
-/// Codec for `HNil` type.
+/// Codec that encodes `len` low bytes and decodes by discarding `len` bytes.
 #[inline(always)]
-pub fn hnil_codec() -> impl Codec<Value = HNil> {
-    HNilCodec
+pub fn ignore(len: usize) -> impl Codec<Value = ()> {
+    IgnoreCodec {
+        len,
+        fill: 0,
+        strict: false,
+    }
 }
 
-struct HNilCodec;
-
-impl Codec for HNilCodec {
-    type Value = HNil;
+/// Codec that encodes `len` low bytes and decodes by discarding `len` bytes, failing if any of the
+/// discarded bytes is non-zero.  This is useful for reserved regions that must be zero.
+#[inline(always)]
+pub fn ignore_strict(len: usize) -> impl Codec<Value = ()> {
+    ignore_strict_with_fill(len, 0)
+}
 
-    fn encode(&self, _value: &HNil) -> EncodeResult {
-        Ok(byte_vector::empty())
+/// Codec that encodes `len` copies of `fill` and decodes by discarding `len` bytes, failing if any
+/// of the discarded bytes differs from `fill`.
+#[inline(always)]
+pub fn ignore_strict_with_fill(len: usize, fill: u8) -> impl Codec<Value = ()> {
+    IgnoreCodec {
+        len,
+        fill,
+        strict: true,
     }
+}
 
-    fn decode(&self, bv: &ByteVector) -> DecodeResult<HNil> {
-        Ok(DecoderResult {
-            value: HNil,
-            remainder: bv.clone(),
-        })
-    }
+struct IgnoreCodec {
+    len: usize,
+    fill: u8,
+    strict: bool,
 }
 
-/// Codec used to convert an `HList` of codecs into a single codec that encodes/decodes an `HList` of values.
-#[inline(always)]
-pub fn hlist_prepend_codec<H, T, HC, TC>(
-    head_codec: HC,
-    tail_codec: TC,
-) -> impl Codec<Value = HCons<H, T>>
-where
-    T: HList,
-    HC: Codec<Value = H>,
-    TC: Codec<Value = T>,
-{
-    HListPrependCodec {
-        head_codec,
-        tail_codec,
+impl IgnoreCodec {
+    /// Returns an error if strict mode is enabled and `bytes` contains anything other than `fill`.
+    fn check(&self, bytes: &[u8]) -> Result<(), Error> {
+        if !self.strict {
+            return Ok(());
+        }
+        match bytes.iter().position(|b| *b != self.fill) {
+            Some(index) => Err(Error::new(lazy_description(|| {
+                format!(
+                    "Expected ignored byte at offset {} to be 0x{:02x} but got 0x{:02x}",
+                    index, self.fill, bytes[index]
+                )
+            }))),
+            None => Ok(()),
+        }
     }
 }
 
-struct HListPrependCodec<HC, TC> {
-    head_codec: HC,
-    tail_codec: TC,
-}
+impl Codec for IgnoreCodec {
+    type Value = ();
 
-impl<H, T, HC, TC> Codec for HListPrependCodec<HC, TC>
-where
-    T: HList,
-    HC: Codec<Value = H>,
-    TC: Codec<Value = T>,
-{
-    type Value = HCons<H, T>;
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(self.len as u64)
+    }
 
-    fn encode(&self, value: &HCons<H, T>) -> EncodeResult {
-        // TODO: Generalize this as an encode_both() function
-        forcomp!({
-            encoded_head <- self.head_codec.encode(&value.head());
-            encoded_tail <- self.tail_codec.encode(&value.tail());
-        } yield {
-            byte_vector::append(&encoded_head, &encoded_tail)
-        })
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("ignore", self.size_bound())
     }
 
-    fn decode(&self, bv: &ByteVector) -> DecodeResult<HCons<H, T>> {
-        // TODO: Generalize this as a decode_both_combine() function
-        forcomp!({
-            decoded_head <- self.head_codec.decode(&bv);
-            decoded_tail <- self.tail_codec.decode(&decoded_head.remainder);
-        } yield {
-            DecoderResult { value: HCons(decoded_head.value, decoded_tail.value), remainder: decoded_tail.remainder }
+    fn encode(&self, _value: &()) -> EncodeResult {
+        Ok(byte_vector::fill(self.fill, self.len))
+    }
+
+    fn encode_into(&self, _value: &(), out: &mut Vec<u8>) -> Result<(), Error> {
+        out.resize(out.len() + self.len, self.fill);
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<()> {
+        let remainder = bv.drop(self.len)?;
+        if self.strict && self.len > 0 {
+            self.check(&bv.take(self.len)?.to_vec()?)?;
+        }
+        Ok(DecoderResult {
+            value: (),
+            remainder,
         })
     }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<((), usize), Error> {
+        slice_prefix(input, self.len)
+            .and_then(|bytes| self.check(bytes))
+            .map(|_| ((), self.len))
+    }
 }
 
-/// Codec that first performs encoding/decoding of `T`, using the resulting value to produce codecs
-/// for the remaining types.
+//
+// Constant codec
+//
+
+/// Codec that always encodes the given bytes, and decodes by returning a unit result if the actual bytes match
+/// the given bytes or an error otherwise.
 ///
-/// This allows later parts of an `HList` codec to be dependent on on earlier values.
+/// The bytes may be given as anything that converts into a `ByteVector`, such as a `&ByteVector`,
+/// a byte array or slice, a `Vec<u8>`, or a `&str`.
+///
+/// Panics if the bytes cannot be read (e.g. due to an I/O error on a file-backed vector); use
+/// `try_constant` to handle that case.
 #[inline(always)]
-pub fn hlist_flat_prepend_codec<H, T, HC, TC, F>(
-    head_codec: HC,
-    tail_codec_fn: F,
-) -> impl Codec<Value = HCons<H, T>>
-where
-    T: HList,
-    HC: Codec<Value = H>,
-    TC: Codec<Value = T>,
-    F: Fn(&H) -> TC,
-{
-    HListFlatPrependCodec {
-        head_codec,
-        tail_codec_fn,
+pub fn constant<B: Into<ByteVector>>(bytes: B) -> ConstantCodec {
+    try_constant(bytes).expect("Failed to read constant bytes")
+}
+
+/// Codec that behaves like `constant`, or an error if the given bytes cannot be read.
+#[inline(always)]
+pub fn try_constant<B: Into<ByteVector>>(bytes: B) -> Result<ConstantCodec, Error> {
+    bytes.into().to_vec().map(|bytes| ConstantCodec { bytes })
+}
+
+/// Codec that always encodes the given bytes; equivalent to `constant` but accepting a plain slice.
+#[inline(always)]
+pub fn constant_bytes(bytes: &[u8]) -> impl Codec<Value = ()> {
+    ConstantCodec {
+        bytes: bytes.to_vec(),
     }
 }
 
-struct HListFlatPrependCodec<HC, F> {
-    head_codec: HC,
-    tail_codec_fn: F,
+/// Codec that matches a constant sequence of bytes, as produced by `constant`.
+///
+/// Unlike most codecs, this type is named so that `constant` does not hold on to the lifetime of
+/// its argument.
+pub struct ConstantCodec {
+    // Held as a `Vec` rather than a `ByteVector` so that the codec is `Send` and `Sync`
+    bytes: Vec<u8>,
 }
 
-impl<H, T, HC, TC, F> Codec for HListFlatPrependCodec<HC, F>
-where
-    T: HList,
-    HC: Codec<Value = H>,
-    TC: Codec<Value = T>,
-    F: Fn(&H) -> TC,
-{
-    type Value = HCons<H, T>;
+impl ConstantCodec {
+    /// Returns the error for decoded bytes that differ from the expected constant bytes, which are
+    /// only converted into a byte vector (by `actual`) if the error is described.
+    fn mismatch<F>(&self, actual: F) -> Error
+    where
+        F: FnOnce() -> ByteVector,
+    {
+        Error::new(lazy_description(|| {
+            format!(
+                "Expected constant {:?} but got {:?}",
+                byte_vector::from_slice_copy(&self.bytes),
+                actual()
+            )
+        }))
+    }
+}
 
-    fn encode(&self, value: &HCons<H, T>) -> EncodeResult {
-        // TODO: Generalize this as an encode_both() function
-        forcomp!({
-            encoded_head <- self.head_codec.encode(&value.head());
-            encoded_tail <- (self.tail_codec_fn)(&value.head()).encode(&value.tail());
-        } yield {
-            byte_vector::append(&encoded_head, &encoded_tail)
-        })
+impl Codec for ConstantCodec {
+    type Value = ();
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(self.bytes.len() as u64)
     }
 
-    fn decode(&self, bv: &ByteVector) -> DecodeResult<HCons<H, T>> {
-        forcomp!({
-            decoded_head <- self.head_codec.decode(&bv);
-            decoded_tail <- (self.tail_codec_fn)(&decoded_head.value).decode(&decoded_head.remainder);
-        } yield {
-            DecoderResult { value: HCons(decoded_head.value, decoded_tail.value), remainder: decoded_tail.remainder }
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("constant", self.size_bound())
+    }
+
+    fn encode(&self, _value: &()) -> EncodeResult {
+        Ok(byte_vector::from_slice_copy(&self.bytes))
+    }
+
+    fn encode_into(&self, _value: &(), out: &mut Vec<u8>) -> Result<(), Error> {
+        out.extend_from_slice(&self.bytes);
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<()> {
+        let len = self.bytes.len();
+        let taken = bv.take(len)?;
+        if taken != self.bytes[..] {
+            return Err(self.mismatch(|| taken));
+        }
+        bv.drop(len).map(|remainder| DecoderResult {
+            value: (),
+            remainder,
         })
     }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<((), usize), Error> {
+        let len = self.bytes.len();
+        let taken = slice_prefix(input, len)?;
+        if taken != &self.bytes[..] {
+            return Err(self.mismatch(|| byte_vector::from_slice_copy(taken)));
+        }
+        Ok(((), len))
+    }
 }
 
 //
-// Struct codec
+// Unit codec
 //
 
-/// Codec for structs that support `HList` conversions.
+/// Codec that converts a codec for values of type `T` into a codec for the unit value, allowing it
+/// to be used in drop-left position (e.g. before `>>` in `hcodec!`).
+///
+///   - Encodes `default_value` using the given codec.
+///   - Decodes using the given codec and then discards the decoded value.
 #[inline(always)]
-pub fn struct_codec<H, S, HC>(hlist_codec: HC) -> impl Codec<Value = S>
+pub fn unit<T, C>(codec: C, default_value: T) -> impl Codec<Value = ()>
 where
-    H: HList,
-    S: FromHList<H> + ToHList<H>,
-    HC: Codec<Value = H>,
+    C: Codec<Value = T>,
 {
-    RecordStructCodec {
-        hlist_codec,
-        _marker: PhantomData::<S>,
+    UnitCodec {
+        codec,
+        default_value,
     }
 }
 
-struct RecordStructCodec<S, HC> {
-    hlist_codec: HC,
-    _marker: PhantomData<S>,
+struct UnitCodec<T, C> {
+    codec: C,
+    default_value: T,
 }
 
-impl<H, S, HC> Codec for RecordStructCodec<S, HC>
+impl<T, C> Codec for UnitCodec<T, C>
 where
-    H: HList,
-    S: FromHList<H> + ToHList<H>,
-    HC: Codec<Value = H>,
+    C: Codec<Value = T>,
 {
-    type Value = S;
+    type Value = ();
 
-    fn encode(&self, value: &S) -> EncodeResult {
-        self.hlist_codec.encode(&value.to_hlist())
+    fn size_bound(&self) -> SizeBound {
+        self.codec.size_bound()
     }
 
-    fn decode(&self, bv: &ByteVector) -> DecodeResult<S> {
-        self.hlist_codec.decode(bv).map(|decoded| DecoderResult {
-            value: S::from_hlist(decoded.value),
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("unit", self.size_bound()).with_child(self.codec.describe())
+    }
+
+    fn encode(&self, _value: &()) -> EncodeResult {
+        self.codec.encode(&self.default_value)
+    }
+
+    fn encode_into(&self, _value: &(), out: &mut Vec<u8>) -> Result<(), Error> {
+        self.codec.encode_into(&self.default_value, out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<()> {
+        self.codec.decode(bv).map(|decoded| DecoderResult {
+            value: (),
             remainder: decoded.remainder,
         })
     }
-}
 
-//
-// Context-injection codec
-//
+    fn decode_slice(&self, input: &[u8]) -> Result<((), usize), Error> {
+        self.codec.decode_slice(input).map(|(_, size)| ((), size))
+    }
+}
 
 //
-// TODO: Can we have a single impl that works on AsCodecRef<T>?  Attempts so far like this:
-//   impl<T: 'static, TC: AsCodecRef<T>> core::ops::BitOr<TC> for &'static str {
-//
-// TODO: The orphan checking rules were changed shortly before Rust 1.0.0 such that we can't implement
-// the BitOr trait with a Codec on the RHS.  Compilation fails with:
-//
-// src/codec.rs:475:1: 481:2 error: type parameter `T` must be used as the type parameter for some local type
-//                           (e.g. `MyStruct<T>`); only traits defined in the current crate can be implemented
-//                           for a type parameter [E0210]
-// src/codec.rs:475 impl<T: 'static> core::ops::BitOr<RcCodec<T>> for &'static str {
-// src/codec.rs:476     type Output = RcCodec<T>;
-// src/codec.rs:477
-// src/codec.rs:478     fn bitor(self, rhs: RcCodec<T>) -> RcCodec<T> {
-// src/codec.rs:479         rcbox!(ContextCodec { codec: rhs.as_codec_ref(), context: self })
-// src/codec.rs:480     }
-//
-// See related discussion here:
-//   https://github.com/rust-lang/rust/issues/20749
+// Fail and provide codecs
 //
-// As a workaround, we handle context injection directly inside the hcodec! macro, sigh.
-//
-// impl<T: 'static> core::ops::BitOr<&'static Codec<T>> for &'static str {
-//     type Output = RcCodec<T>;
-
-//     fn bitor(self, rhs: &'static Codec<T>) -> RcCodec<T> {
-//         rcbox!(ContextCodec { codec: rhs.as_codec_ref(), context: self })
-//     }
-// }
-// impl<T: 'static> core::ops::BitOr<RcCodec<T>> for &'static str {
-//     type Output = RcCodec<T>;
 
-//     fn bitor(self, rhs: RcCodec<T>) -> RcCodec<T> {
-//         rcbox!(ContextCodec { codec: rhs.as_codec_ref(), context: self })
-//     }
-// }
-/// Codec that injects additional context (e.g. in error messages) into the given codec.
+/// Codec that always fails with the given message, both when encoding and when decoding.
 #[inline(always)]
-pub fn with_context<T, C>(context: &'static str, codec: C) -> impl Codec<Value = T>
-where
-    C: Codec<Value = T>,
-{
-    ContextCodec { codec, context }
+pub fn fail<T>(message: &str) -> impl Codec<Value = T> {
+    FailCodec {
+        message: message.to_string(),
+        phantom: PhantomData,
+    }
 }
 
-struct ContextCodec<C> {
-    codec: C,
-    context: &'static str,
+struct FailCodec<T> {
+    message: String,
+    phantom: PhantomData<T>,
 }
 
-impl<T, C> Codec for ContextCodec<C>
-where
-    C: Codec<Value = T>,
-{
+impl<T> Codec for FailCodec<T> {
     type Value = T;
 
-    fn encode(&self, value: &T) -> EncodeResult {
-        self.codec
-            .encode(value)
-            .map_err(|e| e.push_context(self.context))
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(0)
     }
 
-    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
-        self.codec
-            .decode(bv)
-            .map_err(|e| e.push_context(self.context))
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("fail", self.size_bound())
     }
-}
 
-//
-// Drop-left codec
-//
+    fn encode(&self, _value: &T) -> EncodeResult {
+        Err(Error::new(self.message.clone()))
+    }
 
-/// Codec that encodes/decodes the unit value followed by the right-hand value, discarding
-/// the unit value when decoding.
+    fn encode_into(&self, _value: &T, _out: &mut Vec<u8>) -> Result<(), Error> {
+        Err(Error::new(self.message.clone()))
+    }
+
+    fn decode(&self, _bv: &ByteVector) -> DecodeResult<T> {
+        Err(Error::new(self.message.clone()))
+    }
+
+    fn decode_slice(&self, _input: &[u8]) -> Result<(T, usize), Error> {
+        Err(Error::new(self.message.clone()))
+    }
+}
+
+/// Codec that provides a constant value without consuming or producing any bytes.
+///
+///   - Encodes by returning an empty byte vector.
+///   - Decodes by returning a clone of the given value and leaving the input untouched.
 #[inline(always)]
-pub fn drop_left<T, LC, RC>(lhs: LC, rhs: RC) -> impl Codec<Value = T>
-where
-    LC: Codec<Value = ()>,
-    RC: Codec<Value = T>,
-{
-    DropLeftCodec { lhs, rhs }
+pub fn provide<T: Clone>(value: T) -> impl Codec<Value = T> {
+    ProvideCodec { value }
 }
 
-struct DropLeftCodec<LC, RC> {
-    lhs: LC,
-    rhs: RC,
+struct ProvideCodec<T> {
+    value: T,
 }
 
-impl<T, LC, RC> Codec for DropLeftCodec<LC, RC>
-where
-    LC: Codec<Value = ()>,
-    RC: Codec<Value = T>,
-{
+impl<T: Clone> Codec for ProvideCodec<T> {
     type Value = T;
 
-    fn encode(&self, value: &T) -> EncodeResult {
-        forcomp!({
-            encoded_lhs <- self.lhs.encode(&());
-            encoded_rhs <- self.rhs.encode(value);
-        } yield {
-            byte_vector::append(&encoded_lhs, &encoded_rhs)
-        })
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(0)
     }
 
-    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
-        self.lhs
-            .decode(bv)
-            .and_then(|decoded| self.rhs.decode(&decoded.remainder))
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("provide", self.size_bound())
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    // TODO: Restore benchmark support
-    // use test::Bencher;
-    use std::fmt::Debug;
+
+    fn encode(&self, _value: &T) -> EncodeResult {
+        Ok(byte_vector::empty())
+    }
+
+    fn encode_into(&self, _value: &T, _out: &mut Vec<u8>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        Ok(DecoderResult {
+            value: self.value.clone(),
+            remainder: bv.clone(),
+        })
+    }
+
+    fn decode_slice(&self, _input: &[u8]) -> Result<(T, usize), Error> {
+        Ok((self.value.clone(), 0))
+    }
+}
+
+//
+// Identity codec
+//
+
+/// Identity byte vector codec.
+///
+///   - Encodes by returning the given byte vector.
+///   - Decodes by taking all remaining bytes from the given byte vector.
+#[inline(always)]
+pub fn identity_bytes() -> impl Codec<Value = ByteVector> {
+    IdentityCodec
+}
+
+struct IdentityCodec;
+
+impl Codec for IdentityCodec {
+    type Value = ByteVector;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::unknown()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("identity_bytes", self.size_bound())
+    }
+
+    fn encode(&self, value: &ByteVector) -> EncodeResult {
+        Ok((*value).clone())
+    }
+
+    fn encode_into(&self, value: &ByteVector, out: &mut Vec<u8>) -> Result<(), Error> {
+        append_to_vec(value, out)
+    }
+
+    fn encode_to(&self, value: &ByteVector, out: &mut dyn Write) -> Result<(), Error> {
+        value.write_to(out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<ByteVector> {
+        Ok(DecoderResult {
+            value: (*bv).clone(),
+            remainder: byte_vector::empty(),
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(ByteVector, usize), Error> {
+        Ok((byte_vector::from_slice_copy(input), input.len()))
+    }
+
+    fn verbatim_len(&self, available: usize) -> Option<usize> {
+        Some(available)
+    }
+}
+
+//
+// Bytes codec
+//
+
+/// Byte vector codec.
+///
+///   - Encodes by returning the given byte vector if its length is `len` bytes, otherwise returns an error.
+///   - Decodes by taking `len` bytes from the given byte vector.
+#[inline(always)]
+pub fn bytes(len: usize) -> impl Codec<Value = ByteVector> {
+    fixed_size_bytes(len, identity_bytes())
+}
+
+//
+// Fixed size bytes codec
+//
+
+/// Codec that limits the number of bytes that are available to the given `codec`.
+///
+/// When encoding, if the given `codec` encodes fewer than `len` bytes, the byte vector
+/// is right padded with low bytes.  If `codec` instead encodes more than `len` bytes,
+/// an error is returned.
+///
+/// When decoding, the given `codec` is only given `len` bytes.  If `codec` does
+/// not consume all `len` bytes, any remaining bytes are discarded.
+#[inline(always)]
+pub fn fixed_size_bytes<T, C>(len: usize, codec: C) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    fixed_size_bytes_with(len, codec, FixedSizeOptions::default())
+}
+
+/// Codec that limits the number of bytes that are available to the given `codec`, with padding
+/// and decoding behavior controlled by the given `options`.
+///
+/// When encoding, if the given `codec` encodes fewer than `len` bytes, the byte vector
+/// is padded with `options.pad_byte` on the side given by `options.pad_direction`.  If `codec`
+/// instead encodes more than `len` bytes, an error is returned.
+///
+/// When decoding, the given `codec` is only given `len` bytes, minus any left padding.  If `codec`
+/// has an exact size (see `Codec::size_bound`), left padding is skipped by length, so values may
+/// begin with the pad byte.  Otherwise all leading pad bytes are skipped, so encoding fails if the
+/// value encoded by `codec` begins with the pad byte.  If `codec` does not consume all of the bytes
+/// given to it, the remaining bytes are discarded, unless `options.strict` is set, in which case an
+/// error is returned if any of them (or of the skipped left padding) differ from the pad byte.
+#[inline(always)]
+pub fn fixed_size_bytes_with<T, C>(
+    len: usize,
+    codec: C,
+    options: FixedSizeOptions,
+) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    FixedSizeCodec {
+        len,
+        codec,
+        options,
+    }
+}
+
+/// The side on which padding bytes are added by `fixed_size_bytes_with`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PadDirection {
+    /// Padding bytes are added before the encoded value.
+    Left,
+
+    /// Padding bytes are added after the encoded value.
+    Right,
+}
+
+/// Options controlling the padding and decoding behavior of `fixed_size_bytes_with`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedSizeOptions {
+    /// The side on which padding bytes are added.
+    pub pad_direction: PadDirection,
+
+    /// The byte value used for padding.
+    pub pad_byte: u8,
+
+    /// If true, decoding fails if the inner codec leaves any non-padding bytes unconsumed, or if
+    /// the skipped left padding contains any non-padding bytes.
+    pub strict: bool,
+}
+
+impl Default for FixedSizeOptions {
+    /// Returns the options used by `fixed_size_bytes`: right padding with low bytes, and
+    /// discarding of unconsumed bytes.
+    fn default() -> FixedSizeOptions {
+        FixedSizeOptions {
+            pad_direction: PadDirection::Right,
+            pad_byte: 0,
+            strict: false,
+        }
+    }
+}
+
+const LEADING_NON_PADDING: &str = "Left padding contains non-padding bytes";
+const UNCONSUMED_NON_PADDING: &str = "Codec left non-padding bytes unconsumed";
+
+struct FixedSizeCodec<C> {
+    len: usize,
+    codec: C,
+    options: FixedSizeOptions,
+}
+
+impl<C: Codec> FixedSizeCodec<C> {
+    /// Returns the exact size of the inner codec, if it has one.
+    fn exact_size(&self) -> Option<u64> {
+        let bound = self.codec.size_bound();
+        bound.upper.filter(|upper| *upper == bound.lower)
+    }
+
+    /// Returns the number of pad bytes that precede the value within the `len` bytes available
+    /// when left padding is used, reading bytes from the start of them with `get`.
+    fn left_pad_len<F>(&self, available: usize, get: F) -> usize
+    where
+        F: Fn(usize) -> u8,
+    {
+        match self.exact_size() {
+            Some(size) => available.saturating_sub(size as usize),
+            None => (0..available)
+                .take_while(|i| get(*i) == self.options.pad_byte)
+                .count(),
+        }
+    }
+
+    /// Returns an error if strict mode is enabled and `bytes` contains non-padding bytes.
+    fn check_padding(&self, bytes: &[u8], problem: &'static str) -> Result<(), Error> {
+        if !self.options.strict || bytes.iter().all(|b| *b == self.options.pad_byte) {
+            Ok(())
+        } else {
+            Err(Error::new(lazy_description(|| {
+                format!("{} within fixed length of {}", problem, self.len)
+            })))
+        }
+    }
+
+    /// Like `check_padding`, but for bytes held in a byte vector.
+    fn check_padding_vector(&self, bytes: &ByteVector, problem: &'static str) -> Result<(), Error> {
+        if !self.options.strict || bytes.length() == 0 {
+            return Ok(());
+        }
+        match bytes.as_slice() {
+            Some(slice) => self.check_padding(slice, problem),
+            None => self.check_padding(&bytes.to_vec()?, problem),
+        }
+    }
+}
+
+impl<T, C> Codec for FixedSizeCodec<C>
+where
+    C: Codec<Value = T>,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(self.len as u64)
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("fixed_size_bytes", self.size_bound())
+            .with_child(self.codec.describe())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        // Left padding precedes the value, so encode the value into a scratch buffer first
+        let mut scratch = Vec::new();
+        let buffer = match self.options.pad_direction {
+            PadDirection::Left => &mut scratch,
+            PadDirection::Right => &mut *out,
+        };
+        let start = buffer.len();
+        self.codec.encode_into(value, buffer)?;
+        let encoded_len = buffer.len() - start;
+        if encoded_len > self.len {
+            return Err(Error::new(lazy_description(|| {
+                format!(
+                    "Encoding requires {} bytes but codec is limited to fixed length of {}",
+                    encoded_len, self.len
+                )
+            })));
+        }
+        let padding = self.len - encoded_len;
+        match self.options.pad_direction {
+            PadDirection::Left => {
+                if self.exact_size().is_none() && scratch.first() == Some(&self.options.pad_byte) {
+                    return Err(Error::new(lazy_description(|| {
+                        format!(
+                            "Left padded value within fixed length of {} begins with the pad byte, but codec does not have an exact size",
+                            self.len
+                        )
+                    })));
+                }
+                out.resize(out.len() + padding, self.options.pad_byte);
+                out.extend_from_slice(&scratch);
+            }
+            PadDirection::Right => out.resize(out.len() + padding, self.options.pad_byte),
+        }
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        // Give `len` bytes (minus any leading padding) to the decoder; if successful, return the
+        // result along with the remainder of `bv` after dropping `len` bytes from it
+        forcomp!({
+            taken <- bv.take(self.len);
+            window <- match self.options.pad_direction {
+                PadDirection::Left => {
+                    let pad_len =
+                        self.left_pad_len(self.len, |i| taken.get_u8(i).unwrap_or_default());
+                    self.check_padding_vector(&taken.truncate(pad_len), LEADING_NON_PADDING)
+                        .and_then(|_| taken.drop(pad_len))
+                }
+                PadDirection::Right => Ok(taken),
+            };
+            decoded <- cursor::region(bv.length(), self.len, || self.codec.decode(&window))
+                .map_err(Error::within_bounded_input);
+            _checked <- self.check_padding_vector(&decoded.remainder, UNCONSUMED_NON_PADDING);
+        } yield {
+            DecoderResult { value: decoded.value, remainder: bv.drop(self.len).unwrap() }
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        let taken = slice_prefix(input, self.len)?;
+        let window = match self.options.pad_direction {
+            PadDirection::Left => {
+                let pad_len = self.left_pad_len(self.len, |i| taken[i]);
+                self.check_padding(&taken[..pad_len], LEADING_NON_PADDING)?;
+                &taken[pad_len..]
+            }
+            PadDirection::Right => taken,
+        };
+        let (value, consumed) =
+            cursor::region(input.len(), self.len, || self.codec.decode_slice(window))
+                .map_err(Error::within_bounded_input)?;
+        self.check_padding(&window[consumed..], UNCONSUMED_NON_PADDING)?;
+        Ok((value, self.len))
+    }
+
+    fn verbatim_len(&self, _available: usize) -> Option<usize> {
+        // Left padding is skipped when decoding, so only right-padded values are taken verbatim
+        match self.options.pad_direction {
+            PadDirection::Right if self.codec.verbatim_len(self.len) == Some(self.len) => {
+                Some(self.len)
+            }
+            _ => None,
+        }
+    }
+}
+
+//
+// Aligned codec
+//
+
+/// Codec that pads the bytes produced by the given `codec` with low bytes up to the next multiple
+/// of `alignment`.
+///
+/// When decoding, the padding that follows the bytes consumed by `codec` is skipped (but not
+/// otherwise verified).
+///
+/// Panics if `alignment` is zero.
+#[inline(always)]
+pub fn aligned<T, C>(codec: C, alignment: usize) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    aligned_with_fill(codec, alignment, 0)
+}
+
+/// Codec that pads the bytes produced by the given `codec` with `fill` bytes up to the next
+/// multiple of `alignment`; see `aligned` for details.
+///
+/// Panics if `alignment` is zero.
+#[inline(always)]
+pub fn aligned_with_fill<T, C>(codec: C, alignment: usize, fill: u8) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    assert!(alignment > 0, "Alignment must be greater than zero");
+    AlignedCodec {
+        codec,
+        alignment,
+        fill,
+    }
+}
+
+struct AlignedCodec<C> {
+    codec: C,
+    alignment: usize,
+    fill: u8,
+}
+
+impl<C> AlignedCodec<C> {
+    /// Returns the number of padding bytes needed to align a value of `len` bytes.
+    fn padding(&self, len: usize) -> usize {
+        (self.alignment - len % self.alignment) % self.alignment
+    }
+}
+
+impl<T, C> Codec for AlignedCodec<C>
+where
+    C: Codec<Value = T>,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        let bound = self.codec.size_bound();
+        let align = |size: u64| size + self.padding(size as usize) as u64;
+        SizeBound {
+            lower: align(bound.lower),
+            upper: bound.upper.map(align),
+        }
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("aligned", self.size_bound()).with_child(self.codec.describe())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        let start = out.len();
+        self.codec.encode_into(value, out)?;
+        let padding = self.padding(out.len() - start);
+        out.resize(out.len() + padding, self.fill);
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        self.codec.decode(bv).and_then(|decoded| {
+            let consumed = bv.length() - decoded.remainder.length();
+            let padding = self.padding(consumed);
+            decoded
+                .remainder
+                .drop(padding)
+                .map(|remainder| DecoderResult {
+                    value: decoded.value,
+                    remainder,
+                })
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        let (value, consumed) = self.codec.decode_slice(input)?;
+        let size = consumed + self.padding(consumed);
+        slice_prefix(input, size).map(|_| (value, size))
+    }
+}
+
+//
+// Variable size bytes codec
+//
+
+/// Codec for length-delimited values.
+///
+///   - Encodes by encoding the length (in bytes) of the value followed by the value itself.
+///   - Decodes by decoding the length and then attempting to decode the value that follows.
+#[inline(always)]
+pub fn variable_size_bytes<L, V, LC, VC>(len_codec: LC, val_codec: VC) -> impl Codec<Value = V>
+where
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    LC: Codec<Value = L>,
+    VC: Codec<Value = V>,
+{
+    variable_size_bytes_with(len_codec, val_codec, LengthAdjustment::default())
+}
+
+/// Codec for length-delimited values, where the relationship between the encoded length field and
+/// the size of the value (in bytes) is described by the given `adjustment`.
+///
+///   - Encodes by encoding the adjusted length of the value followed by the value itself.
+///   - Decodes by decoding the adjusted length, converting it back to a size in bytes, and then
+///     attempting to decode the value that follows.
+#[inline(always)]
+pub fn variable_size_bytes_with<L, V, LC, VC>(
+    len_codec: LC,
+    val_codec: VC,
+    adjustment: LengthAdjustment,
+) -> impl Codec<Value = V>
+where
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    LC: Codec<Value = L>,
+    VC: Codec<Value = V>,
+{
+    assert!(adjustment.unit > 0, "Length unit must be greater than zero");
+    VariableSizeCodec {
+        len_codec,
+        val_codec,
+        adjustment,
+        max_len: None,
+    }
+}
+
+/// Codec for length-delimited values whose size is limited to `max_len` bytes.
+///
+///   - Encodes as in `variable_size_bytes`, failing if the encoded value is longer than `max_len`.
+///   - Decodes as in `variable_size_bytes`, except that a decoded length greater than `max_len` is
+///     rejected before any further input is examined.
+///
+/// This should be preferred over `variable_size_bytes` when decoding untrusted input, so that a
+/// malicious length prefix cannot cause excessive work or allocation in the value codec.
+#[inline(always)]
+pub fn variable_size_bytes_bounded<L, V, LC, VC>(
+    len_codec: LC,
+    val_codec: VC,
+    max_len: usize,
+) -> impl Codec<Value = V>
+where
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    LC: Codec<Value = L>,
+    VC: Codec<Value = V>,
+{
+    VariableSizeCodec {
+        len_codec,
+        val_codec,
+        adjustment: LengthAdjustment::default(),
+        max_len: Some(max_len),
+    }
+}
+
+/// Codec for a structure (such as an `HList` produced by `hcodec!`) whose encoding is preceded by a
+/// length field.
+///
+///   - Encodes the body using `body_codec`, preceded by its length in bytes, encoded using
+///     `len_codec`.
+///   - Decodes the length, then decodes the body from exactly that many bytes, failing if
+///     `body_codec` does not consume all of them.
+///
+/// The length is always computed from the encoded body and does not appear in the decoded value,
+/// so there is no length field in the user's struct that could become inconsistent with its other
+/// fields.
+#[inline(always)]
+pub fn with_length_field<L, V, LC, VC>(len_codec: LC, body_codec: VC) -> impl Codec<Value = V>
+where
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    LC: Codec<Value = L>,
+    VC: Codec<Value = V>,
+{
+    variable_size_bytes(len_codec, complete(body_codec))
+}
+
+/// Describes how the length field of `variable_size_bytes_with` relates to the size of the value.
+///
+/// The encoded length field is computed as `(size + prefix) / unit + bias`, where `size` is the
+/// size of the encoded value in bytes, and `prefix` is the size of the encoded length field if
+/// `includes_prefix` is true (or zero otherwise).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LengthAdjustment {
+    /// If true, the length includes the size of the length field itself.
+    pub includes_prefix: bool,
+
+    /// The size (in bytes) of the units in which the length is expressed, e.g. 4 for lengths
+    /// that count 32-bit words.  Encoding fails if the size is not a multiple of the unit.
+    pub unit: usize,
+
+    /// A constant that is added to the length.
+    pub bias: i64,
+}
+
+impl Default for LengthAdjustment {
+    /// Returns the adjustment used by `variable_size_bytes`, where the length field holds the size
+    /// of the value in bytes.
+    fn default() -> LengthAdjustment {
+        LengthAdjustment {
+            includes_prefix: false,
+            unit: 1,
+            bias: 0,
+        }
+    }
+}
+
+impl LengthAdjustment {
+    /// Converts the size of an encoded value (and its length field) to a length field value.
+    fn field_for_size(&self, size: usize, prefix_len: usize) -> Result<i128, Error> {
+        let total = size as i128
+            + if self.includes_prefix {
+                prefix_len as i128
+            } else {
+                0
+            };
+        let unit = self.unit as i128;
+        if total % unit != 0 {
+            return Err(Error::new(lazy_description(|| {
+                format!(
+                "Length of encoded value ({} bytes) is not a multiple of the length unit ({} bytes)",
+                total, self.unit
+            )
+            })));
+        }
+        Ok(total / unit + self.bias as i128)
+    }
+
+    /// Converts a decoded length field value to the size of the encoded value.
+    fn size_for_field(&self, field: i128, prefix_len: usize) -> Result<usize, Error> {
+        let total = (field - self.bias as i128) * self.unit as i128;
+        let size = total
+            - if self.includes_prefix {
+                prefix_len as i128
+            } else {
+                0
+            };
+        if size < 0 || size > usize::MAX as i128 {
+            Err(Error::new(lazy_description(|| {
+                format!("Decoded length ({}) does not describe a valid size", field)
+            })))
+        } else {
+            Ok(size as usize)
+        }
+    }
+}
+
+struct VariableSizeCodec<LC, VC> {
+    len_codec: LC,
+    val_codec: VC,
+    adjustment: LengthAdjustment,
+    max_len: Option<usize>,
+}
+
+impl<L, LC, VC> VariableSizeCodec<LC, VC>
+where
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    LC: Codec<Value = L>,
+{
+    /// Returns an error if `size` exceeds the maximum length, if any.
+    fn check_max_len(&self, size: usize) -> Result<(), Error> {
+        match self.max_len {
+            Some(max_len) if size > max_len => Err(Error::new(lazy_description(|| {
+                format!(
+                    "Length of value ({} bytes) exceeds maximum length of {} bytes",
+                    size, max_len
+                )
+            }))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Converts a decoded length field to the size of the value, enforcing the maximum length.
+    fn size_for_field(&self, field: L, prefix_len: usize) -> Result<usize, Error> {
+        let size = self
+            .adjustment
+            .size_for_field(unsigned_to_u64(&field)? as i128, prefix_len)?;
+        self.check_max_len(size)?;
+        limits::check_alloc(size).map(|_| size)
+    }
+
+    /// Encodes the length field for a value of `size` bytes.
+    fn encode_len(&self, size: usize) -> EncodeResult {
+        self.check_max_len(size)?;
+        // When the length includes the length field itself, we may need to encode more than once to
+        // settle on the size of the length field (for variable-length length codecs)
+        let mut prefix_len = 0;
+        for _ in 0..3 {
+            let field = self.adjustment.field_for_size(size, prefix_len)?;
+            // Fail if length is too long to be encoded
+            let len = match u64::try_from(field).ok().and_then(L::from_u64) {
+                Some(len) => len,
+                None => {
+                    return Err(Error::new(lazy_description(|| {
+                        format!(
+                        "Length of encoded value ({} bytes) is greater than maximum value ({}) of length type",
+                        size,
+                        L::max_value()
+                    )
+                    })))
+                }
+            };
+            let encoded_len = self.len_codec.encode(&len)?;
+            if !self.adjustment.includes_prefix || encoded_len.length() == prefix_len {
+                return Ok(encoded_len);
+            }
+            prefix_len = encoded_len.length();
+        }
+        Err(Error::new(lazy_description(|| {
+            "Unable to determine size of length field".to_string()
+        })))
+    }
+}
+
+impl<L, V, LC, VC> Codec for VariableSizeCodec<LC, VC>
+where
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    LC: Codec<Value = L>,
+    VC: Codec<Value = V>,
+{
+    type Value = V;
+
+    fn size_bound(&self) -> SizeBound {
+        self.len_codec.size_bound() + self.val_codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("variable_size_bytes", self.size_bound())
+            .with_child(self.len_codec.describe())
+            .with_child(self.val_codec.describe())
+    }
+
+    fn encode(&self, value: &V) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &V, out: &mut Vec<u8>) -> Result<(), Error> {
+        // Encode the value, then prepend the length of the encoded value
+        let mut encoded_val = Vec::new();
+        self.val_codec.encode_into(value, &mut encoded_val)?;
+        let encoded_len = self.encode_len(encoded_val.len())?;
+        append_to_vec(&encoded_len, out)?;
+        out.extend_from_slice(&encoded_val);
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<V> {
+        // Decode the length, then decode the value
+        forcomp!({
+            decoded_len <- self.len_codec.decode(bv);
+            len <- {
+                let prefix_len = bv.length() - decoded_len.remainder.length();
+                self.size_for_field(decoded_len.value, prefix_len)
+            };
+            remainder <- {
+                // TODO: Ideally we'd just use fixed_size_bytes() here, but not sure how to transfer ownership of val_decoder
+                decoded_len.remainder.take(len)
+                    .map_err(|e| e.offset_by(bv.length() - decoded_len.remainder.length()))
+            };
+            decoded_val <- cursor::region(decoded_len.remainder.length(), len, || self.val_codec.decode(&remainder))
+                .map_err(Error::within_bounded_input);
+        } yield {
+            DecoderResult { value: decoded_val.value, remainder: decoded_len.remainder.drop(remainder.length()).unwrap() }
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(V, usize), Error> {
+        // Decode the length, then decode the value
+        let (field, prefix_len) = self.len_codec.decode_slice(input)?;
+        let len = self.size_for_field(field, prefix_len)?;
+        let taken = slice_prefix(&input[prefix_len..], len).map_err(|e| e.offset_by(prefix_len))?;
+        cursor::region(input.len() - prefix_len, len, || {
+            self.val_codec.decode_slice(taken)
+        })
+        .map(|(value, _)| (value, prefix_len + len))
+        .map_err(Error::within_bounded_input)
+    }
+}
+
+//
+// Delimited codecs
+//
+
+/// Codec for values that are terminated by the given delimiter, as in line-based or
+/// NUL-terminated formats.
+///
+///   - Encodes the value followed by the delimiter; fails if the encoded value itself contains the
+///     delimiter.
+///   - Decodes the value from all bytes preceding the first occurrence of the delimiter, and then
+///     discards the delimiter; fails if there is no delimiter, or if the value codec leaves any of
+///     the preceding bytes unconsumed.
+///
+/// Panics if `delimiter` is empty.
+#[inline(always)]
+pub fn delimited<T, C, D>(codec: C, delimiter: D) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+    D: Into<ByteVector>,
+{
+    DelimitedCodec::new(codec, delimiter.into(), None)
+}
+
+/// Codec for values that are terminated by the given delimiter, where occurrences of the
+/// delimiter within the value are escaped.
+///
+///   - Encodes the value with the `escape` byte inserted before each occurrence of the `escape`
+///     byte and of the first byte of the delimiter, followed by the delimiter.
+///   - Decodes by removing escape bytes up to the first unescaped occurrence of the delimiter, and
+///     then decoding the value from the unescaped bytes as in `delimited`.
+///
+/// Panics if `delimiter` is empty or contains the `escape` byte.
+#[inline(always)]
+pub fn delimited_escaped<T, C, D>(codec: C, delimiter: D, escape: u8) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+    D: Into<ByteVector>,
+{
+    DelimitedCodec::new(codec, delimiter.into(), Some(escape))
+}
+
+struct DelimitedCodec<C> {
+    codec: C,
+    delimiter: Vec<u8>,
+    escape: Option<u8>,
+}
+
+impl<C> DelimitedCodec<C> {
+    fn new(codec: C, delimiter: ByteVector, escape: Option<u8>) -> DelimitedCodec<C> {
+        let delimiter = if delimiter.length() == 0 {
+            Vec::new()
+        } else {
+            delimiter.to_vec().unwrap()
+        };
+        assert!(!delimiter.is_empty(), "Delimiter must not be empty");
+        if let Some(escape) = escape {
+            assert!(
+                !delimiter.contains(&escape),
+                "Delimiter must not contain the escape byte"
+            );
+        }
+        DelimitedCodec {
+            codec,
+            delimiter,
+            escape,
+        }
+    }
+
+    fn missing_delimiter(&self) -> Error {
+        Error::insufficient_data(lazy_description(|| {
+            format!(
+                "Delimiter {:?} not found",
+                byte_vector::from_slice_copy(&self.delimiter)
+            )
+        }))
+    }
+
+    /// Scans for the first unescaped delimiter using the given byte accessor, returning the
+    /// unescaped bytes that precede it along with the number of bytes consumed (including the
+    /// delimiter).
+    fn unescape<F>(&self, escape: u8, len: usize, get_u8: F) -> Result<(Vec<u8>, usize), Error>
+    where
+        F: Fn(usize) -> Result<u8, Error>,
+    {
+        let mut bytes = Vec::new();
+        let mut i = 0;
+        while i < len {
+            let byte = get_u8(i)?;
+            if byte == escape {
+                bytes.push(get_u8(i + 1)?);
+                i += 2;
+            } else if byte == self.delimiter[0]
+                && len - i >= self.delimiter.len()
+                && (1..self.delimiter.len()).all(|j| get_u8(i + j).ok() == Some(self.delimiter[j]))
+            {
+                return Ok((bytes, i + self.delimiter.len()));
+            } else {
+                bytes.push(byte);
+                i += 1;
+            }
+        }
+        Err(self.missing_delimiter())
+    }
+}
+
+impl<T, C> Codec for DelimitedCodec<C>
+where
+    C: Codec<Value = T>,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        let lower = self.codec.size_bound().lower + self.delimiter.len() as u64;
+        match self.escape {
+            // Each value byte may be escaped
+            Some(_) => SizeBound::at_least(lower),
+            None => self.codec.size_bound() + SizeBound::exact(self.delimiter.len() as u64),
+        }
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("delimited", self.size_bound()).with_child(self.codec.describe())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        match self.escape {
+            Some(escape) => {
+                let mut encoded = Vec::new();
+                self.codec.encode_into(value, &mut encoded)?;
+                for byte in encoded {
+                    if byte == escape || byte == self.delimiter[0] {
+                        out.push(escape);
+                    }
+                    out.push(byte);
+                }
+                out.extend_from_slice(&self.delimiter);
+            }
+            None => {
+                let start = out.len();
+                self.codec.encode_into(value, out)?;
+                let value_len = out.len() - start;
+                out.extend_from_slice(&self.delimiter);
+
+                // The first occurrence of the delimiter must be the one that was just appended,
+                // including any occurrence that begins within the value and overlaps it
+                let first = out[start..]
+                    .windows(self.delimiter.len())
+                    .position(|w| w == &self.delimiter[..]);
+                if first != Some(value_len) {
+                    return Err(Error::new(lazy_description(|| {
+                        format!(
+                            "Encoded value contains delimiter {:?}",
+                            byte_vector::from_slice_copy(&self.delimiter)
+                        )
+                    })));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        let (value, consumed) = match self.escape {
+            Some(escape) => {
+                let (bytes, consumed) = self.unescape(escape, bv.length(), |i| bv.get_u8(i))?;
+                let decoded = cursor::detached(|| {
+                    self.codec
+                        .decode(&byte_vector::from_vec(bytes))
+                        .and_then(require_complete)
+                })
+                .map_err(Error::within_bounded_input)?;
+                (decoded.value, consumed)
+            }
+            None => {
+                let index = bv
+                    .index_of_slice(&self.delimiter, 0)?
+                    .ok_or_else(|| self.missing_delimiter())?;
+                let taken = bv.take(index)?;
+                let decoded = cursor::region(bv.length(), index, || {
+                    self.codec.decode(&taken).and_then(require_complete)
+                })
+                .map_err(Error::within_bounded_input)?;
+                (decoded.value, index + self.delimiter.len())
+            }
+        };
+        bv.drop(consumed)
+            .map(|remainder| DecoderResult { value, remainder })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        let (value, consumed) = match self.escape {
+            Some(escape) => {
+                let (bytes, consumed) =
+                    self.unescape(escape, input.len(), |i| slice_u8(input, i))?;
+                let value = cursor::detached(|| self.codec.decode_all(&bytes))
+                    .map_err(Error::within_bounded_input)?;
+                (value, consumed)
+            }
+            None => {
+                let index = input
+                    .windows(self.delimiter.len())
+                    .position(|w| w == &self.delimiter[..])
+                    .ok_or_else(|| self.missing_delimiter())?;
+                let value = cursor::region(input.len(), index, || {
+                    self.codec.decode_all(&input[..index])
+                })
+                .map_err(Error::within_bounded_input)?;
+                (value, index + self.delimiter.len())
+            }
+        };
+        Ok((value, consumed))
+    }
+}
+
+//
+// DER codecs
+//
+
+/// Codec for ASN.1 DER length octets.
+///
+///   - Encodes lengths below 128 in the single-byte short form, and larger lengths in the long form
+///     (a byte holding `0x80` plus the number of length bytes, followed by the length as a
+///     minimal big-endian integer).
+///   - Decodes either form, rejecting the indefinite form and non-minimal encodings as required by
+///     DER.
+#[inline(always)]
+pub fn der_length() -> impl Codec<Value = usize> {
+    DerLengthCodec
+}
+
+/// Codec for an ASN.1 DER tag-length-value structure with the given (single-byte) tag, where the
+/// value is encoded using the given codec.
+///
+///   - Encodes the tag, then the DER length of the encoded value, then the value itself.
+///   - Decodes by checking the tag and decoding the length, and then decoding the value from
+///     exactly that many bytes; it is an error if the value codec leaves any of them unconsumed.
+#[inline(always)]
+pub fn der_tlv<T, C>(tag: u8, codec: C) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    drop_left(
+        constant([tag]),
+        variable_size_bytes(der_length(), complete(codec)),
+    )
+}
+
+struct DerLengthCodec;
+
+impl DerLengthCodec {
+    /// Appends the length octets for the given length to `out`.
+    fn write(len: usize, out: &mut Vec<u8>) {
+        if len < 0x80 {
+            out.push(len as u8);
+        } else {
+            let bytes = len.to_be_bytes();
+            let skip = bytes.iter().take_while(|b| **b == 0).count();
+            out.push(0x80 | (bytes.len() - skip) as u8);
+            out.extend_from_slice(&bytes[skip..]);
+        }
+    }
+
+    /// Decodes length octets using the given byte accessor, returning the length along with the
+    /// number of length octets.
+    fn read<F>(get_u8: F) -> Result<(usize, usize), Error>
+    where
+        F: Fn(usize) -> Result<u8, Error>,
+    {
+        let first = get_u8(0)?;
+        if first < 0x80 {
+            return Ok((first as usize, 1));
+        }
+        let count = (first & 0x7f) as usize;
+        if count == 0 {
+            return Err(Error::new(lazy_description(|| {
+                "Indefinite-length form is not allowed in DER".to_string()
+            })));
+        }
+        if count > size_of::<usize>() {
+            return Err(Error::new(lazy_description(|| {
+                format!(
+                    "DER length of {} bytes exceeds maximum of {} bytes",
+                    count,
+                    size_of::<usize>()
+                )
+            })));
+        }
+        let mut len = 0usize;
+        for i in 1..=count {
+            len = (len << 8) | get_u8(i)? as usize;
+        }
+        if len < 0x80 || len >> ((count - 1) * 8) == 0 {
+            return Err(Error::new(lazy_description(|| {
+                format!("DER length {} is not minimally encoded", len)
+            })));
+        }
+        Ok((len, count + 1))
+    }
+}
+
+impl Codec for DerLengthCodec {
+    type Value = usize;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::bounded(1, 1 + size_of::<usize>() as u64)
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("der_length", self.size_bound())
+    }
+
+    fn encode(&self, value: &usize) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &usize, out: &mut Vec<u8>) -> Result<(), Error> {
+        DerLengthCodec::write(*value, out);
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<usize> {
+        let (value, size) = DerLengthCodec::read(|i| bv.get_u8(i))?;
+        bv.drop(size)
+            .map(|remainder| DecoderResult { value, remainder })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(usize, usize), Error> {
+        DerLengthCodec::read(|i| slice_u8(input, i))
+    }
+}
+
+//
+// Protocol Buffers codecs
+//
+
+/// Protocol Buffers wire types, which determine how the value following a field key is encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WireType {
+    /// A LEB128 varint (`int32`, `uint64`, `sint32`, `bool`, `enum`, etc); see `vuint` and `vint`.
+    Varint = 0,
+    /// A little-endian 64-bit value (`fixed64`, `sfixed64`, `double`); see `protobuf_fixed64`.
+    Fixed64 = 1,
+    /// A varint length followed by that many bytes (`string`, `bytes`, embedded messages, packed
+    /// repeated fields); see `protobuf_length_delimited`.
+    LengthDelimited = 2,
+    /// The start of a group (deprecated).
+    StartGroup = 3,
+    /// The end of a group (deprecated).
+    EndGroup = 4,
+    /// A little-endian 32-bit value (`fixed32`, `sfixed32`, `float`); see `protobuf_fixed32`.
+    Fixed32 = 5,
+}
+
+impl WireType {
+    fn from_u32(value: u32) -> Option<WireType> {
+        match value {
+            0 => Some(WireType::Varint),
+            1 => Some(WireType::Fixed64),
+            2 => Some(WireType::LengthDelimited),
+            3 => Some(WireType::StartGroup),
+            4 => Some(WireType::EndGroup),
+            5 => Some(WireType::Fixed32),
+            _ => None,
+        }
+    }
+}
+
+/// The key that precedes each field in a Protocol Buffers message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ProtobufKey {
+    /// The field number, between 1 and 2^29 - 1 (inclusive).
+    pub field_number: u32,
+
+    /// The wire type of the field value.
+    pub wire_type: WireType,
+}
+
+const PROTOBUF_MAX_FIELD_NUMBER: u32 = (1 << 29) - 1;
+
+/// Codec for Protocol Buffers field keys, encoded as a varint holding `(field_number << 3) |
+/// wire_type`.  Encoding and decoding fail for field numbers outside the valid range, and decoding
+/// fails for unknown wire types.
+#[inline(always)]
+pub fn protobuf_key() -> impl Codec<Value = ProtobufKey> {
+    exmap(
+        vuint::<u32>(),
+        |raw| {
+            let field_number = raw >> 3;
+            if field_number == 0 {
+                return Err(Error::new(lazy_description(|| {
+                    "Invalid protobuf field number 0".to_string()
+                })));
+            }
+            match WireType::from_u32(raw & 0x7) {
+                Some(wire_type) => Ok(ProtobufKey {
+                    field_number,
+                    wire_type,
+                }),
+                None => Err(Error::new(lazy_description(|| {
+                    format!("Unknown protobuf wire type {}", raw & 0x7)
+                }))),
+            }
+        },
+        |key: &ProtobufKey| {
+            if key.field_number == 0 || key.field_number > PROTOBUF_MAX_FIELD_NUMBER {
+                return Err(Error::new(lazy_description(|| {
+                    format!("Invalid protobuf field number {}", key.field_number)
+                })));
+            }
+            Ok((key.field_number << 3) | key.wire_type as u32)
+        },
+    )
+}
+
+/// Codec for a Protocol Buffers field with the given key, where the field value is encoded using
+/// the given codec.
+///
+///   - Encodes the key followed by the value.
+///   - Decodes by checking that the next key matches and then decoding the value.
+///
+/// Note that since protobuf fields may appear in any order (and unknown fields may appear), this
+/// is best suited to formats where the field order is fixed.  Use `protobuf_key` directly to
+/// handle fields in arbitrary order.
+///
+/// Panics if `field_number` is outside the valid range.
+#[inline(always)]
+pub fn protobuf_field<T, C>(
+    field_number: u32,
+    wire_type: WireType,
+    codec: C,
+) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    assert!(
+        field_number > 0 && field_number <= PROTOBUF_MAX_FIELD_NUMBER,
+        "Protobuf field number must be between 1 and 2^29 - 1"
+    );
+    let mut encoded_key = Vec::new();
+    write_leb128(
+        u64::from((field_number << 3) | wire_type as u32),
+        &mut encoded_key,
+    );
+    drop_left(constant(encoded_key), codec)
+}
+
+/// Codec for the value of a Protocol Buffers length-delimited field, encoded as a varint length
+/// followed by the value encoded using the given codec.
+#[inline(always)]
+pub fn protobuf_length_delimited<T, C>(codec: C) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    variable_size_bytes(vuint::<u32>(), codec)
+}
+
+/// Codec for the value of a Protocol Buffers `fixed32` field (a little-endian 32-bit value).
+#[inline(always)]
+pub fn protobuf_fixed32() -> impl Codec<Value = u32> {
+    uint32_l
+}
+
+/// Codec for the value of a Protocol Buffers `fixed64` field (a little-endian 64-bit value).
+#[inline(always)]
+pub fn protobuf_fixed64() -> impl Codec<Value = u64> {
+    uint64_l
+}
+
+//
+// TLV codecs
+//
+
+/// A decoded tag-length-value element, as produced by `tlv`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tlv<T, V> {
+    /// The tag.
+    pub tag: T,
+
+    /// The value.
+    pub value: TlvValue<V>,
+}
+
+/// The value of a tag-length-value element.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TlvValue<V> {
+    /// A value decoded using the codec registered for the element's tag.
+    Known(V),
+
+    /// The raw bytes of a value whose tag has no registered codec.
+    Unknown(ByteVector),
+}
+
+/// Codec for tag-length-value elements, where the codec used for the value depends on the tag.
+///
+///   - Encodes the tag using `tag_codec`, then the length (in bytes) of the encoded value using
+///     `len_codec`, then the value itself.  `Known` values are encoded using the codec registered
+///     for the tag (failing if there is none), and `Unknown` values are encoded as-is.
+///   - Decodes the tag and length, then decodes the value from exactly that many bytes using the
+///     codec registered for the tag; it is an error if the value codec leaves any of them
+///     unconsumed, or if there is no codec registered for the tag.
+///
+/// Note that since the codecs are stored in a `HashMap`, they must all have the same type; boxed
+/// codecs (`Box<dyn Codec<Value = V>>`) can be used to mix codecs of different types.
+#[inline(always)]
+pub fn tlv<T, L, V, TC, LC, C>(
+    tag_codec: TC,
+    len_codec: LC,
+    registry: HashMap<T, C>,
+) -> impl Codec<Value = Tlv<T, V>>
+where
+    T: Eq + Hash + Debug,
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    TC: Codec<Value = T>,
+    LC: Codec<Value = L>,
+    C: Codec<Value = V>,
+{
+    TlvCodec {
+        tag_codec,
+        len_codec,
+        registry,
+        preserve_unknown: false,
+    }
+}
+
+/// Codec for tag-length-value elements that behaves like `tlv`, except that values whose tag has
+/// no registered codec are decoded as `TlvValue::Unknown` rather than causing an error.  This
+/// allows unrecognized elements to be skipped over or passed through unchanged.
+#[inline(always)]
+pub fn tlv_preserving_unknown<T, L, V, TC, LC, C>(
+    tag_codec: TC,
+    len_codec: LC,
+    registry: HashMap<T, C>,
+) -> impl Codec<Value = Tlv<T, V>>
+where
+    T: Eq + Hash + Debug,
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    TC: Codec<Value = T>,
+    LC: Codec<Value = L>,
+    C: Codec<Value = V>,
+{
+    TlvCodec {
+        tag_codec,
+        len_codec,
+        registry,
+        preserve_unknown: true,
+    }
+}
+
+struct TlvCodec<T, TC, LC, C> {
+    tag_codec: TC,
+    len_codec: LC,
+    registry: HashMap<T, C>,
+    preserve_unknown: bool,
+}
+
+impl<T, TC, LC, C> TlvCodec<T, TC, LC, C>
+where
+    T: Eq + Hash + Debug,
+{
+    /// Returns the codec registered for the given tag.
+    fn codec_for(&self, tag: &T) -> Result<&C, Error> {
+        self.registry.get(tag).ok_or_else(|| {
+            Error::new(lazy_description(|| {
+                format!("No codec registered for tag {:?}", tag)
+            }))
+        })
+    }
+}
+
+impl<T, L, V, TC, LC, C> Codec for TlvCodec<T, TC, LC, C>
+where
+    T: Eq + Hash + Debug,
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    TC: Codec<Value = T>,
+    LC: Codec<Value = L>,
+    C: Codec<Value = V>,
+{
+    type Value = Tlv<T, V>;
+
+    fn size_bound(&self) -> SizeBound {
+        self.tag_codec.size_bound() + self.len_codec.size_bound() + SizeBound::unknown()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("tlv", self.size_bound())
+            .with_child(self.tag_codec.describe())
+            .with_child(self.len_codec.describe())
+    }
+
+    fn encode(&self, value: &Tlv<T, V>) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &Tlv<T, V>, out: &mut Vec<u8>) -> Result<(), Error> {
+        let mut encoded_val = Vec::new();
+        match value.value {
+            TlvValue::Known(ref v) => self
+                .codec_for(&value.tag)?
+                .encode_into(v, &mut encoded_val)?,
+            TlvValue::Unknown(ref bytes) => append_to_vec(bytes, &mut encoded_val)?,
+        }
+        let len = L::from_usize(encoded_val.len()).ok_or_else(|| {
+            Error::new(lazy_description(|| format!(
+                "Length of encoded value ({} bytes) is greater than maximum value ({}) of length type",
+                encoded_val.len(),
+                L::max_value()
+            )))
+        })?;
+        self.tag_codec.encode_into(&value.tag, out)?;
+        self.len_codec.encode_into(&len, out)?;
+        out.extend_from_slice(&encoded_val);
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<Tlv<T, V>> {
+        let decoded_tag = self.tag_codec.decode(bv)?;
+        let decoded_len = self
+            .len_codec
+            .decode(&decoded_tag.remainder)
+            .map_err(|e| e.offset_by(bv.length() - decoded_tag.remainder.length()))?;
+        let len = decoded_len.value.to_usize().unwrap();
+        limits::check_alloc(len)?;
+        let region = decoded_len
+            .remainder
+            .take(len)
+            .map_err(|e| e.offset_by(bv.length() - decoded_len.remainder.length()))?;
+        let value = if self.preserve_unknown && !self.registry.contains_key(&decoded_tag.value) {
+            TlvValue::Unknown(region)
+        } else {
+            let codec = self.codec_for(&decoded_tag.value)?;
+            let decoded = cursor::region(decoded_len.remainder.length(), len, || {
+                codec.decode(&region).and_then(require_complete)
+            })
+            .map_err(Error::within_bounded_input)?;
+            TlvValue::Known(decoded.value)
+        };
+        Ok(DecoderResult {
+            value: Tlv {
+                tag: decoded_tag.value,
+                value,
+            },
+            remainder: decoded_len.remainder.drop(len)?,
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(Tlv<T, V>, usize), Error> {
+        let (tag, tag_size) = self.tag_codec.decode_slice(input)?;
+        let (len, len_size) = self
+            .len_codec
+            .decode_slice(&input[tag_size..])
+            .map_err(|e| e.offset_by(tag_size))?;
+        let len = len.to_usize().unwrap();
+        limits::check_alloc(len)?;
+        let start = tag_size + len_size;
+        let region = slice_prefix(&input[start..], len).map_err(|e| e.offset_by(start))?;
+        let value = if self.preserve_unknown && !self.registry.contains_key(&tag) {
+            TlvValue::Unknown(byte_vector::from_slice_copy(region))
+        } else {
+            let codec = self.codec_for(&tag)?;
+            let value = cursor::region(input.len() - start, len, || codec.decode_all(region))
+                .map_err(Error::within_bounded_input)?;
+            TlvValue::Known(value)
+        };
+        Ok((Tlv { tag, value }, start + len))
+    }
+}
+
+//
+// Eager bytes codec
+//
+
+/// Codec that encodes/decodes fully-realized `Vec<u8>` values.
+///
+///   - Encodes by first efficiently converting `Vec<u8>` values to a `ByteVector`.
+///   - Decodes by performing a fully-realized read on the backing `ByteVector`.
+#[inline(always)]
+pub fn eager<C>(bv_codec: C) -> impl Codec<Value = Vec<u8>>
+where
+    C: Codec<Value = ByteVector>,
+{
+    EagerCodec { bv_codec }
+}
+
+struct EagerCodec<C> {
+    bv_codec: C,
+}
+
+impl<C> Codec for EagerCodec<C>
+where
+    C: Codec<Value = ByteVector>,
+{
+    type Value = Vec<u8>;
+
+    fn size_bound(&self) -> SizeBound {
+        self.bv_codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("eager", self.size_bound()).with_child(self.bv_codec.describe())
+    }
+
+    fn encode(&self, value: &Vec<u8>) -> EncodeResult {
+        self.bv_codec.encode(&byte_vector::from_slice_copy(value))
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<Vec<u8>> {
+        // Read bytes that would be decoded verbatim (e.g. by `bytes(n)`) directly into the result,
+        // rather than decoding a view of them and then copying it
+        if let Some(len) = self.verbatim_len(bv.length()) {
+            let mut vec = vec![0u8; len];
+            bv.read(&mut vec, 0, len)?;
+            return Ok(DecoderResult {
+                value: vec,
+                remainder: bv.drop(len)?,
+            });
+        }
+
+        forcomp!({
+            decoded <- self.bv_codec.decode(bv);
+            vec <- decoded.value.to_vec();
+        } yield {
+            DecoderResult { value: vec, remainder: decoded.remainder }
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(Vec<u8>, usize), Error> {
+        match self.verbatim_len(input.len()) {
+            Some(len) => Ok((input[..len].to_vec(), len)),
+            None => self
+                .bv_codec
+                .decode_slice(input)
+                .and_then(|(bv, size)| bv.to_vec().map(|vec| (vec, size))),
+        }
+    }
+
+    fn verbatim_len(&self, available: usize) -> Option<usize> {
+        // Fall back to decoding normally if there is insufficient input, so that the error is the
+        // same as that of the wrapped codec
+        self.bv_codec
+            .verbatim_len(available)
+            .filter(|len| *len <= available)
+    }
+}
+
+//
+// String codecs
+//
+
+/// UTF-8 string codec.
+///
+///   - Encodes by returning the UTF-8 bytes of the given string.
+///   - Decodes by taking all remaining bytes from the given byte vector and interpreting them as
+///     UTF-8, returning an error if they are not valid UTF-8.
+#[inline(always)]
+pub fn utf8() -> impl Codec<Value = String> {
+    Utf8Codec
+}
+
+struct Utf8Codec;
+
+impl Codec for Utf8Codec {
+    type Value = String;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::unknown()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("utf8", self.size_bound())
+    }
+
+    fn encode(&self, value: &String) -> EncodeResult {
+        Ok(byte_vector::from_slice_copy(value.as_bytes()))
+    }
+
+    fn encode_into(&self, value: &String, out: &mut Vec<u8>) -> Result<(), Error> {
+        out.extend_from_slice(value.as_bytes());
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<String> {
+        let bytes = bv.to_vec()?;
+        match String::from_utf8(bytes) {
+            Ok(value) => Ok(DecoderResult {
+                value,
+                remainder: byte_vector::empty(),
+            }),
+            Err(e) => Err(Error::new(lazy_description(|| {
+                format!("Invalid UTF-8 data: {}", e)
+            }))),
+        }
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(String, usize), Error> {
+        match String::from_utf8(input.to_vec()) {
+            Ok(value) => Ok((value, input.len())),
+            Err(e) => Err(Error::new(lazy_description(|| {
+                format!("Invalid UTF-8 data: {}", e)
+            }))),
+        }
+    }
+}
+
+/// UTF-8 string codec with an unsigned 8-bit length prefix.
+#[inline(always)]
+pub fn utf8_8() -> impl Codec<Value = String> {
+    variable_size_bytes(uint8, utf8())
+}
+
+/// UTF-8 string codec with a big-endian unsigned 16-bit length prefix.
+#[inline(always)]
+pub fn utf8_16() -> impl Codec<Value = String> {
+    variable_size_bytes(uint16, utf8())
+}
+
+/// UTF-8 string codec with a big-endian unsigned 32-bit length prefix.
+#[inline(always)]
+pub fn utf8_32() -> impl Codec<Value = String> {
+    variable_size_bytes(uint32, utf8())
+}
+
+/// UTF-8 string codec with a little-endian unsigned 16-bit length prefix.
+#[inline(always)]
+pub fn utf8_16_l() -> impl Codec<Value = String> {
+    variable_size_bytes(uint16_l, utf8())
+}
+
+/// UTF-8 string codec with a little-endian unsigned 32-bit length prefix.
+#[inline(always)]
+pub fn utf8_32_l() -> impl Codec<Value = String> {
+    variable_size_bytes(uint32_l, utf8())
+}
+
+/// Pascal string codec, consisting of a single length byte followed by up to 255 bytes of UTF-8
+/// text. Decoding fails if the text is not valid UTF-8.
+#[inline(always)]
+pub fn pstring() -> impl Codec<Value = String> {
+    utf8_8()
+}
+
+/// Pascal string codec that operates on the raw bytes of the string, consisting of a single
+/// length byte followed by up to 255 bytes of content. Unlike `pstring`, no assumptions are
+/// made about the encoding of the content.
+#[inline(always)]
+pub fn pstring_bytes() -> impl Codec<Value = ByteVector> {
+    variable_size_bytes(uint8, identity_bytes())
+}
+
+/// Codec for a single UTF-8 encoded character, occupying between one and four bytes.
+#[inline(always)]
+pub fn char_utf8() -> impl Codec<Value = char> {
+    CharUtf8Codec
+}
+
+struct CharUtf8Codec;
+
+impl Codec for CharUtf8Codec {
+    type Value = char;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::bounded(1, 4)
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("char_utf8", self.size_bound())
+    }
+
+    fn encode(&self, value: &char) -> EncodeResult {
+        let mut buf = [0u8; 4];
+        Ok(byte_vector::from_slice_copy(
+            value.encode_utf8(&mut buf).as_bytes(),
+        ))
+    }
+
+    fn encode_into(&self, value: &char, out: &mut Vec<u8>) -> Result<(), Error> {
+        let mut buf = [0u8; 4];
+        out.extend_from_slice(value.encode_utf8(&mut buf).as_bytes());
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<char> {
+        let len = utf8_char_len(bv.get_u8(0)?)?;
+        let mut buf = [0u8; 4];
+        bv.read(&mut buf[..len], 0, len)?;
+        Ok(DecoderResult {
+            value: utf8_char(&buf[..len])?,
+            remainder: bv.drop(len)?,
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(char, usize), Error> {
+        let len = utf8_char_len(slice_u8(input, 0)?)?;
+        utf8_char(slice_prefix(input, len)?).map(|value| (value, len))
+    }
+}
+
+/// Returns the length of the UTF-8 encoded character that begins with the given leading byte.
+fn utf8_char_len(lead: u8) -> Result<usize, Error> {
+    match lead {
+        0x00..=0x7f => Ok(1),
+        0xc0..=0xdf => Ok(2),
+        0xe0..=0xef => Ok(3),
+        0xf0..=0xf7 => Ok(4),
+        _ => Err(Error::new(lazy_description(|| {
+            format!("Invalid UTF-8 leading byte 0x{:02x}", lead)
+        }))),
+    }
+}
+
+/// Returns the single character encoded by the given UTF-8 bytes.
+fn utf8_char(bytes: &[u8]) -> Result<char, Error> {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => Ok(s.chars().next().unwrap()),
+        Err(e) => Err(Error::new(lazy_description(|| {
+            format!("Invalid UTF-8 data: {}", e)
+        }))),
+    }
+}
+
+/// Codec for a single ASCII character, occupying one byte. Encoding and decoding fail if the
+/// character is outside of the ASCII range (i.e., if the high bit is set).
+#[inline(always)]
+pub fn ascii_char() -> impl Codec<Value = char> {
+    AsciiCharCodec
+}
+
+struct AsciiCharCodec;
+
+impl Codec for AsciiCharCodec {
+    type Value = char;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(1)
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("ascii_char", self.size_bound())
+    }
+
+    fn encode(&self, value: &char) -> EncodeResult {
+        if value.is_ascii() {
+            uint8.encode(&(*value as u8))
+        } else {
+            Err(Error::new(lazy_description(|| {
+                format!("Character {:?} is not an ASCII character", value)
+            })))
+        }
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<char> {
+        uint8.decode(bv).and_then(|decoded| {
+            ascii_char_of(decoded.value).map(|value| DecoderResult {
+                value,
+                remainder: decoded.remainder,
+            })
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(char, usize), Error> {
+        let (byte, size) = uint8.decode_slice(input)?;
+        ascii_char_of(byte).map(|value| (value, size))
+    }
+}
+
+/// Returns the ASCII character with the given value, or an error if it is outside of the ASCII
+/// range.
+fn ascii_char_of(byte: u8) -> Result<char, Error> {
+    if byte.is_ascii() {
+        Ok(byte as char)
+    } else {
+        Err(Error::new(lazy_description(|| {
+            format!("Byte 0x{:02x} is not an ASCII character", byte)
+        })))
+    }
+}
+
+/// Codec for unsigned integers encoded as exactly `width` ASCII hexadecimal digits, padded on
+/// the left with zeros, using uppercase digits; see `ascii_hex_uint_with` for details.
+#[inline(always)]
+pub fn ascii_hex_uint<T>(width: usize) -> impl Codec<Value = T>
+where
+    T: PrimInt + Unsigned + FromPrimitive + Display,
+{
+    ascii_hex_uint_with(width, HexOptions::default())
+}
+
+/// Codec for unsigned integers encoded as exactly `width` ASCII hexadecimal digits, as used by
+/// Intel HEX, Motorola S-records, and similar text formats.
+///
+///   - Encodes the value using the case given by `options`, padded on the left to `width` bytes
+///     using the pad byte given by `options`; fails if the value requires more than `width` digits.
+///   - Decodes `width` bytes, skipping any leading pad bytes, and accepts digits in either case;
+///     fails if any of the remaining bytes is not a hexadecimal digit, or if the value is too large
+///     for the integer type.
+///
+/// Panics if `width` is zero.
+#[inline(always)]
+pub fn ascii_hex_uint_with<T>(width: usize, options: HexOptions) -> impl Codec<Value = T>
+where
+    T: PrimInt + Unsigned + FromPrimitive + Display,
+{
+    assert!(width > 0, "Width must be greater than zero");
+    AsciiHexCodec {
+        width,
+        options,
+        _marker: PhantomData::<T>,
+    }
+}
+
+/// Options that control how `ascii_hex_uint_with` encodes values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HexOptions {
+    /// If true, the digits `A` through `F` are encoded in uppercase; otherwise lowercase.
+    pub uppercase: bool,
+
+    /// The byte used to pad values on the left, typically `b'0'` or `b' '`.
+    pub pad_byte: u8,
+}
+
+impl Default for HexOptions {
+    /// Returns options for uppercase digits padded with zeros.
+    fn default() -> HexOptions {
+        HexOptions {
+            uppercase: true,
+            pad_byte: b'0',
+        }
+    }
+}
+
+struct AsciiHexCodec<T> {
+    width: usize,
+    options: HexOptions,
+    _marker: PhantomData<T>,
+}
+
+impl<T> AsciiHexCodec<T>
+where
+    T: PrimInt + Unsigned + FromPrimitive + Display,
+{
+    /// Parses the given digits (after skipping leading pad bytes).
+    fn parse(&self, bytes: &[u8]) -> Result<T, Error> {
+        let pad_len = bytes
+            .iter()
+            .take_while(|b| **b == self.options.pad_byte)
+            .count();
+        let mut value = T::zero();
+        let sixteen = T::from_u8(16).unwrap();
+        for &byte in &bytes[pad_len..] {
+            let digit = (byte as char).to_digit(16).ok_or_else(|| {
+                Error::new(lazy_description(|| {
+                    format!("Byte 0x{:02x} is not a hexadecimal digit", byte)
+                }))
+            })?;
+            value = value
+                .checked_mul(&sixteen)
+                .and_then(|v| v.checked_add(&T::from_u32(digit).unwrap()))
+                .ok_or_else(|| {
+                    Error::new(lazy_description(|| {
+                        format!(
+                            "Hexadecimal value {:?} exceeds maximum value ({}) of target type",
+                            String::from_utf8_lossy(bytes),
+                            T::max_value()
+                        )
+                    }))
+                })?;
+        }
+        Ok(value)
+    }
+}
+
+impl<T> Codec for AsciiHexCodec<T>
+where
+    T: PrimInt + Unsigned + FromPrimitive + Display,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(self.width as u64)
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("ascii_hex_uint", self.size_bound())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        let v = unsigned_to_u64(value)?;
+        let digits = if self.options.uppercase {
+            format!("{:X}", v)
+        } else {
+            format!("{:x}", v)
+        };
+        if digits.len() > self.width {
+            return Err(Error::new(lazy_description(|| {
+                format!(
+                    "Value {} requires more than {} hexadecimal digits",
+                    value, self.width
+                )
+            })));
+        }
+        out.resize(out.len() + self.width - digits.len(), self.options.pad_byte);
+        out.extend_from_slice(digits.as_bytes());
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        let bytes = bv.take(self.width)?.to_vec()?;
+        let value = self.parse(&bytes)?;
+        bv.drop(self.width)
+            .map(|remainder| DecoderResult { value, remainder })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        slice_prefix(input, self.width)
+            .and_then(|bytes| self.parse(bytes))
+            .map(|value| (value, self.width))
+    }
+}
+
+//
+// Borrowed-value codecs
+//
+
+/// Codec whose decoded values borrow from the input bytes instead of copying them, which avoids
+/// an allocation per value when decoding large payloads from a contiguous buffer.
+///
+/// Unlike `Codec`, decoding operates on a byte slice (or a `ByteVector` whose storage is
+/// contiguous), and the lifetime of the decoded value is tied to that of the input.
+pub trait BorrowCodec {
+    /// The type of value produced by this codec, borrowing from input with lifetime `'a`.
+    type Value<'a>;
+
+    /// Returns the bounds on the number of bytes produced by encoding a value with this codec.
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::unknown()
+    }
+
+    /// Encodes the given value, appending the resulting bytes to `out`.
+    fn encode_borrowed(&self, value: &Self::Value<'_>, out: &mut Vec<u8>) -> Result<(), Error>;
+
+    /// Decodes a value from the start of `input`, returning a value that borrows from `input`
+    /// along with the number of bytes that were consumed.
+    fn decode_borrowed<'a>(&self, input: &'a [u8]) -> Result<(Self::Value<'a>, usize), Error>;
+
+    /// Decodes a value from the start of the given byte vector, returning a value that borrows
+    /// from its storage along with the number of bytes that were consumed.  Returns an error if
+    /// the storage is not contiguous (e.g. if the vector was assembled by `append`).
+    fn decode_borrowed_bv<'a>(
+        &self,
+        bv: &'a ByteVector,
+    ) -> Result<(Self::Value<'a>, usize), Error> {
+        match bv.as_slice() {
+            Some(input) => self.decode_borrowed(input),
+            None => Err(Error::new(lazy_description(|| {
+                "Borrowed decoding requires contiguous byte vector storage".to_string()
+            }))),
+        }
+    }
+}
+
+/// Borrowed byte slice codec.
+///
+///   - Encodes by appending the given slice if its length is `len` bytes, otherwise returns an
+///     error.
+///   - Decodes by returning the first `len` bytes of the input without copying them.
+#[inline(always)]
+pub fn bytes_ref(len: usize) -> impl for<'a> BorrowCodec<Value<'a> = &'a [u8]> {
+    BytesRefCodec { len }
+}
+
+/// Borrowed UTF-8 string codec.
+///
+///   - Encodes by appending the UTF-8 bytes of the given string if they are `len` bytes long,
+///     otherwise returns an error.
+///   - Decodes by interpreting the first `len` bytes of the input as UTF-8 without copying them,
+///     returning an error if they are not valid UTF-8.
+#[inline(always)]
+pub fn str_ref(len: usize) -> impl for<'a> BorrowCodec<Value<'a> = &'a str> {
+    StrRefCodec { len }
+}
+
+/// Appends `bytes` to `out` if its length matches the fixed length of a borrowed-value codec.
+fn append_fixed(bytes: &[u8], len: usize, out: &mut Vec<u8>) -> Result<(), Error> {
+    if bytes.len() != len {
+        return Err(Error::new(lazy_description(|| {
+            format!(
+                "Encoding requires {} bytes but codec is limited to fixed length of {}",
+                bytes.len(),
+                len
+            )
+        })));
+    }
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+struct BytesRefCodec {
+    len: usize,
+}
+
+impl BorrowCodec for BytesRefCodec {
+    type Value<'a> = &'a [u8];
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(self.len as u64)
+    }
+
+    fn encode_borrowed(&self, value: &&[u8], out: &mut Vec<u8>) -> Result<(), Error> {
+        append_fixed(value, self.len, out)
+    }
+
+    fn decode_borrowed<'a>(&self, input: &'a [u8]) -> Result<(&'a [u8], usize), Error> {
+        slice_prefix(input, self.len).map(|bytes| (bytes, self.len))
+    }
+}
+
+struct StrRefCodec {
+    len: usize,
+}
+
+impl BorrowCodec for StrRefCodec {
+    type Value<'a> = &'a str;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(self.len as u64)
+    }
+
+    fn encode_borrowed(&self, value: &&str, out: &mut Vec<u8>) -> Result<(), Error> {
+        append_fixed(value.as_bytes(), self.len, out)
+    }
+
+    fn decode_borrowed<'a>(&self, input: &'a [u8]) -> Result<(&'a str, usize), Error> {
+        let bytes = slice_prefix(input, self.len)?;
+        match std::str::from_utf8(bytes) {
+            Ok(value) => Ok((value, self.len)),
+            Err(e) => Err(Error::new(lazy_description(|| {
+                format!("Invalid UTF-8 data: {}", e)
+            }))),
+        }
+    }
+}
+
+//
+// Collection codecs
+//
+
+/// Codec for count-prefixed collections of elements.
+///
+///   - Encodes by encoding the number of elements followed by each element in turn.
+///   - Decodes by decoding the number of elements and then decoding that many elements.
+#[inline(always)]
+pub fn vector_of_n<L, T, LC, C>(count_codec: LC, element_codec: C) -> impl Codec<Value = Vec<T>>
+where
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    LC: Codec<Value = L>,
+    C: Codec<Value = T>,
+{
+    VectorOfNCodec {
+        count_codec,
+        element_codec,
+    }
+}
+
+struct VectorOfNCodec<LC, C> {
+    count_codec: LC,
+    element_codec: C,
+}
+
+impl<L, T, LC, C> Codec for VectorOfNCodec<LC, C>
+where
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    LC: Codec<Value = L>,
+    C: Codec<Value = T>,
+{
+    type Value = Vec<T>;
+
+    fn size_bound(&self) -> SizeBound {
+        self.count_codec.size_bound() + SizeBound::unknown()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("vector_of_n", self.size_bound())
+            .with_child(self.count_codec.describe())
+            .with_child(self.element_codec.describe())
+    }
+
+    fn encode(&self, value: &Vec<T>) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &Vec<T>, out: &mut Vec<u8>) -> Result<(), Error> {
+        // Fail if the number of elements is too large to be encoded
+        match L::from_usize(value.len()) {
+            Some(count) => {
+                self.count_codec.encode_into(&count, out)?;
+                encode_elements(&self.element_codec, value, out)
+            }
+            None => Err(Error::new(lazy_description(|| {
+                format!(
+                    "Number of elements ({}) is greater than maximum value ({}) of count type",
+                    value.len(),
+                    L::max_value()
+                )
+            }))),
+        }
+    }
+
+    fn encode_to(&self, value: &Vec<T>, out: &mut dyn Write) -> Result<(), Error> {
+        match L::from_usize(value.len()) {
+            Some(count) => {
+                self.count_codec.encode_to(&count, out)?;
+                encode_elements_to(&self.element_codec, value, out)
+            }
+            None => Err(Error::new(lazy_description(|| {
+                format!(
+                    "Number of elements ({}) is greater than maximum value ({}) of count type",
+                    value.len(),
+                    L::max_value()
+                )
+            }))),
+        }
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<Vec<T>> {
+        let decoded_count = self.count_codec.decode(bv)?;
+        let count = decoded_count.value.to_usize().unwrap();
+        decode_elements(&self.element_codec, &decoded_count.remainder, count)
+            .map_err(|e| e.offset_by(bv.length() - decoded_count.remainder.length()))
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(Vec<T>, usize), Error> {
+        let (count, size) = self.count_codec.decode_slice(input)?;
+        let count = count.to_usize().unwrap();
+        decode_slice_elements(&self.element_codec, &input[size..], count)
+            .map(|(values, consumed)| (values, size + consumed))
+            .map_err(|e| e.offset_by(size))
+    }
+}
+
+/// Codec for collections containing exactly `count` elements.
+///
+///   - Encodes by encoding each element in turn, returning an error if the collection does not
+///     contain exactly `count` elements.
+///   - Decodes by decoding exactly `count` elements.
+#[inline(always)]
+pub fn repeat<T, C>(count: usize, element_codec: C) -> impl Codec<Value = Vec<T>>
+where
+    C: Codec<Value = T>,
+{
+    RepeatCodec {
+        count,
+        element_codec,
+    }
+}
+
+struct RepeatCodec<C> {
+    count: usize,
+    element_codec: C,
+}
+
+impl<T, C> Codec for RepeatCodec<C>
+where
+    C: Codec<Value = T>,
+{
+    type Value = Vec<T>;
+
+    fn size_bound(&self) -> SizeBound {
+        self.element_codec.size_bound().times(self.count as u64)
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("repeat", self.size_bound()).with_child(self.element_codec.describe())
+    }
+
+    fn encode(&self, value: &Vec<T>) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &Vec<T>, out: &mut Vec<u8>) -> Result<(), Error> {
+        if value.len() != self.count {
+            return Err(Error::new(lazy_description(|| {
+                format!("Expected {} elements but got {}", self.count, value.len())
+            })));
+        }
+        encode_elements(&self.element_codec, value, out)
+    }
+
+    fn encode_to(&self, value: &Vec<T>, out: &mut dyn Write) -> Result<(), Error> {
+        if value.len() != self.count {
+            return Err(Error::new(lazy_description(|| {
+                format!("Expected {} elements but got {}", self.count, value.len())
+            })));
+        }
+        encode_elements_to(&self.element_codec, value, out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<Vec<T>> {
+        decode_elements(&self.element_codec, bv, self.count)
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(Vec<T>, usize), Error> {
+        decode_slice_elements(&self.element_codec, input, self.count)
+    }
+}
+
+/// Codec for collections that extend to the end of the input.
+///
+///   - Encodes by encoding each element in turn.
+///   - Decodes by repeatedly decoding elements until no input remains. An error is returned if
+///     the remaining input does not contain a complete element.
+#[inline(always)]
+pub fn list<T, C>(element_codec: C) -> impl Codec<Value = Vec<T>>
+where
+    C: Codec<Value = T>,
+{
+    ListCodec { element_codec }
+}
+
+struct ListCodec<C> {
+    element_codec: C,
+}
+
+impl<T, C> Codec for ListCodec<C>
+where
+    C: Codec<Value = T>,
+{
+    type Value = Vec<T>;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::unknown()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("list", self.size_bound()).with_child(self.element_codec.describe())
+    }
+
+    fn encode(&self, value: &Vec<T>) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &Vec<T>, out: &mut Vec<u8>) -> Result<(), Error> {
+        encode_elements(&self.element_codec, value, out)
+    }
+
+    fn encode_to(&self, value: &Vec<T>, out: &mut dyn Write) -> Result<(), Error> {
+        encode_elements_to(&self.element_codec, value, out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<Vec<T>> {
+        decode_remaining_elements(&self.element_codec, bv)
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(Vec<T>, usize), Error> {
+        decode_slice_remaining_elements(&self.element_codec, input)
+            .map(|values| (values, input.len()))
+    }
+}
+
+/// Codec for collections that occupy a region of exactly `len` bytes.
+///
+///   - Encodes by encoding each element in turn, returning an error if the encoded elements do
+///     not occupy exactly `len` bytes.
+///   - Decodes by repeatedly decoding elements from the next `len` bytes until that region is
+///     exhausted.
+#[inline(always)]
+pub fn sized_list<T, C>(len: usize, element_codec: C) -> impl Codec<Value = Vec<T>>
+where
+    C: Codec<Value = T>,
+{
+    SizedListCodec { len, element_codec }
+}
+
+struct SizedListCodec<C> {
+    len: usize,
+    element_codec: C,
+}
+
+impl<T, C> Codec for SizedListCodec<C>
+where
+    C: Codec<Value = T>,
+{
+    type Value = Vec<T>;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(self.len as u64)
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("sized_list", self.size_bound())
+            .with_child(self.element_codec.describe())
+    }
+
+    fn encode(&self, value: &Vec<T>) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &Vec<T>, out: &mut Vec<u8>) -> Result<(), Error> {
+        let start = out.len();
+        encode_elements(&self.element_codec, value, out)?;
+        let encoded_len = out.len() - start;
+        if encoded_len != self.len {
+            Err(Error::new(lazy_description(|| {
+                format!(
+                    "Encoded elements require {} bytes but list is limited to fixed length of {}",
+                    encoded_len, self.len
+                )
+            })))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<Vec<T>> {
+        forcomp!({
+            taken <- bv.take(self.len);
+            decoded <- cursor::region(bv.length(), self.len, || decode_remaining_elements(&self.element_codec, &taken))
+                .map_err(Error::within_bounded_input);
+        } yield {
+            DecoderResult { value: decoded.value, remainder: bv.drop(self.len).unwrap() }
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(Vec<T>, usize), Error> {
+        let taken = slice_prefix(input, self.len)?;
+        let values = cursor::region(input.len(), self.len, || {
+            decode_slice_remaining_elements(&self.element_codec, taken)
+        })
+        .map_err(Error::within_bounded_input)?;
+        Ok((values, self.len))
+    }
+}
+
+/// Codec for collections that are prefixed by the length (in bytes) of the encoded elements.
+///
+///   - Encodes by encoding the length of the encoded elements followed by the elements themselves.
+///   - Decodes by decoding the length and then repeatedly decoding elements from that many bytes
+///     until the region is exhausted.
+#[inline(always)]
+pub fn variable_sized_list<L, T, LC, C>(
+    len_codec: LC,
+    element_codec: C,
+) -> impl Codec<Value = Vec<T>>
+where
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    LC: Codec<Value = L>,
+    C: Codec<Value = T>,
+{
+    variable_size_bytes(len_codec, list(element_codec))
+}
+
+/// Codec for count-prefixed maps of key/value pairs.
+///
+///   - Encodes by encoding the number of entries followed by each key and value in turn, in
+///     ascending key order.
+///   - Decodes by decoding the number of entries and then decoding that many keys and values,
+///     returning an error if a key occurs more than once.
+#[inline(always)]
+pub fn map_of_n<L, K, V, LC, KC, VC>(
+    count_codec: LC,
+    key_codec: KC,
+    value_codec: VC,
+) -> impl Codec<Value = BTreeMap<K, V>>
+where
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    K: Ord,
+    LC: Codec<Value = L>,
+    KC: Codec<Value = K>,
+    VC: Codec<Value = V>,
+{
+    MapOfNCodec {
+        count_codec,
+        key_codec,
+        value_codec,
+        phantom: PhantomData,
+    }
+}
+
+/// Codec for count-prefixed `HashMap`s of key/value pairs.  Entries are encoded in ascending key
+/// order so that the encoded form is deterministic; see `map_of_n` for details.
+#[inline(always)]
+pub fn hash_map_of_n<L, K, V, LC, KC, VC>(
+    count_codec: LC,
+    key_codec: KC,
+    value_codec: VC,
+) -> impl Codec<Value = HashMap<K, V>>
+where
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    K: Ord + Hash,
+    LC: Codec<Value = L>,
+    KC: Codec<Value = K>,
+    VC: Codec<Value = V>,
+{
+    MapOfNCodec {
+        count_codec,
+        key_codec,
+        value_codec,
+        phantom: PhantomData,
+    }
+}
+
+/// Abstracts over the map types supported by `MapOfNCodec`.
+trait MapEntries<K, V>: Default {
+    /// Returns the entries of the map, sorted by key.
+    fn sorted_entries(&self) -> Vec<(&K, &V)>;
+
+    /// Inserts the given entry, returning false if the key was already present.
+    fn insert_entry(&mut self, key: K, value: V) -> bool;
+}
+
+impl<K: Ord, V> MapEntries<K, V> for BTreeMap<K, V> {
+    fn sorted_entries(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+
+    fn insert_entry(&mut self, key: K, value: V) -> bool {
+        self.insert(key, value).is_none()
+    }
+}
+
+impl<K: Ord + Hash, V> MapEntries<K, V> for HashMap<K, V> {
+    fn sorted_entries(&self) -> Vec<(&K, &V)> {
+        let mut entries: Vec<(&K, &V)> = self.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries
+    }
+
+    fn insert_entry(&mut self, key: K, value: V) -> bool {
+        self.insert(key, value).is_none()
+    }
+}
+
+struct MapOfNCodec<LC, KC, VC, M> {
+    count_codec: LC,
+    key_codec: KC,
+    value_codec: VC,
+    phantom: PhantomData<M>,
+}
+
+impl<L, K, V, LC, KC, VC, M> Codec for MapOfNCodec<LC, KC, VC, M>
+where
+    L: PrimInt + Unsigned + FromPrimitive + Display,
+    LC: Codec<Value = L>,
+    KC: Codec<Value = K>,
+    VC: Codec<Value = V>,
+    M: MapEntries<K, V>,
+{
+    type Value = M;
+
+    fn size_bound(&self) -> SizeBound {
+        self.count_codec.size_bound() + SizeBound::unknown()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("map_of_n", self.size_bound())
+            .with_child(self.count_codec.describe())
+            .with_child(self.key_codec.describe())
+            .with_child(self.value_codec.describe())
+    }
+
+    fn encode(&self, value: &M) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &M, out: &mut Vec<u8>) -> Result<(), Error> {
+        let entries = value.sorted_entries();
+
+        // Fail if the number of entries is too large to be encoded
+        let count = L::from_usize(entries.len()).ok_or_else(|| {
+            Error::new(lazy_description(|| {
+                format!(
+                    "Number of entries ({}) is greater than maximum value ({}) of count type",
+                    entries.len(),
+                    L::max_value()
+                )
+            }))
+        })?;
+
+        self.count_codec.encode_into(&count, out)?;
+        for (k, v) in entries {
+            self.key_codec.encode_into(k, out)?;
+            self.value_codec.encode_into(v, out)?;
+        }
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<M> {
+        let decoded_count = self.count_codec.decode(bv)?;
+        let count = decoded_count.value.to_usize().unwrap();
+        limits::check_elements(count)?;
+        let mut map = M::default();
+        let mut remainder = decoded_count.remainder;
+        for _ in 0..count {
+            let decoded_key = self
+                .key_codec
+                .decode(&remainder)
+                .map_err(|e| e.offset_by(bv.length() - remainder.length()))?;
+            let decoded_value = self
+                .value_codec
+                .decode(&decoded_key.remainder)
+                .map_err(|e| e.offset_by(bv.length() - decoded_key.remainder.length()))?;
+            if !map.insert_entry(decoded_key.value, decoded_value.value) {
+                return Err(cursor::locate(
+                    Error::new(lazy_description(|| "Duplicate key in map".to_string())),
+                    remainder.length(),
+                ));
+            }
+            remainder = decoded_value.remainder;
+        }
+        Ok(DecoderResult {
+            value: map,
+            remainder,
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(M, usize), Error> {
+        let (count, mut consumed) = self.count_codec.decode_slice(input)?;
+        let count = count.to_usize().unwrap();
+        limits::check_elements(count)?;
+        let mut map = M::default();
+        for _ in 0..count {
+            let entry_start = consumed;
+            let (key, key_size) = self
+                .key_codec
+                .decode_slice(&input[consumed..])
+                .map_err(|e| e.offset_by(consumed))?;
+            consumed += key_size;
+            let (value, value_size) = self
+                .value_codec
+                .decode_slice(&input[consumed..])
+                .map_err(|e| e.offset_by(consumed))?;
+            consumed += value_size;
+            if !map.insert_entry(key, value) {
+                return Err(cursor::locate(
+                    Error::new(lazy_description(|| "Duplicate key in map".to_string())),
+                    input.len() - entry_start,
+                ));
+            }
+        }
+        Ok((map, consumed))
+    }
+}
+
+/// Encodes each of the given values in turn, appending the results to `out`.
+fn encode_elements<T, C>(codec: &C, values: &[T], out: &mut Vec<u8>) -> Result<(), Error>
+where
+    C: Codec<Value = T>,
+{
+    values
+        .iter()
+        .try_for_each(|value| codec.encode_into(value, out))
+}
+
+/// Encodes each of the given values in turn, writing the encoded bytes to `out`.
+fn encode_elements_to<T, C>(codec: &C, values: &[T], out: &mut dyn Write) -> Result<(), Error>
+where
+    C: Codec<Value = T>,
+{
+    values
+        .iter()
+        .try_for_each(|value| codec.encode_to(value, out))
+}
+
+/// Decodes values in turn until the given byte vector is exhausted.
+fn decode_remaining_elements<T, C>(codec: &C, bv: &ByteVector) -> DecodeResult<Vec<T>>
+where
+    C: Codec<Value = T>,
+{
+    let max_elements = limits::current_limits().max_elements;
+    let mut values = Vec::new();
+    let mut remainder = bv.clone();
+    while remainder.length() > 0 {
+        let decoded = codec.decode(&remainder).map_err(|e| {
+            cursor::locate(
+                e.offset_by(bv.length() - remainder.length()),
+                remainder.length(),
+            )
+        })?;
+        if decoded.remainder.length() == remainder.length() {
+            // Bail out rather than looping forever if the element codec doesn't consume any input
+            return Err(Error::new(lazy_description(|| {
+                "Element codec did not consume any input".to_string()
+            })));
+        }
+        limits::check_element_limit(values.len() + 1, max_elements)?;
+        values.push(decoded.value);
+        remainder = decoded.remainder;
+    }
+    Ok(DecoderResult {
+        value: values,
+        remainder,
+    })
+}
+
+/// Decodes values in turn from the given slice until it is exhausted.
+fn decode_slice_remaining_elements<T, C>(codec: &C, input: &[u8]) -> Result<Vec<T>, Error>
+where
+    C: Codec<Value = T>,
+{
+    let max_elements = limits::current_limits().max_elements;
+    let mut values = Vec::new();
+    let mut consumed = 0;
+    while consumed < input.len() {
+        let (value, size) = codec
+            .decode_slice(&input[consumed..])
+            .map_err(|e| cursor::locate(e.offset_by(consumed), input.len() - consumed))?;
+        if size == 0 {
+            // Bail out rather than looping forever if the element codec doesn't consume any input
+            return Err(Error::new(lazy_description(|| {
+                "Element codec did not consume any input".to_string()
+            })));
+        }
+        limits::check_element_limit(values.len() + 1, max_elements)?;
+        values.push(value);
+        consumed += size;
+    }
+    Ok(values)
+}
+
+/// Decodes exactly `count` values in turn, returning them along with the remainder.
+fn decode_elements<T, C>(codec: &C, bv: &ByteVector, count: usize) -> DecodeResult<Vec<T>>
+where
+    C: Codec<Value = T>,
+{
+    // Note that we intentionally avoid preallocating based on `count`, since it may have come from
+    // untrusted input
+    limits::check_elements(count)?;
+    let mut values = Vec::new();
+    let mut remainder = bv.clone();
+    for _ in 0..count {
+        let decoded = codec.decode(&remainder).map_err(|e| {
+            cursor::locate(
+                e.offset_by(bv.length() - remainder.length()),
+                remainder.length(),
+            )
+        })?;
+        values.push(decoded.value);
+        remainder = decoded.remainder;
+    }
+    Ok(DecoderResult {
+        value: values,
+        remainder,
+    })
+}
+
+/// Decodes exactly `count` values in turn from the given slice, returning them along with the
+/// number of bytes consumed.
+fn decode_slice_elements<T, C>(
+    codec: &C,
+    input: &[u8],
+    count: usize,
+) -> Result<(Vec<T>, usize), Error>
+where
+    C: Codec<Value = T>,
+{
+    limits::check_elements(count)?;
+    let mut values = Vec::new();
+    let mut consumed = 0;
+    for _ in 0..count {
+        let (value, size) = codec
+            .decode_slice(&input[consumed..])
+            .map_err(|e| cursor::locate(e.offset_by(consumed), input.len() - consumed))?;
+        values.push(value);
+        consumed += size;
+    }
+    Ok((values, consumed))
+}
+
+//
+// Choice codecs
+//
+
+/// Codec that tries each of the given codecs in order.
+///
+///   - Encodes using the first codec that is able to encode the value.
+///   - Decodes using the first codec that is able to decode the input.
+///
+/// If none of the codecs succeed, an error that includes each codec's failure is returned.
+///
+/// Note that since the codecs are stored in a `Vec`, they must all have the same type; boxed
+/// codecs (`Box<dyn Codec<Value = T>>`) can be used to mix codecs of different types.
+#[inline(always)]
+pub fn choice<T, C>(codecs: Vec<C>) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    ChoiceCodec { codecs }
+}
+
+struct ChoiceCodec<C> {
+    codecs: Vec<C>,
+}
+
+impl<T, C> Codec for ChoiceCodec<C>
+where
+    C: Codec<Value = T>,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        self.codecs
+            .iter()
+            .map(|codec| codec.size_bound())
+            .reduce(SizeBound::or)
+            .unwrap_or_else(SizeBound::unknown)
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("choice", self.size_bound())
+            .with_children(self.codecs.iter().map(|codec| codec.describe()))
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        first_success(self.codecs.iter().map(|codec| codec.encode(value)))
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        // Discard any partial output from a failed codec before trying the next one
+        let start = out.len();
+        let result = first_success(self.codecs.iter().map(|codec| {
+            out.truncate(start);
+            codec.encode_into(value, out)
+        }));
+        if result.is_err() {
+            out.truncate(start);
+        }
+        result
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        validation::speculatively(|| {
+            first_success(self.codecs.iter().map(|codec| codec.decode(bv)))
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        validation::speculatively(|| {
+            first_success(self.codecs.iter().map(|codec| codec.decode_slice(input)))
+        })
+    }
+}
+
+/// Codec that tries `primary` and then `secondary` if `primary` fails, for both encoding and decoding.
+#[inline(always)]
+pub fn fallback<T, PC, SC>(primary: PC, secondary: SC) -> impl Codec<Value = T>
+where
+    PC: Codec<Value = T>,
+    SC: Codec<Value = T>,
+{
+    FallbackCodec { primary, secondary }
+}
+
+struct FallbackCodec<PC, SC> {
+    primary: PC,
+    secondary: SC,
+}
+
+impl<T, PC, SC> Codec for FallbackCodec<PC, SC>
+where
+    PC: Codec<Value = T>,
+    SC: Codec<Value = T>,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        self.primary.size_bound().or(self.secondary.size_bound())
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("fallback", self.size_bound())
+            .with_child(self.primary.describe())
+            .with_child(self.secondary.describe())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        first_success(
+            iter::once_with(|| self.primary.encode(value))
+                .chain(iter::once_with(|| self.secondary.encode(value))),
+        )
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        // Discard any partial output from the primary codec before trying the secondary
+        let start = out.len();
+        match self.primary.encode_into(value, out) {
+            Ok(()) => Ok(()),
+            Err(primary_err) => {
+                out.truncate(start);
+                self.secondary
+                    .encode_into(value, out)
+                    .map_err(|secondary_err| {
+                        out.truncate(start);
+                        Error::new(lazy_description(|| {
+                            format!(
+                                "None of the choices succeeded: [{}; {}]",
+                                primary_err.message(),
+                                secondary_err.message()
+                            )
+                        }))
+                    })
+            }
+        }
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        validation::speculatively(|| {
+            first_success(
+                iter::once_with(|| self.primary.decode(bv))
+                    .chain(iter::once_with(|| self.secondary.decode(bv))),
+            )
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        validation::speculatively(|| {
+            first_success(
+                iter::once_with(|| self.primary.decode_slice(input))
+                    .chain(iter::once_with(|| self.secondary.decode_slice(input))),
+            )
+        })
+    }
+}
+
+/// Returns the first successful result produced by the given (lazy) iterator, or an error that
+/// includes every failure if none succeed.
+fn first_success<V, I>(results: I) -> Result<V, Error>
+where
+    I: Iterator<Item = Result<V, Error>>,
+{
+    let mut failures = 0;
+    let mut messages = Vec::new();
+    for result in results {
+        match result {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                failures += 1;
+                // Collecting the messages of terse errors would defeat their purpose
+                if !e.is_terse() {
+                    messages.push(e.message());
+                }
+            }
+        }
+    }
+    if failures == 0 {
+        Err(Error::new(lazy_description(|| {
+            "No codecs to choose from".to_string()
+        })))
+    } else {
+        Err(Error::new(lazy_description(|| {
+            format!("None of the choices succeeded: [{}]", messages.join("; "))
+        })))
+    }
+}
+
+//
+// Lookahead codec
+//
+
+/// Codec that reports whether the given codec would successfully decode the input, without
+/// consuming any input.
+///
+///   - Encodes by returning an empty byte vector.
+///   - Decodes by attempting to decode using the given codec and returning `true` on success or
+///     `false` on failure. The input is returned unmodified as the remainder in either case.
+#[inline(always)]
+pub fn lookahead<T, C>(codec: C) -> impl Codec<Value = bool>
+where
+    C: Codec<Value = T>,
+{
+    LookaheadCodec { codec }
+}
+
+struct LookaheadCodec<C> {
+    codec: C,
+}
+
+impl<T, C> Codec for LookaheadCodec<C>
+where
+    C: Codec<Value = T>,
+{
+    type Value = bool;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(0)
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("lookahead", self.size_bound()).with_child(self.codec.describe())
+    }
+
+    fn encode(&self, _value: &bool) -> EncodeResult {
+        Ok(byte_vector::empty())
+    }
+
+    fn encode_into(&self, _value: &bool, _out: &mut Vec<u8>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<bool> {
+        Ok(DecoderResult {
+            value: validation::speculatively(|| self.codec.decode(bv)).is_ok(),
+            remainder: bv.clone(),
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(bool, usize), Error> {
+        let value = validation::speculatively(|| self.codec.decode_slice(input)).is_ok();
+        Ok((value, 0))
+    }
+}
+
+//
+// Recover codec
+//
+
+/// Codec that optionally decodes the given unit codec, reporting whether it was present.
+///
+///   - Encodes by encoding the unit value using the given codec if the value is `true`, or by
+///     returning an empty byte vector if the value is `false`.
+///   - Decodes by attempting to decode using the given codec. On success, the input is consumed
+///     and `true` is returned; on failure, no input is consumed and `false` is returned.
+#[inline(always)]
+pub fn recover<C>(codec: C) -> impl Codec<Value = bool>
+where
+    C: Codec<Value = ()>,
+{
+    RecoverCodec { codec }
+}
+
+struct RecoverCodec<C> {
+    codec: C,
+}
+
+impl<C> Codec for RecoverCodec<C>
+where
+    C: Codec<Value = ()>,
+{
+    type Value = bool;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(0).or(self.codec.size_bound())
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("recover", self.size_bound()).with_child(self.codec.describe())
+    }
+
+    fn encode(&self, value: &bool) -> EncodeResult {
+        if *value {
+            self.codec.encode(&())
+        } else {
+            Ok(byte_vector::empty())
+        }
+    }
+
+    fn encode_into(&self, value: &bool, out: &mut Vec<u8>) -> Result<(), Error> {
+        if *value {
+            self.codec.encode_into(&(), out)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<bool> {
+        match validation::speculatively(|| self.codec.decode(bv)) {
+            Ok(decoded) => Ok(DecoderResult {
+                value: true,
+                remainder: decoded.remainder,
+            }),
+            Err(_) => Ok(DecoderResult {
+                value: false,
+                remainder: bv.clone(),
+            }),
+        }
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(bool, usize), Error> {
+        match validation::speculatively(|| self.codec.decode_slice(input)) {
+            Ok(((), size)) => Ok((true, size)),
+            Err(_) => Ok((false, 0)),
+        }
+    }
+}
+
+//
+// Complete codec
+//
+
+/// Codec that decodes using the given codec and then fails if any bytes remain unconsumed.
+/// Encoding is delegated to the given codec unchanged.
+#[inline(always)]
+pub fn complete<T, C>(codec: C) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    CompleteCodec { codec }
+}
+
+struct CompleteCodec<C> {
+    codec: C,
+}
+
+impl<T, C> Codec for CompleteCodec<C>
+where
+    C: Codec<Value = T>,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        self.codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("complete", self.size_bound()).with_child(self.codec.describe())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        self.codec.encode(value)
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.codec.encode_into(value, out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        self.codec.decode(bv).and_then(require_complete)
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        let (value, consumed) = self.codec.decode_slice(input)?;
+        check_complete(input.len() - consumed).map(|_| (value, consumed))
+    }
+}
+
+/// Returns the given decoder result if its remainder is empty, or an error otherwise.
+fn require_complete<T>(decoded: DecoderResult<T>) -> DecodeResult<T> {
+    check_complete(decoded.remainder.length()).map(|_| decoded)
+}
+
+/// Returns an error if `remaining` bytes were left unconsumed.
+fn check_complete(remaining: usize) -> Result<(), Error> {
+    if remaining == 0 {
+        Ok(())
+    } else {
+        Err(Error::new(lazy_description(|| {
+            format!("Expected end of input but {} bytes remain", remaining)
+        })))
+    }
+}
+
+//
+// Lazy codec
+//
+
+/// Codec that defers construction of the inner codec until it is first used for encoding or
+/// decoding, after which the constructed codec is cached and reused.
+///
+/// This allows for recursive codecs (e.g. for trees or other nested structures), which would
+/// otherwise recurse infinitely at construction time.
+#[inline(always)]
+pub fn lazily<T, C, F>(f: F) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+    F: Fn() -> C,
+{
+    LazyCodec {
+        f,
+        codec: OnceLock::new(),
+    }
+}
+
+struct LazyCodec<C, F> {
+    f: F,
+    codec: OnceLock<C>,
+}
+
+impl<T, C, F> Codec for LazyCodec<C, F>
+where
+    C: Codec<Value = T>,
+    F: Fn() -> C,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        // Avoid forcing construction of the inner codec, which may be recursive
+        SizeBound::unknown()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        // As with `size_bound`, avoid forcing construction of a possibly recursive inner codec
+        CodecDescription::new("lazily", self.size_bound())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        self.codec.get_or_init(&self.f).encode(value)
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.codec.get_or_init(&self.f).encode_into(value, out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        let _depth = DepthGuard::enter()?;
+        self.codec.get_or_init(&self.f).decode(bv)
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        let _depth = DepthGuard::enter()?;
+        self.codec.get_or_init(&self.f).decode_slice(input)
+    }
+}
+
+//
+// Limited codec
+//
+
+/// Codec that decodes using the given codec with `limits` in effect, in place of the default
+/// limits (see `limits::set_default_limits`).
+///
+///   - Encodes using the given codec; the limits apply only to decoding.
+///   - Decodes using the given codec, failing if the input would cause it to exceed any of the
+///     limits.
+#[inline(always)]
+pub fn limited<T, C>(limits: DecodeLimits, codec: C) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    LimitedCodec { codec, limits }
+}
+
+struct LimitedCodec<C> {
+    codec: C,
+    limits: DecodeLimits,
+}
+
+impl<T, C> Codec for LimitedCodec<C>
+where
+    C: Codec<Value = T>,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        self.codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        self.codec.describe()
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        self.codec.encode(value)
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.codec.encode_into(value, out)
+    }
+
+    fn encode_to(&self, value: &T, out: &mut dyn Write) -> Result<(), Error> {
+        self.codec.encode_to(value, out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        limits::with_limits(self.limits, || self.codec.decode(bv))
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        limits::with_limits(self.limits, || self.codec.decode_slice(input))
+    }
+}
+
+//
+// Terse codec
+//
+
+/// Codec that encodes and decodes using the given codec with terse errors enabled (see
+/// `error::with_terse_errors`), so that failures are cheap at the cost of omitting their details.
+/// This suits codecs that are expected to fail often, such as the alternatives given to `choice`.
+#[inline(always)]
+pub fn terse<T, C>(codec: C) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    TerseCodec { codec }
+}
+
+struct TerseCodec<C> {
+    codec: C,
+}
+
+impl<T, C> Codec for TerseCodec<C>
+where
+    C: Codec<Value = T>,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        self.codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        self.codec.describe()
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        error::with_terse_errors(|| self.codec.encode(value))
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        error::with_terse_errors(|| self.codec.encode_into(value, out))
+    }
+
+    fn encode_to(&self, value: &T, out: &mut dyn Write) -> Result<(), Error> {
+        error::with_terse_errors(|| self.codec.encode_to(value, out))
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        error::with_terse_errors(|| self.codec.decode(bv))
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        error::with_terse_errors(|| self.codec.decode_slice(input))
+    }
+}
+
+//
+// Value mapping codecs
+//
+
+/// Codec that converts a codec for values of type `A` into a codec for values of type `B`, given
+/// a pair of total functions that convert between the two types.
+///
+///   - Encodes by converting the value using `g` and then encoding it using the given codec.
+///   - Decodes using the given codec and then converting the decoded value using `f`.
+#[inline(always)]
+pub fn xmap<A, B, C, F, G>(codec: C, f: F, g: G) -> impl Codec<Value = B>
+where
+    C: Codec<Value = A>,
+    F: Fn(A) -> B,
+    G: Fn(&B) -> A,
+{
+    XMapCodec { codec, f, g }
+}
+
+struct XMapCodec<C, F, G> {
+    codec: C,
+    f: F,
+    g: G,
+}
+
+impl<A, B, C, F, G> Codec for XMapCodec<C, F, G>
+where
+    C: Codec<Value = A>,
+    F: Fn(A) -> B,
+    G: Fn(&B) -> A,
+{
+    type Value = B;
+
+    fn size_bound(&self) -> SizeBound {
+        self.codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("xmap", self.size_bound()).with_child(self.codec.describe())
+    }
+
+    fn encode(&self, value: &B) -> EncodeResult {
+        self.codec.encode(&(self.g)(value))
+    }
+
+    fn encode_into(&self, value: &B, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.codec.encode_into(&(self.g)(value), out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<B> {
+        self.codec.decode(bv).map(|decoded| DecoderResult {
+            value: (self.f)(decoded.value),
+            remainder: decoded.remainder,
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(B, usize), Error> {
+        self.codec
+            .decode_slice(input)
+            .map(|(value, size)| ((self.f)(value), size))
+    }
+}
+
+/// Codec that converts a codec for values of type `A` into a codec for values of type `B`, given
+/// a pair of partial functions that convert between the two types.
+///
+///   - Encodes by converting the value using `g` and then encoding it using the given codec.
+///   - Decodes using the given codec and then converting the decoded value using `f`.
+///
+/// If either conversion fails, the returned error is propagated.
+#[inline(always)]
+pub fn exmap<A, B, C, F, G>(codec: C, f: F, g: G) -> impl Codec<Value = B>
+where
+    C: Codec<Value = A>,
+    F: Fn(A) -> Result<B, Error>,
+    G: Fn(&B) -> Result<A, Error>,
+{
+    EXMapCodec { codec, f, g }
+}
+
+/// Codec that converts a codec for values of type `A` into a codec for values of type `B`, where
+/// only the decode-side conversion `f` can fail (e.g. when decoded values must be validated).
+#[inline(always)]
+pub fn narrow<A, B, C, F, G>(codec: C, f: F, g: G) -> impl Codec<Value = B>
+where
+    C: Codec<Value = A>,
+    F: Fn(A) -> Result<B, Error>,
+    G: Fn(&B) -> A,
+{
+    exmap(codec, f, move |value: &B| Ok(g(value)))
+}
+
+/// Codec that converts a codec for values of type `A` into a codec for values of type `B`, where
+/// only the encode-side conversion `g` can fail (e.g. when values to be encoded must be validated).
+#[inline(always)]
+pub fn widen<A, B, C, F, G>(codec: C, f: F, g: G) -> impl Codec<Value = B>
+where
+    C: Codec<Value = A>,
+    F: Fn(A) -> B,
+    G: Fn(&B) -> Result<A, Error>,
+{
+    exmap(codec, move |value: A| Ok(f(value)), g)
+}
+
+struct EXMapCodec<C, F, G> {
+    codec: C,
+    f: F,
+    g: G,
+}
+
+impl<A, B, C, F, G> Codec for EXMapCodec<C, F, G>
+where
+    C: Codec<Value = A>,
+    F: Fn(A) -> Result<B, Error>,
+    G: Fn(&B) -> Result<A, Error>,
+{
+    type Value = B;
+
+    fn size_bound(&self) -> SizeBound {
+        self.codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("exmap", self.size_bound()).with_child(self.codec.describe())
+    }
+
+    fn encode(&self, value: &B) -> EncodeResult {
+        (self.g)(value).and_then(|mapped| self.codec.encode(&mapped))
+    }
+
+    fn encode_into(&self, value: &B, out: &mut Vec<u8>) -> Result<(), Error> {
+        (self.g)(value).and_then(|mapped| self.codec.encode_into(&mapped, out))
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<B> {
+        self.codec.decode(bv).and_then(|decoded| {
+            let DecoderResult { value, remainder } = decoded;
+            (self.f)(value).map(|value| DecoderResult { value, remainder })
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(B, usize), Error> {
+        self.codec
+            .decode_slice(input)
+            .and_then(|(value, size)| (self.f)(value).map(|value| (value, size)))
+    }
+}
+
+/// Codec that maps raw values (e.g. `u8` tags) encoded by `base_codec` to domain values and back,
+/// using the given mapping.
+///
+///   - Encodes by looking up the raw value for the given domain value and encoding it using
+///     `base_codec`; fails if the domain value is not present in the mapping.
+///   - Decodes using `base_codec` and then looking up the corresponding domain value; fails if
+///     the decoded raw value is not present in the mapping.
+///
+/// Panics if the mapping contains duplicate domain values, since encoding would then be ambiguous.
+#[inline(always)]
+pub fn mapped_enum<T, V, C>(base_codec: C, map: HashMap<T, V>) -> impl Codec<Value = V>
+where
+    T: Eq + Hash + Clone + Debug,
+    V: Eq + Hash + Clone + Debug,
+    C: Codec<Value = T>,
+{
+    let reverse = invert_map(&map);
+    MappedEnumCodec {
+        codec: base_codec,
+        decode_map: map,
+        encode_map: reverse,
+        placeholder: None,
+    }
+}
+
+/// Codec that behaves like `mapped_enum`, except that when decoding using
+/// `validation::decode_collecting_errors`, an unknown raw value is reported and decoded as
+/// `placeholder`.  The placeholder is encoded only if it is present in the mapping.
+///
+/// Panics if the mapping contains duplicate domain values.
+#[inline(always)]
+pub fn mapped_enum_or<T, V, C>(
+    base_codec: C,
+    map: HashMap<T, V>,
+    placeholder: V,
+) -> impl Codec<Value = V>
+where
+    T: Eq + Hash + Clone + Debug,
+    V: Eq + Hash + Clone + Debug,
+    C: Codec<Value = T>,
+{
+    let reverse = invert_map(&map);
+    MappedEnumCodec {
+        codec: base_codec,
+        decode_map: map,
+        encode_map: reverse,
+        placeholder: Some(placeholder),
+    }
+}
+
+/// Returns the inverse of the given mapping.  Panics if the mapping contains duplicate values.
+fn invert_map<T, V>(map: &HashMap<T, V>) -> HashMap<V, T>
+where
+    T: Clone,
+    V: Eq + Hash + Clone + Debug,
+{
+    let mut inverse = HashMap::with_capacity(map.len());
+    for (raw, value) in map {
+        assert!(
+            inverse.insert(value.clone(), raw.clone()).is_none(),
+            "Mapping contains duplicate value {:?}",
+            value
+        );
+    }
+    inverse
+}
+
+struct MappedEnumCodec<T, V, C> {
+    codec: C,
+    decode_map: HashMap<T, V>,
+    encode_map: HashMap<V, T>,
+    placeholder: Option<V>,
+}
+
+impl<T, V, C> MappedEnumCodec<T, V, C>
+where
+    T: Eq + Hash + Debug,
+    V: Clone,
+{
+    /// Returns the domain value for the given raw value, which was decoded from input with
+    /// `remaining` bytes left in the current region.
+    fn lookup(&self, raw: &T, remaining: usize) -> Result<V, Error> {
+        match (self.decode_map.get(raw), &self.placeholder) {
+            (Some(value), _) => Ok(value.clone()),
+            (None, placeholder) => {
+                let error = Error::new(lazy_description(|| format!("Unknown tag {:?}", raw)));
+                match placeholder {
+                    Some(placeholder) => validation::recover(error, remaining, placeholder.clone()),
+                    None => Err(error),
+                }
+            }
+        }
+    }
+}
+
+impl<T, V, C> Codec for MappedEnumCodec<T, V, C>
+where
+    T: Eq + Hash + Clone + Debug,
+    V: Eq + Hash + Clone + Debug,
+    C: Codec<Value = T>,
+{
+    type Value = V;
+
+    fn size_bound(&self) -> SizeBound {
+        self.codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("mapped_enum", self.size_bound()).with_child(self.codec.describe())
+    }
+
+    fn encode(&self, value: &V) -> EncodeResult {
+        match self.encode_map.get(value) {
+            Some(raw) => self.codec.encode(raw),
+            None => Err(Error::new(lazy_description(|| {
+                format!("No mapping for value {:?}", value)
+            }))),
+        }
+    }
+
+    fn encode_into(&self, value: &V, out: &mut Vec<u8>) -> Result<(), Error> {
+        match self.encode_map.get(value) {
+            Some(raw) => self.codec.encode_into(raw, out),
+            None => Err(Error::new(lazy_description(|| {
+                format!("No mapping for value {:?}", value)
+            }))),
+        }
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<V> {
+        self.codec.decode(bv).and_then(|decoded| {
+            self.lookup(&decoded.value, bv.length())
+                .map(|value| DecoderResult {
+                    value,
+                    remainder: decoded.remainder,
+                })
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(V, usize), Error> {
+        self.codec
+            .decode_slice(input)
+            .and_then(|(raw, size)| self.lookup(&raw, input.len()).map(|value| (value, size)))
+    }
+}
+
+//
+// Time codecs
+//
+
+/// Codec for `SystemTime` values, encoded as a big-endian unsigned 32-bit count of seconds since
+/// the Unix epoch.
+///
+/// Encoding fails for times before the epoch or after 2106-02-07T06:28:15Z; any fractional
+/// seconds are discarded.
+#[inline(always)]
+pub fn unix_time32() -> impl Codec<Value = SystemTime> {
+    exmap(
+        uint32,
+        |secs| epoch_units_to_time(i128::from(secs), 1),
+        |time| {
+            let secs = time_to_epoch_units(time, 1);
+            u32::try_from(secs).map_err(|_| time_out_of_range(time, "32-bit"))
+        },
+    )
+}
+
+/// Codec for `SystemTime` values, encoded as a big-endian signed 64-bit count of seconds since
+/// the Unix epoch (negative for times before the epoch).
+///
+/// Any fractional seconds are discarded on encode (rounding toward the past).  Decoding fails if
+/// the timestamp cannot be represented as a `SystemTime` on the current platform.
+#[inline(always)]
+pub fn unix_time64() -> impl Codec<Value = SystemTime> {
+    exmap(
+        int64,
+        |secs| epoch_units_to_time(i128::from(secs), 1),
+        |time| {
+            let secs = time_to_epoch_units(time, 1);
+            i64::try_from(secs).map_err(|_| time_out_of_range(time, "64-bit"))
+        },
+    )
+}
+
+/// Codec for `SystemTime` values, encoded as a big-endian signed 64-bit count of milliseconds
+/// since the Unix epoch (negative for times before the epoch), as used by Java and JavaScript.
+///
+/// Any fractional milliseconds are discarded on encode (rounding toward the past).  Decoding fails
+/// if the timestamp cannot be represented as a `SystemTime` on the current platform.
+#[inline(always)]
+pub fn unix_time_millis64() -> impl Codec<Value = SystemTime> {
+    exmap(
+        int64,
+        |millis| epoch_units_to_time(i128::from(millis), 1000),
+        |time| {
+            let millis = time_to_epoch_units(time, 1000);
+            i64::try_from(millis).map_err(|_| time_out_of_range(time, "64-bit millisecond"))
+        },
+    )
+}
+
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+
+/// Converts the given time to a (floored) number of units since the Unix epoch, where there are
+/// `units_per_sec` units per second.
+fn time_to_epoch_units(time: &SystemTime, units_per_sec: i128) -> i128 {
+    let nanos = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_nanos() as i128,
+        Err(e) => -(e.duration().as_nanos() as i128),
+    };
+    nanos.div_euclid(NANOS_PER_SEC / units_per_sec)
+}
+
+/// Converts the given number of units since the Unix epoch to a time, where there are
+/// `units_per_sec` units per second.
+fn epoch_units_to_time(units: i128, units_per_sec: i128) -> Result<SystemTime, Error> {
+    let nanos = units * (NANOS_PER_SEC / units_per_sec);
+    let offset = Duration::new(
+        (nanos.unsigned_abs() / NANOS_PER_SEC as u128) as u64,
+        (nanos.unsigned_abs() % NANOS_PER_SEC as u128) as u32,
+    );
+    let time = if nanos >= 0 {
+        UNIX_EPOCH.checked_add(offset)
+    } else {
+        UNIX_EPOCH.checked_sub(offset)
+    };
+    time.ok_or_else(|| {
+        Error::new(lazy_description(|| {
+            format!(
+                "Timestamp {} is outside the range of representable times",
+                units
+            )
+        }))
+    })
+}
+
+/// Codec for `Duration` values, encoded as a whole number of seconds using the given unsigned
+/// integer codec.
+///
+/// Any fractional seconds are discarded on encode.  Encoding fails if the number of seconds is
+/// greater than the maximum value of the integer type.
+#[inline(always)]
+pub fn duration_secs<T, C>(codec: C) -> impl Codec<Value = Duration>
+where
+    T: PrimInt + Unsigned + FromPrimitive + Display,
+    C: Codec<Value = T>,
+{
+    exmap(
+        codec,
+        |secs: T| unsigned_to_u64(&secs).map(Duration::from_secs),
+        |duration: &Duration| duration_to_units(duration.as_secs().into(), "seconds"),
+    )
+}
+
+/// Codec for `Duration` values, encoded as a whole number of milliseconds using the given unsigned
+/// integer codec.
+///
+/// Any fractional milliseconds are discarded on encode.  Encoding fails if the number of
+/// milliseconds is greater than the maximum value of the integer type.
+#[inline(always)]
+pub fn duration_millis<T, C>(codec: C) -> impl Codec<Value = Duration>
+where
+    T: PrimInt + Unsigned + FromPrimitive + Display,
+    C: Codec<Value = T>,
+{
+    exmap(
+        codec,
+        |millis: T| unsigned_to_u64(&millis).map(Duration::from_millis),
+        |duration: &Duration| duration_to_units(duration.as_millis(), "milliseconds"),
+    )
+}
+
+/// Converts the given number of duration units to the given integer type.
+fn duration_to_units<T>(units: u128, unit_name: &str) -> Result<T, Error>
+where
+    T: PrimInt + Unsigned + FromPrimitive + Display,
+{
+    u64::try_from(units)
+        .ok()
+        .and_then(T::from_u64)
+        .ok_or_else(|| {
+            Error::new(lazy_description(|| {
+                format!(
+                    "Duration of {} {} is greater than maximum value ({}) of integer type",
+                    units,
+                    unit_name,
+                    T::max_value()
+                )
+            }))
+        })
+}
+
+/// Returns an error indicating that the given time cannot be encoded as the given kind of timestamp.
+fn time_out_of_range(time: &SystemTime, kind: &str) -> Error {
+    Error::new(lazy_description(|| {
+        format!(
+            "Time {:?} is outside the range of {} Unix timestamps",
+            time, kind
+        )
+    }))
+}
+
+//
+// Validation codecs
+//
+
+/// Codec that decodes using the given codec and then checks the decoded value against `predicate`,
+/// failing with `message` if the value is rejected.  Values are encoded without being checked.
+#[inline(always)]
+pub fn validated<T, C, P>(codec: C, predicate: P, message: &'static str) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+    P: Fn(&T) -> bool,
+{
+    ValidatedCodec {
+        codec,
+        predicate,
+        message,
+        validate_encode: false,
+    }
+}
+
+/// Codec that checks values against `predicate` both before encoding and after decoding, failing
+/// with `message` if the value is rejected.
+#[inline(always)]
+pub fn strictly_validated<T, C, P>(
+    codec: C,
+    predicate: P,
+    message: &'static str,
+) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+    P: Fn(&T) -> bool,
+{
+    ValidatedCodec {
+        codec,
+        predicate,
+        message,
+        validate_encode: true,
+    }
+}
+
+struct ValidatedCodec<C, P> {
+    codec: C,
+    predicate: P,
+    message: &'static str,
+    validate_encode: bool,
+}
+
+impl<C, P> ValidatedCodec<C, P> {
+    fn validation_error(&self) -> Error {
+        Error::new(lazy_description(|| {
+            format!("Validation failed: {}", self.message)
+        }))
+    }
+}
+
+impl<T, C, P> Codec for ValidatedCodec<C, P>
+where
+    C: Codec<Value = T>,
+    P: Fn(&T) -> bool,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        self.codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("validated", self.size_bound()).with_child(self.codec.describe())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        if self.validate_encode && !(self.predicate)(value) {
+            return Err(self.validation_error());
+        }
+        self.codec.encode(value)
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        if self.validate_encode && !(self.predicate)(value) {
+            return Err(self.validation_error());
+        }
+        self.codec.encode_into(value, out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        self.codec.decode(bv).and_then(|decoded| {
+            if (self.predicate)(&decoded.value) {
+                Ok(decoded)
+            } else {
+                validation::recover(self.validation_error(), bv.length(), decoded)
+            }
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        self.codec.decode_slice(input).and_then(|decoded| {
+            if (self.predicate)(&decoded.0) {
+                Ok(decoded)
+            } else {
+                validation::recover(self.validation_error(), input.len(), decoded)
+            }
+        })
+    }
+}
+
+/// Codec that rejects values falling outside of `range`, checking both before encoding and after
+/// decoding.
+#[inline(always)]
+pub fn bounded<T, C, R>(codec: C, range: R) -> impl Codec<Value = T>
+where
+    T: PartialOrd + Display,
+    C: Codec<Value = T>,
+    R: RangeBounds<T> + Debug,
+{
+    BoundedCodec { codec, range }
+}
+
+/// Codec for 8-bit unsigned integers that must fall within `min..=max`.
+#[inline(always)]
+pub fn bounded_uint8(min: u8, max: u8) -> impl Codec<Value = u8> {
+    bounded(uint8, min..=max)
+}
+
+/// Codec for big-endian 16-bit unsigned integers that must fall within `min..=max`.
+#[inline(always)]
+pub fn bounded_uint16(min: u16, max: u16) -> impl Codec<Value = u16> {
+    bounded(uint16, min..=max)
+}
+
+/// Codec for big-endian 32-bit unsigned integers that must fall within `min..=max`.
+#[inline(always)]
+pub fn bounded_uint32(min: u32, max: u32) -> impl Codec<Value = u32> {
+    bounded(uint32, min..=max)
+}
+
+struct BoundedCodec<C, R> {
+    codec: C,
+    range: R,
+}
+
+impl<C, R: Debug> BoundedCodec<C, R> {
+    fn check<T>(&self, value: &T) -> Result<(), Error>
+    where
+        T: PartialOrd + Display,
+        R: RangeBounds<T>,
+    {
+        if self.range.contains(value) {
+            Ok(())
+        } else {
+            Err(Error::new(lazy_description(|| {
+                format!("Value {} is out of range {:?}", value, self.range)
+            })))
+        }
+    }
+}
+
+impl<T, C, R> Codec for BoundedCodec<C, R>
+where
+    T: PartialOrd + Display,
+    C: Codec<Value = T>,
+    R: RangeBounds<T> + Debug,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        self.codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("bounded", self.size_bound()).with_child(self.codec.describe())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        self.check(value).and_then(|_| self.codec.encode(value))
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.check(value)
+            .and_then(|_| self.codec.encode_into(value, out))
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        self.codec
+            .decode(bv)
+            .and_then(|decoded| match self.check(&decoded.value) {
+                Ok(()) => Ok(decoded),
+                Err(e) => validation::recover(e, bv.length(), decoded),
+            })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        self.codec
+            .decode_slice(input)
+            .and_then(|decoded| match self.check(&decoded.0) {
+                Ok(()) => Ok(decoded),
+                Err(e) => validation::recover(e, input.len(), decoded),
+            })
+    }
+}
+
+//
+// Checksum codecs
+//
+
+/// Codec that protects the bytes produced by the given `codec` with a trailing checksum computed
+/// using the given algorithm and encoded using `checksum_codec`.
+///
+///   - Encodes using the given codec and then appends the checksum of the encoded bytes.
+///   - Decodes using the given codec and then verifies the checksum that follows the consumed
+///     bytes, returning an error if it does not match.  The checksum is removed from the remainder.
+#[inline(always)]
+pub fn checksummed<T, S, C, SC, A>(codec: C, checksum_codec: SC, algo: A) -> impl Codec<Value = T>
+where
+    S: PartialEq + Debug,
+    C: Codec<Value = T>,
+    SC: Codec<Value = S>,
+    A: Checksum<Output = S>,
+{
+    ChecksummedCodec {
+        codec,
+        checksum_codec,
+        algo,
+        kind: "Checksum",
+    }
+}
+
+/// Codec that protects the bytes produced by the given `codec` with a trailing big-endian
+/// CRC-16/ARC checksum; see `crc_with` for details.
+#[inline(always)]
+pub fn crc16<T, C>(codec: C) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    crc_with(codec, crc::CRC_16_ARC, ByteOrdering::BigEndian)
+}
+
+/// Codec that protects the bytes produced by the given `codec` with a trailing big-endian CRC-32
+/// checksum; see `crc_with` for details.
+#[inline(always)]
+pub fn crc32<T, C>(codec: C) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    crc_with(codec, crc::CRC_32, ByteOrdering::BigEndian)
+}
+
+/// Codec that protects the bytes produced by the given `codec` with a trailing checksum computed
+/// using the given CRC algorithm and encoded using the given byte ordering.
+///
+///   - Encodes using the given codec and then appends the CRC of the encoded bytes.
+///   - Decodes using the given codec and then verifies the CRC that follows the consumed bytes,
+///     returning an error if it does not match.  The CRC is removed from the remainder.
+#[inline(always)]
+pub fn crc_with<T, C>(codec: C, crc: Crc, ordering: ByteOrdering) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    ChecksummedCodec {
+        codec,
+        checksum_codec: CrcValueCodec {
+            len: crc.byte_len(),
+            ordering,
+        },
+        algo: crc.digest(),
+        kind: "CRC",
+    }
+}
+
+struct ChecksummedCodec<C, SC, A> {
+    codec: C,
+    checksum_codec: SC,
+    algo: A,
+    /// The kind of checksum, as reported in mismatch errors.
+    kind: &'static str,
+}
+
+impl<C, SC, A: Checksum> ChecksummedCodec<C, SC, A> {
+    /// Returns the checksum for the given bytes.
+    fn checksum(&self, bv: &ByteVector) -> Result<A::Output, Error> {
+        bv.to_vec().map(|bytes| self.algo.checksum(&bytes))
+    }
+}
+
+impl<T, S, C, SC, A> Codec for ChecksummedCodec<C, SC, A>
+where
+    S: PartialEq + Debug,
+    C: Codec<Value = T>,
+    SC: Codec<Value = S>,
+    A: Checksum<Output = S>,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        self.codec.size_bound() + self.checksum_codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("checksummed", self.size_bound())
+            .with_child(self.codec.describe())
+            .with_child(self.checksum_codec.describe())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        let start = out.len();
+        self.codec.encode_into(value, out)?;
+        let checksum = self.algo.checksum(&out[start..]);
+        self.checksum_codec.encode_into(&checksum, out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        let decoded = self.codec.decode(bv)?;
+        let region = bv.take(bv.length() - decoded.remainder.length())?;
+        let expected = self.checksum(&region)?;
+        let actual = self.checksum_codec.decode(&decoded.remainder)?;
+        if actual.value != expected {
+            let error = Error::new(lazy_description(|| {
+                format!(
+                    "{} mismatch: expected {:?} but got {:?}",
+                    self.kind, expected, actual.value
+                )
+            }));
+            validation::recover(error, decoded.remainder.length(), ())?;
+        }
+        Ok(DecoderResult {
+            value: decoded.value,
+            remainder: actual.remainder,
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        let (value, size) = self.codec.decode_slice(input)?;
+        let expected = self.algo.checksum(&input[..size]);
+        let (actual, checksum_size) = self.checksum_codec.decode_slice(&input[size..])?;
+        if actual != expected {
+            let error = Error::new(lazy_description(|| {
+                format!(
+                    "{} mismatch: expected {:?} but got {:?}",
+                    self.kind, expected, actual
+                )
+            }));
+            validation::recover(error, input.len() - size, ())?;
+        }
+        Ok((value, size + checksum_size))
+    }
+}
+
+/// Codec for a structure that contains a checksum field covering the rest of the structure, as in
+/// IPv4 headers.  The value consists of the fields before and after the checksum field, which are
+/// encoded using `before_codec` and `after_codec`.
+///
+///   - Encodes both parts, then computes the checksum of the encoded bytes (excluding the checksum
+///     field itself) and writes it between them using `checksum_codec`.
+///   - Decodes all three parts and verifies the checksum, returning an error if it does not match
+///     (or, within `validation::decode_collecting_errors`, reporting the mismatch and returning the
+///     decoded value).
+///
+/// Since the checksum is always computed by the codec, it does not appear in the value and so
+/// cannot become inconsistent with the other fields.
+#[inline(always)]
+pub fn with_checksum_field<B, T, S, BC, SC, AC, A>(
+    before_codec: BC,
+    checksum_codec: SC,
+    after_codec: AC,
+    algo: A,
+) -> impl Codec<Value = (B, T)>
+where
+    S: PartialEq + Debug,
+    BC: Codec<Value = B>,
+    SC: Codec<Value = S>,
+    AC: Codec<Value = T>,
+    A: Checksum<Output = S>,
+{
+    ChecksumFieldCodec {
+        before_codec,
+        checksum_codec,
+        after_codec,
+        algo,
+    }
+}
+
+struct ChecksumFieldCodec<BC, SC, AC, A> {
+    before_codec: BC,
+    checksum_codec: SC,
+    after_codec: AC,
+    algo: A,
+}
+
+impl<BC, SC, AC, A> ChecksumFieldCodec<BC, SC, AC, A>
+where
+    A: Checksum,
+    A::Output: PartialEq + Debug,
+{
+    /// Verifies that `actual` matches the checksum of the given bytes.  The checksum field begins
+    /// with `remaining` bytes left in the input.
+    fn verify(
+        &self,
+        before: &[u8],
+        after: &[u8],
+        actual: A::Output,
+        remaining: usize,
+    ) -> Result<(), Error> {
+        let mut state = self.algo.clone();
+        state.update(before);
+        state.update(after);
+        let expected = state.finalize();
+        if actual == expected {
+            Ok(())
+        } else {
+            let error = Error::new(lazy_description(|| {
+                format!(
+                    "Checksum mismatch: expected {:?} but got {:?}",
+                    expected, actual
+                )
+            }));
+            validation::recover(error, remaining, ())
+        }
+    }
+}
+
+impl<B, T, S, BC, SC, AC, A> Codec for ChecksumFieldCodec<BC, SC, AC, A>
+where
+    S: PartialEq + Debug,
+    BC: Codec<Value = B>,
+    SC: Codec<Value = S>,
+    AC: Codec<Value = T>,
+    A: Checksum<Output = S>,
+{
+    type Value = (B, T);
+
+    fn size_bound(&self) -> SizeBound {
+        self.before_codec.size_bound()
+            + self.checksum_codec.size_bound()
+            + self.after_codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("with_checksum_field", self.size_bound())
+            .with_child(self.before_codec.describe())
+            .with_child(self.checksum_codec.describe())
+            .with_child(self.after_codec.describe())
+    }
+
+    fn encode(&self, value: &(B, T)) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &(B, T), out: &mut Vec<u8>) -> Result<(), Error> {
+        let start = out.len();
+        self.before_codec.encode_into(&value.0, out)?;
+        let mut after = Vec::new();
+        self.after_codec.encode_into(&value.1, &mut after)?;
+
+        let mut state = self.algo.clone();
+        state.update(&out[start..]);
+        state.update(&after);
+        self.checksum_codec.encode_into(&state.finalize(), out)?;
+        out.extend_from_slice(&after);
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<(B, T)> {
+        let before = self.before_codec.decode(bv)?;
+        let checksum = self.checksum_codec.decode(&before.remainder)?;
+        let after = self.after_codec.decode(&checksum.remainder)?;
+
+        // Only the consumed regions on either side of the checksum field are covered
+        let before_region = bv.take(bv.length() - before.remainder.length())?;
+        let after_region = checksum
+            .remainder
+            .take(checksum.remainder.length() - after.remainder.length())?;
+        self.verify(
+            &before_region.to_vec()?,
+            &after_region.to_vec()?,
+            checksum.value,
+            before.remainder.length(),
+        )?;
+        Ok(DecoderResult {
+            value: (before.value, after.value),
+            remainder: after.remainder,
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<((B, T), usize), Error> {
+        let (before, before_size) = self.before_codec.decode_slice(input)?;
+        let rest = &input[before_size..];
+        let (checksum, checksum_size) = self.checksum_codec.decode_slice(rest)?;
+        let rest = &rest[checksum_size..];
+        let (after, after_size) = self.after_codec.decode_slice(rest)?;
+        self.verify(
+            &input[..before_size],
+            &rest[..after_size],
+            checksum,
+            input.len() - before_size,
+        )?;
+        Ok(((before, after), before_size + checksum_size + after_size))
+    }
+}
+
+/// Codec for CRC values that are `len` bytes wide.
+struct CrcValueCodec {
+    len: usize,
+    ordering: ByteOrdering,
+}
+
+impl Codec for CrcValueCodec {
+    type Value = u64;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(self.len as u64)
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new(
+            integral_name(false, self.len, Some(self.ordering)),
+            self.size_bound(),
+        )
+        .with_byte_ordering(Some(self.ordering))
+    }
+
+    fn encode(&self, value: &u64) -> EncodeResult {
+        let bytes = match self.ordering {
+            ByteOrdering::BigEndian => value.to_be_bytes()[8 - self.len..].to_vec(),
+            ByteOrdering::LittleEndian => value.to_le_bytes()[..self.len].to_vec(),
+        };
+        Ok(byte_vector::from_vec(bytes))
+    }
+
+    fn encode_into(&self, value: &u64, out: &mut Vec<u8>) -> Result<(), Error> {
+        match self.ordering {
+            ByteOrdering::BigEndian => out.extend_from_slice(&value.to_be_bytes()[8 - self.len..]),
+            ByteOrdering::LittleEndian => out.extend_from_slice(&value.to_le_bytes()[..self.len]),
+        }
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<u64> {
+        let mut buf = [0u8; 8];
+        bv.read(&mut buf[..self.len], 0, self.len)?;
+        let value = self.value_of(&buf[..self.len]);
+        bv.drop(self.len)
+            .map(|remainder| DecoderResult { value, remainder })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(u64, usize), Error> {
+        slice_prefix(input, self.len).map(|bytes| (self.value_of(bytes), self.len))
+    }
+}
+
+impl CrcValueCodec {
+    /// Returns the CRC value encoded by the given bytes.
+    fn value_of(&self, bytes: &[u8]) -> u64 {
+        match self.ordering {
+            ByteOrdering::BigEndian => bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64),
+            ByteOrdering::LittleEndian => bytes
+                .iter()
+                .rev()
+                .fold(0u64, |acc, b| (acc << 8) | *b as u64),
+        }
+    }
+}
+
+//
+// Chunk codecs
+//
+
+/// Codec for a four-character code (as used to identify chunks in PNG, RIFF, and similar formats),
+/// represented as an array of four bytes.
+#[inline(always)]
+pub fn fourcc() -> impl Codec<Value = [u8; 4]> {
+    uint32.xmap(u32::to_be_bytes, |code| u32::from_be_bytes(*code))
+}
+
+/// Codec for a chunk consisting of a big-endian 32-bit length, a chunk type encoded using
+/// `type_codec` (typically `fourcc()` or a `constant`), and a payload encoded using
+/// `payload_codec`, where the length holds the size of the payload in bytes.
+///
+///   - Encodes the length, the chunk type, and the payload.
+///   - Decodes the length and the chunk type, and then decodes the payload from exactly `length`
+///     bytes; it is an error if the payload codec leaves any of them unconsumed.
+#[inline(always)]
+pub fn chunk<F, P, FC, PC>(type_codec: FC, payload_codec: PC) -> impl Codec<Value = (F, P)>
+where
+    FC: Codec<Value = F>,
+    PC: Codec<Value = P>,
+{
+    ChunkCodec {
+        type_codec,
+        payload_codec,
+        crc: None,
+    }
+}
+
+/// Codec for a chunk that behaves like `chunk`, except that the chunk is followed by a big-endian
+/// checksum computed over the chunk type and payload (but not the length) using the given CRC
+/// algorithm, as in PNG (which uses `crc::CRC_32`).  Decoding fails if the checksum does not
+/// match.
+#[inline(always)]
+pub fn chunk_with_crc<F, P, FC, PC>(
+    type_codec: FC,
+    payload_codec: PC,
+    crc: Crc,
+) -> impl Codec<Value = (F, P)>
+where
+    FC: Codec<Value = F>,
+    PC: Codec<Value = P>,
+{
+    ChunkCodec {
+        type_codec,
+        payload_codec,
+        crc: Some(crc),
+    }
+}
+
+struct ChunkCodec<FC, PC> {
+    type_codec: FC,
+    payload_codec: PC,
+    crc: Option<Crc>,
+}
+
+/// Returns a codec for CRC values computed using the given algorithm, in big-endian order.
+fn crc_value_codec(crc: &Crc) -> CrcValueCodec {
+    CrcValueCodec {
+        len: crc.byte_len(),
+        ordering: ByteOrdering::BigEndian,
+    }
+}
+
+impl<F, P, FC, PC> Codec for ChunkCodec<FC, PC>
+where
+    FC: Codec<Value = F>,
+    PC: Codec<Value = P>,
+{
+    type Value = (F, P);
+
+    fn size_bound(&self) -> SizeBound {
+        let crc_bound = match self.crc {
+            Some(ref crc) => SizeBound::exact(crc.byte_len() as u64),
+            None => SizeBound::exact(0),
+        };
+        SizeBound::exact(4)
+            + self.type_codec.size_bound()
+            + self.payload_codec.size_bound()
+            + crc_bound
+    }
+
+    fn describe(&self) -> CodecDescription {
+        let description = CodecDescription::new("chunk", self.size_bound())
+            .with_child(uint32.describe())
+            .with_child(self.type_codec.describe())
+            .with_child(self.payload_codec.describe());
+        match self.crc {
+            Some(ref crc) => description.with_child(crc_value_codec(crc).describe()),
+            None => description,
+        }
+    }
+
+    fn encode(&self, value: &(F, P)) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &(F, P), out: &mut Vec<u8>) -> Result<(), Error> {
+        let mut body = Vec::new();
+        self.type_codec.encode_into(&value.0, &mut body)?;
+        let type_len = body.len();
+        self.payload_codec.encode_into(&value.1, &mut body)?;
+        let payload_len = u32::try_from(body.len() - type_len).map_err(|_| {
+            Error::new(lazy_description(|| format!(
+                "Length of chunk payload ({} bytes) is greater than maximum value ({}) of length type",
+                body.len() - type_len,
+                u32::MAX
+            )))
+        })?;
+        uint32.encode_into(&payload_len, out)?;
+        out.extend_from_slice(&body);
+        match self.crc {
+            Some(ref crc) => crc_value_codec(crc).encode_into(&crc.checksum(&body), out),
+            None => Ok(()),
+        }
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<(F, P)> {
+        let decoded_len = uint32.decode(bv)?;
+        let len = decoded_len.value as usize;
+        limits::check_alloc(len)?;
+        let decoded_type = self
+            .type_codec
+            .decode(&decoded_len.remainder)
+            .map_err(|e| e.offset_by(bv.length() - decoded_len.remainder.length()))?;
+        let payload = decoded_type
+            .remainder
+            .take(len)
+            .map_err(|e| e.offset_by(bv.length() - decoded_type.remainder.length()))?;
+        let decoded_payload = cursor::region(decoded_type.remainder.length(), len, || {
+            self.payload_codec
+                .decode(&payload)
+                .and_then(require_complete)
+        })
+        .map_err(Error::within_bounded_input)?;
+        let remainder = decoded_type.remainder.drop(len)?;
+        let remainder = match self.crc {
+            Some(ref crc) => {
+                let body_len = decoded_len.remainder.length() - remainder.length();
+                let body = decoded_len.remainder.take(body_len)?.to_vec()?;
+                let actual = crc_value_codec(crc)
+                    .decode(&remainder)
+                    .map_err(|e| e.offset_by(bv.length() - remainder.length()))?;
+                verify_crc(crc, &body, actual.value)?;
+                actual.remainder
+            }
+            None => remainder,
+        };
+        Ok(DecoderResult {
+            value: (decoded_type.value, decoded_payload.value),
+            remainder,
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<((F, P), usize), Error> {
+        let (len, len_size) = uint32.decode_slice(input)?;
+        let len = len as usize;
+        limits::check_alloc(len)?;
+        let (chunk_type, type_size) = self
+            .type_codec
+            .decode_slice(&input[len_size..])
+            .map_err(|e| e.offset_by(len_size))?;
+        let start = len_size + type_size;
+        let payload = slice_prefix(&input[start..], len).map_err(|e| e.offset_by(start))?;
+        let value = cursor::region(input.len() - start, len, || {
+            self.payload_codec.decode_all(payload)
+        })
+        .map_err(Error::within_bounded_input)?;
+        let mut consumed = start + len;
+        if let Some(ref crc) = self.crc {
+            let (actual, crc_size) = crc_value_codec(crc)
+                .decode_slice(&input[consumed..])
+                .map_err(|e| e.offset_by(consumed))?;
+            verify_crc(crc, &input[len_size..consumed], actual)?;
+            consumed += crc_size;
+        }
+        Ok(((chunk_type, value), consumed))
+    }
+}
+
+/// Verifies that `actual` matches the checksum of the given chunk body.
+fn verify_crc(crc: &Crc, body: &[u8], actual: u64) -> Result<(), Error> {
+    let expected = crc.checksum(body);
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(Error::new(lazy_description(|| {
+            format!(
+                "Checksum mismatch: expected {:?} but got {:?}",
+                expected, actual
+            )
+        })))
+    }
+}
+
+//
+// Encrypted codec
+//
+
+/// Codec for values that are encrypted using the given cipher.
+///
+///   - Encodes using the given codec and then encrypts the encoded bytes.
+///   - Decodes by decrypting all of the given bytes and then decoding the plaintext using the given
+///     codec.  Any plaintext bytes not consumed by the given codec are discarded.
+///
+/// Since all available bytes are decrypted, this is typically wrapped in a codec that limits the
+/// size of the encrypted region (e.g. `variable_size_bytes` or `fixed_size_bytes`).
+#[inline(always)]
+pub fn encrypted<T, C, K>(codec: C, cipher: K) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+    K: Cipher,
+{
+    EncryptedCodec { codec, cipher }
+}
+
+struct EncryptedCodec<C, K> {
+    codec: C,
+    cipher: K,
+}
+
+impl<T, C, K> Codec for EncryptedCodec<C, K>
+where
+    C: Codec<Value = T>,
+    K: Cipher,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::unknown()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("encrypted", self.size_bound()).with_child(self.codec.describe())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        let mut plaintext = Vec::new();
+        self.codec.encode_into(value, &mut plaintext)?;
+        self.cipher
+            .encrypt(&plaintext)
+            .map(|ciphertext| out.extend_from_slice(&ciphertext))
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        forcomp!({
+            ciphertext <- bv.to_vec();
+            plaintext <- self.cipher.decrypt(&ciphertext);
+            decoded <- cursor::detached(|| self.codec.decode(&byte_vector::from_vec(plaintext)));
+        } yield {
+            DecoderResult { value: decoded.value, remainder: byte_vector::empty() }
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        let plaintext = self.cipher.decrypt(input)?;
+        let (value, _) = cursor::detached(|| self.codec.decode_slice(&plaintext))?;
+        Ok((value, input.len()))
+    }
+}
+
+//
+// Tuple codecs
+//
+
+// Provides implementations of the `Codec` trait for tuples of codecs, where each element is
+// encoded/decoded in turn.
+macro_rules! tuple_codec_impl {
+    { $($C:ident $idx:tt $v:ident),+ } => {
+        impl<$($C: Codec),+> Codec for ($($C,)+) {
+            type Value = ($($C::Value,)+);
+
+            fn size_bound(&self) -> SizeBound {
+                SizeBound::exact(0) $(+ self.$idx.size_bound())+
+            }
+
+            fn describe(&self) -> CodecDescription {
+                CodecDescription::new("tuple", self.size_bound())
+                    $(.with_child(self.$idx.describe()))+
+            }
+
+            fn encode(&self, value: &Self::Value) -> EncodeResult {
+                encode_via_buffer(self, value)
+            }
+
+            fn encode_into(&self, value: &Self::Value, out: &mut Vec<u8>) -> Result<(), Error> {
+                $(self.$idx.encode_into(&value.$idx, out)?;)+
+                Ok(())
+            }
+
+            fn encode_to(&self, value: &Self::Value, out: &mut dyn Write) -> Result<(), Error> {
+                $(self.$idx.encode_to(&value.$idx, out)?;)+
+                Ok(())
+            }
+
+            fn decode(&self, bv: &ByteVector) -> DecodeResult<Self::Value> {
+                let remainder = bv.clone();
+                $(
+                    let DecoderResult { value: $v, remainder } = self.$idx
+                        .decode(&remainder)
+                        .map_err(|e| e.offset_by(bv.length() - remainder.length()))?;
+                )+
+                Ok(DecoderResult {
+                    value: ($($v,)+),
+                    remainder,
+                })
+            }
+
+            fn decode_slice(&self, input: &[u8]) -> Result<(Self::Value, usize), Error> {
+                let mut consumed = 0;
+                $(
+                    let ($v, size) = self.$idx
+                        .decode_slice(&input[consumed..])
+                        .map_err(|e| e.offset_by(consumed))?;
+                    consumed += size;
+                )+
+                Ok((($($v,)+), consumed))
+            }
+        }
+    };
+}
+
+tuple_codec_impl! { A 0 a, B 1 b }
+tuple_codec_impl! { A 0 a, B 1 b, C 2 c }
+tuple_codec_impl! { A 0 a, B 1 b, C 2 c, D 3 d }
+tuple_codec_impl! { A 0 a, B 1 b, C 2 c, D 3 d, E 4 e }
+tuple_codec_impl! { A 0 a, B 1 b, C 2 c, D 3 d, E 4 e, F 5 f }
+tuple_codec_impl! { A 0 a, B 1 b, C 2 c, D 3 d, E 4 e, F 5 f, G 6 g }
+tuple_codec_impl! { A 0 a, B 1 b, C 2 c, D 3 d, E 4 e, F 5 f, G 6 g, H 7 h }
+
+/// Codec for pairs in which the codec for the second element depends on the value of the first.
+///
+///   - Encodes the first element using `first_codec`, followed by the second element using the
+///     codec returned by `f` for the first element.
+///   - Decodes the first element using `first_codec`, and then decodes the second element from
+///     the remaining bytes using the codec returned by `f` for the decoded first element.
+///
+/// This is a lightweight alternative to `hlist_flat_prepend_codec` for the common case of two
+/// dependent values.
+#[inline(always)]
+pub fn flat_zip<A, B, AC, BC, F>(first_codec: AC, f: F) -> impl Codec<Value = (A, B)>
+where
+    AC: Codec<Value = A>,
+    BC: Codec<Value = B>,
+    F: Fn(&A) -> BC,
+{
+    FlatZipCodec { first_codec, f }
+}
+
+struct FlatZipCodec<AC, F> {
+    first_codec: AC,
+    f: F,
+}
+
+impl<A, B, AC, BC, F> Codec for FlatZipCodec<AC, F>
+where
+    AC: Codec<Value = A>,
+    BC: Codec<Value = B>,
+    F: Fn(&A) -> BC,
+{
+    type Value = (A, B);
+
+    fn size_bound(&self) -> SizeBound {
+        self.first_codec.size_bound() + SizeBound::unknown()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        // The second codec depends on the decoded first value, so only the first can be described
+        CodecDescription::new("flat_zip", self.size_bound()).with_child(self.first_codec.describe())
+    }
+
+    fn encode(&self, value: &(A, B)) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &(A, B), out: &mut Vec<u8>) -> Result<(), Error> {
+        self.first_codec.encode_into(&value.0, out)?;
+        (self.f)(&value.0).encode_into(&value.1, out)
+    }
+
+    fn encode_to(&self, value: &(A, B), out: &mut dyn Write) -> Result<(), Error> {
+        self.first_codec.encode_to(&value.0, out)?;
+        (self.f)(&value.0).encode_to(&value.1, out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<(A, B)> {
+        forcomp!({
+            first <- self.first_codec.decode(bv);
+            second <- cursor::with_position(first.remainder.length(), || (self.f)(&first.value))
+                .decode(&first.remainder)
+                .map_err(|e| e.offset_by(bv.length() - first.remainder.length()));
+        } yield {
+            DecoderResult { value: (first.value, second.value), remainder: second.remainder }
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<((A, B), usize), Error> {
+        let (first, first_size) = self.first_codec.decode_slice(input)?;
+        let second_codec = cursor::with_position(input.len() - first_size, || (self.f)(&first));
+        let (second, second_size) = second_codec
+            .decode_slice(&input[first_size..])
+            .map_err(|e| e.offset_by(first_size))?;
+        Ok(((first, second), first_size + second_size))
+    }
+}
+
+//
+// HList-related codecs
+//
+
+/// Codec for `HNil` type.
+#[inline(always)]
+pub fn hnil_codec() -> impl Codec<Value = HNil> {
+    HNilCodec
+}
+
+struct HNilCodec;
+
+impl Codec for HNilCodec {
+    type Value = HNil;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::exact(0)
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("hlist", self.size_bound())
+    }
+
+    fn encode(&self, _value: &HNil) -> EncodeResult {
+        Ok(byte_vector::empty())
+    }
+
+    fn encode_into(&self, _value: &HNil, _out: &mut Vec<u8>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn encode_to(&self, _value: &HNil, _out: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn section_count(&self) -> usize {
+        0
+    }
+
+    fn encode_sections_into(
+        &self,
+        _value: &HNil,
+        _range: Range<usize>,
+        _out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<HNil> {
+        Ok(DecoderResult {
+            value: HNil,
+            remainder: bv.clone(),
+        })
+    }
+
+    fn decode_slice(&self, _input: &[u8]) -> Result<(HNil, usize), Error> {
+        Ok((HNil, 0))
+    }
+}
+
+/// Codec used to convert an `HList` of codecs into a single codec that encodes/decodes an `HList` of values.
+#[inline(always)]
+pub fn hlist_prepend_codec<H, T, HC, TC>(
+    head_codec: HC,
+    tail_codec: TC,
+) -> impl Codec<Value = HCons<H, T>>
+where
+    T: HList,
+    HC: Codec<Value = H>,
+    TC: Codec<Value = T>,
+{
+    HListPrependCodec {
+        head_codec,
+        tail_codec,
+    }
+}
+
+struct HListPrependCodec<HC, TC> {
+    head_codec: HC,
+    tail_codec: TC,
+}
+
+impl<H, T, HC, TC> Codec for HListPrependCodec<HC, TC>
+where
+    T: HList,
+    HC: Codec<Value = H>,
+    TC: Codec<Value = T>,
+{
+    type Value = HCons<H, T>;
+
+    fn size_bound(&self) -> SizeBound {
+        self.head_codec.size_bound() + self.tail_codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        // Flatten the nested cons cells into a single list of fields
+        let tail = self.tail_codec.describe();
+        CodecDescription::new("hlist", self.size_bound())
+            .with_child(self.head_codec.describe())
+            .with_children(tail.children)
+    }
+
+    fn encode(&self, value: &HCons<H, T>) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &HCons<H, T>, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.head_codec.encode_into(value.head(), out)?;
+        self.tail_codec.encode_into(value.tail(), out)
+    }
+
+    fn encode_to(&self, value: &HCons<H, T>, out: &mut dyn Write) -> Result<(), Error> {
+        self.head_codec.encode_to(value.head(), out)?;
+        self.tail_codec.encode_to(value.tail(), out)
+    }
+
+    fn section_count(&self) -> usize {
+        1 + self.tail_codec.section_count()
+    }
+
+    fn encode_sections_into(
+        &self,
+        value: &HCons<H, T>,
+        range: Range<usize>,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        if range.contains(&0) {
+            self.head_codec.encode_into(value.head(), out)?;
+        }
+        self.tail_codec
+            .encode_sections_into(value.tail(), tail_sections(range), out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<HCons<H, T>> {
+        // TODO: Generalize this as a decode_both_combine() function
+        forcomp!({
+            decoded_head <- self.head_codec.decode(bv);
+            decoded_tail <- self.tail_codec
+                .decode(&decoded_head.remainder)
+                .map_err(|e| e.offset_by(bv.length() - decoded_head.remainder.length()));
+        } yield {
+            DecoderResult { value: HCons(decoded_head.value, decoded_tail.value), remainder: decoded_tail.remainder }
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(HCons<H, T>, usize), Error> {
+        let (head, head_size) = self.head_codec.decode_slice(input)?;
+        let (tail, tail_size) = self
+            .tail_codec
+            .decode_slice(&input[head_size..])
+            .map_err(|e| e.offset_by(head_size))?;
+        Ok((HCons(head, tail), head_size + tail_size))
+    }
+}
+
+/// Codec that first performs encoding/decoding of `T`, using the resulting value to produce codecs
+/// for the remaining types.
+///
+/// This allows later parts of an `HList` codec to be dependent on on earlier values.  The function
+/// may return any codec type (such as the `impl Codec` produced by `hcodec!`), so the tail codec
+/// does not need to be boxed.
+#[inline(always)]
+pub fn hlist_flat_prepend_codec<H, T, HC, TC, F>(
+    head_codec: HC,
+    tail_codec_fn: F,
+) -> impl Codec<Value = HCons<H, T>>
+where
+    T: HList,
+    HC: Codec<Value = H>,
+    TC: Codec<Value = T>,
+    F: Fn(&H) -> TC,
+{
+    HListFlatPrependCodec {
+        head_codec,
+        tail_codec_fn,
+    }
+}
+
+struct HListFlatPrependCodec<HC, F> {
+    head_codec: HC,
+    tail_codec_fn: F,
+}
+
+impl<H, T, HC, TC, F> Codec for HListFlatPrependCodec<HC, F>
+where
+    T: HList,
+    HC: Codec<Value = H>,
+    TC: Codec<Value = T>,
+    F: Fn(&H) -> TC,
+{
+    type Value = HCons<H, T>;
+
+    fn size_bound(&self) -> SizeBound {
+        self.head_codec.size_bound() + SizeBound::unknown()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        // The tail codec depends on the decoded head value, so only the head can be described
+        CodecDescription::new("hlist_flat_prepend", self.size_bound())
+            .with_child(self.head_codec.describe())
+    }
+
+    fn encode(&self, value: &HCons<H, T>) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &HCons<H, T>, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.head_codec.encode_into(value.head(), out)?;
+        (self.tail_codec_fn)(value.head()).encode_into(value.tail(), out)
+    }
+
+    fn encode_to(&self, value: &HCons<H, T>, out: &mut dyn Write) -> Result<(), Error> {
+        self.head_codec.encode_to(value.head(), out)?;
+        (self.tail_codec_fn)(value.head()).encode_to(value.tail(), out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<HCons<H, T>> {
+        forcomp!({
+            decoded_head <- self.head_codec.decode(bv);
+            decoded_tail <- cursor::with_position(decoded_head.remainder.length(), || (self.tail_codec_fn)(&decoded_head.value))
+                .decode(&decoded_head.remainder)
+                .map_err(|e| e.offset_by(bv.length() - decoded_head.remainder.length()));
+        } yield {
+            DecoderResult { value: HCons(decoded_head.value, decoded_tail.value), remainder: decoded_tail.remainder }
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(HCons<H, T>, usize), Error> {
+        let (head, head_size) = self.head_codec.decode_slice(input)?;
+        let tail_codec =
+            cursor::with_position(input.len() - head_size, || (self.tail_codec_fn)(&head));
+        let (tail, tail_size) = tail_codec
+            .decode_slice(&input[head_size..])
+            .map_err(|e| e.offset_by(head_size))?;
+        Ok((HCons(head, tail), head_size + tail_size))
+    }
+}
+
+/// Support for appending the `HList` `S` to the end of an `HList`.
+pub trait HListAppend<S: HList>: HList + Sized {
+    /// The type of the concatenated `HList`.
+    type Output: HList;
+
+    /// Returns the concatenation of this `HList` and `suffix`.
+    fn append(self, suffix: S) -> Self::Output;
+
+    /// Splits a concatenated `HList` into a copy of its prefix and a reference to its suffix.  Each
+    /// element of the prefix is cloned, while the suffix is borrowed in place.
+    fn split(hlist: &Self::Output) -> (Self, &S);
+}
+
+impl<S: HList> HListAppend<S> for HNil {
+    type Output = S;
+
+    fn append(self, suffix: S) -> S {
+        suffix
+    }
+
+    fn split(hlist: &S) -> (HNil, &S) {
+        (HNil, hlist)
+    }
+}
+
+impl<H, T, S> HListAppend<S> for HCons<H, T>
+where
+    H: Clone,
+    T: HListAppend<S>,
+    S: HList,
+{
+    type Output = HCons<H, T::Output>;
+
+    fn append(self, suffix: S) -> Self::Output {
+        HCons(self.0, self.1.append(suffix))
+    }
+
+    fn split(hlist: &Self::Output) -> (Self, &S) {
+        let (tail, suffix) = T::split(hlist.tail());
+        (HCons(hlist.head().clone(), tail), suffix)
+    }
+}
+
+/// Codec that encodes/decodes the `HList` `P` followed by the `HList` `S`; the value is the
+/// concatenation of both lists.
+///
+/// This allows large formats to be defined in reusable sections (e.g. a common header followed by
+/// a message-specific body) that are then stitched together.
+///
+/// Since `prefix_codec` encodes an owned `P`, every encode clones each element of the prefix (see
+/// `HListAppend::split`), so the types in `P` must implement `Clone`.  The suffix is encoded in
+/// place.  Large values (such as a `Vec` or `String` payload) are best kept in the suffix, or held
+/// in a type that is cheap to clone (such as `ByteVector` or `Rc`), to avoid copying them on every
+/// encode.
+#[inline(always)]
+pub fn hlist_concat_codec<P, S, PC, SC>(
+    prefix_codec: PC,
+    suffix_codec: SC,
+) -> impl Codec<Value = P::Output>
+where
+    P: HListAppend<S>,
+    S: HList,
+    PC: Codec<Value = P>,
+    SC: Codec<Value = S>,
+{
+    HListConcatCodec {
+        prefix_codec,
+        suffix_codec,
+    }
+}
+
+struct HListConcatCodec<PC, SC> {
+    prefix_codec: PC,
+    suffix_codec: SC,
+}
+
+impl<P, S, PC, SC> Codec for HListConcatCodec<PC, SC>
+where
+    P: HListAppend<S>,
+    S: HList,
+    PC: Codec<Value = P>,
+    SC: Codec<Value = S>,
+{
+    type Value = P::Output;
+
+    fn size_bound(&self) -> SizeBound {
+        self.prefix_codec.size_bound() + self.suffix_codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        // Flatten both lists into a single list of fields
+        let prefix = self.prefix_codec.describe();
+        let suffix = self.suffix_codec.describe();
+        CodecDescription::new("hlist", self.size_bound())
+            .with_children(prefix.children)
+            .with_children(suffix.children)
+    }
+
+    fn encode(&self, value: &P::Output) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &P::Output, out: &mut Vec<u8>) -> Result<(), Error> {
+        let (prefix, suffix) = P::split(value);
+        self.prefix_codec.encode_into(&prefix, out)?;
+        self.suffix_codec.encode_into(suffix, out)
+    }
+
+    fn encode_to(&self, value: &P::Output, out: &mut dyn Write) -> Result<(), Error> {
+        let (prefix, suffix) = P::split(value);
+        self.prefix_codec.encode_to(&prefix, out)?;
+        self.suffix_codec.encode_to(suffix, out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<P::Output> {
+        forcomp!({
+            decoded_prefix <- self.prefix_codec.decode(bv);
+            decoded_suffix <- self.suffix_codec
+                .decode(&decoded_prefix.remainder)
+                .map_err(|e| e.offset_by(bv.length() - decoded_prefix.remainder.length()));
+        } yield {
+            DecoderResult { value: decoded_prefix.value.append(decoded_suffix.value), remainder: decoded_suffix.remainder }
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(P::Output, usize), Error> {
+        let (prefix, prefix_size) = self.prefix_codec.decode_slice(input)?;
+        let (suffix, suffix_size) = self
+            .suffix_codec
+            .decode_slice(&input[prefix_size..])
+            .map_err(|e| e.offset_by(prefix_size))?;
+        Ok((prefix.append(suffix), prefix_size + suffix_size))
+    }
+}
+
+/// Codec that first performs encoding/decoding of the `HList` `P`, using the resulting values to
+/// produce a codec for the `HList` `S`; the value is the concatenation of both lists.
+///
+/// This is a generalization of `hlist_flat_prepend_codec` that allows the remaining part of an
+/// `HList` codec to depend on any number of earlier values.
+///
+/// As with `hlist_concat_codec`, every encode clones each element of the prefix, so the types in
+/// `P` must implement `Clone`, and large values are best kept in the suffix.
+#[inline(always)]
+pub fn hlist_flat_concat_codec<P, S, PC, SC, F>(
+    prefix_codec: PC,
+    suffix_codec_fn: F,
+) -> impl Codec<Value = P::Output>
+where
+    P: HListAppend<S>,
+    S: HList,
+    PC: Codec<Value = P>,
+    SC: Codec<Value = S>,
+    F: Fn(&P) -> SC,
+{
+    HListFlatConcatCodec {
+        prefix_codec,
+        suffix_codec_fn,
+        _marker: PhantomData::<(P, S)>,
+    }
+}
+
+struct HListFlatConcatCodec<PC, F, M> {
+    prefix_codec: PC,
+    suffix_codec_fn: F,
+    _marker: PhantomData<M>,
+}
+
+impl<P, S, PC, SC, F> Codec for HListFlatConcatCodec<PC, F, (P, S)>
+where
+    P: HListAppend<S>,
+    S: HList,
+    PC: Codec<Value = P>,
+    SC: Codec<Value = S>,
+    F: Fn(&P) -> SC,
+{
+    type Value = P::Output;
+
+    fn size_bound(&self) -> SizeBound {
+        self.prefix_codec.size_bound() + SizeBound::unknown()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        // The suffix codec depends on the decoded prefix, so only the prefix can be described
+        let prefix = self.prefix_codec.describe();
+        CodecDescription::new("hlist_flat_concat", self.size_bound()).with_children(prefix.children)
+    }
+
+    fn encode(&self, value: &P::Output) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &P::Output, out: &mut Vec<u8>) -> Result<(), Error> {
+        let (prefix, suffix) = P::split(value);
+        self.prefix_codec.encode_into(&prefix, out)?;
+        (self.suffix_codec_fn)(&prefix).encode_into(suffix, out)
+    }
+
+    fn encode_to(&self, value: &P::Output, out: &mut dyn Write) -> Result<(), Error> {
+        let (prefix, suffix) = P::split(value);
+        self.prefix_codec.encode_to(&prefix, out)?;
+        (self.suffix_codec_fn)(&prefix).encode_to(suffix, out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<P::Output> {
+        forcomp!({
+            decoded_prefix <- self.prefix_codec.decode(bv);
+            decoded_suffix <- cursor::with_position(decoded_prefix.remainder.length(), || (self.suffix_codec_fn)(&decoded_prefix.value))
+                .decode(&decoded_prefix.remainder)
+                .map_err(|e| e.offset_by(bv.length() - decoded_prefix.remainder.length()));
+        } yield {
+            DecoderResult { value: decoded_prefix.value.append(decoded_suffix.value), remainder: decoded_suffix.remainder }
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(P::Output, usize), Error> {
+        let (prefix, prefix_size) = self.prefix_codec.decode_slice(input)?;
+        let suffix_codec = cursor::with_position(input.len() - prefix_size, || {
+            (self.suffix_codec_fn)(&prefix)
+        });
+        let (suffix, suffix_size) = suffix_codec
+            .decode_slice(&input[prefix_size..])
+            .map_err(|e| e.offset_by(prefix_size))?;
+        Ok((prefix.append(suffix), prefix_size + suffix_size))
+    }
+}
+
+/// Conversions between `HList`s of up to 8 elements and tuples of the same element types.
+pub trait HListTuple: HList {
+    /// The equivalent tuple type.
+    type Tuple;
+
+    /// Converts this `HList` into the equivalent tuple.
+    fn to_tuple(self) -> Self::Tuple;
+
+    /// Converts the given tuple into the equivalent `HList`.
+    fn from_tuple(tuple: Self::Tuple) -> Self;
+}
+
+// Provides implementations of the `HListTuple` trait for `HList`s of the given element types.
+macro_rules! hlist_tuple_impl {
+    { @type } => {
+        HNil
+    };
+    { @type $head:ident $(, $tail:ident)* } => {
+        HCons<$head, hlist_tuple_impl!(@type $($tail),*)>
+    };
+    { $($type:ident $name:ident),+ } => {
+        impl<$($type),+> HListTuple for hlist_tuple_impl!(@type $($type),+) {
+            type Tuple = ($($type,)+);
+
+            fn to_tuple(self) -> Self::Tuple {
+                let crate::hlist_pat!($($name),+) = self;
+                ($($name,)+)
+            }
+
+            fn from_tuple(tuple: Self::Tuple) -> Self {
+                let ($($name,)+) = tuple;
+                hlist!($($name),+)
+            }
+        }
+    };
+}
+
+impl HListTuple for HNil {
+    type Tuple = ();
+
+    fn to_tuple(self) {}
+
+    fn from_tuple(_tuple: ()) -> HNil {
+        HNil
+    }
+}
+
+hlist_tuple_impl! { A a }
+hlist_tuple_impl! { A a, B b }
+hlist_tuple_impl! { A a, B b, C c }
+hlist_tuple_impl! { A a, B b, C c, D d }
+hlist_tuple_impl! { A a, B b, C c, D d, E e }
+hlist_tuple_impl! { A a, B b, C c, D d, E e, F f }
+hlist_tuple_impl! { A a, B b, C c, D d, E e, F f, G g }
+hlist_tuple_impl! { A a, B b, C c, D d, E e, F f, G g, H h }
+
+/// Codec that converts a codec for an `HList` (such as one produced by `hcodec!`) into a codec for
+/// the equivalent tuple, which is easier to destructure than a chain of `HCons` values.
+///
+/// Encoding requires a copy of each tuple element, so the element types must implement `Clone`.
+#[inline(always)]
+pub fn tuple_codec<H, HC>(hlist_codec: HC) -> impl Codec<Value = H::Tuple>
+where
+    H: HListTuple,
+    H::Tuple: Clone,
+    HC: Codec<Value = H>,
+{
+    HListTupleCodec { hlist_codec }
+}
+
+struct HListTupleCodec<HC> {
+    hlist_codec: HC,
+}
+
+impl<H, HC> Codec for HListTupleCodec<HC>
+where
+    H: HListTuple,
+    H::Tuple: Clone,
+    HC: Codec<Value = H>,
+{
+    type Value = H::Tuple;
+
+    fn size_bound(&self) -> SizeBound {
+        self.hlist_codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        let fields = self.hlist_codec.describe();
+        CodecDescription::new("tuple", self.size_bound()).with_children(fields.children)
+    }
+
+    fn encode(&self, value: &H::Tuple) -> EncodeResult {
+        self.hlist_codec.encode(&H::from_tuple(value.clone()))
+    }
+
+    fn encode_into(&self, value: &H::Tuple, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.hlist_codec
+            .encode_into(&H::from_tuple(value.clone()), out)
+    }
+
+    fn encode_to(&self, value: &H::Tuple, out: &mut dyn Write) -> Result<(), Error> {
+        self.hlist_codec
+            .encode_to(&H::from_tuple(value.clone()), out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<H::Tuple> {
+        self.hlist_codec.decode(bv).map(|decoded| DecoderResult {
+            value: decoded.value.to_tuple(),
+            remainder: decoded.remainder,
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(H::Tuple, usize), Error> {
+        self.hlist_codec
+            .decode_slice(input)
+            .map(|(hlist, size)| (hlist.to_tuple(), size))
+    }
+}
+
+//
+// Struct codec
+//
+
+/// Codec for structs that support `HList` conversions.
+#[inline(always)]
+pub fn struct_codec<H, S, HC>(hlist_codec: HC) -> impl Codec<Value = S>
+where
+    H: HList,
+    S: FromHList<H> + ToHList<H>,
+    HC: Codec<Value = H>,
+{
+    RecordStructCodec {
+        name: None,
+        hlist_codec,
+        _marker: PhantomData::<S>,
+    }
+}
+
+/// Codec for structs that support `HList` conversions, where errors are prefixed with the given
+/// name of the struct (as used by `struct_codec!`).
+#[inline(always)]
+pub fn named_struct_codec<H, S, HC>(name: &'static str, hlist_codec: HC) -> impl Codec<Value = S>
+where
+    H: HList,
+    S: FromHList<H> + ToHList<H>,
+    HC: Codec<Value = H>,
+{
+    RecordStructCodec {
+        name: Some(name),
+        hlist_codec,
+        _marker: PhantomData::<S>,
+    }
+}
+
+struct RecordStructCodec<S, HC> {
+    name: Option<&'static str>,
+    hlist_codec: HC,
+    _marker: PhantomData<S>,
+}
+
+impl<S, HC> RecordStructCodec<S, HC> {
+    /// Prefixes the given error with the name of the struct, if any.
+    fn push_name(&self, error: Error) -> Error {
+        match self.name {
+            Some(name) => error.push_context(name),
+            None => error,
+        }
+    }
+
+    /// Calls `f` to decode the fields of the struct within the context of its name, if any.
+    fn within_name<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        match self.name {
+            Some(name) => validation::within_context(name, f),
+            None => f(),
+        }
+    }
+}
+
+impl<H, S, HC> Codec for RecordStructCodec<S, HC>
+where
+    H: HList,
+    S: FromHList<H> + ToHList<H>,
+    HC: Codec<Value = H>,
+{
+    type Value = S;
+
+    fn size_bound(&self) -> SizeBound {
+        self.hlist_codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        let fields = self.hlist_codec.describe();
+        CodecDescription::new(type_name::<S>(), self.size_bound()).with_children(fields.children)
+    }
+
+    fn encode(&self, value: &S) -> EncodeResult {
+        self.hlist_codec
+            .encode(&value.to_hlist())
+            .map_err(|e| self.push_name(e))
+    }
+
+    fn encode_into(&self, value: &S, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.hlist_codec
+            .encode_into(&value.to_hlist(), out)
+            .map_err(|e| self.push_name(e))
+    }
+
+    fn encode_to(&self, value: &S, out: &mut dyn Write) -> Result<(), Error> {
+        self.hlist_codec
+            .encode_to(&value.to_hlist(), out)
+            .map_err(|e| self.push_name(e))
+    }
+
+    fn section_count(&self) -> usize {
+        self.hlist_codec.section_count()
+    }
+
+    fn encode_sections_into(
+        &self,
+        value: &S,
+        range: Range<usize>,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        // The fields are converted to an HList once per range rather than once per section
+        if range.is_empty() {
+            return Ok(());
+        }
+        self.hlist_codec
+            .encode_sections_into(&value.to_hlist(), range, out)
+            .map_err(|e| self.push_name(e))
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<S> {
+        self.within_name(|| self.hlist_codec.decode(bv))
+            .map(|decoded| DecoderResult {
+                value: S::from_hlist(decoded.value),
+                remainder: decoded.remainder,
+            })
+            .map_err(|e| self.push_name(e))
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(S, usize), Error> {
+        self.within_name(|| self.hlist_codec.decode_slice(input))
+            .map(|(hlist, size)| (S::from_hlist(hlist), size))
+            .map_err(|e| self.push_name(e))
+    }
+}
+
+//
+// Struct codec builder
+//
+
+/// Builder for struct codecs that does not require `HList` support, as an alternative to
+/// `struct_codec!` that trades some verbosity (and a small amount of runtime overhead) for plainer
+/// types and error messages.
+///
+/// Each field is given a name, a codec, and a function that borrows the field from the struct for
+/// encoding.  Fields are encoded and decoded in the order they were added, and any errors are
+/// prefixed with the name of the field.  The decoded field values are then passed to the function
+/// given to `build`, which retrieves them by name:
+///
+/// ```
+/// use rcodec::byte_vector;
+/// use rcodec::codec::*;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Header {
+///     version: u8,
+///     name: String,
+/// }
+///
+/// let codec = StructCodecBuilder::new()
+///     .field("version", uint8, |h: &Header| &h.version)
+///     .field("name", utf8_8(), |h: &Header| &h.name)
+///     .build(|fields| {
+///         Ok(Header {
+///             version: fields.take("version")?,
+///             name: fields.take("name")?,
+///         })
+///     });
+///
+/// let header = Header { version: 1, name: "hi".to_string() };
+/// assert_eq!(codec.encode(&header).unwrap(), byte_vector!(1, 2, b'h', b'i'));
+/// ```
+pub struct StructCodecBuilder<S> {
+    fields: Vec<Box<dyn StructField<S> + Send + Sync>>,
+}
+
+impl<S> StructCodecBuilder<S> {
+    /// Returns a builder with no fields.
+    pub fn new() -> StructCodecBuilder<S> {
+        StructCodecBuilder { fields: Vec::new() }
+    }
+
+    /// Adds a field with the given name, which is encoded/decoded using `codec`; `getter` returns
+    /// a reference to the field's value within the struct.
+    pub fn field<C, G>(mut self, name: &'static str, codec: C, getter: G) -> StructCodecBuilder<S>
+    where
+        C: Codec + Send + Sync + 'static,
+        C::Value: 'static,
+        G: Fn(&S) -> &C::Value + Send + Sync + 'static,
+    {
+        self.fields.push(Box::new(NamedField {
+            name,
+            codec: with_context(name, codec),
+            getter,
+        }));
+        self
+    }
+
+    /// Returns a codec for the struct, which is constructed from its decoded fields by `f`.
+    pub fn build<F>(self, f: F) -> impl Codec<Value = S>
+    where
+        F: Fn(&mut DecodedFields) -> Result<S, Error>,
+    {
+        BuiltStructCodec {
+            fields: self.fields,
+            f,
+        }
+    }
+}
+
+impl<S> Default for StructCodecBuilder<S> {
+    fn default() -> StructCodecBuilder<S> {
+        StructCodecBuilder::new()
+    }
+}
+
+/// The field values decoded by a codec produced by `StructCodecBuilder`.
+pub struct DecodedFields {
+    values: Vec<(&'static str, Option<Box<dyn Any>>)>,
+}
+
+impl DecodedFields {
+    /// Removes and returns the decoded value of the named field.  Returns an error if there is no
+    /// such field, if its value has already been taken, or if it is not of type `T`.
+    pub fn take<T: 'static>(&mut self, name: &str) -> Result<T, Error> {
+        let slot = self
+            .values
+            .iter_mut()
+            .find(|(field_name, _)| *field_name == name)
+            .ok_or_else(|| Error::new(lazy_description(|| format!("No field named {:?}", name))))?;
+        let value = slot.1.take().ok_or_else(|| {
+            Error::new(lazy_description(|| {
+                format!("Value of field {:?} was already taken", name)
+            }))
+        })?;
+        match value.downcast::<T>() {
+            Ok(value) => Ok(*value),
+            Err(value) => {
+                slot.1 = Some(value);
+                Err(Error::new(lazy_description(|| {
+                    format!("Field {:?} is not of type {}", name, type_name::<T>())
+                })))
+            }
+        }
+    }
+}
+
+/// Type-erased field of a struct codec produced by `StructCodecBuilder`.
+trait StructField<S> {
+    fn name(&self) -> &'static str;
+    fn size_bound(&self) -> SizeBound;
+    fn describe(&self) -> CodecDescription;
+    fn encode_into(&self, value: &S, out: &mut Vec<u8>) -> Result<(), Error>;
+    fn encode_to(&self, value: &S, out: &mut dyn Write) -> Result<(), Error>;
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<Box<dyn Any>>;
+    fn decode_slice(&self, input: &[u8]) -> Result<(Box<dyn Any>, usize), Error>;
+}
+
+struct NamedField<C, G> {
+    name: &'static str,
+    codec: C,
+    getter: G,
+}
+
+impl<S, C, G> StructField<S> for NamedField<C, G>
+where
+    C: Codec,
+    C::Value: 'static,
+    G: Fn(&S) -> &C::Value,
+{
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn size_bound(&self) -> SizeBound {
+        self.codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        self.codec.describe()
+    }
+
+    fn encode_into(&self, value: &S, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.codec.encode_into((self.getter)(value), out)
+    }
+
+    fn encode_to(&self, value: &S, out: &mut dyn Write) -> Result<(), Error> {
+        self.codec.encode_to((self.getter)(value), out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<Box<dyn Any>> {
+        self.codec.decode(bv).map(|decoded| DecoderResult {
+            value: Box::new(decoded.value) as Box<dyn Any>,
+            remainder: decoded.remainder,
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(Box<dyn Any>, usize), Error> {
+        self.codec
+            .decode_slice(input)
+            .map(|(value, size)| (Box::new(value) as Box<dyn Any>, size))
+    }
+}
+
+struct BuiltStructCodec<S, F> {
+    fields: Vec<Box<dyn StructField<S> + Send + Sync>>,
+    f: F,
+}
+
+impl<S, F> Codec for BuiltStructCodec<S, F>
+where
+    F: Fn(&mut DecodedFields) -> Result<S, Error>,
+{
+    type Value = S;
+
+    fn size_bound(&self) -> SizeBound {
+        self.fields
+            .iter()
+            .fold(SizeBound::exact(0), |bound, field| {
+                bound + field.size_bound()
+            })
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new(type_name::<S>(), self.size_bound())
+            .with_children(self.fields.iter().map(|field| field.describe()))
+    }
+
+    fn encode(&self, value: &S) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &S, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.fields
+            .iter()
+            .try_for_each(|field| field.encode_into(value, out))
+    }
+
+    fn encode_to(&self, value: &S, out: &mut dyn Write) -> Result<(), Error> {
+        self.fields
+            .iter()
+            .try_for_each(|field| field.encode_to(value, out))
+    }
+
+    fn section_count(&self) -> usize {
+        self.fields.len()
+    }
+
+    fn encode_sections_into(
+        &self,
+        value: &S,
+        range: Range<usize>,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        self.fields
+            .iter()
+            .take(range.end)
+            .skip(range.start)
+            .try_for_each(|field| field.encode_into(value, out))
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<S> {
+        let mut values = Vec::with_capacity(self.fields.len());
+        let mut remainder = bv.clone();
+        for field in &self.fields {
+            let decoded = field
+                .decode(&remainder)
+                .map_err(|e| e.offset_by(bv.length() - remainder.length()))?;
+            values.push((field.name(), Some(decoded.value)));
+            remainder = decoded.remainder;
+        }
+        (self.f)(&mut DecodedFields { values }).map(|value| DecoderResult { value, remainder })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(S, usize), Error> {
+        let mut values = Vec::with_capacity(self.fields.len());
+        let mut consumed = 0;
+        for field in &self.fields {
+            let (value, size) = field
+                .decode_slice(&input[consumed..])
+                .map_err(|e| e.offset_by(consumed))?;
+            values.push((field.name(), Some(value)));
+            consumed += size;
+        }
+        (self.f)(&mut DecodedFields { values }).map(|value| (value, consumed))
+    }
+}
+
+//
+// Default codecs
+//
+
+#[cfg(feature = "derive")]
+pub use rcodec_derive::DefaultCodec;
+
+/// Types that have a natural binary encoding, which can be used without spelling out a codec.
+///
+/// Implementations are provided for the following types:
+///   - Integral and floating point types, encoded in big-endian order.
+///   - `String` and `ByteVector`, encoded with a big-endian 32-bit length prefix (in bytes).
+///   - `Vec<T>`, encoded with a big-endian 32-bit count prefix followed by each element.
+///   - Tuples and `HList`s, encoded by concatenating the encodings of their elements.
+///
+/// Structs that support `HList` conversions can derive an implementation (using
+/// `#[derive(DefaultCodec)]`, available with the `derive` feature) that encodes each field in
+/// order.
+pub trait DefaultCodec: Sized {
+    /// Returns the default codec for this type.
+    fn codec() -> impl Codec<Value = Self>;
+}
+
+macro_rules! default_codec_impl {
+    { $type:ty, $codec:expr } => {
+        impl DefaultCodec for $type {
+            fn codec() -> impl Codec<Value = Self> {
+                $codec
+            }
+        }
+    };
+}
+
+default_codec_impl!(u8, uint8);
+default_codec_impl!(i8, int8);
+default_codec_impl!(u16, uint16);
+default_codec_impl!(i16, int16);
+default_codec_impl!(u32, uint32);
+default_codec_impl!(i32, int32);
+default_codec_impl!(u64, uint64);
+default_codec_impl!(i64, int64);
+default_codec_impl!(f32, float32);
+default_codec_impl!(f64, float64);
+default_codec_impl!(String, utf8_32());
+default_codec_impl!(ByteVector, variable_size_bytes(uint32, identity_bytes()));
+
+impl<T: DefaultCodec> DefaultCodec for Vec<T> {
+    fn codec() -> impl Codec<Value = Self> {
+        vector_of_n(uint32, T::codec())
+    }
+}
+
+impl DefaultCodec for HNil {
+    fn codec() -> impl Codec<Value = Self> {
+        hnil_codec()
+    }
+}
+
+impl<H: DefaultCodec, T: DefaultCodec + HList> DefaultCodec for HCons<H, T> {
+    fn codec() -> impl Codec<Value = Self> {
+        hlist_prepend_codec(H::codec(), T::codec())
+    }
+}
+
+macro_rules! default_codec_tuple_impl {
+    { $($T:ident),+ } => {
+        impl<$($T: DefaultCodec),+> DefaultCodec for ($($T,)+) {
+            fn codec() -> impl Codec<Value = Self> {
+                ($($T::codec(),)+)
+            }
+        }
+    };
+}
+
+default_codec_tuple_impl! { A, B }
+default_codec_tuple_impl! { A, B, C }
+default_codec_tuple_impl! { A, B, C, D }
+default_codec_tuple_impl! { A, B, C, D, E }
+default_codec_tuple_impl! { A, B, C, D, E, F }
+default_codec_tuple_impl! { A, B, C, D, E, F, G }
+default_codec_tuple_impl! { A, B, C, D, E, F, G, H }
+
+/// Returns the default codec for values of type `T`; see `DefaultCodec`.
+#[inline(always)]
+pub fn default_codec<T: DefaultCodec>() -> impl Codec<Value = T> {
+    T::codec()
+}
+
+//
+// Context-injection codec
+//
+
+//
+// TODO: Can we have a single impl that works on AsCodecRef<T>?  Attempts so far like this:
+//   impl<T: 'static, TC: AsCodecRef<T>> core::ops::BitOr<TC> for &'static str {
+//
+// TODO: The orphan checking rules were changed shortly before Rust 1.0.0 such that we can't implement
+// the BitOr trait with a Codec on the RHS.  Compilation fails with:
+//
+// src/codec.rs:475:1: 481:2 error: type parameter `T` must be used as the type parameter for some local type
+//                           (e.g. `MyStruct<T>`); only traits defined in the current crate can be implemented
+//                           for a type parameter [E0210]
+// src/codec.rs:475 impl<T: 'static> core::ops::BitOr<RcCodec<T>> for &'static str {
+// src/codec.rs:476     type Output = RcCodec<T>;
+// src/codec.rs:477
+// src/codec.rs:478     fn bitor(self, rhs: RcCodec<T>) -> RcCodec<T> {
+// src/codec.rs:479         rcbox!(ContextCodec { codec: rhs.as_codec_ref(), context: self })
+// src/codec.rs:480     }
+//
+// See related discussion here:
+//   https://github.com/rust-lang/rust/issues/20749
+//
+// As a workaround, we handle context injection directly inside the hcodec! macro, sigh.  Outside of
+// the macro, the `Ctx` wrapper type (defined below) can be used on the LHS instead, since it is a
+// local type and therefore isn't subject to the orphan rules.
+//
+// impl<T: 'static> core::ops::BitOr<&'static Codec<T>> for &'static str {
+//     type Output = RcCodec<T>;
+
+//     fn bitor(self, rhs: &'static Codec<T>) -> RcCodec<T> {
+//         rcbox!(ContextCodec { codec: rhs.as_codec_ref(), context: self })
+//     }
+// }
+// impl<T: 'static> core::ops::BitOr<RcCodec<T>> for &'static str {
+//     type Output = RcCodec<T>;
+
+//     fn bitor(self, rhs: RcCodec<T>) -> RcCodec<T> {
+//         rcbox!(ContextCodec { codec: rhs.as_codec_ref(), context: self })
+//     }
+// }
+/// Codec that injects additional context (e.g. in error messages) into the given codec.
+#[inline(always)]
+pub fn with_context<T, C>(context: &'static str, codec: C) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    ContextCodec { codec, context }
+}
+
+/// Wrapper for a context string that allows context to be injected into a codec using the `|`
+/// operator, e.g. `Ctx("magic") | uint8`.  See `with_context` for details.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ctx(pub &'static str);
+
+impl<C: Codec> core::ops::BitOr<C> for Ctx {
+    type Output = ContextCodec<C>;
+
+    fn bitor(self, rhs: C) -> ContextCodec<C> {
+        ContextCodec {
+            codec: rhs,
+            context: self.0,
+        }
+    }
+}
+
+/// Codec that injects additional context into the given codec, as produced by `Ctx(...) | codec`.
+pub struct ContextCodec<C> {
+    codec: C,
+    context: &'static str,
+}
+
+impl<T, C> Codec for ContextCodec<C>
+where
+    C: Codec<Value = T>,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        self.codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new(self.context, self.size_bound()).with_child(self.codec.describe())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        self.codec
+            .encode(value)
+            .map_err(|e| e.push_context(self.context))
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.codec
+            .encode_into(value, out)
+            .map_err(|e| e.push_context(self.context))
+    }
+
+    fn encode_to(&self, value: &T, out: &mut dyn Write) -> Result<(), Error> {
+        self.codec
+            .encode_to(value, out)
+            .map_err(|e| e.push_context(self.context))
+    }
+
+    fn section_count(&self) -> usize {
+        self.codec.section_count()
+    }
+
+    fn encode_sections_into(
+        &self,
+        value: &T,
+        range: Range<usize>,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        self.codec
+            .encode_sections_into(value, range, out)
+            .map_err(|e| e.push_context(self.context))
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        validation::within_context(self.context, || self.codec.decode(bv))
+            .map_err(|e| cursor::locate(e.push_context(self.context), bv.length()))
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        validation::within_context(self.context, || self.codec.decode_slice(input))
+            .map_err(|e| cursor::locate(e.push_context(self.context), input.len()))
+    }
+}
+
+//
+// Instrumented codec
+//
+
+/// Codec that reports the outcome, size, and duration of each operation performed by the given
+/// codec to `sink`, identified by `name`.
+///
+/// Nested operations are reported separately for each instrumented codec, so the time recorded for
+/// an outer codec includes the time spent in any instrumented codecs it contains.
+#[inline(always)]
+pub fn instrumented<T, C, S>(name: &'static str, codec: C, sink: S) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+    S: MetricsSink,
+{
+    InstrumentedCodec { codec, name, sink }
+}
+
+struct InstrumentedCodec<C, S> {
+    codec: C,
+    name: &'static str,
+    sink: S,
+}
+
+impl<C, S: MetricsSink> InstrumentedCodec<C, S> {
+    /// Performs the given operation, recording its outcome along with the number of bytes it
+    /// produced or consumed.
+    fn measure<R>(
+        &self,
+        operation: Operation,
+        f: impl FnOnce() -> Result<R, Error>,
+        bytes: impl FnOnce(&R) -> usize,
+    ) -> Result<R, Error> {
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+        self.sink.record(&Event {
+            name: self.name,
+            operation,
+            bytes: result.as_ref().map_or(0, bytes),
+            elapsed,
+            success: result.is_ok(),
+        });
+        result
+    }
+}
+
+impl<T, C, S> Codec for InstrumentedCodec<C, S>
+where
+    C: Codec<Value = T>,
+    S: MetricsSink,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        self.codec.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        self.codec.describe()
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        self.measure(
+            Operation::Encode,
+            || self.codec.encode(value),
+            |encoded| encoded.length(),
+        )
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        let start = out.len();
+        self.measure(
+            Operation::Encode,
+            || {
+                self.codec
+                    .encode_into(value, out)
+                    .map(|_| out.len() - start)
+            },
+            |size| *size,
+        )
+        .map(|_| ())
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        self.measure(
+            Operation::Decode,
+            || self.codec.decode(bv),
+            |decoded| bv.length() - decoded.remainder.length(),
+        )
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        self.measure(
+            Operation::Decode,
+            || self.codec.decode_slice(input),
+            |&(_, size)| size,
+        )
+    }
+}
+
+//
+// Drop-left codec
+//
+
+/// Codec that encodes/decodes the unit value followed by the right-hand value, discarding
+/// the unit value when decoding.
+#[inline(always)]
+pub fn drop_left<T, LC, RC>(lhs: LC, rhs: RC) -> impl Codec<Value = T>
+where
+    LC: Codec<Value = ()>,
+    RC: Codec<Value = T>,
+{
+    DropLeftCodec { lhs, rhs }
+}
+
+struct DropLeftCodec<LC, RC> {
+    lhs: LC,
+    rhs: RC,
+}
+
+impl<T, LC, RC> Codec for DropLeftCodec<LC, RC>
+where
+    LC: Codec<Value = ()>,
+    RC: Codec<Value = T>,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        self.lhs.size_bound() + self.rhs.size_bound()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        CodecDescription::new("drop_left", self.size_bound())
+            .with_child(self.lhs.describe())
+            .with_child(self.rhs.describe())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        encode_via_buffer(self, value)
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        self.lhs.encode_into(&(), out)?;
+        self.rhs.encode_into(value, out)
+    }
+
+    fn section_count(&self) -> usize {
+        1 + self.rhs.section_count()
+    }
+
+    fn encode_sections_into(
+        &self,
+        value: &T,
+        range: Range<usize>,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        if range.contains(&0) {
+            self.lhs.encode_into(&(), out)?;
+        }
+        self.rhs
+            .encode_sections_into(value, tail_sections(range), out)
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        let decoded = self.lhs.decode(bv)?;
+        self.rhs
+            .decode(&decoded.remainder)
+            .map_err(|e| e.offset_by(bv.length() - decoded.remainder.length()))
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        let (_, lhs_size) = self.lhs.decode_slice(input)?;
+        self.rhs
+            .decode_slice(&input[lhs_size..])
+            .map(|(value, rhs_size)| (value, lhs_size + rhs_size))
+            .map_err(|e| e.offset_by(lhs_size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::assert_round_trip;
+    // TODO: Restore benchmark support
+    // use test::Bencher;
+    use std::fmt::Debug;
+
+    #[test]
+    fn forcomp_macro_should_work() {
+        let v1 = forcomp!({
+            part1 <- Some(1u8);
+        } yield { part1 });
+        assert!(v1.is_some());
+
+        let v2 = forcomp!({
+            part1 <- Some(1u8);
+            part2 <- None::<u8>;
+        } yield { part1 + part2 });
+        assert!(v2.is_none());
+
+        let v3 = forcomp!({
+            part1 <- Some(1u8);
+            part2 <- Some(2u8);
+        } yield { part1 + part2 });
+        assert_eq!(v3.unwrap(), 3u8);
+    }
+
+    //
+    // CodecExt
+    //
+
+    #[test]
+    fn decode_all_should_decode_values_from_slices() {
+        assert_eq!(uint16.decode_all(&[1, 2]).unwrap(), 0x0102);
+        assert_eq!(
+            uint16.decode_all(&[1, 2, 3]).unwrap_err().message(),
+            "Expected end of input but 1 bytes remain"
+        );
+        assert!(uint16.decode_all(&[1]).is_err());
+    }
+
+    /// Asserts that decoding each of the given inputs from a slice produces the same result as
+    /// decoding it from a byte vector.
+    fn assert_decode_slice_matches<C>(codec: C, inputs: &[&[u8]])
+    where
+        C: Codec,
+        C::Value: PartialEq + Debug,
+    {
+        for input in inputs {
+            let from_slice = codec.decode_slice(input);
+            let from_bv = codec
+                .decode(&byte_vector::from_slice_copy(input))
+                .map(|decoded| (decoded.value, input.len() - decoded.remainder.length()));
+            match (from_slice, from_bv) {
+                (Ok(lhs), Ok(rhs)) => assert_eq!(lhs, rhs, "input {:?}", input),
+                (Err(_), Err(_)) => (),
+                (lhs, rhs) => panic!("input {:?}: {:?} != {:?}", input, lhs, rhs),
+            }
+        }
+    }
+
+    #[test]
+    fn built_in_codecs_should_decode_slices_like_byte_vectors() {
+        let registry: HashMap<u8, _> = vec![(1, uint16)].into_iter().collect();
+        let xor = crate::cipher::Xor::new(&[0x5a]).unwrap();
+        assert_decode_slice_matches(identity_bytes(), &[&[], &[1, 2, 3]]);
+        assert_decode_slice_matches(
+            tlv_preserving_unknown(uint8, uint8, registry),
+            &[&[1, 2, 0, 7, 9], &[2, 1, 7], &[1, 1, 7], &[1, 3, 0]],
+        );
+        assert_decode_slice_matches(char_utf8(), &[b"a", "é!".as_bytes(), &[0xc3], &[0xff]]);
+        assert_decode_slice_matches(ascii_char(), &[b"ab", &[0x80], &[]]);
+        assert_decode_slice_matches(sized_list(4, uint16), &[&[0, 1, 0, 2, 9], &[0, 1, 0], &[]]);
+        assert_decode_slice_matches(
+            map_of_n(uint8, uint8, uint8),
+            &[&[2, 1, 10, 2, 20, 9], &[2, 1, 10, 1, 20], &[1, 1]],
+        );
+        assert_decode_slice_matches(
+            fallback(constant_bytes(&[1]).xmap(|_| 1u8, |_| ()), uint8),
+            &[&[1], &[2], &[]],
+        );
+        assert_decode_slice_matches((lookahead(uint16), uint8), &[&[1, 2], &[1]]);
+        assert_decode_slice_matches(
+            (recover(constant_bytes(&[1])), uint8),
+            &[&[1, 2], &[2], &[1]],
+        );
+        assert_decode_slice_matches(
+            crc32(uint8),
+            &[
+                &crc32(uint8).encode_to_vec(&7).unwrap(),
+                &[7, 0, 0, 0, 0],
+                &[7],
+            ],
+        );
+        let chunk_codec = chunk_with_crc(fourcc(), uint8, crc::CRC_32);
+        let mut chunk_input = chunk_codec.encode_to_vec(&(*b"abcd", 7)).unwrap();
+        chunk_input.push(9);
+        assert_decode_slice_matches(
+            chunk_codec,
+            &[
+                &chunk_input,
+                &[0, 0, 0, 1, b'a', b'b', b'c', b'd', 7, 0, 0, 0, 0],
+                &[0, 0, 0, 2, b'a'],
+            ],
+        );
+        assert_decode_slice_matches(encrypted(list(uint8), xor), &[&[0x5b, 0x58], &[]]);
+    }
+
+    #[test]
+    fn list_decode_slice_should_take_linear_time() {
+        // Decoding each element must not copy the rest of the input
+        let input = "é".repeat(200_000);
+        let start = Instant::now();
+        let chars = list(char_utf8()).decode_all(input.as_bytes()).unwrap();
+        assert_eq!(chars.len(), 200_000);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn encode_to_vec_should_encode_values_to_vecs() {
+        assert_eq!(uint16.encode_to_vec(&0x0102).unwrap(), vec![1, 2]);
+        assert_eq!(
+            (uint8, utf8_8())
+                .encode_to_vec(&(7, "a".to_string()))
+                .unwrap(),
+            vec![7, 1, b'a']
+        );
+    }
+
+    #[test]
+    fn codec_ext_combinators_should_compose_fluently() {
+        let codec = uint16
+            .validated(|v: &u16| *v > 0, "must be positive")
+            .xmap(Port, |port: &Port| port.0)
+            .framed_by(uint8)
+            .with_context("port")
+            .boxed();
+        assert_round_trip(codec, &Port(80), &Some(byte_vector!(2, 0, 80)));
+
+        assert_round_trip(uint8.fixed_size(2), &7, &Some(byte_vector!(7, 0)));
+
+        assert_eq!(
+            uint16
+                .validated(|v: &u16| *v > 0, "must be positive")
+                .with_context("port")
+                .decode(&byte_vector!(0, 0))
+                .unwrap_err()
+                .message(),
+            "port: Validation failed: must be positive"
+        );
+    }
+
+    #[test]
+    fn shared_codecs_should_be_usable_from_multiple_owners() {
+        let codec = uint16.with_context("port").shared();
+        let registry: HashMap<&str, Rc<dyn Codec<Value = u16>>> =
+            vec![("port", codec.clone())].into_iter().collect();
+        assert_round_trip(codec, &80, &Some(byte_vector!(0, 80)));
+        assert_round_trip(registry["port"].clone(), &443, &Some(byte_vector!(1, 0xbb)));
+
+        let arc_codec = Arc::new(utf8());
+        assert_round_trip(
+            arc_codec,
+            &"hi".to_string(),
+            &Some(byte_vector!(b'h', b'i')),
+        );
+    }
+
+    //
+    // Size bounds
+    //
+
+    #[test]
+    fn size_bounds_should_combine() {
+        let a = SizeBound::exact(2);
+        let b = SizeBound::bounded(1, 4);
+        assert_eq!(a + b, SizeBound::bounded(3, 6));
+        assert_eq!(a.or(b), SizeBound::bounded(1, 4));
+        assert_eq!(b.times(3), SizeBound::bounded(3, 12));
+        assert_eq!(a + SizeBound::unknown(), SizeBound::at_least(2));
+        assert_eq!(a.or(SizeBound::at_least(5)), SizeBound::at_least(2));
+    }
+
+    #[test]
+    fn codecs_should_report_size_bounds() {
+        assert_eq!(uint8.size_bound(), SizeBound::exact(1));
+        assert_eq!(uint24_l.size_bound(), SizeBound::exact(3));
+        assert_eq!(float64.size_bound(), SizeBound::exact(8));
+        assert_eq!(vuint::<u32>().size_bound(), SizeBound::bounded(1, 5));
+        assert_eq!(utf8().size_bound(), SizeBound::unknown());
+        assert_eq!(utf8_16().size_bound(), SizeBound::at_least(2));
+        assert_eq!((uint8, uint16, bytes(5)).size_bound(), SizeBound::exact(8));
+        assert_eq!(repeat(3, uint32).size_bound(), SizeBound::exact(12));
+        assert_eq!(fallback(uint16, uint16_l).size_bound(), SizeBound::exact(2));
+        assert_eq!(
+            recover(constant(byte_vector!(1, 2))).size_bound(),
+            SizeBound::bounded(0, 2)
+        );
+        assert_eq!(aligned(uint8, 4).size_bound(), SizeBound::exact(4));
+        assert_eq!(crc32(uint16).size_bound(), SizeBound::exact(6));
+        assert_eq!(
+            hcodec!({ uint8 } :: { uint32 }).size_bound(),
+            SizeBound::exact(5)
+        );
+        assert_eq!(
+            hcodec!({ ignore(2) } >> { uint8 }).size_bound(),
+            SizeBound::exact(3)
+        );
+    }
+
+    //
+    // Integral codecs
+    //
+
+    #[test]
+    fn a_u8_value_should_round_trip() {
+        assert_round_trip(uint8, &7, &Some(byte_vector!(7)));
+    }
+
+    #[test]
+    fn an_i8_value_should_round_trip() {
+        assert_round_trip(int8, &7, &Some(byte_vector!(7)));
+        assert_round_trip(int8, &-2, &Some(byte_vector!(0xfe)));
+        assert_round_trip(int8, &-16, &Some(byte_vector!(0xf0)));
+        assert_round_trip(int8, &-128, &Some(byte_vector!(0x80)));
+    }
+
+    #[test]
+    fn a_u16_value_should_round_trip() {
+        assert_round_trip(uint16, &0x1234, &Some(byte_vector!(0x12, 0x34)));
+        assert_round_trip(uint16_l, &0x1234, &Some(byte_vector!(0x34, 0x12)));
+    }
+
+    #[test]
+    fn an_i16_value_should_round_trip() {
+        assert_round_trip(int16, &0x1234, &Some(byte_vector!(0x12, 0x34)));
+        assert_round_trip(int16, &-2, &Some(byte_vector!(0xff, 0xfe)));
+        assert_round_trip(int16_l, &0x1234, &Some(byte_vector!(0x34, 0x12)));
+        assert_round_trip(int16_l, &-2, &Some(byte_vector!(0xfe, 0xff)));
+    }
+
+    #[test]
+    fn a_u32_value_should_round_trip() {
+        assert_round_trip(
+            uint32,
+            &0x1234_5678,
+            &Some(byte_vector!(0x12, 0x34, 0x56, 0x78)),
+        );
+        assert_round_trip(
+            uint32_l,
+            &0x1234_5678,
+            &Some(byte_vector!(0x78, 0x56, 0x34, 0x12)),
+        );
+    }
+
+    #[test]
+    fn an_i32_value_should_round_trip() {
+        assert_round_trip(
+            int32,
+            &0x1234_5678,
+            &Some(byte_vector!(0x12, 0x34, 0x56, 0x78)),
+        );
+        assert_round_trip(int32, &-2, &Some(byte_vector!(0xff, 0xff, 0xff, 0xfe)));
+        assert_round_trip(
+            int32_l,
+            &0x1234_5678,
+            &Some(byte_vector!(0x78, 0x56, 0x34, 0x12)),
+        );
+        assert_round_trip(int32_l, &-2, &Some(byte_vector!(0xfe, 0xff, 0xff, 0xff)));
+    }
+
+    #[test]
+    fn a_u64_value_should_round_trip() {
+        assert_round_trip(
+            uint64,
+            &0x1234_5678_90ab_cdef,
+            &Some(byte_vector!(0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef)),
+        );
+        assert_round_trip(
+            uint64_l,
+            &0x1234_5678_90ab_cdef,
+            &Some(byte_vector!(0xef, 0xcd, 0xab, 0x90, 0x78, 0x56, 0x34, 0x12)),
+        );
+    }
+
+    #[test]
+    fn an_i64_value_should_round_trip() {
+        assert_round_trip(
+            int64,
+            &0x1234_5678_90ab_cdef,
+            &Some(byte_vector!(0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef)),
+        );
+        assert_round_trip(
+            int64,
+            &-2,
+            &Some(byte_vector!(0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe)),
+        );
+        assert_round_trip(
+            int64_l,
+            &0x1234_5678_90ab_cdef,
+            &Some(byte_vector!(0xef, 0xcd, 0xab, 0x90, 0x78, 0x56, 0x34, 0x12)),
+        );
+        assert_round_trip(
+            int64_l,
+            &-2,
+            &Some(byte_vector!(0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff)),
+        );
+    }
+
+    #[test]
+    fn a_u24_value_should_round_trip() {
+        assert_round_trip(uint24, &0x12_3456, &Some(byte_vector!(0x12, 0x34, 0x56)));
+        assert_round_trip(uint24_l, &0x12_3456, &Some(byte_vector!(0x56, 0x34, 0x12)));
+    }
+
+    #[test]
+    fn u40_u48_and_u56_values_should_round_trip() {
+        assert_round_trip(
+            uint40,
+            &0x12_3456_7890,
+            &Some(byte_vector!(0x12, 0x34, 0x56, 0x78, 0x90)),
+        );
+        assert_round_trip(
+            uint48_l,
+            &0x1234_5678_90ab,
+            &Some(byte_vector!(0xab, 0x90, 0x78, 0x56, 0x34, 0x12)),
+        );
+        assert_round_trip(
+            uint56,
+            &0x12_3456_7890_abcd,
+            &Some(byte_vector!(0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xcd)),
+        );
+    }
+
+    #[test]
+    fn encoding_a_u24_value_should_fail_when_value_is_too_large() {
+        assert_eq!(
+            uint24.encode(&0x100_0000).unwrap_err().message(),
+            "Value 16777216 is greater than maximum value (16777215) of 24-bit integer"
+        );
+    }
+
+    // macro_rules! bench_int_codec {
+    //     { $codec:ident, $enc:ident, $dec:ident } => {
+    //         #[bench]
+    //         fn $enc(b: &mut Bencher) {
+    //             b.iter(|| $codec.encode(&7));
+    //         }
+
+    //         #[bench]
+    //         fn $dec(b: &mut Bencher) {
+    //             let bv = byte_vector!(0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07);
+    //             b.iter(|| $codec.decode(&bv));
+    //         }
+    //     };
+    // }
+
+    // bench_int_codec!(uint8,    bench_enc_uint8,    bench_dec_uint8);
+    // bench_int_codec!(int8,     bench_enc_int8,     bench_dec_int8);
+
+    // bench_int_codec!(uint16,   bench_enc_uint16,   bench_dec_uint16);
+    // bench_int_codec!(int16,    bench_enc_int16,    bench_dec_int16);
+    // bench_int_codec!(uint16_l, bench_enc_uint16_l, bench_dec_uint16_l);
+    // bench_int_codec!(int16_l,  bench_enc_int16_l,  bench_dec_int16_l);
+
+    // bench_int_codec!(uint32,   bench_enc_uint32,   bench_dec_uint32);
+    // bench_int_codec!(int32,    bench_enc_int32,    bench_dec_int32);
+    // bench_int_codec!(uint32_l, bench_enc_uint32_l, bench_dec_uint32_l);
+    // bench_int_codec!(int32_l,  bench_enc_int32_l,  bench_dec_int32_l);
+
+    // bench_int_codec!(uint64,   bench_enc_uint64,   bench_dec_uint64);
+    // bench_int_codec!(int64,    bench_enc_int64,    bench_dec_int64);
+    // bench_int_codec!(uint64_l, bench_enc_uint64_l, bench_dec_uint64_l);
+    // bench_int_codec!(int64_l,  bench_enc_int64_l,  bench_dec_int64_l);
+
+    //
+    // Floating point codecs
+    //
+
+    #[test]
+    fn an_f32_value_should_round_trip() {
+        let value = 1.5f32;
+        let encoded = float32.encode(&value).unwrap();
+        assert_eq!(encoded, byte_vector!(0x3f, 0xc0, 0x00, 0x00));
+        assert_eq!(float32.decode(&encoded).unwrap().value, value);
+
+        let encoded_l = float32_l.encode(&value).unwrap();
+        assert_eq!(encoded_l, byte_vector!(0x00, 0x00, 0xc0, 0x3f));
+        assert_eq!(float32_l.decode(&encoded_l).unwrap().value, value);
+    }
+
+    #[test]
+    fn an_f64_value_should_round_trip() {
+        let value = -2.25f64;
+        let encoded = float64.encode(&value).unwrap();
+        assert_eq!(
+            encoded,
+            byte_vector!(0xc0, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00)
+        );
+        assert_eq!(float64.decode(&encoded).unwrap().value, value);
+
+        let encoded_l = float64_l.encode(&value).unwrap();
+        assert_eq!(
+            encoded_l,
+            byte_vector!(0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0xc0)
+        );
+        assert_eq!(float64_l.decode(&encoded_l).unwrap().value, value);
+    }
+
+    //
+    // Endianness-dependent codecs
+    //
+
+    #[test]
+    fn ordered_codecs_should_round_trip() {
+        assert_round_trip(
+            uint32_with(ByteOrdering::BigEndian),
+            &0x01020304,
+            &Some(byte_vector!(1, 2, 3, 4)),
+        );
+        assert_round_trip(
+            uint32_with(ByteOrdering::LittleEndian),
+            &0x01020304,
+            &Some(byte_vector!(4, 3, 2, 1)),
+        );
+        assert_round_trip(
+            int16_with(ByteOrdering::LittleEndian),
+            &-2,
+            &Some(byte_vector!(0xfe, 0xff)),
+        );
+    }
+
+    #[test]
+    fn an_endianness_dependent_codec_should_select_codec_at_runtime() {
+        // TIFF-style byte order mark followed by a value in that byte order
+        let codec = hcodec!(
+            { uint16 } >>= |bom| {
+                let ordering = if *bom == 0x4949 {
+                    ByteOrdering::LittleEndian
+                } else {
+                    ByteOrdering::BigEndian
+                };
+                hcodec!({ endianness_dependent(uint16, uint16_l, ordering) })
+            }
+        );
+        assert_round_trip(
+            codec,
+            &hlist!(0x4949, 42),
+            &Some(byte_vector!(0x49, 0x49, 42, 0)),
+        );
+    }
+
+    //
+    // Variable-length integer codecs
+    //
+
+    #[test]
+    fn an_unsigned_varint_value_should_round_trip() {
+        assert_round_trip(vuint::<u32>(), &0, &Some(byte_vector!(0x00)));
+        assert_round_trip(vuint::<u32>(), &127, &Some(byte_vector!(0x7f)));
+        assert_round_trip(vuint::<u32>(), &300, &Some(byte_vector!(0xac, 0x02)));
+        assert_round_trip(
+            vuint::<u64>(),
+            &u64::MAX,
+            &Some(byte_vector!(
+                0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01
+            )),
+        );
+    }
+
+    #[test]
+    fn a_varint_codec_should_fail_to_encode_values_wider_than_64_bits() {
+        assert_eq!(
+            vuint::<u128>().encode(&u128::MAX).unwrap_err().message(),
+            format!(
+                "Value {} exceeds maximum supported value ({})",
+                u128::MAX,
+                u64::MAX
+            )
+        );
+        assert!(vint::<i128>().encode(&i128::MIN).is_err());
+        assert_eq!(
+            vuint::<u128>()
+                .encode(&u128::from(u64::MAX))
+                .unwrap()
+                .length(),
+            10
+        );
+    }
+
+    #[test]
+    fn a_signed_varint_value_should_round_trip() {
+        assert_round_trip(vint::<i32>(), &0, &Some(byte_vector!(0x00)));
+        assert_round_trip(vint::<i32>(), &-1, &Some(byte_vector!(0x01)));
+        assert_round_trip(vint::<i32>(), &1, &Some(byte_vector!(0x02)));
+        assert_round_trip(vint::<i32>(), &-64, &Some(byte_vector!(0x7f)));
+        assert_round_trip(
+            vint::<i32>(),
+            &i32::MIN,
+            &Some(byte_vector!(0xff, 0xff, 0xff, 0xff, 0x0f)),
+        );
+        assert_round_trip(vint::<i64>(), &i64::MAX, &None);
+        assert_round_trip(vint::<i64>(), &i64::MIN, &None);
+    }
+
+    #[test]
+    fn decoding_a_varint_should_leave_the_remainder() {
+        let decoded = vuint::<u16>()
+            .decode(&byte_vector!(0xac, 0x02, 0x07))
+            .unwrap();
+        assert_eq!(decoded.value, 300);
+        assert_eq!(decoded.remainder, byte_vector!(0x07));
+    }
+
+    #[test]
+    fn decoding_a_varint_should_fail_when_value_is_too_large() {
+        assert_eq!(
+            vuint::<u8>()
+                .decode(&byte_vector!(0xac, 0x02))
+                .unwrap_err()
+                .message(),
+            "Varint value 300 exceeds maximum value (255) of target type"
+        );
+        assert_eq!(
+            vuint::<u8>()
+                .decode(&byte_vector!(0xff, 0xff, 0x01))
+                .unwrap_err()
+                .message(),
+            "Varint exceeds maximum length of 2 bytes for target type"
+        );
+        assert!(vuint::<u32>().decode(&byte_vector!(0xff, 0xff)).is_err());
+    }
+
+    //
+    // Ignore codec
+    //
+
+    #[test]
+    fn an_ignore_codec_should_round_trip() {
+        assert_round_trip(ignore(4), &(), &Some(byte_vector!(0, 0, 0, 0)));
+    }
+
+    #[test]
+    fn a_strict_ignore_codec_should_round_trip() {
+        assert_round_trip(ignore_strict(2), &(), &Some(byte_vector!(0, 0)));
+        assert_round_trip(
+            ignore_strict_with_fill(3, 0xff),
+            &(),
+            &Some(byte_vector!(0xff, 0xff, 0xff)),
+        );
+    }
+
+    #[test]
+    fn decoding_with_strict_ignore_codec_should_fail_on_unexpected_bytes() {
+        let input = byte_vector!(0, 7, 1);
+        let expected = "Expected ignored byte at offset 1 to be 0x00 but got 0x07";
+        assert_eq!(
+            ignore_strict(2).decode(&input).unwrap_err().message(),
+            expected
+        );
+        assert_eq!(
+            ignore_strict(2)
+                .decode_slice(&[0, 7, 1])
+                .unwrap_err()
+                .message(),
+            expected
+        );
+        assert_eq!(ignore(2).decode(&input).unwrap().remainder, byte_vector!(1));
+    }
+
+    #[test]
+    fn decoding_with_ignore_codec_should_succeed_if_the_input_vector_is_long_enough() {
+        let input = byte_vector!(7, 1, 2, 3, 4);
+        let codec = ignore(3);
+        match codec.decode(&input) {
+            Ok(result) => {
+                let expected_remainder = byte_vector!(3, 4);
+                assert_eq!(expected_remainder, result.remainder);
+            }
+            Err(e) => panic!("Decoding failed: {}", e.message()),
+        }
+    }
+
+    #[test]
+    fn decoding_with_ignore_codec_should_fail_if_the_input_vector_is_smaller_than_the_ignored_length(
+    ) {
+        let input = byte_vector!(1u8);
+        let codec = ignore(3);
+        assert_eq!(
+            codec.decode(&input).unwrap_err().message(),
+            "Requested length of 3 bytes exceeds vector length of 1"
+        );
+    }
+
+    //
+    // Constant codec
+    //
+
+    #[test]
+    fn a_constant_codec_should_round_trip() {
+        let input = byte_vector!(1, 2, 3, 4);
+        assert_round_trip(constant(&input), &(), &Some(input));
+    }
+
+    #[test]
+    fn decoding_with_constant_codec_should_fail_if_the_input_vector_does_not_match_the_constant_vector(
+    ) {
+        let input = byte_vector!(1, 2, 3, 4);
+        let codec = constant(byte_vector!(6, 6, 6));
+        assert_eq!(
+            codec.decode(&input).unwrap_err().message(),
+            "Expected constant 060606 but got 010203"
+        );
+        assert_eq!(
+            codec.decode_slice(&[1, 2, 3, 4]).unwrap_err().message(),
+            "Expected constant 060606 but got 010203"
+        );
+
+        // Input spanning appended storage is compared in place
+        let input = byte_vector::append(&byte_vector!(6, 6), &byte_vector!(6, 7));
+        assert_eq!(codec.decode(&input).unwrap().remainder, byte_vector!(7));
+        let input = byte_vector::append(&byte_vector!(6, 6), &byte_vector!(7, 6));
+        assert_eq!(
+            codec.decode(&input).unwrap_err().message(),
+            "Expected constant 060606 but got 060607"
+        );
+    }
+
+    #[test]
+    fn decoding_with_constant_codec_should_fail_if_the_input_vector_is_smaller_than_the_constant_vector(
+    ) {
+        let input = byte_vector!(1);
+        let codec = constant(byte_vector!(6, 6, 6));
+        assert_eq!(
+            codec.decode(&input).unwrap_err().message(),
+            "Requested view offset of 0 and length 3 bytes exceeds vector length of 1"
+        );
+    }
+
+    #[test]
+    fn a_constant_codec_should_accept_values_convertible_to_bytes() {
+        let expected = Some(byte_vector!(b'P', b'K', 3, 4));
+        assert_round_trip(constant(b"PK\x03\x04"), &(), &expected);
+        assert_round_trip(constant([b'P', b'K', 3, 4]), &(), &expected);
+        assert_round_trip(constant(vec![b'P', b'K', 3, 4]), &(), &expected);
+        assert_round_trip(constant("PK\u{3}\u{4}"), &(), &expected);
+        assert_round_trip(constant_bytes(&[b'P', b'K', 3, 4]), &(), &expected);
+    }
+
+    #[test]
+    fn try_constant_should_fail_if_the_bytes_cannot_be_read() {
+        let codec = try_constant(byte_vector!(1, 2)).unwrap();
+        assert_round_trip(codec, &(), &Some(byte_vector!(1, 2)));
+
+        // Reading from a directory fails on Unix platforms
+        if cfg!(unix) {
+            let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+            assert!(try_constant(byte_vector::file(&dir).unwrap()).is_err());
+        }
+    }
+
+    //
+    // Identity codec
+    //
+
+    #[test]
+    fn a_unit_codec_should_encode_the_default_value_and_discard_decoded_values() {
+        let codec = || unit(uint16, 0x1234);
+        assert_eq!(codec().encode(&()).unwrap(), byte_vector!(0x12, 0x34));
+        let decoded = codec().decode(&byte_vector!(0xab, 0xcd, 0xef)).unwrap();
+        assert_eq!(decoded.value, ());
+        assert_eq!(decoded.remainder, byte_vector!(0xef));
+
+        let hcodec = hcodec!({ unit(uint8, 7) } >> { uint8 });
+        assert_round_trip(hcodec, &hlist!(9u8), &Some(byte_vector!(7, 9)));
+    }
+
+    #[test]
+    fn a_fail_codec_should_always_fail() {
+        let codec = || fail::<u8>("Unsupported version");
+        assert_eq!(
+            codec().encode(&1).unwrap_err().message(),
+            "Unsupported version"
+        );
+        assert_eq!(
+            codec().decode(&byte_vector!(1)).unwrap_err().message(),
+            "Unsupported version"
+        );
+    }
+
+    #[test]
+    fn a_provide_codec_should_round_trip() {
+        let decoded = provide(42u8).decode(&byte_vector!(1, 2)).unwrap();
+        assert_eq!(decoded.value, 42);
+        assert_eq!(decoded.remainder, byte_vector!(1, 2));
+        assert_round_trip(provide(42u8), &42, &Some(byte_vector::empty()));
+    }
+
+    #[test]
+    fn an_identity_codec_should_round_trip() {
+        let input = byte_vector!(1, 2, 3, 4);
+        assert_round_trip(identity_bytes(), &input, &Some(input.clone()));
+    }
+
+    //
+    // Bytes codec
+    //
+
+    #[test]
+    fn a_byte_vector_codec_should_round_trip() {
+        let input = byte_vector!(7, 1, 2, 3, 4);
+        assert_round_trip(bytes(5), &input, &Some(input.clone()));
+    }
+
+    #[test]
+    fn decoding_with_byte_vector_codec_should_return_remainder_that_had_len_bytes_dropped() {
+        let input = byte_vector!(7, 1, 2, 3, 4);
+        let codec = bytes(3);
+        match codec.decode(&input) {
+            Ok(result) => {
+                assert_eq!(result.value, byte_vector!(7, 1, 2));
+                assert_eq!(result.remainder, byte_vector!(3, 4));
+            }
+            Err(e) => panic!("Decoding failed: {}", e.message()),
+        }
+    }
+
+    #[test]
+    fn decoding_with_byte_vector_codec_should_fail_when_vector_has_less_space_than_given_length() {
+        let input = byte_vector!(1, 2);
+        let codec = bytes(4);
+        assert_eq!(
+            codec.decode(&input).unwrap_err().message(),
+            "Requested view offset of 0 and length 4 bytes exceeds vector length of 2"
+        );
+    }
+
+    //
+    // Fixed size bytes codec
+    //
+
+    #[test]
+    fn a_fixed_size_bytes_codec_should_round_trip() {
+        let codec = fixed_size_bytes(1, uint8);
+        assert_round_trip(codec, &7u8, &Some(byte_vector!(7)));
+    }
+
+    #[test]
+    fn encoding_with_fixed_size_codec_should_pad_with_zeros_when_value_is_smaller_than_given_length(
+    ) {
+        let codec = fixed_size_bytes(3, uint8);
+        assert_round_trip(codec, &7u8, &Some(byte_vector!(7, 0, 0)));
+    }
+
+    #[test]
+    fn encoding_with_fixed_size_codec_should_fail_when_value_needs_more_space_than_given_length() {
+        let codec = fixed_size_bytes(1, constant(byte_vector!(6, 6, 6)));
+        assert_eq!(
+            codec.encode(&()).unwrap_err().message(),
+            "Encoding requires 3 bytes but codec is limited to fixed length of 1"
+        );
+    }
+
+    #[test]
+    fn decoding_with_fixed_size_codec_should_return_remainder_that_had_len_bytes_dropped() {
+        let input = byte_vector!(7, 1, 2, 3, 4);
+        let codec = fixed_size_bytes(3, uint8);
+        match codec.decode(&input) {
+            Ok(result) => {
+                assert_eq!(result.value, 7u8);
+                assert_eq!(result.remainder, byte_vector!(3, 4));
+            }
+            Err(e) => panic!("Decoding failed: {}", e.message()),
+        }
+    }
+
+    #[test]
+    fn decoding_with_fixed_size_codec_should_fail_when_vector_has_less_space_than_given_length() {
+        let input = byte_vector!(1, 2);
+        let codec = fixed_size_bytes(4, bytes(6));
+        assert_eq!(
+            codec.decode(&input).unwrap_err().message(),
+            "Requested view offset of 0 and length 4 bytes exceeds vector length of 2"
+        );
+    }
+
+    //
+    // Variable size bytes codec
+    //
+
+    #[test]
+    fn a_fixed_size_codec_with_left_padding_should_round_trip() {
+        let options = FixedSizeOptions {
+            pad_direction: PadDirection::Left,
+            pad_byte: b' ',
+            ..FixedSizeOptions::default()
+        };
+        assert_round_trip(
+            fixed_size_bytes_with(5, fixed_size_bytes(3, utf8()), options),
+            &"abc".to_string(),
+            &Some(byte_vector!(b' ', b' ', b'a', b'b', b'c')),
+        );
+    }
+
+    #[test]
+    fn a_fixed_size_codec_with_left_padding_should_round_trip_values_starting_with_the_pad_byte() {
+        let options = FixedSizeOptions {
+            pad_direction: PadDirection::Left,
+            ..FixedSizeOptions::default()
+        };
+        assert_round_trip(
+            fixed_size_bytes_with(2, uint8, options),
+            &0,
+            &Some(byte_vector!(0, 0)),
+        );
+        assert_round_trip(
+            fixed_size_bytes_with(4, uint16, options),
+            &0x0001,
+            &Some(byte_vector!(0, 0, 0, 1)),
+        );
+        assert_eq!(
+            fixed_size_bytes_with(4, uint16, options)
+                .decode_slice(&[0, 0, 0, 0, 9])
+                .unwrap(),
+            (0, 4)
+        );
+    }
+
+    #[test]
+    fn a_fixed_size_codec_with_left_padding_should_round_trip_codecs_without_an_exact_size() {
+        let options = FixedSizeOptions {
+            pad_direction: PadDirection::Left,
+            ..FixedSizeOptions::default()
+        };
+        let codec = || fixed_size_bytes_with(4, utf8(), options);
+        assert_round_trip(
+            codec(),
+            &"ab".to_string(),
+            &Some(byte_vector!(0, 0, 97, 98)),
+        );
+        assert_round_trip(codec(), &"".to_string(), &Some(byte_vector!(0, 0, 0, 0)));
+        assert_eq!(
+            codec().decode_slice(&[0, 0, 97, 98, 9]).unwrap(),
+            ("ab".to_string(), 4)
+        );
+        assert_eq!(
+            codec().encode(&"\0a".to_string()).unwrap_err().message(),
+            "Left padded value within fixed length of 4 begins with the pad byte, but codec does not have an exact size"
+        );
+        assert!(fixed_size_bytes_with(1, uint16, options)
+            .decode(&byte_vector!(0))
+            .is_err());
+    }
+
+    #[test]
+    fn decoding_with_strict_fixed_size_codec_should_fail_on_non_padding_left_padding() {
+        let options = FixedSizeOptions {
+            pad_direction: PadDirection::Left,
+            strict: true,
+            ..FixedSizeOptions::default()
+        };
+        let codec = fixed_size_bytes_with(3, uint8, options);
+        let expected = "Left padding contains non-padding bytes within fixed length of 3";
+        assert_eq!(
+            codec.decode(&byte_vector!(0, 1, 7)).unwrap_err().message(),
+            expected
+        );
+        assert_eq!(
+            codec.decode_slice(&[1, 0, 7]).unwrap_err().message(),
+            expected
+        );
+        assert_eq!(codec.decode_slice(&[0, 0, 7]).unwrap(), (7, 3));
+    }
+
+    #[test]
+    fn a_fixed_size_codec_with_custom_pad_byte_should_round_trip() {
+        let options = FixedSizeOptions {
+            pad_byte: 0xff,
+            ..FixedSizeOptions::default()
+        };
+        assert_round_trip(
+            fixed_size_bytes_with(3, uint8, options),
+            &7,
+            &Some(byte_vector!(7, 0xff, 0xff)),
+        );
+    }
+
+    #[test]
+    fn decoding_with_strict_fixed_size_codec_should_fail_on_unconsumed_bytes() {
+        let options = FixedSizeOptions {
+            strict: true,
+            ..FixedSizeOptions::default()
+        };
+        let codec = || fixed_size_bytes_with(3, uint8, options);
+        assert_eq!(
+            codec()
+                .decode(&byte_vector!(7, 0, 1))
+                .unwrap_err()
+                .message(),
+            "Codec left non-padding bytes unconsumed within fixed length of 3"
+        );
+        let decoded = codec().decode(&byte_vector!(7, 0, 0, 9)).unwrap();
+        assert_eq!(decoded.value, 7);
+        assert_eq!(decoded.remainder, byte_vector!(9));
+    }
+
+    #[test]
+    fn an_aligned_codec_should_round_trip() {
+        assert_round_trip(
+            aligned(utf8_8(), 4),
+            &"hello".to_string(),
+            &Some(byte_vector!(5, b'h', b'e', b'l', b'l', b'o', 0, 0)),
+        );
+        assert_round_trip(
+            aligned(uint32, 4),
+            &0x01020304,
+            &Some(byte_vector!(1, 2, 3, 4)),
+        );
+        assert_round_trip(
+            aligned_with_fill(uint8, 2, 0xff),
+            &7,
+            &Some(byte_vector!(7, 0xff)),
+        );
+    }
+
+    #[test]
+    fn decoding_with_aligned_codec_should_skip_padding() {
+        let decoded = aligned(uint8, 4)
+            .decode(&byte_vector!(1, 0, 0, 0, 2))
+            .unwrap();
+        assert_eq!(decoded.value, 1);
+        assert_eq!(decoded.remainder, byte_vector!(2));
+        assert!(aligned(uint8, 4).decode(&byte_vector!(1, 0)).is_err());
+    }
+
+    #[test]
+    fn a_variable_size_bytes_codec_should_round_trip() {
+        let input = byte_vector!(7, 1, 2, 3, 4);
+        let codec = variable_size_bytes(uint16, identity_bytes());
+        assert_round_trip(codec, &input, &Some(byte_vector!(0, 5, 7, 1, 2, 3, 4)));
+    }
+
+    #[test]
+    fn decoding_with_variable_size_codec_should_skip_the_length_prefix_in_the_remainder() {
+        let codec = variable_size_bytes(uint16, identity_bytes());
+        let decoded = codec.decode(&byte_vector!(0, 2, 7, 1, 2, 3)).unwrap();
+        assert_eq!(decoded.value, byte_vector!(7, 1));
+        assert_eq!(decoded.remainder, byte_vector!(2, 3));
+    }
+
+    #[test]
+    fn a_codec_following_a_variable_size_codec_should_see_only_the_remaining_bytes() {
+        let input = byte_vector!(0, 2, 7, 1, 9, 0xff);
+        let codec = (variable_size_bytes(uint16, identity_bytes()), uint8);
+        let decoded = codec.decode(&input).unwrap();
+        assert_eq!(decoded.value, (byte_vector!(7, 1), 9));
+        assert_eq!(decoded.remainder, byte_vector!(0xff));
+        assert_eq!(
+            codec.decode_slice(&input.to_vec().unwrap()).unwrap(),
+            ((byte_vector!(7, 1), 9), 5)
+        );
+
+        let bounded = (variable_size_bytes_bounded(uint16, utf8(), 4), uint8);
+        let decoded = bounded.decode(&byte_vector!(0, 1, b'a', 9)).unwrap();
+        assert_eq!(decoded.value, ("a".to_string(), 9));
+        assert_eq!(decoded.remainder, byte_vector!());
+    }
+
+    #[test]
+    fn a_variable_size_bytes_codec_with_length_adjustment_should_round_trip() {
+        // Length includes the length field itself
+        let adjustment = LengthAdjustment {
+            includes_prefix: true,
+            ..LengthAdjustment::default()
+        };
+        assert_round_trip(
+            variable_size_bytes_with(uint16, identity_bytes(), adjustment),
+            &byte_vector!(7, 1, 2),
+            &Some(byte_vector!(0, 5, 7, 1, 2)),
+        );
+
+        // Length is expressed in 4-byte words, and also includes the length field
+        let adjustment = LengthAdjustment {
+            includes_prefix: true,
+            unit: 4,
+            bias: 0,
+        };
+        assert_round_trip(
+            variable_size_bytes_with(uint32, identity_bytes(), adjustment),
+            &byte_vector!(1, 2, 3, 4),
+            &Some(byte_vector!(0, 0, 0, 2, 1, 2, 3, 4)),
+        );
+
+        // Length has a constant bias
+        let adjustment = LengthAdjustment {
+            bias: -1,
+            ..LengthAdjustment::default()
+        };
+        assert_round_trip(
+            variable_size_bytes_with(uint8, identity_bytes(), adjustment),
+            &byte_vector!(9, 9),
+            &Some(byte_vector!(1, 9, 9)),
+        );
+    }
+
+    #[test]
+    fn a_variable_size_bytes_codec_with_length_adjustment_should_reject_invalid_lengths() {
+        let adjustment = LengthAdjustment {
+            unit: 4,
+            ..LengthAdjustment::default()
+        };
+        let codec = || variable_size_bytes_with(uint8, identity_bytes(), adjustment);
+        assert_eq!(
+            codec()
+                .encode(&byte_vector!(1, 2, 3))
+                .unwrap_err()
+                .message(),
+            "Length of encoded value (3 bytes) is not a multiple of the length unit (4 bytes)"
+        );
+
+        let adjustment = LengthAdjustment {
+            includes_prefix: true,
+            ..LengthAdjustment::default()
+        };
+        let codec = variable_size_bytes_with(uint16, identity_bytes(), adjustment);
+        assert_eq!(
+            codec.decode(&byte_vector!(0, 1)).unwrap_err().message(),
+            "Decoded length (1) does not describe a valid size"
+        );
+    }
+
+    #[test]
+    fn encoding_with_variable_size_codec_should_fail_when_length_of_encoded_value_is_too_large() {
+        let input = byte_vector::fill(0x7, 256);
+        let codec = variable_size_bytes(uint8, identity_bytes());
+        assert_eq!(codec.encode(&input).unwrap_err().message(), "Length of encoded value (256 bytes) is greater than maximum value (255) of length type");
+    }
+
+    #[test]
+    fn a_bounded_variable_size_bytes_codec_should_enforce_maximum_length() {
+        let codec = || variable_size_bytes_bounded(uint32, identity_bytes(), 4);
+        assert_round_trip(
+            codec(),
+            &byte_vector!(1, 2, 3, 4),
+            &Some(byte_vector!(0, 0, 0, 4, 1, 2, 3, 4)),
+        );
+        assert_eq!(
+            codec()
+                .encode(&byte_vector!(1, 2, 3, 4, 5))
+                .unwrap_err()
+                .message(),
+            "Length of value (5 bytes) exceeds maximum length of 4 bytes"
+        );
+
+        // A huge length prefix should be rejected without regard to the remaining input
+        let input = byte_vector!(0xff, 0xff, 0xff, 0xff, 1, 2);
+        let expected = "Length of value (4294967295 bytes) exceeds maximum length of 4 bytes";
+        assert_eq!(codec().decode(&input).unwrap_err().message(), expected);
+        assert_eq!(
+            codec()
+                .decode_slice(&[0xff, 0xff, 0xff, 0xff, 1, 2])
+                .unwrap_err()
+                .message(),
+            expected
+        );
+    }
+
+    #[test]
+    fn a_length_field_codec_should_compute_the_length_of_the_body() {
+        let codec = || with_length_field(uint16, hcodec!({ uint8 } :: { utf8() }));
+        assert_round_trip(
+            codec(),
+            &hlist!(7u8, "hi".to_string()),
+            &Some(byte_vector!(0, 3, 7, b'h', b'i')),
+        );
+
+        // The body must consume all of the bytes given by the length field
+        let codec = with_length_field(uint8, hcodec!({ uint8 } :: { uint8 }));
+        assert_eq!(
+            codec
+                .decode(&byte_vector!(3, 1, 2, 3, 4))
+                .unwrap_err()
+                .message(),
+            "Expected end of input but 1 bytes remain"
+        );
+    }
+
+    // #[bench]
+    // fn bench_enc_variable_size_bytes(b: &mut Bencher) {
+    //     let input = byte_vector!(7, 1, 2, 3, 4);
+    //     let codec = variable_size_bytes(uint16, identity_bytes());
+    //     b.iter(|| codec.encode(&input));
+    // }
+
+    // #[bench]
+    // fn bench_dec_variable_size_bytes(b: &mut Bencher) {
+    //     let input = byte_vector!(0, 5, 7, 1, 2, 3, 4);
+    //     let codec = variable_size_bytes(uint16, identity_bytes());
+    //     b.iter(|| codec.decode(&input));
+    // }
+
+    //
+    // Delimited codecs
+    //
+
+    #[test]
+    fn a_delimited_codec_should_round_trip() {
+        assert_round_trip(
+            delimited(utf8(), b"\r\n"),
+            &"GET / HTTP/1.1".to_string(),
+            &Some(byte_vector::from_slice_copy(b"GET / HTTP/1.1\r\n")),
+        );
+        assert_round_trip(
+            list(delimited(utf8(), [0])),
+            &vec!["ab".to_string(), "".to_string(), "c".to_string()],
+            &Some(byte_vector!(b'a', b'b', 0, 0, b'c', 0)),
+        );
+    }
+
+    #[test]
+    fn decoding_with_delimited_codec_should_leave_bytes_after_delimiter() {
+        let codec = || delimited(utf8(), b"\n");
+        let input = byte_vector::from_slice_copy(b"one\ntwo\n");
+        let decoded = codec().decode(&input).unwrap();
+        assert_eq!(decoded.value, "one");
+        assert_eq!(decoded.remainder, byte_vector::from_slice_copy(b"two\n"));
+        assert_eq!(
+            codec().decode_slice(b"one\ntwo\n").unwrap(),
+            ("one".to_string(), 4)
+        );
+    }
+
+    #[test]
+    fn a_delimited_codec_should_fail_on_missing_or_embedded_delimiters() {
+        let codec = || delimited(utf8(), b"\n");
+        assert_eq!(
+            codec()
+                .decode(&byte_vector::from_slice_copy(b"abc"))
+                .unwrap_err()
+                .message(),
+            "Delimiter 0a not found"
+        );
+        assert_eq!(
+            codec().encode(&"a\nb".to_string()).unwrap_err().message(),
+            "Encoded value contains delimiter 0a"
+        );
+        assert_eq!(
+            delimited(uint8, [0])
+                .decode(&byte_vector!(1, 2, 0))
+                .unwrap_err()
+                .message(),
+            "Expected end of input but 1 bytes remain"
+        );
+    }
+
+    #[test]
+    fn a_delimited_codec_should_reject_values_whose_suffix_overlaps_the_delimiter() {
+        let codec = || delimited(utf8(), b"aa");
+        assert_eq!(
+            codec().encode(&"xa".to_string()).unwrap_err().message(),
+            "Encoded value contains delimiter 6161"
+        );
+        assert_round_trip(
+            codec(),
+            &"ax".to_string(),
+            &Some(byte_vector::from_slice_copy(b"axaa")),
+        );
+    }
+
+    #[test]
+    fn an_escaped_delimited_codec_should_round_trip() {
+        let codec = || delimited_escaped(identity_bytes(), [0x7e], 0x7d);
+        assert_round_trip(
+            codec(),
+            &byte_vector!(1, 0x7e, 2, 0x7d, 3),
+            &Some(byte_vector!(1, 0x7d, 0x7e, 2, 0x7d, 0x7d, 3, 0x7e)),
+        );
+        let codec = || delimited_escaped(identity_bytes(), b"\r\n", b'\\');
+        assert_round_trip(
+            codec(),
+            &byte_vector::from_slice_copy(b"a\r\nb\\"),
+            &Some(byte_vector::from_slice_copy(b"a\\\r\nb\\\\\r\n")),
+        );
+        assert!(codec()
+            .decode(&byte_vector::from_slice_copy(b"a\\\r\n"))
+            .is_err());
+    }
+
+    //
+    // DER codecs
+    //
+
+    #[test]
+    fn a_der_length_codec_should_round_trip() {
+        assert_round_trip(der_length(), &0, &Some(byte_vector!(0x00)));
+        assert_round_trip(der_length(), &0x7f, &Some(byte_vector!(0x7f)));
+        assert_round_trip(der_length(), &0x80, &Some(byte_vector!(0x81, 0x80)));
+        assert_round_trip(der_length(), &0x0100, &Some(byte_vector!(0x82, 0x01, 0x00)));
+        assert_round_trip(
+            der_length(),
+            &0x01_0000,
+            &Some(byte_vector!(0x83, 0x01, 0x00, 0x00)),
+        );
+    }
+
+    #[test]
+    fn decoding_with_der_length_codec_should_reject_non_der_forms() {
+        let decode_err = |bytes: &[u8]| der_length().decode_slice(bytes).unwrap_err().message();
+        assert_eq!(
+            decode_err(&[0x80]),
+            "Indefinite-length form is not allowed in DER"
+        );
+        assert_eq!(
+            decode_err(&[0x81, 0x7f]),
+            "DER length 127 is not minimally encoded"
+        );
+        assert_eq!(
+            decode_err(&[0x82, 0x00, 0x80]),
+            "DER length 128 is not minimally encoded"
+        );
+        assert_eq!(
+            decode_err(&[0x89, 1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            "DER length of 9 bytes exceeds maximum of 8 bytes"
+        );
+        assert!(der_length().decode(&byte_vector!(0x82, 0x01)).is_err());
+    }
+
+    #[test]
+    fn a_der_tlv_codec_should_round_trip() {
+        // SEQUENCE { INTEGER 5, OCTET STRING "hi" }
+        let codec = || der_tlv(0x30, (der_tlv(0x02, uint8), der_tlv(0x04, utf8())));
+        assert_round_trip(
+            codec(),
+            &(5, "hi".to_string()),
+            &Some(byte_vector!(
+                0x30, 0x07, 0x02, 0x01, 0x05, 0x04, 0x02, b'h', b'i'
+            )),
+        );
+
+        let long = "x".repeat(200);
+        let encoded = der_tlv(0x04, utf8()).encode(&long).unwrap();
+        assert_eq!(encoded.take(3).unwrap(), byte_vector!(0x04, 0x81, 200));
+    }
+
+    #[test]
+    fn decoding_with_der_tlv_codec_should_check_tag_and_length() {
+        let codec = || der_tlv(0x02, uint8);
+        assert!(codec().decode(&byte_vector!(0x04, 0x01, 0x05)).is_err());
+        assert_eq!(
+            codec()
+                .decode(&byte_vector!(0x02, 0x02, 0x05, 0x06))
+                .unwrap_err()
+                .message(),
+            "Expected end of input but 1 bytes remain"
+        );
+    }
+
+    //
+    // Protocol Buffers codecs
+    //
+
+    #[test]
+    fn a_protobuf_key_codec_should_round_trip() {
+        let key = |field_number, wire_type| ProtobufKey {
+            field_number,
+            wire_type,
+        };
+        assert_round_trip(
+            protobuf_key(),
+            &key(1, WireType::Varint),
+            &Some(byte_vector!(0x08)),
+        );
+        assert_round_trip(
+            protobuf_key(),
+            &key(2, WireType::LengthDelimited),
+            &Some(byte_vector!(0x12)),
+        );
+        assert_round_trip(
+            protobuf_key(),
+            &key(16, WireType::Fixed32),
+            &Some(byte_vector!(0x85, 0x01)),
+        );
+    }
+
+    #[test]
+    fn a_protobuf_key_codec_should_reject_invalid_keys() {
+        assert_eq!(
+            protobuf_key()
+                .decode(&byte_vector!(0x0e))
+                .unwrap_err()
+                .message(),
+            "Unknown protobuf wire type 6"
+        );
+        assert_eq!(
+            protobuf_key()
+                .decode(&byte_vector!(0x00))
+                .unwrap_err()
+                .message(),
+            "Invalid protobuf field number 0"
+        );
+        let key = ProtobufKey {
+            field_number: 1 << 29,
+            wire_type: WireType::Varint,
+        };
+        assert!(protobuf_key().encode(&key).is_err());
+    }
+
+    #[test]
+    fn protobuf_field_codecs_should_describe_messages() {
+        // message Test { int32 a = 1; string b = 2; fixed32 c = 3; }
+        let codec = || {
+            (
+                protobuf_field(1, WireType::Varint, vuint::<u32>()),
+                protobuf_field(
+                    2,
+                    WireType::LengthDelimited,
+                    protobuf_length_delimited(utf8()),
+                ),
+                protobuf_field(3, WireType::Fixed32, protobuf_fixed32()),
+            )
+        };
+        assert_round_trip(
+            codec(),
+            &(150, "testing".to_string(), 1),
+            &Some(byte_vector!(
+                0x08, 0x96, 0x01, 0x12, 0x07, b't', b'e', b's', b't', b'i', b'n', b'g', 0x1d, 0x01,
+                0x00, 0x00, 0x00
+            )),
+        );
+        assert!(codec().decode(&byte_vector!(0x10, 0x96, 0x01)).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Protobuf field number must be between 1 and 2^29 - 1")]
+    fn a_protobuf_field_codec_should_reject_invalid_field_numbers() {
+        let _ = protobuf_field(0, WireType::Varint, uint8);
+    }
+
+    //
+    // TLV codecs
+    //
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    enum Field {
+        Version(u8),
+        Name(String),
+    }
+
+    fn field_registry() -> HashMap<u8, Box<dyn Codec<Value = Field>>> {
+        let mut registry: HashMap<u8, Box<dyn Codec<Value = Field>>> = HashMap::new();
+        registry.insert(
+            1,
+            uint8
+                .xmap(Field::Version, |f| match f {
+                    Field::Version(v) => *v,
+                    _ => 0,
+                })
+                .boxed(),
+        );
+        registry.insert(
+            2,
+            utf8()
+                .xmap(Field::Name, |f| match f {
+                    Field::Name(n) => n.clone(),
+                    _ => String::new(),
+                })
+                .boxed(),
+        );
+        registry
+    }
+
+    #[test]
+    fn a_tlv_codec_should_dispatch_on_tag() {
+        let codec = || list(tlv(uint8, uint8, field_registry()));
+        let known = |tag, value| Tlv {
+            tag,
+            value: TlvValue::Known(value),
+        };
+        assert_round_trip(
+            codec(),
+            &vec![
+                known(2, Field::Name("ab".to_string())),
+                known(1, Field::Version(3)),
+            ],
+            &Some(byte_vector!(2, 2, b'a', b'b', 1, 1, 3)),
+        );
+    }
+
+    #[test]
+    fn a_tlv_codec_should_reject_unknown_tags_and_partial_values() {
+        let codec = || tlv(uint8, uint8, field_registry());
+        assert_eq!(
+            codec()
+                .decode(&byte_vector!(9, 1, 0))
+                .unwrap_err()
+                .message(),
+            "No codec registered for tag 9"
+        );
+        assert_eq!(
+            codec()
+                .decode(&byte_vector!(1, 2, 3, 4))
+                .unwrap_err()
+                .message(),
+            "Expected end of input but 1 bytes remain"
+        );
+    }
+
+    #[test]
+    fn a_tlv_codec_preserving_unknown_tags_should_round_trip_raw_values() {
+        let codec = || list(tlv_preserving_unknown(uint8, uint16, field_registry()));
+        let elements = vec![
+            Tlv {
+                tag: 9,
+                value: TlvValue::Unknown(byte_vector!(0xca, 0xfe)),
+            },
+            Tlv {
+                tag: 1,
+                value: TlvValue::Known(Field::Version(3)),
+            },
+        ];
+        assert_round_trip(
+            codec(),
+            &elements,
+            &Some(byte_vector!(9, 0, 2, 0xca, 0xfe, 1, 0, 1, 3)),
+        );
+    }
+
+    //
+    // Eager bytes codec
+    //
+
+    #[test]
+    fn an_eager_codec_should_round_trip() {
+        let input = vec![7, 1, 2, 3, 4];
+        let codec = eager(variable_size_bytes(uint16, identity_bytes()));
+        assert_round_trip(codec, &input, &Some(byte_vector!(0, 5, 7, 1, 2, 3, 4)));
+    }
+
+    #[test]
+    fn an_eager_bytes_codec_should_read_verbatim_bytes_directly() {
+        let codec = eager(bytes(3));
+        assert_eq!(codec.verbatim_len(5), Some(3));
+        assert_eq!(codec.verbatim_len(2), None);
+        assert_eq!(eager(identity_bytes()).verbatim_len(4), Some(4));
+        assert_eq!(
+            eager(fixed_size_bytes_with(
+                3,
+                identity_bytes(),
+                FixedSizeOptions {
+                    pad_direction: PadDirection::Left,
+                    ..FixedSizeOptions::default()
+                }
+            ))
+            .verbatim_len(5),
+            None
+        );
+        assert_eq!(
+            eager(variable_size_bytes(uint8, identity_bytes())).verbatim_len(5),
+            None
+        );
+
+        // The bytes may span appended storage
+        let input = byte_vector::append(&byte_vector!(1, 2), &byte_vector!(3, 4, 5));
+        let decoded = codec.decode(&input).unwrap();
+        assert_eq!(decoded.value, vec![1, 2, 3]);
+        assert_eq!(decoded.remainder, byte_vector!(4, 5));
+        assert_eq!(
+            codec.decode_slice(&[1, 2, 3, 4]).unwrap(),
+            (vec![1, 2, 3], 3)
+        );
+
+        // Insufficient input is reported by the wrapped codec
+        assert_eq!(
+            codec.decode(&byte_vector!(1, 2)).unwrap_err().message(),
+            bytes(3).decode(&byte_vector!(1, 2)).unwrap_err().message()
+        );
+        assert_eq!(
+            codec.decode_slice(&[1, 2]).unwrap_err().message(),
+            bytes(3).decode_slice(&[1, 2]).unwrap_err().message()
+        );
+    }
+
+    //
+    // String codecs
+    //
 
     #[test]
-    fn forcomp_macro_should_work() {
-        let v1 = forcomp!({
-            part1 <- Some(1u8);
-        } yield { part1 });
-        assert!(v1.is_some());
+    fn a_utf8_codec_should_round_trip() {
+        assert_round_trip(
+            utf8(),
+            &"héllo".to_string(),
+            &Some(byte_vector!(0x68, 0xc3, 0xa9, 0x6c, 0x6c, 0x6f)),
+        );
+        assert_round_trip(utf8(), &String::new(), &Some(byte_vector::empty()));
+        assert_eq!(utf8().decode(&byte_vector::empty()).unwrap().value, "");
+    }
+
+    #[test]
+    fn decoding_with_utf8_codec_should_fail_for_invalid_data() {
+        assert_eq!(
+            utf8()
+                .decode(&byte_vector!(0x68, 0xff))
+                .unwrap_err()
+                .message(),
+            "Invalid UTF-8 data: invalid utf-8 sequence of 1 bytes from index 1"
+        );
+    }
+
+    #[test]
+    fn length_prefixed_utf8_codecs_should_round_trip() {
+        let value = "hi".to_string();
+        assert_round_trip(utf8_8(), &value, &Some(byte_vector!(2, 0x68, 0x69)));
+        assert_round_trip(utf8_16(), &value, &Some(byte_vector!(0, 2, 0x68, 0x69)));
+        assert_round_trip(utf8_16_l(), &value, &Some(byte_vector!(2, 0, 0x68, 0x69)));
+        assert_round_trip(
+            utf8_32(),
+            &value,
+            &Some(byte_vector!(0, 0, 0, 2, 0x68, 0x69)),
+        );
+        assert_round_trip(
+            utf8_32_l(),
+            &value,
+            &Some(byte_vector!(2, 0, 0, 0, 0x68, 0x69)),
+        );
+    }
+
+    #[test]
+    fn a_pstring_codec_should_round_trip() {
+        assert_round_trip(
+            pstring(),
+            &"abc".to_string(),
+            &Some(byte_vector!(3, 0x61, 0x62, 0x63)),
+        );
+        assert!(pstring().encode(&"x".repeat(256)).is_err());
+    }
+
+    #[test]
+    fn a_pstring_bytes_codec_should_round_trip() {
+        let input = byte_vector!(0xff, 0xfe);
+        assert_round_trip(pstring_bytes(), &input, &Some(byte_vector!(2, 0xff, 0xfe)));
+    }
+
+    #[test]
+    fn a_char_utf8_codec_should_round_trip() {
+        assert_round_trip(char_utf8(), &'a', &Some(byte_vector!(0x61)));
+        assert_round_trip(char_utf8(), &'é', &Some(byte_vector!(0xc3, 0xa9)));
+        assert_round_trip(char_utf8(), &'€', &Some(byte_vector!(0xe2, 0x82, 0xac)));
+        assert_round_trip(
+            char_utf8(),
+            &'😀',
+            &Some(byte_vector!(0xf0, 0x9f, 0x98, 0x80)),
+        );
+    }
+
+    #[test]
+    fn decoding_with_char_utf8_codec_should_fail_for_invalid_data() {
+        assert!(char_utf8().decode(&byte_vector!(0x80)).is_err());
+        assert!(char_utf8().decode(&byte_vector!(0xc3)).is_err());
+        assert!(char_utf8().decode(&byte_vector!(0xc3, 0x41)).is_err());
+    }
+
+    #[test]
+    fn an_ascii_char_codec_should_round_trip() {
+        assert_round_trip(ascii_char(), &'Z', &Some(byte_vector!(0x5a)));
+    }
+
+    #[test]
+    fn an_ascii_char_codec_should_reject_non_ascii_characters() {
+        assert_eq!(
+            ascii_char().encode(&'é').unwrap_err().message(),
+            "Character 'é' is not an ASCII character"
+        );
+        assert_eq!(
+            ascii_char()
+                .decode(&byte_vector!(0xc3))
+                .unwrap_err()
+                .message(),
+            "Byte 0xc3 is not an ASCII character"
+        );
+    }
+
+    #[test]
+    fn an_ascii_hex_uint_codec_should_round_trip() {
+        assert_round_trip(
+            ascii_hex_uint::<u16>(4),
+            &0x0abc,
+            &Some(byte_vector!(b'0', b'A', b'B', b'C')),
+        );
+        let options = HexOptions {
+            uppercase: false,
+            pad_byte: b' ',
+        };
+        assert_round_trip(
+            ascii_hex_uint_with::<u32>(6, options),
+            &0xbeef,
+            &Some(byte_vector!(b' ', b' ', b'b', b'e', b'e', b'f')),
+        );
+        assert_round_trip(ascii_hex_uint::<u8>(2), &0, &Some(byte_vector!(b'0', b'0')));
+    }
+
+    #[test]
+    fn decoding_with_ascii_hex_uint_codec_should_accept_either_case() {
+        let decoded = ascii_hex_uint::<u32>(4)
+            .decode(&byte_vector!(b'a', b'B', b'c', b'D', b'!'))
+            .unwrap();
+        assert_eq!(decoded.value, 0xabcd);
+        assert_eq!(decoded.remainder, byte_vector!(b'!'));
+    }
+
+    #[test]
+    fn an_ascii_hex_uint_codec_should_reject_invalid_values() {
+        assert_eq!(
+            ascii_hex_uint::<u16>(2)
+                .encode(&0x100)
+                .unwrap_err()
+                .message(),
+            "Value 256 requires more than 2 hexadecimal digits"
+        );
+        assert!(ascii_hex_uint::<u128>(32).encode(&u128::MAX).is_err());
+        assert_eq!(
+            ascii_hex_uint::<u16>(2)
+                .decode(&byte_vector!(b'0', b'G'))
+                .unwrap_err()
+                .message(),
+            "Byte 0x47 is not a hexadecimal digit"
+        );
+        assert_eq!(
+            ascii_hex_uint::<u8>(3)
+                .decode_slice(b"100")
+                .unwrap_err()
+                .message(),
+            "Hexadecimal value \"100\" exceeds maximum value (255) of target type"
+        );
+    }
+
+    //
+    // Borrowed-value codecs
+    //
+
+    #[test]
+    fn a_bytes_ref_codec_should_borrow_from_the_input() {
+        let input = [1u8, 2, 3, 4];
+        let (value, consumed) = bytes_ref(3).decode_borrowed(&input).unwrap();
+        assert_eq!(value, &[1, 2, 3]);
+        assert_eq!(value.as_ptr(), input.as_ptr());
+        assert_eq!(consumed, 3);
+
+        let mut out = vec![0xaa];
+        bytes_ref(3).encode_borrowed(&value, &mut out).unwrap();
+        assert_eq!(out, vec![0xaa, 1, 2, 3]);
+        assert_eq!(bytes_ref(3).size_bound(), SizeBound::exact(3));
+    }
+
+    #[test]
+    fn a_bytes_ref_codec_should_fail_on_length_mismatch() {
+        assert_eq!(
+            bytes_ref(4)
+                .decode_borrowed(&[1, 2, 3])
+                .unwrap_err()
+                .message(),
+            "Requested read offset of 0 and length 4 bytes exceeds vector length of 3"
+        );
+        assert_eq!(
+            bytes_ref(1)
+                .encode_borrowed(&&[1u8, 2][..], &mut Vec::new())
+                .unwrap_err()
+                .message(),
+            "Encoding requires 2 bytes but codec is limited to fixed length of 1"
+        );
+    }
+
+    #[test]
+    fn a_str_ref_codec_should_borrow_from_the_input() {
+        let input = b"hello!";
+        let (value, consumed) = str_ref(5).decode_borrowed(input).unwrap();
+        assert_eq!(value, "hello");
+        assert_eq!(value.as_ptr(), input.as_ptr());
+        assert_eq!(consumed, 5);
+
+        let mut out = Vec::new();
+        str_ref(5).encode_borrowed(&"world", &mut out).unwrap();
+        assert_eq!(out, b"world".to_vec());
+
+        assert!(str_ref(2)
+            .decode_borrowed(&[0xc3, 0x28])
+            .unwrap_err()
+            .message()
+            .starts_with("Invalid UTF-8 data"));
+    }
+
+    #[test]
+    fn a_borrow_codec_should_decode_from_contiguous_byte_vectors_only() {
+        let bv = byte_vector!(1, 2, 3, 4).drop(1).unwrap();
+        let (value, consumed) = bytes_ref(2).decode_borrowed_bv(&bv).unwrap();
+        assert_eq!(value, &[2, 3]);
+        assert_eq!(consumed, 2);
+
+        let appended = byte_vector::append(&byte_vector!(1), &byte_vector!(2));
+        assert_eq!(
+            bytes_ref(2)
+                .decode_borrowed_bv(&appended)
+                .unwrap_err()
+                .message(),
+            "Borrowed decoding requires contiguous byte vector storage"
+        );
+    }
+
+    //
+    // Collection codecs
+    //
+
+    #[test]
+    fn a_vector_of_n_codec_should_round_trip() {
+        let codec = vector_of_n(uint8, uint16);
+        assert_round_trip(
+            codec,
+            &vec![1u16, 2, 0x0304],
+            &Some(byte_vector!(3, 0, 1, 0, 2, 3, 4)),
+        );
+        assert_round_trip(
+            vector_of_n(uint16, uint8),
+            &vec![],
+            &Some(byte_vector!(0, 0)),
+        );
+    }
+
+    #[test]
+    fn decoding_with_vector_of_n_codec_should_fail_when_too_few_elements_are_available() {
+        let codec = vector_of_n(uint8, uint16);
+        assert!(codec.decode(&byte_vector!(2, 0, 1, 0)).is_err());
+    }
+
+    #[test]
+    fn encoding_with_vector_of_n_codec_should_fail_when_count_is_too_large() {
+        let codec = vector_of_n(uint8, uint8);
+        assert_eq!(
+            codec.encode(&vec![0u8; 256]).unwrap_err().message(),
+            "Number of elements (256) is greater than maximum value (255) of count type"
+        );
+    }
+
+    #[test]
+    fn a_repeat_codec_should_round_trip() {
+        let codec = repeat(3, uint8);
+        assert_round_trip(codec, &vec![1u8, 2, 3], &Some(byte_vector!(1, 2, 3)));
+    }
+
+    #[test]
+    fn encoding_with_repeat_codec_should_fail_when_element_count_does_not_match() {
+        let codec = repeat(3, uint8);
+        assert_eq!(
+            codec.encode(&vec![1u8, 2]).unwrap_err().message(),
+            "Expected 3 elements but got 2"
+        );
+    }
+
+    #[test]
+    fn a_repeat_codec_should_work_with_a_count_from_an_earlier_field() {
+        let codec =
+            hlist_flat_prepend_codec(uint8, |count| hcodec!({ repeat(*count as usize, uint16) }));
+        assert_round_trip(
+            codec,
+            &hlist!(2u8, vec![0x0102u16, 0x0304]),
+            &Some(byte_vector!(2, 1, 2, 3, 4)),
+        );
+    }
+
+    #[test]
+    fn a_list_codec_should_round_trip() {
+        assert_round_trip(
+            list(uint16),
+            &vec![1u16, 2, 3],
+            &Some(byte_vector!(0, 1, 0, 2, 0, 3)),
+        );
+        assert_round_trip(list(uint16), &vec![], &Some(byte_vector::empty()));
+    }
+
+    #[test]
+    fn decoding_with_list_codec_should_fail_when_a_partial_element_remains() {
+        let codec = list(uint16);
+        assert!(codec.decode(&byte_vector!(0, 1, 0)).is_err());
+    }
+
+    #[test]
+    fn decoding_with_list_codec_should_fail_when_element_codec_consumes_nothing() {
+        let codec = list(hnil_codec());
+        assert_eq!(
+            codec.decode(&byte_vector!(1)).unwrap_err().message(),
+            "Element codec did not consume any input"
+        );
+    }
+
+    #[test]
+    fn a_sized_list_codec_should_round_trip() {
+        let codec = sized_list(4, uint16);
+        assert_round_trip(codec, &vec![1u16, 2], &Some(byte_vector!(0, 1, 0, 2)));
+    }
+
+    #[test]
+    fn decoding_with_sized_list_codec_should_be_confined_to_the_region() {
+        let codec = sized_list(4, uint16);
+        let decoded = codec.decode(&byte_vector!(0, 1, 0, 2, 0, 3)).unwrap();
+        assert_eq!(decoded.value, vec![1u16, 2]);
+        assert_eq!(decoded.remainder, byte_vector!(0, 3));
+        assert!(codec.decode(&byte_vector!(0, 1, 0)).is_err());
+    }
+
+    #[test]
+    fn encoding_with_sized_list_codec_should_fail_when_size_does_not_match() {
+        let codec = sized_list(4, uint16);
+        assert_eq!(
+            codec.encode(&vec![1u16]).unwrap_err().message(),
+            "Encoded elements require 2 bytes but list is limited to fixed length of 4"
+        );
+    }
+
+    #[test]
+    fn a_variable_sized_list_codec_should_round_trip() {
+        let codec = variable_sized_list(uint8, uint16);
+        assert_round_trip(
+            codec,
+            &vec![1u16, 2, 3],
+            &Some(byte_vector!(6, 0, 1, 0, 2, 0, 3)),
+        );
+    }
+
+    #[test]
+    fn a_map_of_n_codec_should_round_trip() {
+        let mut map = BTreeMap::new();
+        map.insert(2u8, "b".to_string());
+        map.insert(1u8, "a".to_string());
+        assert_round_trip(
+            map_of_n(uint8, uint8, utf8_8()),
+            &map,
+            &Some(byte_vector!(2, 1, 1, b'a', 2, 1, b'b')),
+        );
+    }
+
+    #[test]
+    fn a_hash_map_of_n_codec_should_encode_entries_in_key_order() {
+        let map: HashMap<u16, u8> = (0..10u16).map(|k| (k, k as u8 * 2)).collect();
+        let encoded = hash_map_of_n(uint8, uint16, uint8).encode(&map).unwrap();
+        let mut expected = vec![10u8];
+        for k in 0..10u8 {
+            expected.extend_from_slice(&[0, k, k * 2]);
+        }
+        assert_eq!(encoded, expected);
+        assert_round_trip(hash_map_of_n(uint8, uint16, uint8), &map, &None);
+    }
+
+    #[test]
+    fn decoding_with_map_of_n_codec_should_fail_on_duplicate_keys() {
+        assert_eq!(
+            map_of_n(uint8, uint8, uint8)
+                .decode(&byte_vector!(2, 1, 1, 1, 2))
+                .unwrap_err()
+                .message(),
+            "Duplicate key in map"
+        );
+    }
+
+    //
+    // Choice codecs
+    //
+
+    #[test]
+    fn a_choice_codec_should_use_the_first_codec_that_succeeds() {
+        let codecs: Vec<Box<dyn Codec<Value = u8>>> = vec![
+            Box::new(drop_left(constant(byte_vector!(0xAA)), uint8)),
+            Box::new(drop_left(constant(byte_vector!(0xBB)), uint8)),
+        ];
+        let codec = choice(codecs);
+        assert_eq!(codec.decode(&byte_vector!(0xBB, 7)).unwrap().value, 7);
+        assert_round_trip(codec, &7, &Some(byte_vector!(0xAA, 7)));
+    }
+
+    #[test]
+    fn a_choice_codec_should_fail_when_no_codec_succeeds() {
+        let codec = choice(vec![constant(byte_vector!(1)), constant(byte_vector!(2))]);
+        assert_eq!(
+            codec.decode(&byte_vector!(3)).unwrap_err().message(),
+            "None of the choices succeeded: [Expected constant 01 but got 03; Expected constant 02 but got 03]"
+        );
+        let empty: Vec<Box<dyn Codec<Value = ()>>> = Vec::new();
+        assert_eq!(
+            choice(empty)
+                .decode(&byte_vector!(3))
+                .unwrap_err()
+                .message(),
+            "No codecs to choose from"
+        );
+    }
+
+    #[test]
+    fn a_fallback_codec_should_try_the_secondary_codec() {
+        let codec = fallback(drop_left(constant(byte_vector!(0xAA)), uint8), uint8);
+        assert_eq!(codec.decode(&byte_vector!(0xAA, 7)).unwrap().value, 7);
+        assert_eq!(codec.decode(&byte_vector!(5)).unwrap().value, 5);
+        assert_round_trip(codec, &7, &Some(byte_vector!(0xAA, 7)));
+    }
+
+    //
+    // Lookahead codec
+    //
+
+    #[test]
+    fn a_lookahead_codec_should_not_consume_input() {
+        let codec = lookahead(constant(byte_vector!(0xCA, 0xFE)));
+        let input = byte_vector!(0xCA, 0xFE, 0x01);
+        let decoded = codec.decode(&input).unwrap();
+        assert!(decoded.value);
+        assert_eq!(decoded.remainder, input);
+
+        let decoded = codec.decode(&byte_vector!(0x01)).unwrap();
+        assert!(!decoded.value);
+        assert_eq!(decoded.remainder, byte_vector!(0x01));
+
+        assert_eq!(codec.encode(&true).unwrap(), byte_vector::empty());
+    }
+
+    //
+    // Recover codec
+    //
+
+    #[test]
+    fn a_recover_codec_should_round_trip() {
+        let codec = || recover(constant(byte_vector!(0xCA, 0xFE)));
+        assert_round_trip(codec(), &true, &Some(byte_vector!(0xCA, 0xFE)));
+        assert_round_trip(codec(), &false, &Some(byte_vector::empty()));
+    }
+
+    #[test]
+    fn a_recover_codec_should_consume_input_only_on_success() {
+        let codec = hcodec!({ recover(constant(byte_vector!(0xCA))) } :: { uint8 });
+        assert_eq!(
+            codec.decode(&byte_vector!(0xCA, 0x07)).unwrap().value,
+            hlist!(true, 0x07)
+        );
+        assert_eq!(
+            codec.decode(&byte_vector!(0x07)).unwrap().value,
+            hlist!(false, 0x07)
+        );
+    }
+
+    //
+    // Value mapping codecs
+    //
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Port(u16);
+
+    #[test]
+    fn an_xmap_codec_should_round_trip() {
+        let codec = xmap(uint16, Port, |port: &Port| port.0);
+        assert_round_trip(codec, &Port(80), &Some(byte_vector!(0, 80)));
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    enum Color {
+        Red,
+        Green,
+    }
+
+    fn color_from_u8(value: u8) -> Result<Color, Error> {
+        match value {
+            1 => Ok(Color::Red),
+            2 => Ok(Color::Green),
+            _ => Err(Error::new(format!("Unknown color {}", value))),
+        }
+    }
+
+    #[test]
+    fn a_narrow_codec_should_reject_invalid_decoded_values() {
+        let codec = narrow(uint8, color_from_u8, |color: &Color| match color {
+            Color::Red => 1,
+            Color::Green => 2,
+        });
+        assert_eq!(
+            codec.decode(&byte_vector!(3)).unwrap_err().message(),
+            "Unknown color 3"
+        );
+        assert_round_trip(codec, &Color::Green, &Some(byte_vector!(2)));
+    }
+
+    #[test]
+    fn a_widen_codec_should_reject_invalid_encoded_values() {
+        let codec = widen(uint8, u32::from, |value: &u32| {
+            u8::from_u32(*value).ok_or_else(|| Error::new(format!("{} is too large", value)))
+        });
+        assert_eq!(
+            codec.encode(&256).unwrap_err().message(),
+            "256 is too large"
+        );
+        assert_round_trip(codec, &7u32, &Some(byte_vector!(7)));
+    }
+
+    #[test]
+    fn an_exmap_codec_should_round_trip() {
+        let codec = exmap(uint8, color_from_u8, |color: &Color| match color {
+            Color::Red => Ok(1),
+            Color::Green => Err(Error::new("Green is not supported".to_string())),
+        });
+        assert!(codec.encode(&Color::Green).is_err());
+        assert!(codec.decode(&byte_vector!(0)).is_err());
+        assert_round_trip(codec, &Color::Red, &Some(byte_vector!(1)));
+    }
+
+    #[test]
+    fn a_validated_codec_should_reject_invalid_decoded_values() {
+        let codec = || validated(uint8, |value: &u8| *value < 100, "percentage must be < 100");
+        assert_eq!(
+            codec().decode(&byte_vector!(100)).unwrap_err().message(),
+            "Validation failed: percentage must be < 100"
+        );
+        assert_eq!(codec().encode(&100).unwrap(), byte_vector!(100));
+        assert_round_trip(codec(), &42, &Some(byte_vector!(42)));
+    }
+
+    #[test]
+    fn a_strictly_validated_codec_should_reject_invalid_values_in_both_directions() {
+        let codec =
+            || strictly_validated(uint16, |value: &u16| *value != 0, "port must be non-zero");
+        assert_eq!(
+            codec().encode(&0).unwrap_err().message(),
+            "Validation failed: port must be non-zero"
+        );
+        assert!(codec().decode(&byte_vector!(0, 0)).is_err());
+        assert_round_trip(codec(), &8080, &Some(byte_vector!(0x1f, 0x90)));
+    }
+
+    #[test]
+    fn a_bounded_codec_should_reject_out_of_range_values() {
+        assert_eq!(
+            bounded_uint8(1, 4).encode(&5).unwrap_err().message(),
+            "Value 5 is out of range 1..=4"
+        );
+        assert_eq!(
+            bounded_uint8(1, 4)
+                .decode(&byte_vector!(0))
+                .unwrap_err()
+                .message(),
+            "Value 0 is out of range 1..=4"
+        );
+        assert!(bounded(int16, ..0).decode(&byte_vector!(0, 1)).is_err());
+        assert_round_trip(bounded_uint16(1, 4), &4, &Some(byte_vector!(0, 4)));
+        assert_round_trip(bounded(int16, ..0), &-1, &Some(byte_vector!(0xff, 0xff)));
+    }
 
-        let v2 = forcomp!({
-            part1 <- Some(1u8);
-            part2 <- None::<u8>;
-        } yield { part1 + part2 });
-        assert!(v2.is_none());
+    #[test]
+    fn a_mapped_enum_codec_should_round_trip() {
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        enum Suit {
+            Hearts,
+            Spades,
+            Clubs,
+        }
 
-        let v3 = forcomp!({
-            part1 <- Some(1u8);
-            part2 <- Some(2u8);
-        } yield { part1 + part2 });
-        assert_eq!(v3.unwrap(), 3u8);
+        let codec = || {
+            let mut map = HashMap::new();
+            map.insert(1u8, Suit::Hearts);
+            map.insert(2u8, Suit::Spades);
+            mapped_enum(uint8, map)
+        };
+        assert_eq!(
+            codec().decode(&byte_vector!(3)).unwrap_err().message(),
+            "Unknown tag 3"
+        );
+        assert_eq!(
+            codec().encode(&Suit::Clubs).unwrap_err().message(),
+            "No mapping for value Clubs"
+        );
+        assert_round_trip(codec(), &Suit::Spades, &Some(byte_vector!(2)));
     }
 
-    fn assert_round_trip<T, C>(codec: C, value: &T, raw_bytes: &Option<ByteVector>)
-    where
-        T: 'static + Eq + Debug,
-        C: Codec<Value = T>,
-    {
-        // Encode
-        let result = codec.encode(value).and_then(|encoded| {
-            // Compare encoded bytes to the expected bytes, if provided
-            let compare_result = match *raw_bytes {
-                Some(ref expected) => {
-                    if encoded != *expected {
-                        Err(Error::new(format!(
-                            "Encoded bytes {:?} do not match expected bytes {:?}",
-                            encoded, *expected
-                        )))
-                    } else {
-                        Ok(())
-                    }
-                }
-                None => Ok(()),
-            };
-            if let Err(error) = compare_result {
-                return Err(error);
-            }
+    #[test]
+    #[should_panic(expected = "Mapping contains duplicate value \"hearts\"")]
+    fn a_mapped_enum_codec_should_reject_duplicate_values() {
+        let mut map = HashMap::new();
+        map.insert(1u8, "hearts");
+        map.insert(2u8, "hearts");
+        mapped_enum(uint8, map);
+    }
 
-            // Decode and drop the remainder
-            codec.decode(&encoded).map(|decoded| decoded.value)
-        });
+    #[test]
+    fn a_complete_codec_should_reject_trailing_bytes() {
+        assert_eq!(
+            complete(uint16)
+                .decode(&byte_vector!(1, 2, 3))
+                .unwrap_err()
+                .message(),
+            "Expected end of input but 1 bytes remain"
+        );
+        assert_round_trip(complete(uint16), &0x0102, &Some(byte_vector!(1, 2)));
+    }
 
-        // Verify result
-        match result {
-            Ok(decoded) => assert_eq!(decoded, *value),
-            Err(e) => panic!("Round-trip encoding failed: {}", e.message()),
+    #[test]
+    fn a_lazy_codec_should_support_recursive_structures() {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Tree {
+            children: Vec<Tree>,
         }
-    }
 
-    //
-    // Integral codecs
-    //
+        fn tree_codec() -> Box<dyn Codec<Value = Tree>> {
+            Box::new(xmap(
+                vector_of_n(uint8, lazily(tree_codec)),
+                |children| Tree { children },
+                |tree: &Tree| tree.children.clone(),
+            ))
+        }
 
-    #[test]
-    fn a_u8_value_should_round_trip() {
-        assert_round_trip(uint8, &7, &Some(byte_vector!(7)));
+        let leaf = || Tree { children: vec![] };
+        let tree = Tree {
+            children: vec![
+                leaf(),
+                Tree {
+                    children: vec![leaf(), leaf()],
+                },
+            ],
+        };
+        assert_round_trip(tree_codec(), &tree, &Some(byte_vector!(2, 0, 2, 0, 0)));
     }
 
     #[test]
-    fn an_i8_value_should_round_trip() {
-        assert_round_trip(int8, &7, &Some(byte_vector!(7)));
-        assert_round_trip(int8, &-2, &Some(byte_vector!(0xfe)));
-        assert_round_trip(int8, &-16, &Some(byte_vector!(0xf0)));
-        assert_round_trip(int8, &-128, &Some(byte_vector!(0x80)));
+    fn a_limited_codec_should_enforce_the_element_limit() {
+        let limits = DecodeLimits {
+            max_elements: 2,
+            ..DecodeLimits::unlimited()
+        };
+        let codec = limited(limits, vector_of_n(uint8, uint8));
+        assert_eq!(
+            codec.decode(&byte_vector!(2, 7, 8)).unwrap().value,
+            vec![7, 8]
+        );
+        assert_eq!(
+            codec
+                .decode(&byte_vector!(3, 7, 8, 9))
+                .unwrap_err()
+                .message(),
+            "Number of elements (3) exceeds decode limit of 2"
+        );
+        assert!(codec.decode_slice(&[3, 7, 8, 9]).is_err());
+
+        // The count is checked before any elements are decoded
+        assert!(!codec
+            .decode(&byte_vector!(0xff))
+            .unwrap_err()
+            .is_insufficient_data());
+
+        let codec = limited(limits, list(uint8));
+        assert!(codec.decode(&byte_vector!(7, 8)).is_ok());
+        assert!(codec.decode(&byte_vector!(7, 8, 9)).is_err());
+        assert!(codec.decode_slice(&[7, 8, 9]).is_err());
+
+        let codec = limited(limits, map_of_n(uint8, uint8, uint8));
+        assert!(codec.decode(&byte_vector!(3, 1, 1, 2, 2, 3, 3)).is_err());
     }
 
     #[test]
-    fn a_u16_value_should_round_trip() {
-        assert_round_trip(uint16, &0x1234, &Some(byte_vector!(0x12, 0x34)));
-        assert_round_trip(uint16_l, &0x1234, &Some(byte_vector!(0x34, 0x12)));
+    fn a_limited_codec_should_enforce_the_allocation_limit() {
+        let limits = DecodeLimits {
+            max_alloc: 4,
+            ..DecodeLimits::unlimited()
+        };
+        let codec = limited(limits, variable_size_bytes(uint32, identity_bytes()));
+        assert!(codec.decode(&byte_vector!(0, 0, 0, 4, 1, 2, 3, 4)).is_ok());
+        assert_eq!(
+            codec
+                .decode(&byte_vector!(0xff, 0xff, 0xff, 0xff))
+                .unwrap_err()
+                .message(),
+            "Length of value (4294967295 bytes) exceeds decode limit of 4 bytes"
+        );
+        assert!(codec.decode_slice(&[0, 0, 0, 5]).is_err());
+
+        // Encoding is not limited
+        assert!(codec.encode(&byte_vector!(1, 2, 3, 4, 5)).is_ok());
     }
 
     #[test]
-    fn an_i16_value_should_round_trip() {
-        assert_round_trip(int16, &0x1234, &Some(byte_vector!(0x12, 0x34)));
-        assert_round_trip(int16, &-2, &Some(byte_vector!(0xff, 0xfe)));
-        assert_round_trip(int16_l, &0x1234, &Some(byte_vector!(0x34, 0x12)));
-        assert_round_trip(int16_l, &-2, &Some(byte_vector!(0xfe, 0xff)));
+    fn a_limited_codec_should_enforce_the_depth_limit() {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Tree {
+            children: Vec<Tree>,
+        }
+
+        fn tree_codec() -> Box<dyn Codec<Value = Tree>> {
+            Box::new(xmap(
+                vector_of_n(uint8, lazily(tree_codec)),
+                |children| Tree { children },
+                |tree: &Tree| tree.children.clone(),
+            ))
+        }
+
+        let limits = DecodeLimits {
+            max_depth: 2,
+            ..DecodeLimits::unlimited()
+        };
+        let codec = tree_codec().limited(limits);
+        assert!(codec.decode(&byte_vector!(1, 1, 0)).is_ok());
+        assert_eq!(
+            codec
+                .decode(&byte_vector!(1, 1, 1, 0))
+                .unwrap_err()
+                .message(),
+            "Nesting depth exceeds decode limit of 2"
+        );
+        assert!(codec.decode_slice(&[1, 1, 1, 0]).is_err());
+
+        // The depth is reset after a failed decode
+        assert!(codec.decode(&byte_vector!(2, 1, 0, 1, 0)).is_ok());
     }
 
     #[test]
-    fn a_u32_value_should_round_trip() {
-        assert_round_trip(
-            uint32,
-            &0x1234_5678,
-            &Some(byte_vector!(0x12, 0x34, 0x56, 0x78)),
+    fn a_terse_codec_should_produce_errors_without_details() {
+        let codec = hcodec!(
+            { "tag"   => constant(byte_vector!(1)) } >>
+            { "value" => choice(vec![uint16, uint16_l]) }
         );
-        assert_round_trip(
-            uint32_l,
-            &0x1234_5678,
-            &Some(byte_vector!(0x78, 0x56, 0x34, 0x12)),
+        let full = codec.decode(&byte_vector!(2, 3)).unwrap_err();
+        assert!(!full.is_terse());
+        assert!(full.message().starts_with("tag: "));
+
+        let codec = codec.terse();
+        let error = codec.decode(&byte_vector!(2, 3)).unwrap_err();
+        assert!(error.is_terse());
+        assert_eq!(error.message(), "Details omitted from terse error");
+        let error = crate::cursor::DecodeCursor::new(byte_vector!(2, 3))
+            .decode(&codec)
+            .unwrap_err();
+        assert_eq!(error.offset(), Some(0));
+        assert_eq!(
+            error.message(),
+            "Details omitted from terse error (at offset 0)"
         );
+        assert!(codec.decode_slice(&[1]).unwrap_err().is_terse());
+        assert!(codec.encode(&hlist!(0x1234)).is_ok());
+        assert!(!uint8.decode(&byte_vector::empty()).unwrap_err().is_terse());
+
+        // The flags of errors are preserved
+        let error = uint32.terse().decode(&byte_vector!(1)).unwrap_err();
+        assert!(error.is_terse());
+        assert!(error.is_insufficient_data());
     }
 
     #[test]
-    fn an_i32_value_should_round_trip() {
+    fn a_checksummed_codec_should_round_trip() {
+        use crate::checksum::{Adler32, Sum8};
+
         assert_round_trip(
-            int32,
-            &0x1234_5678,
-            &Some(byte_vector!(0x12, 0x34, 0x56, 0x78)),
+            checksummed(uint16, uint8, Sum8::default()),
+            &0x0102,
+            &Some(byte_vector!(1, 2, 3)),
         );
-        assert_round_trip(int32, &-2, &Some(byte_vector!(0xff, 0xff, 0xff, 0xfe)));
         assert_round_trip(
-            int32_l,
-            &0x1234_5678,
-            &Some(byte_vector!(0x78, 0x56, 0x34, 0x12)),
+            checksummed(fixed_size_bytes(9, utf8()), uint32, Adler32::default()),
+            &"Wikipedia".to_string(),
+            &Some(byte_vector!(
+                b'W', b'i', b'k', b'i', b'p', b'e', b'd', b'i', b'a', 0x11, 0xe6, 0x03, 0x98
+            )),
         );
-        assert_round_trip(int32_l, &-2, &Some(byte_vector!(0xfe, 0xff, 0xff, 0xff)));
     }
 
     #[test]
-    fn a_u64_value_should_round_trip() {
-        assert_round_trip(
-            uint64,
-            &0x1234_5678_90ab_cdef,
-            &Some(byte_vector!(0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef)),
-        );
-        assert_round_trip(
-            uint64_l,
-            &0x1234_5678_90ab_cdef,
-            &Some(byte_vector!(0xef, 0xcd, 0xab, 0x90, 0x78, 0x56, 0x34, 0x12)),
+    fn decoding_with_checksummed_codec_should_fail_on_mismatch() {
+        use crate::checksum::Sum8;
+
+        let codec = checksummed(uint16, uint8, Sum8::default());
+        assert_eq!(
+            codec.decode(&byte_vector!(1, 2, 4)).unwrap_err().message(),
+            "Checksum mismatch: expected 3 but got 4"
         );
     }
 
     #[test]
-    fn an_i64_value_should_round_trip() {
-        assert_round_trip(
-            int64,
-            &0x1234_5678_90ab_cdef,
-            &Some(byte_vector!(0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef)),
+    fn a_checksum_field_codec_should_fill_in_an_embedded_checksum() {
+        use crate::checksum::InternetChecksum;
+
+        // IPv4 header, with the checksum following the first 10 bytes
+        let codec = || {
+            with_checksum_field(
+                (uint32, uint32, uint16),
+                uint16,
+                (uint32, uint32),
+                InternetChecksum::default(),
+            )
+        };
+        let header = (
+            (0x4500_0073, 0x0000_4000, 0x4011),
+            (0xc0a8_0001, 0xc0a8_00c7),
         );
-        assert_round_trip(
-            int64,
-            &-2,
-            &Some(byte_vector!(0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe)),
+        let bytes = byte_vector!(
+            0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0xb8, 0x61, 0xc0, 0xa8,
+            0x00, 0x01, 0xc0, 0xa8, 0x00, 0xc7
         );
-        assert_round_trip(
-            int64_l,
-            &0x1234_5678_90ab_cdef,
-            &Some(byte_vector!(0xef, 0xcd, 0xab, 0x90, 0x78, 0x56, 0x34, 0x12)),
+        assert_round_trip(codec(), &header, &Some(bytes.clone()));
+
+        // Decoding works across appended storage and leaves the remainder in place
+        let input = byte_vector::append(
+            &bytes.take(11).unwrap(),
+            &byte_vector::append(&bytes.drop(11).unwrap(), &byte_vector!(0xff)),
         );
-        assert_round_trip(
-            int64_l,
-            &-2,
-            &Some(byte_vector!(0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff)),
+        let decoded = codec().decode(&input).unwrap();
+        assert_eq!(decoded.value, header);
+        assert_eq!(decoded.remainder, byte_vector!(0xff));
+
+        let corrupted = byte_vector!(
+            0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0xb8, 0x61, 0xc0, 0xa8,
+            0x00, 0x02, 0xc0, 0xa8, 0x00, 0xc7
+        );
+        assert_eq!(
+            codec().decode(&corrupted).unwrap_err().message(),
+            "Checksum mismatch: expected 47200 but got 47201"
+        );
+        assert_eq!(
+            codec()
+                .decode_slice(&corrupted.to_vec().unwrap())
+                .unwrap_err()
+                .message(),
+            "Checksum mismatch: expected 47200 but got 47201"
         );
-    }
 
-    // macro_rules! bench_int_codec {
-    //     { $codec:ident, $enc:ident, $dec:ident } => {
-    //         #[bench]
-    //         fn $enc(b: &mut Bencher) {
-    //             b.iter(|| $codec.encode(&7));
-    //         }
+        // When collecting errors, the mismatch is reported at the checksum field and the value is
+        // still decoded
+        let report = validation::decode_collecting_errors(&codec(), &corrupted);
+        assert_eq!(
+            report.result.unwrap().value,
+            (
+                (0x4500_0073, 0x0000_4000, 0x4011),
+                (0xc0a8_0002, 0xc0a8_00c7)
+            )
+        );
+        let messages: Vec<_> = report.errors.iter().map(|e| e.message()).collect();
+        assert_eq!(
+            messages,
+            vec!["Checksum mismatch: expected 47200 but got 47201 (at offset 10)"]
+        );
+    }
 
-    //         #[bench]
-    //         fn $dec(b: &mut Bencher) {
-    //             let bv = byte_vector!(0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07);
-    //             b.iter(|| $codec.decode(&bv));
-    //         }
-    //     };
-    // }
+    #[test]
+    fn a_crc_codec_should_round_trip() {
+        let input = "123456789".to_string();
+        let mut expected = input.clone().into_bytes();
+        expected.extend_from_slice(&[0xbb, 0x3d]);
+        assert_round_trip(
+            crc16(fixed_size_bytes(9, utf8())),
+            &input,
+            &Some(byte_vector::from_vec(expected)),
+        );
 
-    // bench_int_codec!(uint8,    bench_enc_uint8,    bench_dec_uint8);
-    // bench_int_codec!(int8,     bench_enc_int8,     bench_dec_int8);
+        let mut expected = input.clone().into_bytes();
+        expected.extend_from_slice(&[0x26, 0x39, 0xf4, 0xcb]);
+        assert_round_trip(
+            crc_with(
+                fixed_size_bytes(9, utf8()),
+                crc::CRC_32,
+                ByteOrdering::LittleEndian,
+            ),
+            &input,
+            &Some(byte_vector::from_vec(expected)),
+        );
+    }
 
-    // bench_int_codec!(uint16,   bench_enc_uint16,   bench_dec_uint16);
-    // bench_int_codec!(int16,    bench_enc_int16,    bench_dec_int16);
-    // bench_int_codec!(uint16_l, bench_enc_uint16_l, bench_dec_uint16_l);
-    // bench_int_codec!(int16_l,  bench_enc_int16_l,  bench_dec_int16_l);
+    #[test]
+    fn decoding_with_crc_codec_should_verify_and_strip_the_checksum() {
+        let codec = || crc32(uint16);
+        let encoded = codec().encode(&0x0102).unwrap();
+        let input = byte_vector::append(&encoded, &byte_vector!(9));
+        let decoded = codec().decode(&input).unwrap();
+        assert_eq!(decoded.value, 0x0102);
+        assert_eq!(decoded.remainder, byte_vector!(9));
+
+        let mut corrupted = encoded.to_vec().unwrap();
+        corrupted[1] ^= 0xff;
+        assert!(codec()
+            .decode(&byte_vector::from_vec(corrupted))
+            .unwrap_err()
+            .message()
+            .starts_with("CRC mismatch"));
+        assert!(codec().decode(&byte_vector!(1, 2, 3)).is_err());
+    }
 
-    // bench_int_codec!(uint32,   bench_enc_uint32,   bench_dec_uint32);
-    // bench_int_codec!(int32,    bench_enc_int32,    bench_dec_int32);
-    // bench_int_codec!(uint32_l, bench_enc_uint32_l, bench_dec_uint32_l);
-    // bench_int_codec!(int32_l,  bench_enc_int32_l,  bench_dec_int32_l);
+    #[test]
+    fn an_encrypted_codec_should_round_trip() {
+        use crate::cipher::Xor;
 
-    // bench_int_codec!(uint64,   bench_enc_uint64,   bench_dec_uint64);
-    // bench_int_codec!(int64,    bench_enc_int64,    bench_dec_int64);
-    // bench_int_codec!(uint64_l, bench_enc_uint64_l, bench_dec_uint64_l);
-    // bench_int_codec!(int64_l,  bench_enc_int64_l,  bench_dec_int64_l);
+        let codec = || encrypted(uint16, Xor::new(&[0xff]).unwrap());
+        assert_round_trip(codec(), &0x0102, &Some(byte_vector!(0xfe, 0xfd)));
 
-    //
-    // Ignore codec
-    //
+        let framed = variable_size_bytes(uint8, codec());
+        let decoded = framed.decode(&byte_vector!(2, 0xfe, 0xfd, 9)).unwrap();
+        assert_eq!(decoded.value, 0x0102);
+        assert_eq!(decoded.remainder, byte_vector!(9));
+    }
 
     #[test]
-    fn an_ignore_codec_should_round_trip() {
-        assert_round_trip(ignore(4), &(), &Some(byte_vector!(0, 0, 0, 0)));
+    fn a_tuple_codec_should_round_trip() {
+        assert_round_trip((uint8, uint16), &(1, 0x0203), &Some(byte_vector!(1, 2, 3)));
+        assert_round_trip(
+            (uint8, int8, utf8_8(), uint16_l),
+            &(1, -1, "hi".to_string(), 0x0302),
+            &Some(byte_vector!(1, 0xff, 2, b'h', b'i', 2, 3)),
+        );
     }
 
     #[test]
-    fn decoding_with_ignore_codec_should_succeed_if_the_input_vector_is_long_enough() {
-        let input = byte_vector!(7, 1, 2, 3, 4);
-        let codec = ignore(3);
-        match codec.decode(&input) {
-            Ok(result) => {
-                let expected_remainder = byte_vector!(3, 4);
-                assert_eq!(expected_remainder, result.remainder);
-            }
-            Err(e) => panic!("Decoding failed: {}", e.message()),
-        }
+    fn a_tuple_codec_should_fail_on_insufficient_data() {
+        assert!((uint8, uint16).decode(&byte_vector!(1, 2)).is_err());
     }
 
     #[test]
-    fn decoding_with_ignore_codec_should_fail_if_the_input_vector_is_smaller_than_the_ignored_length(
-    ) {
-        let input = byte_vector!(1u8);
-        let codec = ignore(3);
-        assert_eq!(
-            codec.decode(&input).unwrap_err().message(),
-            "Requested length of 3 bytes exceeds vector length of 1"
+    fn a_flat_zip_codec_should_round_trip() {
+        let codec = || flat_zip(uint8, |len: &u8| bytes(*len as usize));
+        assert_round_trip(
+            codec(),
+            &(2, byte_vector!(7, 8)),
+            &Some(byte_vector!(2, 7, 8)),
         );
+
+        // The second codec is derived from the first value when decoding
+        let decoded = codec().decode(&byte_vector!(1, 7, 8)).unwrap();
+        assert_eq!(decoded.value, (1, byte_vector!(7)));
+        assert_eq!(decoded.remainder, byte_vector!(8));
+        assert!(codec().decode(&byte_vector!(3, 7, 8)).is_err());
+        assert!(codec().encode(&(1, byte_vector!(7, 8))).is_err());
+
+        let codec = uint8.flat_zip(|count: &u8| repeat(*count as usize, uint8));
+        assert_round_trip(codec, &(2, vec![5, 6]), &Some(byte_vector!(2, 5, 6)));
     }
 
     //
-    // Constant codec
+    // Chunk codecs
     //
 
     #[test]
-    fn a_constant_codec_should_round_trip() {
-        let input = byte_vector!(1, 2, 3, 4);
-        assert_round_trip(constant(&input), &(), &Some(input));
+    fn a_chunk_codec_should_round_trip() {
+        let codec = || chunk(fourcc(), identity_bytes());
+        assert_round_trip(
+            codec(),
+            &(*b"data", byte_vector!(1, 2, 3)),
+            &Some(byte_vector!(0, 0, 0, 3, b'd', b'a', b't', b'a', 1, 2, 3)),
+        );
+
+        // The payload codec is constrained to the chunk length
+        let decoded = codec()
+            .decode(&byte_vector!(0, 0, 0, 1, b'd', b'a', b't', b'a', 1, 2, 3))
+            .unwrap();
+        assert_eq!(decoded.value.1, byte_vector!(1));
+        assert_eq!(decoded.remainder, byte_vector!(2, 3));
     }
 
     #[test]
-    fn decoding_with_constant_codec_should_fail_if_the_input_vector_does_not_match_the_constant_vector(
-    ) {
-        let input = byte_vector!(1, 2, 3, 4);
-        let codec = constant(&byte_vector!(6, 6, 6));
+    fn a_chunk_codec_with_crc_should_match_png_layout() {
+        // The IEND chunk that terminates every PNG file
+        let codec = || chunk_with_crc(constant(b"IEND"), ignore(0), crc::CRC_32);
+        assert_round_trip(
+            codec(),
+            &((), ()),
+            &Some(byte_vector!(
+                0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82
+            )),
+        );
         assert_eq!(
-            codec.decode(&input).unwrap_err().message(),
-            "Expected constant 060606 but got 010203"
+            codec()
+                .decode(&byte_vector!(
+                    0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x83
+                ))
+                .unwrap_err()
+                .message(),
+            "Checksum mismatch: expected 2923585666 but got 2923585667"
         );
     }
 
     #[test]
-    fn decoding_with_constant_codec_should_fail_if_the_input_vector_is_smaller_than_the_constant_vector(
-    ) {
-        let input = byte_vector!(1);
-        let codec = constant(&byte_vector!(6, 6, 6));
+    fn decoding_with_chunk_codec_should_fail_if_payload_is_not_fully_consumed() {
+        let codec = chunk(fourcc(), uint8);
         assert_eq!(
-            codec.decode(&input).unwrap_err().message(),
-            "Requested view offset of 0 and length 3 bytes exceeds vector length of 1"
+            codec
+                .decode(&byte_vector!(0, 0, 0, 2, b'd', b'a', b't', b'a', 1, 2))
+                .unwrap_err()
+                .message(),
+            "Expected end of input but 1 bytes remain"
         );
     }
 
     //
-    // Identity codec
+    // Time codecs
     //
 
     #[test]
-    fn an_identity_codec_should_round_trip() {
-        let input = byte_vector!(1, 2, 3, 4);
-        assert_round_trip(identity_bytes(), &input, &Some(input.clone()));
+    fn timestamp_codecs_should_round_trip() {
+        let time = UNIX_EPOCH + Duration::from_secs(0x6543_2100);
+        assert_round_trip(
+            unix_time32(),
+            &time,
+            &Some(byte_vector!(0x65, 0x43, 0x21, 0x00)),
+        );
+        assert_round_trip(
+            unix_time64(),
+            &time,
+            &Some(byte_vector!(0, 0, 0, 0, 0x65, 0x43, 0x21, 0x00)),
+        );
+
+        let time = UNIX_EPOCH - Duration::from_millis(1);
+        assert_round_trip(
+            unix_time_millis64(),
+            &time,
+            &Some(byte_vector!(0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff)),
+        );
     }
 
-    //
-    // Bytes codec
-    //
+    #[test]
+    fn timestamp_codecs_should_discard_fractional_units() {
+        let time = UNIX_EPOCH + Duration::from_millis(1500);
+        assert_eq!(
+            unix_time32().encode(&time).unwrap(),
+            byte_vector!(0, 0, 0, 1)
+        );
+
+        // Times before the epoch are rounded toward the past
+        let time = UNIX_EPOCH - Duration::from_millis(1500);
+        assert_eq!(
+            unix_time64().encode(&time).unwrap(),
+            byte_vector!(0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe)
+        );
+    }
 
     #[test]
-    fn a_byte_vector_codec_should_round_trip() {
-        let input = byte_vector!(7, 1, 2, 3, 4);
-        assert_round_trip(bytes(5), &input, &Some(input.clone()));
+    fn timestamp_codecs_should_reject_out_of_range_times() {
+        let time = UNIX_EPOCH - Duration::from_secs(1);
+        assert!(unix_time32()
+            .encode(&time)
+            .unwrap_err()
+            .message()
+            .ends_with("is outside the range of 32-bit Unix timestamps"));
+
+        let time = UNIX_EPOCH + Duration::from_secs(1 << 32);
+        assert!(unix_time32().encode(&time).is_err());
+        assert!(unix_time64().encode(&time).is_ok());
+
+        let time = UNIX_EPOCH + Duration::from_secs(i64::MAX as u64 / 1000 + 1);
+        assert!(unix_time64().encode(&time).is_ok());
+        assert!(unix_time_millis64()
+            .encode(&time)
+            .unwrap_err()
+            .message()
+            .ends_with("is outside the range of 64-bit millisecond Unix timestamps"));
     }
 
     #[test]
-    fn decoding_with_byte_vector_codec_should_return_remainder_that_had_len_bytes_dropped() {
-        let input = byte_vector!(7, 1, 2, 3, 4);
-        let codec = bytes(3);
-        match codec.decode(&input) {
-            Ok(result) => {
-                assert_eq!(result.value, byte_vector!(7, 1, 2));
-                assert_eq!(result.remainder, byte_vector!(3, 4));
-            }
-            Err(e) => panic!("Decoding failed: {}", e.message()),
-        }
+    fn duration_codecs_should_round_trip() {
+        assert_round_trip(
+            duration_secs(uint16),
+            &Duration::from_secs(300),
+            &Some(byte_vector!(0x01, 0x2c)),
+        );
+        assert_round_trip(
+            duration_millis(vuint::<u64>()),
+            &Duration::from_millis(1500),
+            &Some(byte_vector!(0xdc, 0x0b)),
+        );
     }
 
     #[test]
-    fn decoding_with_byte_vector_codec_should_fail_when_vector_has_less_space_than_given_length() {
-        let input = byte_vector!(1, 2);
-        let codec = bytes(4);
+    fn duration_codecs_should_discard_fractional_units() {
+        let duration = Duration::from_millis(2999);
         assert_eq!(
-            codec.decode(&input).unwrap_err().message(),
-            "Requested view offset of 0 and length 4 bytes exceeds vector length of 2"
+            duration_secs(uint8).encode(&duration).unwrap(),
+            byte_vector!(2)
+        );
+        assert_eq!(
+            duration_millis(uint16)
+                .encode(&Duration::from_micros(1999))
+                .unwrap(),
+            byte_vector!(0, 1)
+        );
+    }
+
+    #[test]
+    fn duration_codecs_should_reject_durations_that_are_too_long() {
+        assert_eq!(
+            duration_secs(uint8)
+                .encode(&Duration::from_secs(256))
+                .unwrap_err()
+                .message(),
+            "Duration of 256 seconds is greater than maximum value (255) of integer type"
         );
+        assert!(duration_millis(uint64)
+            .encode(&Duration::from_secs(u64::MAX))
+            .is_err());
+    }
+
+    #[test]
+    fn duration_codecs_should_fail_to_decode_units_wider_than_64_bits() {
+        assert!(duration_secs(provide(u128::MAX))
+            .decode(&byte_vector!())
+            .is_err());
+        assert!(duration_millis(provide(u128::MAX))
+            .decode(&byte_vector!())
+            .is_err());
     }
 
     //
-    // Fixed size bytes codec
+    // Encoding into buffers
     //
 
     #[test]
-    fn a_fixed_size_bytes_codec_should_round_trip() {
-        let codec = fixed_size_bytes(1, uint8);
-        assert_round_trip(codec, &7u8, &Some(byte_vector!(7)));
+    fn encode_into_should_append_to_existing_bytes() {
+        let codec = (uint8, utf8_8(), vector_of_n(uint8, uint16_l));
+        let mut out = vec![9];
+        codec
+            .encode_into(&(1, "hi".to_string(), vec![0x0302]), &mut out)
+            .unwrap();
+        assert_eq!(out, vec![9, 1, 2, b'h', b'i', 1, 2, 3]);
     }
 
     #[test]
-    fn encoding_with_fixed_size_codec_should_pad_with_zeros_when_value_is_smaller_than_given_length(
-    ) {
-        let codec = fixed_size_bytes(3, uint8);
-        assert_round_trip(codec, &7u8, &Some(byte_vector!(7, 0, 0)));
+    fn encode_into_should_discard_partial_output_from_failed_choices() {
+        // The first codec writes its first byte before failing on the second element
+        let primary = || (uint8, strictly_validated(uint8, |v| *v < 10, "small"));
+        let secondary = || (uint8, uint8.xmap(|v| v - 10, |v| v + 10));
+
+        let mut out = vec![9];
+        choice(vec![primary().boxed(), secondary().boxed()])
+            .encode_into(&(1, 20), &mut out)
+            .unwrap();
+        assert_eq!(out, vec![9, 1, 30]);
+
+        let mut out = vec![9];
+        fallback(primary(), secondary())
+            .encode_into(&(1, 20), &mut out)
+            .unwrap();
+        assert_eq!(out, vec![9, 1, 30]);
+
+        let mut out = vec![9];
+        assert!(choice(vec![primary()])
+            .encode_into(&(1, 20), &mut out)
+            .is_err());
+        assert_eq!(out, vec![9]);
     }
 
-    #[test]
-    fn encoding_with_fixed_size_codec_should_fail_when_value_needs_more_space_than_given_length() {
-        let codec = fixed_size_bytes(1, constant(&byte_vector!(6, 6, 6)));
-        assert_eq!(
-            codec.encode(&()).unwrap_err().message(),
-            "Encoding requires 3 bytes but codec is limited to fixed length of 1"
-        );
+    //
+    // Encoding to writers
+    //
+
+    // Writer that records the size of each write, and fails once `limit` bytes have been written
+    struct ChunkWriter {
+        bytes: Vec<u8>,
+        writes: Vec<usize>,
+        limit: usize,
     }
 
-    #[test]
-    fn decoding_with_fixed_size_codec_should_return_remainder_that_had_len_bytes_dropped() {
-        let input = byte_vector!(7, 1, 2, 3, 4);
-        let codec = fixed_size_bytes(3, uint8);
-        match codec.decode(&input) {
-            Ok(result) => {
-                assert_eq!(result.value, 7u8);
-                assert_eq!(result.remainder, byte_vector!(3, 4));
+    impl std::io::Write for ChunkWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.bytes.len() + buf.len() > self.limit {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionReset,
+                    "connection reset",
+                ));
             }
-            Err(e) => panic!("Decoding failed: {}", e.message()),
+            self.bytes.extend_from_slice(buf);
+            self.writes.push(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn chunk_writer(limit: usize) -> ChunkWriter {
+        ChunkWriter {
+            bytes: Vec::new(),
+            writes: Vec::new(),
+            limit,
         }
     }
 
     #[test]
-    fn decoding_with_fixed_size_codec_should_fail_when_vector_has_less_space_than_given_length() {
-        let input = byte_vector!(1, 2);
-        let codec = fixed_size_bytes(4, bytes(6));
+    fn encode_to_should_write_each_part_in_turn() {
+        let codec = hcodec!(
+            { "id"     => uint16                       } ::
+            { "name"   => utf8_8()                     } ::
+            { "values" => vector_of_n(uint8, uint16_l) }
+        );
+        let value = hlist!(7, "hi".to_string(), vec![0x0302, 0x0504]);
+        let mut out = chunk_writer(usize::MAX);
+        codec.encode_to(&value, &mut out).unwrap();
+        assert_eq!(out.bytes, codec.encode(&value).unwrap().to_vec().unwrap());
+        assert_eq!(out.writes, vec![2, 3, 1, 2, 2]);
+
+        // Byte vector values are written without copying them into a single buffer
+        let bytes = byte_vector::append(&byte_vector!(1, 2), &byte_vector!(3));
+        let mut out = chunk_writer(usize::MAX);
+        identity_bytes().encode_to(&bytes, &mut out).unwrap();
+        assert_eq!(out.bytes, vec![1, 2, 3]);
+        assert_eq!(out.writes, vec![2, 1]);
+
+        // Trait objects can be written to any writer
+        let boxed: Box<dyn Codec<Value = u32>> = Box::new(uint32);
+        let mut out = Vec::new();
+        boxed.encode_to(&0x01020304, &mut out).unwrap();
+        assert_eq!(out, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn encode_to_should_report_write_failures() {
+        let codec = hcodec!({ "id" => uint16 } :: { "name" => utf8_8() });
+        let mut out = chunk_writer(3);
+        let err = codec
+            .encode_to(&hlist!(7, "hi".to_string()), &mut out)
+            .unwrap_err();
         assert_eq!(
-            codec.decode(&input).unwrap_err().message(),
-            "Requested view offset of 0 and length 4 bytes exceeds vector length of 2"
+            err.message(),
+            "name: Failed to write bytes: connection reset"
         );
+        assert_eq!(out.bytes, vec![0, 7]);
     }
 
     //
-    // Variable size bytes codec
+    // Decoding from slices
     //
 
     #[test]
-    fn a_variable_size_bytes_codec_should_round_trip() {
-        let input = byte_vector!(7, 1, 2, 3, 4);
-        let codec = variable_size_bytes(uint16, identity_bytes());
-        assert_round_trip(codec, &input, &Some(byte_vector!(0, 5, 7, 1, 2, 3, 4)));
+    fn decode_slice_should_return_the_number_of_bytes_consumed() {
+        let codec = (uint8, utf8_8(), vector_of_n(uint8, uint16_l));
+        let input = [1, 2, b'h', b'i', 1, 2, 3, 9, 9];
+        assert_eq!(
+            codec.decode_slice(&input).unwrap(),
+            ((1, "hi".to_string(), vec![0x0302]), 7)
+        );
+        assert_eq!(
+            vuint::<u32>().decode_slice(&[0x96, 0x01, 7]).unwrap(),
+            (150, 2)
+        );
+        assert_eq!(uint24.decode_slice(&[1, 2, 3, 4]).unwrap(), (0x010203, 3));
     }
 
     #[test]
-    fn encoding_with_variable_size_codec_should_fail_when_length_of_encoded_value_is_too_large() {
-        let input = byte_vector::fill(0x7, 256);
-        let codec = variable_size_bytes(uint8, identity_bytes());
-        assert_eq!(codec.encode(&input).unwrap_err().message(), "Length of encoded value (256 bytes) is greater than maximum value (255) of length type");
+    fn decode_slice_should_fail_on_insufficient_data() {
+        assert_eq!(
+            uint32.decode_slice(&[1, 2]).unwrap_err().message(),
+            "Requested read offset of 0 and length 4 bytes exceeds vector length of 2"
+        );
+        assert!((uint8, utf8_8()).decode_slice(&[1, 3, b'a']).is_err());
+        assert!(vuint::<u32>().decode_slice(&[0x96]).is_err());
     }
 
-    // #[bench]
-    // fn bench_enc_variable_size_bytes(b: &mut Bencher) {
-    //     let input = byte_vector!(7, 1, 2, 3, 4);
-    //     let codec = variable_size_bytes(uint16, identity_bytes());
-    //     b.iter(|| codec.encode(&input));
-    // }
+    #[test]
+    fn insufficient_data_errors_should_report_the_number_of_bytes_needed() {
+        use crate::error::InsufficientBytes;
 
-    // #[bench]
-    // fn bench_dec_variable_size_bytes(b: &mut Bencher) {
-    //     let input = byte_vector!(0, 5, 7, 1, 2, 3, 4);
-    //     let codec = variable_size_bytes(uint16, identity_bytes());
-    //     b.iter(|| codec.decode(&input));
-    // }
+        // The counts are relative to the start of the input
+        let codec = hcodec!({ uint8 } :: { "value" => uint32 });
+        let expected = Some(InsufficientBytes { have: 3, needed: 5 });
+        assert_eq!(
+            codec
+                .decode(&byte_vector!(1, 2, 3))
+                .unwrap_err()
+                .insufficient_bytes(),
+            expected
+        );
+        assert_eq!(
+            codec
+                .decode_slice(&[1, 2, 3])
+                .unwrap_err()
+                .insufficient_bytes(),
+            expected
+        );
+        assert_eq!(
+            variable_size_bytes(uint8, identity_bytes())
+                .decode(&byte_vector!(4, 1))
+                .unwrap_err()
+                .insufficient_bytes(),
+            Some(InsufficientBytes { have: 2, needed: 5 })
+        );
 
-    //
-    // Eager bytes codec
-    //
+        // Running out of input within a region of known length is not recoverable
+        assert_eq!(
+            fixed_size_bytes(2, uint32)
+                .decode(&byte_vector!(1, 2, 3))
+                .unwrap_err()
+                .insufficient_bytes(),
+            None
+        );
+    }
 
     #[test]
-    fn an_eager_codec_should_round_trip() {
-        let input = vec![7, 1, 2, 3, 4];
-        let codec = eager(variable_size_bytes(uint16, identity_bytes()));
-        assert_round_trip(codec, &input, &Some(byte_vector!(0, 5, 7, 1, 2, 3, 4)));
+    fn decode_slice_should_fall_back_to_decode_for_other_codecs() {
+        use crate::checksum::Sum8;
+
+        let codec = checksummed(uint16, uint8, Sum8::default());
+        assert_eq!(codec.decode_slice(&[1, 2, 3, 4]).unwrap(), (0x0102, 3));
     }
 
     //
@@ -1164,18 +10440,94 @@ mod tests {
     #[allow(unused_parens)]
     #[test]
     fn context_should_be_pushed_when_using_the_bitor_operator() {
-        // TODO: This test is temporarily written using with_context() rather than the `|` operator
-        // while we figure out a solution for the operator overloading issues
+        let input = byte_vector::empty();
+        let codec = Ctx("section") | (Ctx("header") | (Ctx("magic") | uint8));
+
+        // Verify that the error message is prefexed with the correct context
+        assert_eq!(codec.decode(&input).unwrap_err().message(), "section/header/magic: Requested read offset of 0 and length 1 bytes exceeds vector length of 0");
+    }
+
+    #[test]
+    fn context_should_be_pushed_when_using_with_context() {
         let input = byte_vector::empty();
         let codec = with_context(
             "section",
             with_context("header", with_context("magic", uint8)),
         );
-
-        // Verify that the error message is prefexed with the correct context
         assert_eq!(codec.decode(&input).unwrap_err().message(), "section/header/magic: Requested read offset of 0 and length 1 bytes exceeds vector length of 0");
     }
 
+    #[test]
+    fn decode_errors_should_report_the_offset_of_the_failing_part() {
+        let codec = hcodec!(
+            { "magic"   => constant(byte_vector!(0xca, 0xfe))                          } ::
+            { "entries" => vector_of_n(uint8, variable_size_bytes(uint8, utf8()))      }
+        );
+        let input = byte_vector!(0xff, 0xca, 0xfe, 2, 1, b'a', 2, 0xff, 0xff);
+        let mut cursor = crate::cursor::DecodeCursor::new(input.clone());
+        cursor.seek(1).unwrap();
+
+        // The innermost failing part is the second element
+        let error = cursor.decode(&codec).unwrap_err();
+        assert_eq!(error.offset(), Some(6));
+        assert!(error.message().starts_with("entries: "));
+        assert!(error.message().ends_with(" (at offset 6)"));
+
+        // Offsets are not recorded without a cursor
+        let input = input.drop(1).unwrap();
+        assert_eq!(codec.decode(&input).unwrap_err().offset(), None);
+        assert_eq!(
+            codec
+                .decode_slice(&input.to_vec().unwrap())
+                .unwrap_err()
+                .offset(),
+            None
+        );
+
+        // Errors from transformed input are reported at the start of the field containing it
+        let codec = hcodec!(
+            { "len"   => uint8                                            } ::
+            { "value" => delimited_escaped(uint16, byte_vector!(0), 0xff) }
+        );
+        let mut cursor = crate::cursor::DecodeCursor::new(byte_vector!(1, 2, 0));
+        assert_eq!(cursor.decode(&codec).unwrap_err().offset(), Some(1));
+    }
+
+    //
+    // Instrumented codec
+    //
+
+    #[test]
+    fn instrumented_codec_should_record_each_operation() {
+        use crate::metrics::{CodecMetrics, Operation};
+
+        let metrics = CodecMetrics::new();
+        let codec = || {
+            instrumented(
+                "pair",
+                (uint8, instrumented("port", uint16, &metrics)),
+                &metrics,
+            )
+        };
+        assert_round_trip(codec(), &(1, 0x1234), &Some(byte_vector!(1, 0x12, 0x34)));
+
+        // `assert_round_trip` encodes three times (via `encode`, `encode_into` and `encode_to`) and
+        // decodes twice (via `decode` and `decode_slice`)
+        let pair_encode = metrics.stats("pair", Operation::Encode);
+        assert_eq!((pair_encode.count, pair_encode.bytes), (3, 9));
+        let pair_decode = metrics.stats("pair", Operation::Decode);
+        assert_eq!((pair_decode.count, pair_decode.bytes), (2, 6));
+        let port_decode = metrics.stats("port", Operation::Decode);
+        assert_eq!((port_decode.count, port_decode.bytes), (2, 4));
+
+        assert!(codec().decode(&byte_vector!(1, 2)).is_err());
+        let pair_decode = metrics.stats("pair", Operation::Decode);
+        assert_eq!(
+            (pair_decode.count, pair_decode.failures, pair_decode.bytes),
+            (3, 1, 6)
+        );
+    }
+
     //
     // HList-related codecs
     //
@@ -1226,6 +10578,88 @@ mod tests {
         assert_eq!(decoded, hlist!(1, 2));
     }
 
+    #[test]
+    fn an_hlist_concat_codec_should_round_trip() {
+        let header = || hcodec!({ "version" => uint8 } :: { "flags" => uint16 });
+        let body = || hcodec!({ "name" => utf8_8() } :: { "count" => uint8 });
+        let codec = || hlist_concat_codec(header(), body());
+        assert_round_trip(
+            codec(),
+            &hlist!(1u8, 0x0203u16, "hi".to_string(), 4u8),
+            &Some(byte_vector!(1, 2, 3, 2, b'h', b'i', 4)),
+        );
+        assert_eq!(codec().size_bound(), SizeBound::at_least(5));
+        assert_eq!(codec().describe().children.len(), 4);
+
+        // Sections may themselves be concatenations
+        let codec = hlist_concat_codec(codec(), hcodec!({ uint8 }));
+        let decoded = codec.decode(&byte_vector!(1, 2, 3, 0, 4, 5, 6)).unwrap();
+        assert_eq!(
+            decoded.value,
+            hlist!(1u8, 0x0203u16, String::new(), 4u8, 5u8)
+        );
+        assert_eq!(decoded.remainder, byte_vector!(6));
+    }
+
+    #[test]
+    fn hlists_should_convert_to_and_from_tuples() {
+        assert_eq!(HNil.to_tuple(), ());
+        assert_eq!(hlist!(1u8).to_tuple(), (1u8,));
+        assert_eq!(hlist!(1u8, "two", 3.0).to_tuple(), (1u8, "two", 3.0));
+        assert_eq!(
+            <HCons<u8, HCons<&str, HNil>>>::from_tuple((1, "two")),
+            hlist!(1u8, "two")
+        );
+    }
+
+    #[test]
+    fn a_tuple_codec_should_convert_hlist_values() {
+        let codec = || tuple_codec(hcodec!({ uint8 } :: { "flags" => uint16 } :: { bytes(2) }));
+        assert_round_trip(
+            codec(),
+            &(1, 0x0203, byte_vector!(4, 5)),
+            &Some(byte_vector!(1, 2, 3, 4, 5)),
+        );
+
+        let (version, flags, data) = codec().decode_all(&[1, 2, 3, 4, 5]).unwrap();
+        assert_eq!((version, flags, data), (1, 0x0203, byte_vector!(4, 5)));
+        assert_eq!(codec().describe().children.len(), 3);
+    }
+
+    #[test]
+    fn an_hlist_flat_concat_codec_should_round_trip() {
+        let codec = || {
+            hlist_flat_concat_codec(hcodec!({ uint8 } :: { uint8 }), |hlist_pat!(a, b)| {
+                hcodec!({ bytes((*a + *b) as usize) })
+            })
+        };
+        assert_round_trip(
+            codec(),
+            &hlist!(1u8, 2u8, byte_vector!(7, 8, 9)),
+            &Some(byte_vector!(1, 2, 7, 8, 9)),
+        );
+        assert!(codec().decode(&byte_vector!(1, 2, 7, 8)).is_err());
+    }
+
+    #[test]
+    fn the_hcodec_macro_should_support_flat_prepend_with_multiple_fields() {
+        let m = byte_vector!(0xCA, 0xFE);
+        let codec = hcodec!(
+            { "magic"   => constant(&m) } >>
+            { "version" => uint8        } ::
+            { "count"   => uint8        } ::
+            { "width"   => uint8        } >>= |hlist_pat!(version, count, width)| {
+                let width = if *version > 1 { *width as usize } else { 1 };
+                hcodec!({ "items" => repeat(*count as usize, bytes(width)) })
+            }
+        );
+        assert_round_trip(
+            codec,
+            &hlist!(2u8, 2u8, 2u8, vec![byte_vector!(1, 2), byte_vector!(3, 4)]),
+            &Some(byte_vector!(0xCA, 0xFE, 2, 2, 2, 1, 2, 3, 4)),
+        );
+    }
+
     // This is implemented as a macro as otherwise we'd have to write out an explicit return type
     // and good luck with that...
     macro_rules! make_test_hcodec {
@@ -1296,6 +10730,249 @@ mod tests {
         );
     }
 
+    #[test]
+    fn a_struct_codec_should_prefix_errors_with_the_struct_and_field_names() {
+        let codec = struct_codec!(
+            TestStruct1 from
+            { "byte1" => uint8 } ::
+            { "byte2" => drop_left(constant(byte_vector!(1)), uint8) }
+        );
+        let error = codec.decode(&byte_vector!(7, 2, 3)).unwrap_err();
+        assert!(error.message().starts_with("TestStruct1/byte2: "));
+        let error = crate::cursor::DecodeCursor::new(byte_vector!(7, 2, 3))
+            .decode(&codec)
+            .unwrap_err();
+        assert_eq!(error.offset(), Some(1));
+
+        // Labels of nested codecs follow the name of the field that contains them
+        let codec = hcodec!(
+            { "header" => struct_codec!(TestStruct1 from { "byte1" => uint8 } :: { "byte2" => uint8 }) }
+        );
+        assert!(codec
+            .decode(&byte_vector!(7))
+            .unwrap_err()
+            .message()
+            .starts_with("header/TestStruct1/byte2: "));
+
+        // Unlabeled fields are identified by their position among the struct's fields
+        let codec = struct_codec!(TestStruct1 from {uint8} :: {uint8});
+        assert!(codec
+            .decode(&byte_vector!(7))
+            .unwrap_err()
+            .message()
+            .starts_with("TestStruct1/1: "));
+        let codec = struct_codec!(
+            TestStruct1 from
+            { constant(byte_vector!(1)) } >>
+            { "byte1" => uint8 } ::
+            { uint8 }
+        );
+        assert!(codec
+            .decode(&byte_vector!(1, 7))
+            .unwrap_err()
+            .message()
+            .starts_with("TestStruct1/1: "));
+        assert!(codec
+            .decode(&byte_vector!(2, 7, 3))
+            .unwrap_err()
+            .message()
+            .starts_with("TestStruct1: "));
+    }
+
+    //
+    // Struct codec builder
+    //
+
+    #[derive(Debug, PartialEq)]
+    struct TestBuiltStruct {
+        version: u8,
+        name: String,
+        flags: u16,
+    }
+
+    fn test_struct_builder() -> StructCodecBuilder<TestBuiltStruct> {
+        StructCodecBuilder::new()
+            .field("version", uint8, |s: &TestBuiltStruct| &s.version)
+            .field("name", utf8_8(), |s: &TestBuiltStruct| &s.name)
+            .field("flags", uint16, |s: &TestBuiltStruct| &s.flags)
+    }
+
+    #[test]
+    fn a_built_struct_codec_should_round_trip() {
+        let codec = test_struct_builder().build(|fields| {
+            Ok(TestBuiltStruct {
+                version: fields.take("version")?,
+                name: fields.take("name")?,
+                flags: fields.take("flags")?,
+            })
+        });
+        assert_round_trip(
+            codec,
+            &TestBuiltStruct {
+                version: 1,
+                name: "hi".to_string(),
+                flags: 0x0203,
+            },
+            &Some(byte_vector!(1, 2, b'h', b'i', 2, 3)),
+        );
+    }
+
+    #[test]
+    fn a_built_struct_codec_should_describe_its_fields() {
+        let codec = test_struct_builder().build(|_| Err(Error::new("unused".to_string())));
+        assert_eq!(codec.size_bound(), SizeBound::at_least(4));
+        let description = codec.describe();
+        assert!(description.name.ends_with("TestBuiltStruct"));
+        let names: Vec<_> = description
+            .children
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["version", "name", "flags"]);
+
+        // Decoding errors are prefixed with the name of the field
+        assert_eq!(
+            codec
+                .decode(&byte_vector!(1, 2, b'h', b'i', 2))
+                .unwrap_err()
+                .message(),
+            "flags: Requested read offset of 0 and length 2 bytes exceeds vector length of 1"
+        );
+    }
+
+    #[test]
+    fn decoded_fields_should_report_misuse() {
+        let decode_with = |take: fn(&mut DecodedFields) -> Result<u8, Error>| {
+            StructCodecBuilder::new()
+                .field("version", uint8, |v: &u8| v)
+                .build(take)
+                .decode(&byte_vector!(1))
+                .map(|decoded| decoded.value)
+        };
+        assert_eq!(decode_with(|fields| fields.take("version")).unwrap(), 1);
+        assert_eq!(
+            decode_with(|fields| fields.take("flags"))
+                .unwrap_err()
+                .message(),
+            "No field named \"flags\""
+        );
+        assert_eq!(
+            decode_with(|fields| fields.take::<u16>("version").map(|v| v as u8))
+                .unwrap_err()
+                .message(),
+            "Field \"version\" is not of type u16"
+        );
+        assert_eq!(
+            decode_with(|fields| {
+                fields.take::<u8>("version")?;
+                fields.take("version")
+            })
+            .unwrap_err()
+            .message(),
+            "Value of field \"version\" was already taken"
+        );
+    }
+
+    //
+    // Default codecs
+    //
+
+    #[test]
+    fn default_codecs_should_round_trip() {
+        assert_round_trip(default_codec(), &0x0102u16, &Some(byte_vector!(1, 2)));
+        assert_round_trip(
+            default_codec(),
+            &(-1i8, "hi".to_string()),
+            &Some(byte_vector!(0xff, 0, 0, 0, 2, b'h', b'i')),
+        );
+        assert_round_trip(
+            default_codec(),
+            &vec![hlist!(1u8, 2.0f32)],
+            &Some(byte_vector!(0, 0, 0, 1, 1, 0x40, 0, 0, 0)),
+        );
+        assert_round_trip(
+            default_codec(),
+            &byte_vector!(7, 8),
+            &Some(byte_vector!(0, 0, 0, 2, 7, 8)),
+        );
+    }
+
+    //
+    // Codec descriptions
+    //
+
+    #[test]
+    fn describe_should_return_a_tree_of_fields() {
+        let codec = struct_codec!(TestStruct1 from { "byte1" => uint8 } :: { "byte2" => uint8 });
+        let description = codec.describe();
+        assert!(description.name.ends_with("TestStruct1"));
+        assert_eq!(description.fixed_size(), Some(2));
+        assert_eq!(
+            description.children,
+            vec![
+                CodecDescription::new("byte1", SizeBound::exact(1))
+                    .with_child(CodecDescription::new("uint8", SizeBound::exact(1))),
+                CodecDescription::new("byte2", SizeBound::exact(1))
+                    .with_child(CodecDescription::new("uint8", SizeBound::exact(1))),
+            ]
+        );
+    }
+
+    #[test]
+    fn describe_should_identify_built_in_codecs() {
+        assert_eq!(int64.describe().name, "int64");
+        assert_eq!(uint24_l.describe().name, "uint24_l");
+        assert_eq!(float32_l.describe().name, "float32_l");
+        assert_eq!(
+            float64.describe().byte_ordering,
+            Some(ByteOrdering::BigEndian)
+        );
+        assert_eq!(uint8.describe().byte_ordering, None);
+
+        let description = variable_size_bytes(uint8, list(utf8())).describe();
+        assert_eq!(description.name, "variable_size_bytes");
+        assert_eq!(description.fixed_size(), None);
+        let names: Vec<_> = description
+            .children
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["uint8", "list"]);
+    }
+
+    #[test]
+    fn describe_should_fall_back_to_type_name_for_custom_codecs() {
+        struct Custom;
+
+        impl Codec for Custom {
+            type Value = ();
+
+            fn encode(&self, _value: &()) -> EncodeResult {
+                Ok(byte_vector::empty())
+            }
+
+            fn decode(&self, bv: &ByteVector) -> DecodeResult<()> {
+                Ok(DecoderResult {
+                    value: (),
+                    remainder: bv.clone(),
+                })
+            }
+        }
+
+        let description = (uint8, Custom).describe();
+        assert!(description.children[1].name.ends_with("Custom"));
+        assert_eq!(description.size, SizeBound::at_least(1));
+    }
+
+    #[test]
+    fn descriptions_should_display_as_an_indented_tree() {
+        let codec = (uint16, vuint::<u32>(), utf8());
+        assert_eq!(
+            codec.describe().to_string(),
+            "tuple: at least 3 bytes\n  uint16 (big-endian): 2 bytes\n  vuint: 1..=5 bytes\n  utf8: variable size\n"
+        );
+    }
+
     //
     // Boxed codec and static ref support
     //
@@ -1323,4 +11000,45 @@ mod tests {
             &Some(byte_vector!(0x12, 0x34, 0x56, 0x78)),
         );
     }
+
+    fn assert_send_sync<C: Codec + Send + Sync>(_codec: C) {}
+
+    #[test]
+    fn built_in_codecs_should_be_send_and_sync() {
+        assert_send_sync(uint8);
+        assert_send_sync(uint24_l);
+        assert_send_sync(float32_with(ByteOrdering::LittleEndian));
+        assert_send_sync(float64_l);
+        assert_send_sync(constant(b"PK"));
+        assert_send_sync(delimited(utf8(), "\n"));
+        assert_send_sync(lazily(|| uint16));
+        assert_send_sync(vector_of_n(uint8, utf8_16()));
+        assert_send_sync(chunk_with_crc(constant(b"IEND"), ignore(0), crc::CRC_32));
+        assert_send_sync(instrumented(
+            "port",
+            uint16,
+            Arc::new(crate::metrics::CodecMetrics::new()),
+        ));
+        assert_send_sync(hcodec!({ "magic" => constant("PK") } >> { uint8 } :: { float32 }));
+    }
+
+    #[test]
+    fn sync_codecs_should_be_shareable_between_threads() {
+        let codec: SharedSyncCodec<'static, u16> = Arc::new(uint16.with_context("port"));
+        let handles: Vec<_> = (0..4u16)
+            .map(|i| {
+                let codec = codec.clone();
+                std::thread::spawn(move || codec.encode_to_vec(&i).unwrap())
+            })
+            .collect();
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.join().unwrap(), vec![0, i as u8]);
+        }
+
+        let boxed: BoxedSyncCodec<'static, ()> = Box::new(constant(b"PK"));
+        let decoded = std::thread::spawn(move || boxed.decode_all(b"PK"))
+            .join()
+            .unwrap();
+        assert!(decoded.is_ok());
+    }
 }
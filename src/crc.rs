@@ -0,0 +1,226 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+use crate::checksum::Checksum;
+
+/// Parameters describing a CRC algorithm, using the conventions of the "Rocksoft" model (as used
+/// by the CRC RevEng catalogue).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crc {
+    /// The width of the CRC in bits, between 8 and 64 (inclusive).
+    pub width: u8,
+
+    /// The generator polynomial, without the leading (implicit) bit.
+    pub poly: u64,
+
+    /// The initial value of the CRC register.
+    pub init: u64,
+
+    /// If true, each input byte is bit-reversed before being processed.
+    pub reflect_in: bool,
+
+    /// If true, the final CRC register is bit-reversed before `xor_out` is applied.
+    pub reflect_out: bool,
+
+    /// The value that is XORed with the final CRC register.
+    pub xor_out: u64,
+}
+
+/// CRC-16/ARC (also known as CRC-16/IBM), as used by LHA and many serial protocols.
+pub const CRC_16_ARC: Crc = Crc {
+    width: 16,
+    poly: 0x8005,
+    init: 0x0000,
+    reflect_in: true,
+    reflect_out: true,
+    xor_out: 0x0000,
+};
+
+/// CRC-16/CCITT-FALSE (also known as CRC-16/IBM-3740).
+pub const CRC_16_CCITT_FALSE: Crc = Crc {
+    width: 16,
+    poly: 0x1021,
+    init: 0xffff,
+    reflect_in: false,
+    reflect_out: false,
+    xor_out: 0x0000,
+};
+
+/// CRC-16/XMODEM, as used by the XMODEM and ZMODEM protocols.
+pub const CRC_16_XMODEM: Crc = Crc {
+    width: 16,
+    poly: 0x1021,
+    init: 0x0000,
+    reflect_in: false,
+    reflect_out: false,
+    xor_out: 0x0000,
+};
+
+/// CRC-16/MODBUS, as used by the Modbus RTU protocol.
+pub const CRC_16_MODBUS: Crc = Crc {
+    width: 16,
+    poly: 0x8005,
+    init: 0xffff,
+    reflect_in: true,
+    reflect_out: true,
+    xor_out: 0x0000,
+};
+
+/// CRC-32 (also known as CRC-32/ISO-HDLC), as used by Ethernet, zlib, PNG, and ZIP.
+pub const CRC_32: Crc = Crc {
+    width: 32,
+    poly: 0x04c1_1db7,
+    init: 0xffff_ffff,
+    reflect_in: true,
+    reflect_out: true,
+    xor_out: 0xffff_ffff,
+};
+
+/// CRC-32C (also known as CRC-32/ISCSI), as used by iSCSI, SCTP, and ext4.
+pub const CRC_32C: Crc = Crc {
+    width: 32,
+    poly: 0x1edc_6f41,
+    init: 0xffff_ffff,
+    reflect_in: true,
+    reflect_out: true,
+    xor_out: 0xffff_ffff,
+};
+
+/// CRC-32/BZIP2, as used by bzip2.
+pub const CRC_32_BZIP2: Crc = Crc {
+    width: 32,
+    poly: 0x04c1_1db7,
+    init: 0xffff_ffff,
+    reflect_in: false,
+    reflect_out: false,
+    xor_out: 0xffff_ffff,
+};
+
+/// CRC-32/MPEG-2, as used by MPEG transport streams.
+pub const CRC_32_MPEG2: Crc = Crc {
+    width: 32,
+    poly: 0x04c1_1db7,
+    init: 0xffff_ffff,
+    reflect_in: false,
+    reflect_out: false,
+    xor_out: 0x0000_0000,
+};
+
+impl Crc {
+    /// Returns the CRC of the given bytes.
+    ///
+    /// Panics if `width` is not between 8 and 64 (inclusive).
+    pub fn checksum(&self, bytes: &[u8]) -> u64 {
+        let mut digest = self.digest();
+        digest.update(bytes);
+        digest.finalize()
+    }
+
+    /// Returns a `Checksum` that computes CRCs incrementally using this algorithm.
+    ///
+    /// Panics if `width` is not between 8 and 64 (inclusive).
+    pub fn digest(&self) -> CrcDigest {
+        assert!(
+            self.width >= 8 && self.width <= 64,
+            "CRC width must be between 8 and 64"
+        );
+        CrcDigest {
+            crc: *self,
+            register: self.init & self.mask(),
+        }
+    }
+
+    /// Returns the number of bytes needed to hold a CRC value.
+    pub fn byte_len(&self) -> usize {
+        (self.width as usize).div_ceil(8)
+    }
+
+    /// Returns a mask covering the low `width` bits.
+    fn mask(&self) -> u64 {
+        u64::MAX >> (64 - self.width as u32)
+    }
+}
+
+/// Incremental CRC computation, created using `Crc::digest`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CrcDigest {
+    crc: Crc,
+    register: u64,
+}
+
+impl Checksum for CrcDigest {
+    type Output = u64;
+
+    fn update(&mut self, bytes: &[u8]) {
+        let width = self.crc.width as u32;
+        let mask = self.crc.mask();
+        let top_bit = 1u64 << (width - 1);
+
+        let mut register = self.register;
+        for &byte in bytes {
+            let byte = if self.crc.reflect_in {
+                byte.reverse_bits()
+            } else {
+                byte
+            };
+            register ^= (byte as u64) << (width - 8);
+            for _ in 0..8 {
+                register = if register & top_bit != 0 {
+                    (register << 1) ^ self.crc.poly
+                } else {
+                    register << 1
+                };
+            }
+            register &= mask;
+        }
+        self.register = register;
+    }
+
+    fn finalize(self) -> u64 {
+        let width = self.crc.width as u32;
+        let register = if self.crc.reflect_out {
+            self.register.reverse_bits() >> (64 - width)
+        } else {
+            self.register
+        };
+        (register ^ self.crc.xor_out) & self.crc.mask()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHECK_INPUT: &[u8] = b"123456789";
+
+    #[test]
+    fn presets_should_produce_catalogued_check_values() {
+        assert_eq!(CRC_16_ARC.checksum(CHECK_INPUT), 0xbb3d);
+        assert_eq!(CRC_16_CCITT_FALSE.checksum(CHECK_INPUT), 0x29b1);
+        assert_eq!(CRC_16_XMODEM.checksum(CHECK_INPUT), 0x31c3);
+        assert_eq!(CRC_16_MODBUS.checksum(CHECK_INPUT), 0x4b37);
+        assert_eq!(CRC_32.checksum(CHECK_INPUT), 0xcbf4_3926);
+        assert_eq!(CRC_32C.checksum(CHECK_INPUT), 0xe306_9283);
+        assert_eq!(CRC_32_BZIP2.checksum(CHECK_INPUT), 0xfc89_1918);
+        assert_eq!(CRC_32_MPEG2.checksum(CHECK_INPUT), 0x0376_e6e7);
+    }
+
+    #[test]
+    fn incremental_digest_should_match_single_checksum() {
+        let mut digest = CRC_32C.digest();
+        digest.update(b"1234");
+        digest.update(b"56789");
+        assert_eq!(digest.finalize(), CRC_32C.checksum(CHECK_INPUT));
+    }
+
+    #[test]
+    fn checksum_of_empty_input_should_be_init_xor_out() {
+        assert_eq!(CRC_32.checksum(&[]), 0);
+        assert_eq!(CRC_16_CCITT_FALSE.checksum(&[]), 0xffff);
+    }
+}
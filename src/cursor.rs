@@ -0,0 +1,294 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+//! Decoding with tracking of absolute offsets, for formats (such as ELF, ZIP or PDF) that refer to
+//! other parts of the input by their offset from the start.
+
+use std::cell::Cell;
+use std::thread::LocalKey;
+
+use crate::byte_vector::ByteVector;
+use crate::codec::Codec;
+use crate::error::Error;
+
+thread_local! {
+    /// The absolute offset at which the region currently being decoded ends, or `None` if no
+    /// cursor is decoding on this thread (or the input being decoded is not part of the original
+    /// input, for example because it was decompressed).
+    static REGION_END: Cell<Option<usize>> = const { Cell::new(None) };
+
+    /// The absolute offset of the input following the value passed to the `>>=` closure that is
+    /// currently being called, if any.
+    static POSITION: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Wraps an input and tracks the absolute offset of the next value to be decoded from it.
+///
+/// While a value is being decoded by `decode`, the offset of the input that follows each value is
+/// available to the closures of dependent codecs (such as `flat_zip` and the `>>=` form of
+/// `hcodec!`) by calling `current_offset`, and errors report the offset at which the innermost
+/// failing field (see `with_context`) or collection element began (see `Error::offset`).  Offsets
+/// are tracked through codecs that decode from a region of the input (such as `fixed_size_bytes`
+/// and `variable_size_bytes`), but not through codecs that transform the input before decoding it
+/// (such as escaped `delimited` values or compressed and encrypted data).
+///
+/// # Examples
+///
+/// ```
+/// use rcodec::byte_vector;
+/// use rcodec::codec::*;
+/// use rcodec::cursor::DecodeCursor;
+///
+/// // A header containing the offset of a table that appears later in the input
+/// let input = byte_vector!(0, 6, 0xff, 0xff, 0xff, 0xff, 2, 7, 8);
+/// let mut cursor = DecodeCursor::new(input);
+/// let table_offset = cursor.decode(&uint16).unwrap();
+/// assert_eq!(cursor.offset(), 2);
+///
+/// cursor.seek(table_offset as usize).unwrap();
+/// let table = cursor.decode(&vector_of_n(uint8, uint8)).unwrap();
+/// assert_eq!(table, vec![7, 8]);
+/// ```
+pub struct DecodeCursor {
+    /// The complete input.
+    input: ByteVector,
+
+    /// The absolute offset of the next value to be decoded.
+    offset: usize,
+}
+
+impl DecodeCursor {
+    /// Returns a new cursor positioned at the start of `input`.
+    pub fn new(input: ByteVector) -> DecodeCursor {
+        DecodeCursor { input, offset: 0 }
+    }
+
+    /// Returns the complete input.
+    pub fn input(&self) -> &ByteVector {
+        &self.input
+    }
+
+    /// Returns the absolute offset of the next value to be decoded.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the input following the current offset.
+    pub fn remainder(&self) -> ByteVector {
+        self.input.drop(self.offset).unwrap()
+    }
+
+    /// Moves the cursor to the given absolute offset, returning an error if it is beyond the end
+    /// of the input.
+    pub fn seek(&mut self, offset: usize) -> Result<(), Error> {
+        if offset > self.input.length() {
+            return Err(Error::new(format!(
+                "Requested offset of {} bytes exceeds input length of {}",
+                offset,
+                self.input.length()
+            )));
+        }
+        self.offset = offset;
+        Ok(())
+    }
+
+    /// Decodes a value using `codec` at the current offset, advancing the cursor past the
+    /// consumed input if successful.
+    pub fn decode<C: Codec>(&mut self, codec: &C) -> Result<C::Value, Error> {
+        let remainder = self.remainder();
+        let end = self.input.length();
+        let decoded = tracking(end, || codec.decode(&remainder))
+            .map_err(|e| e.offset_by(self.offset).at_offset(self.offset))?;
+        self.offset = end - decoded.remainder.length();
+        Ok(decoded.value)
+    }
+}
+
+/// Returns the absolute offset of the input that follows the value being passed to a dependent
+/// codec's closure (for example, the offset of the first byte after the length field in
+/// `hcodec!({ uint8 } >>= |len| { ... })`).
+///
+/// This is only available while decoding using `DecodeCursor::decode`; otherwise (including when
+/// the closure is called for encoding), `None` is returned.
+pub fn current_offset() -> Option<usize> {
+    POSITION.with(Cell::get)
+}
+
+/// Calls `f` with `key` set to `value`, restoring the previous value afterwards.
+fn scoped<R, F>(key: &'static LocalKey<Cell<Option<usize>>>, value: Option<usize>, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    // Restore the previous value even if `f` panics
+    struct Restore(&'static LocalKey<Cell<Option<usize>>>, Option<usize>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            self.0.with(|cell| cell.set(self.1));
+        }
+    }
+
+    let _restore = Restore(key, key.with(|cell| cell.replace(value)));
+    f()
+}
+
+/// Returns the absolute offset of input with `remaining` bytes left in the current region.
+pub(crate) fn offset_of(remaining: usize) -> Option<usize> {
+    REGION_END
+        .with(Cell::get)
+        .and_then(|end| end.checked_sub(remaining))
+}
+
+/// Returns `error` with the offset of input with `remaining` bytes left in the current region, if
+/// it does not already have an offset.
+pub(crate) fn locate(error: Error, remaining: usize) -> Error {
+    match offset_of(remaining) {
+        Some(offset) => error.at_offset(offset),
+        None => error,
+    }
+}
+
+/// Calls `f` to decode from an input of `len` bytes, tracking absolute offsets from its start.
+pub(crate) fn tracking<R, F>(len: usize, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    scoped(&REGION_END, Some(len), f)
+}
+
+/// Calls `f` to decode from a region of `len` bytes at the start of input with `remaining` bytes
+/// left in the current region.
+pub(crate) fn region<R, F>(remaining: usize, len: usize, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let end = offset_of(remaining).map(|start| start + len);
+    scoped(&REGION_END, end, f)
+}
+
+/// Calls `f` to decode from input that is not part of the original input (such as decompressed
+/// data), within which offsets are not tracked.
+pub(crate) fn detached<R, F>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    scoped(&REGION_END, None, f)
+}
+
+/// Calls `f`, the closure of a dependent codec, with the offset of input with `remaining` bytes
+/// left in the current region available from `current_offset`.
+pub(crate) fn with_position<R, F>(remaining: usize, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    scoped(&POSITION, offset_of(remaining), f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::*;
+    use pl_hlist::*;
+
+    #[test]
+    fn a_decode_cursor_should_track_the_offset_across_decodes() {
+        let mut cursor = DecodeCursor::new(byte_vector!(1, 2, 3, 4, 5));
+        assert_eq!(cursor.decode(&uint8).unwrap(), 1);
+        assert_eq!(cursor.decode(&uint16).unwrap(), 0x0203);
+        assert_eq!(cursor.offset(), 3);
+        assert_eq!(cursor.remainder(), byte_vector!(4, 5));
+
+        assert!(cursor.seek(6).is_err());
+        cursor.seek(1).unwrap();
+        assert_eq!(cursor.decode(&uint8).unwrap(), 2);
+
+        // A failed decode leaves the cursor in place
+        cursor.seek(4).unwrap();
+        assert_eq!(cursor.decode(&uint16).unwrap_err().offset(), Some(4));
+        assert_eq!(cursor.offset(), 4);
+    }
+
+    #[test]
+    fn a_decode_cursor_should_expose_offsets_to_dependent_codecs() {
+        let offsets = Cell::new(Vec::new());
+        let record_offset = || {
+            let mut v = offsets.take();
+            v.push(current_offset());
+            offsets.set(v);
+        };
+        let codec = hcodec!(
+            { uint8 } ::
+            { variable_size_bytes(uint8, hcodec!(
+                { uint16 } >>= |_n| {
+                    record_offset();
+                    hcodec!({ uint8 })
+                }
+            )) } >>= |hlist_pat!(_a, _b)| {
+                record_offset();
+                hcodec!({ uint8 })
+            }
+        );
+
+        // The offset is not available without a cursor
+        assert!(codec.decode(&byte_vector!(1, 3, 0, 2, 3, 4)).is_ok());
+        assert_eq!(offsets.take(), vec![None, None]);
+
+        let mut cursor = DecodeCursor::new(byte_vector!(0xff, 1, 3, 0, 2, 3, 4));
+        cursor.seek(1).unwrap();
+        assert_eq!(cursor.decode(&codec).unwrap(), hlist!(1, hlist!(2, 3), 4));
+        assert_eq!(offsets.take(), vec![Some(5), Some(6)]);
+        assert_eq!(current_offset(), None);
+    }
+
+    #[test]
+    fn a_decode_cursor_should_report_the_offset_of_the_failing_field() {
+        let codec = hcodec!(
+            { "magic"  => uint16                                   } ::
+            { "record" => variable_size_bytes(uint8, hcodec!(
+                { "kind" => uint8 } ::
+                { "name" => utf8() }
+            )) }
+        );
+        let mut cursor = DecodeCursor::new(byte_vector!(0xca, 0xfe, 3, 1, 0xff, 0xff, 9));
+        let error = cursor.decode(&codec).unwrap_err();
+        assert_eq!(error.offset(), Some(4));
+        assert!(error.message().starts_with("record/name: "));
+        assert!(error.message().ends_with(" (at offset 4)"));
+
+        // Offsets are not recorded without a cursor
+        let error = codec
+            .decode(&byte_vector!(0xca, 0xfe, 3, 1, 0xff, 0xff, 9))
+            .unwrap_err();
+        assert_eq!(error.offset(), None);
+    }
+
+    #[test]
+    fn a_decode_cursor_should_report_the_offset_of_a_failing_struct_field() {
+        let codec = StructCodecBuilder::new()
+            .field("kind", uint8, |s: &(u8, u16)| &s.0)
+            .field("len", uint16, |s: &(u8, u16)| &s.1)
+            .build(|fields| Ok((fields.take("kind")?, fields.take("len")?)));
+        let mut cursor = DecodeCursor::new(byte_vector!(0xff, 1, 2));
+        cursor.seek(1).unwrap();
+        let error = cursor.decode(&codec).unwrap_err();
+        assert_eq!(error.offset(), Some(2));
+        assert_eq!(
+            error.insufficient_bytes().map(|b| (b.have, b.needed)),
+            Some((3, 4))
+        );
+
+        // The same applies to the type of a chunk, which follows its length
+        let codec = chunk(uint32, identity_bytes());
+        let mut cursor = DecodeCursor::new(byte_vector!(0xff, 0, 0, 0, 0, 1, 2));
+        cursor.seek(1).unwrap();
+        let error = cursor.decode(&codec).unwrap_err();
+        assert_eq!(
+            error.insufficient_bytes().map(|b| (b.have, b.needed)),
+            Some((7, 9))
+        );
+    }
+}
@@ -6,6 +6,88 @@
 // Scala scodec library: https://github.com/scodec/scodec/
 //
 
+use std::cell::Cell;
+use std::fmt;
+use std::io;
+
+thread_local! {
+    /// Whether terse errors are enabled on this thread (see `with_terse_errors`).
+    static TERSE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Calls `f` with terse errors enabled on the current thread, restoring the previous setting
+/// afterwards.
+///
+/// Terse errors are cheap to construct: codecs skip formatting their descriptions (see
+/// `lazy_description`) and context is not recorded, so constructing an error does not allocate.
+/// This is useful when decoding speculatively (for example, using `choice` or `fallback`) where
+/// failures are expected and their details are discarded.  The error flags, offset, and counts
+/// of insufficient bytes are still recorded.  See also `codec::terse`.
+///
+/// # Examples
+///
+/// ```
+/// use rcodec::byte_vector;
+/// use rcodec::codec::*;
+/// use rcodec::error::with_terse_errors;
+///
+/// let error = with_terse_errors(|| uint16.decode(&byte_vector!(1))).unwrap_err();
+/// assert!(error.is_insufficient_data());
+/// assert_eq!(error.description, "");
+/// ```
+pub fn with_terse_errors<R, F>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    // Restore the previous setting even if `f` panics
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            TERSE.with(|t| t.set(self.0));
+        }
+    }
+
+    let _restore = Restore(TERSE.with(|t| t.replace(true)));
+    f()
+}
+
+/// Returns true if terse errors are enabled on the current thread.
+pub fn terse_errors_enabled() -> bool {
+    TERSE.with(Cell::get)
+}
+
+/// Returns the error description produced by `describe`, or an empty string (without calling
+/// `describe`) if terse errors are enabled on the current thread.
+#[inline]
+pub fn lazy_description<F>(describe: F) -> String
+where
+    F: FnOnce() -> String,
+{
+    if terse_errors_enabled() {
+        String::new()
+    } else {
+        describe()
+    }
+}
+
+/// The number of bytes that were available and the number that were needed when decoding failed
+/// because the input ended early (see `Error::insufficient_bytes`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InsufficientBytes {
+    /// The number of bytes that were available.
+    pub have: usize,
+
+    /// The number of bytes that were needed, which is greater than `have`.
+    pub needed: usize,
+}
+
+impl InsufficientBytes {
+    /// Returns the number of additional bytes needed.
+    pub fn remaining(&self) -> usize {
+        self.needed - self.have
+    }
+}
+
 /// Error type for codec operations.
 // TODO: Perhaps we should have separate error types for codec and byte_vector
 #[derive(Debug)]
@@ -15,6 +97,20 @@ pub struct Error {
 
     /// The stack of context strings, with outermost context identifier at the front of the vector.
     context: Vec<String>,
+
+    /// Whether the error was caused by the input ending before a complete value was decoded.
+    insufficient_data: bool,
+
+    /// The number of bytes that were available and needed, if the error was caused by the input
+    /// ending early and that number is known.
+    insufficient_bytes: Option<InsufficientBytes>,
+
+    /// The absolute offset in the input at which the error occurred, if known.
+    offset: Option<usize>,
+
+    /// Whether the error was constructed with terse errors enabled, in which case its context is
+    /// not recorded.
+    terse: bool,
 }
 
 impl Error {
@@ -23,18 +119,113 @@ impl Error {
         Error {
             description,
             context: Vec::new(),
+            insufficient_data: false,
+            insufficient_bytes: None,
+            offset: None,
+            terse: terse_errors_enabled(),
+        }
+    }
+
+    /// Return a new Error with the given description, indicating that the input ended before a
+    /// complete value could be decoded.
+    pub fn insufficient_data(description: String) -> Error {
+        Error {
+            description,
+            context: Vec::new(),
+            insufficient_data: true,
+            insufficient_bytes: None,
+            offset: None,
+            terse: terse_errors_enabled(),
+        }
+    }
+
+    /// Return a new Error with the given description, indicating that the input ended before a
+    /// complete value could be decoded because only `have` of the `needed` bytes were available.
+    pub fn short_input(description: String, have: usize, needed: usize) -> Error {
+        Error {
+            insufficient_bytes: Some(InsufficientBytes { have, needed }),
+            ..Error::insufficient_data(description)
+        }
+    }
+
+    /// Return true if this error was caused by the input ending before a complete value could be
+    /// decoded, in which case decoding may succeed once more input is available.
+    pub fn is_insufficient_data(&self) -> bool {
+        self.insufficient_data
+    }
+
+    /// Return the number of bytes that were available and needed, if this error was caused by the
+    /// input ending before a complete value could be decoded and that number is known.  The counts
+    /// are relative to the start of the input being decoded, so once `needed` bytes are available,
+    /// decoding can proceed past the point at which it failed.
+    pub fn insufficient_bytes(&self) -> Option<InsufficientBytes> {
+        self.insufficient_bytes
+    }
+
+    /// Return this error with the insufficient data flag cleared.  This is used by codecs that
+    /// decode from a region of known length, where running out of input indicates malformed data
+    /// rather than a need for more input.
+    pub(crate) fn within_bounded_input(self) -> Error {
+        Error {
+            insufficient_data: false,
+            insufficient_bytes: None,
+            ..self
+        }
+    }
+
+    /// Return the absolute offset in the input at which the error occurred, if known.  Offsets are
+    /// only tracked when decoding using a `cursor::DecodeCursor`.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Return this error with any counts of insufficient bytes increased by `start`, for a codec
+    /// that encountered the error while decoding a part of its input that begins at `start`.
+    pub(crate) fn offset_by(self, start: usize) -> Error {
+        Error {
+            insufficient_bytes: self.insufficient_bytes.map(|bytes| InsufficientBytes {
+                have: bytes.have + start,
+                needed: bytes.needed + start,
+            }),
+            ..self
         }
     }
 
-    /// Return a human-readable error message that includes context, if any.
+    /// Return this error with the given offset, unless an offset (from a more deeply nested
+    /// codec) has already been recorded.
+    pub(crate) fn at_offset(self, offset: usize) -> Error {
+        Error {
+            offset: self.offset.or(Some(offset)),
+            ..self
+        }
+    }
+
+    /// Return true if this error was constructed with terse errors enabled (see
+    /// `with_terse_errors`), in which case its context (and usually its description) is omitted.
+    pub fn is_terse(&self) -> bool {
+        self.terse
+    }
+
+    /// Return a human-readable error message that includes context and offset, if any.
     pub fn message(&self) -> String {
-        if self.context.is_empty() {
+        let message = self.message_without_offset();
+        match self.offset {
+            Some(offset) => format!("{} (at offset {})", message, offset),
+            None => message,
+        }
+    }
+
+    /// Return the error message including context, if any.
+    fn message_without_offset(&self) -> String {
+        if self.terse && self.description.is_empty() {
+            "Details omitted from terse error".to_string()
+        } else if self.context.is_empty() {
             self.description.clone()
         } else {
             // TODO: Implement a proper string joiner
             let ctx = self.context.iter().fold(String::new(), |mut a, b| {
                 if !a.is_empty() {
-                    a.push_str("/");
+                    a.push('/');
                 }
                 a + b
             });
@@ -45,14 +236,59 @@ impl Error {
     /// Return a new Error with the given context identifier pushed into the context stack.
     pub fn push_context(&self, context: &str) -> Error {
         let mut new_context = self.context.clone();
-        new_context.insert(0, context.to_string());
+        if !self.terse {
+            new_context.insert(0, context.to_string());
+        }
         Error {
             description: self.description.clone(),
             context: new_context,
+            insufficient_data: self.insufficient_data,
+            insufficient_bytes: self.insufficient_bytes,
+            offset: self.offset,
+            terse: self.terse,
         }
     }
 }
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Converts an I/O error into an `Error`.  An I/O error that was itself converted from an `Error`
+/// is converted back into the original error, and an `UnexpectedEof` error is treated as
+/// insufficient data.
+impl From<io::Error> for Error {
+    fn from(io_err: io::Error) -> Error {
+        if io_err.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            return *io_err.into_inner().unwrap().downcast::<Error>().unwrap();
+        }
+        let description = format!("I/O error: {}", io_err);
+        if io_err.kind() == io::ErrorKind::UnexpectedEof {
+            Error::insufficient_data(description)
+        } else {
+            Error::new(description)
+        }
+    }
+}
+
+/// Converts an `Error` into an I/O error of kind `UnexpectedEof` if it was caused by insufficient
+/// data, or `InvalidData` otherwise.  The original error can be recovered using `Error::from` (or
+/// `io::Error::into_inner`).
+impl From<Error> for io::Error {
+    fn from(error: Error) -> io::Error {
+        let kind = if error.is_insufficient_data() {
+            io::ErrorKind::UnexpectedEof
+        } else {
+            io::ErrorKind::InvalidData
+        };
+        io::Error::new(kind, error)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,4 +302,102 @@ mod tests {
             .push_context("outer");
         assert_eq!(error.message(), expected);
     }
+
+    #[test]
+    fn context_should_preserve_the_insufficient_data_flag() {
+        let error = Error::insufficient_data("Out of bytes".to_string()).push_context("field");
+        assert!(error.is_insufficient_data());
+        assert!(!error.within_bounded_input().is_insufficient_data());
+        assert!(!Error::new("Bad tag".to_string()).is_insufficient_data());
+    }
+
+    #[test]
+    fn insufficient_bytes_should_be_relative_to_the_start_of_the_input() {
+        let error = Error::short_input("Out of bytes".to_string(), 1, 4);
+        assert!(error.is_insufficient_data());
+        assert_eq!(
+            error.insufficient_bytes(),
+            Some(InsufficientBytes { have: 1, needed: 4 })
+        );
+
+        let error = error.push_context("field").offset_by(2);
+        assert_eq!(
+            error.insufficient_bytes(),
+            Some(InsufficientBytes { have: 3, needed: 6 })
+        );
+        assert_eq!(error.insufficient_bytes().unwrap().remaining(), 3);
+        assert_eq!(error.within_bounded_input().insufficient_bytes(), None);
+        assert_eq!(
+            Error::insufficient_data("Out of bytes".to_string()).insufficient_bytes(),
+            None
+        );
+    }
+
+    #[test]
+    fn the_error_message_should_include_the_innermost_offset() {
+        let error = Error::new("Bad tag".to_string())
+            .at_offset(6)
+            .push_context("field")
+            .at_offset(4);
+        assert_eq!(error.offset(), Some(6));
+        assert_eq!(error.message(), "field: Bad tag (at offset 6)");
+
+        // Relative counts of insufficient bytes do not record an offset
+        let error = Error::new("Bad tag".to_string()).offset_by(2);
+        assert_eq!(error.offset(), None);
+        assert_eq!(error.message(), "Bad tag");
+    }
+
+    #[test]
+    fn errors_should_convert_to_and_from_io_errors() {
+        let error = Error::new("Bad tag".to_string()).push_context("field");
+        let io_err = io::Error::from(error);
+        assert_eq!(io_err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(io_err.to_string(), "field: Bad tag");
+
+        // The original error is recovered when converting back
+        let error = Error::from(io_err);
+        assert_eq!(error.message(), "field: Bad tag");
+        assert!(!error.is_insufficient_data());
+
+        let error = Error::short_input("Out of bytes".to_string(), 1, 2).offset_by(3);
+        let io_err = io::Error::from(error);
+        assert_eq!(io_err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(
+            Error::from(io_err).insufficient_bytes(),
+            Some(InsufficientBytes { have: 4, needed: 5 })
+        );
+
+        let error = Error::from(io::Error::new(io::ErrorKind::UnexpectedEof, "early eof"));
+        assert_eq!(error.message(), "I/O error: early eof");
+        assert!(error.is_insufficient_data());
+        assert!(
+            !Error::from(io::Error::new(io::ErrorKind::ConnectionReset, "reset"))
+                .is_insufficient_data()
+        );
+    }
+
+    #[test]
+    fn terse_errors_should_omit_the_description_and_context() {
+        let describe = || "Bad tag".to_string();
+        let error = with_terse_errors(|| {
+            assert!(terse_errors_enabled());
+            Error::short_input(lazy_description(describe), 1, 2)
+        });
+        assert!(!terse_errors_enabled());
+        assert!(error.is_terse());
+        assert_eq!(error.description, "");
+
+        let error = error.push_context("field").offset_by(3);
+        assert_eq!(error.message(), "Details omitted from terse error");
+        assert!(error.is_insufficient_data());
+        assert_eq!(
+            error.insufficient_bytes(),
+            Some(InsufficientBytes { have: 4, needed: 5 })
+        );
+
+        let error = Error::new(lazy_description(describe)).push_context("field");
+        assert!(!error.is_terse());
+        assert_eq!(error.message(), "field: Bad tag");
+    }
 }
@@ -0,0 +1,142 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+//! Asynchronous encoding and decoding using the `futures-io` traits, available with the `futures`
+//! feature.  These functions do not depend on any particular async runtime.
+
+use std::future::poll_fn;
+use std::pin::Pin;
+
+use futures_io::{AsyncBufRead, AsyncWrite};
+
+use crate::codec::Codec;
+use crate::error::Error;
+
+/// Decodes a single value from `reader` using `codec`, reading only as much input as is needed.
+///
+/// Input is requested from `reader` until the accumulated bytes contain a complete value; only the
+/// bytes that make up the value are consumed, so any following input remains available for
+/// subsequent reads.  (An `AsyncBufRead` is required for this reason; a plain `AsyncRead` can be
+/// wrapped in a `BufReader`.)  If the input ends before a complete value has been read, an error is
+/// returned for which `Error::is_insufficient_data` is true.
+pub async fn decode_from_async<C, R>(codec: &C, reader: &mut R) -> Result<C::Value, Error>
+where
+    C: Codec,
+    R: AsyncBufRead + Unpin + ?Sized,
+{
+    let mut buffered = Vec::new();
+    loop {
+        let prior_len = buffered.len();
+        let available = poll_fn(|cx| {
+            Pin::new(&mut *reader).poll_fill_buf(cx).map_ok(|bytes| {
+                buffered.extend_from_slice(bytes);
+                bytes.len()
+            })
+        })
+        .await
+        .map_err(|io_err| Error::new(format!("Failed to read bytes: {}", io_err)))?;
+        if available == 0 {
+            return Err(Error::insufficient_data(format!(
+                "Input ended after {} bytes without a complete value",
+                prior_len
+            )));
+        }
+
+        match codec.decode_slice(&buffered) {
+            Ok((value, consumed)) => {
+                Pin::new(&mut *reader).consume(consumed.saturating_sub(prior_len));
+                return Ok(value);
+            }
+            Err(e) if e.is_insufficient_data() => Pin::new(&mut *reader).consume(available),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Encodes `value` using `codec` and writes the encoded bytes to `writer`, flushing it once all of
+/// the bytes have been written.
+///
+/// The value is encoded into a buffer before any bytes are written, so nothing is written if
+/// encoding fails.
+pub async fn encode_to_async<C, W>(codec: &C, value: &C::Value, writer: &mut W) -> Result<(), Error>
+where
+    C: Codec,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let mut buf = Vec::new();
+    codec.encode_into(value, &mut buf)?;
+
+    let mut written = 0;
+    while written < buf.len() {
+        let count = poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, &buf[written..]))
+            .await
+            .map_err(|io_err| Error::new(format!("Failed to write bytes: {}", io_err)))?;
+        if count == 0 {
+            return Err(Error::new(
+                "Failed to write bytes: writer accepted no input".to_string(),
+            ));
+        }
+        written += count;
+    }
+    poll_fn(|cx| Pin::new(&mut *writer).poll_flush(cx))
+        .await
+        .map_err(|io_err| Error::new(format!("Failed to flush writer: {}", io_err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::*;
+    use futures_executor::block_on;
+    use futures_util::io::{AsyncReadExt, BufReader, Cursor};
+
+    #[test]
+    fn decode_from_async_should_consume_only_the_decoded_value() {
+        let codec = variable_size_bytes(uint8, utf8());
+        let input = Cursor::new(vec![2, b'h', b'i', 3, b'y', b'o', b'u', 0xff]);
+
+        // Read a couple of bytes at a time, so that values span several reads
+        let mut reader = BufReader::with_capacity(2, input);
+        block_on(async {
+            assert_eq!(decode_from_async(&codec, &mut reader).await.unwrap(), "hi");
+            assert_eq!(decode_from_async(&codec, &mut reader).await.unwrap(), "you");
+
+            let mut rest = Vec::new();
+            reader.read_to_end(&mut rest).await.unwrap();
+            assert_eq!(rest, vec![0xff]);
+        });
+    }
+
+    #[test]
+    fn decode_from_async_should_fail_on_truncated_input() {
+        let codec = variable_size_bytes(uint8, utf8());
+        let mut reader = Cursor::new(vec![3, b'h', b'i']);
+        let e = block_on(decode_from_async(&codec, &mut reader)).unwrap_err();
+        assert!(e.is_insufficient_data());
+        assert_eq!(
+            e.message(),
+            "Input ended after 3 bytes without a complete value"
+        );
+
+        let mut reader = Cursor::new(vec![1, 0xff]);
+        let e = block_on(decode_from_async(&codec, &mut reader)).unwrap_err();
+        assert!(!e.is_insufficient_data());
+    }
+
+    #[test]
+    fn encode_to_async_should_write_the_encoded_value() {
+        let codec = (uint16, variable_size_bytes(uint8, utf8()));
+        let mut out = Vec::new();
+        block_on(encode_to_async(&codec, &(7, "hi".to_string()), &mut out)).unwrap();
+        assert_eq!(out, vec![0, 7, 2, b'h', b'i']);
+
+        let mut out = Vec::new();
+        assert!(block_on(encode_to_async(&codec, &(7, "x".repeat(256)), &mut out)).is_err());
+        assert!(out.is_empty());
+    }
+}
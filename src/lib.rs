@@ -14,9 +14,32 @@
 #[macro_use]
 pub mod macros;
 
+pub mod bit_codec;
+pub mod bit_vector;
 pub mod byte_vector;
+pub mod checksum;
+pub mod cipher;
 pub mod codec;
+pub mod crc;
+pub mod cursor;
 pub mod error;
+pub mod limits;
+pub mod metrics;
+pub mod stream;
+pub mod testing;
+pub mod validation;
+
+#[cfg(feature = "futures")]
+pub mod futures;
+
+#[cfg(feature = "rayon")]
+pub mod parallel;
+
+#[cfg(feature = "tokio")]
+pub mod tokio;
+
+#[cfg(feature = "zlib")]
+pub mod zlib;
 
 // TODO: Restore benchmark support
 // // The following is used for benchmark tests.
@@ -0,0 +1,238 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+//! Limits on the resources that decoding may consume, for safely decoding untrusted input.
+//!
+//! The limits are enforced by the codecs themselves: collection codecs (such as `vector_of_n`,
+//! `list` and `map_of_n`) check the number of elements, variable-size codecs (such as
+//! `variable_size_bytes` and `tlv`) check the decoded length of each value, the compressed codecs
+//! in `zlib` check the decompressed length, and recursive codecs constructed using `lazily` check
+//! the nesting depth.  The limits in effect are the process-wide
+//! defaults (see `set_default_limits`) unless overridden for the current thread using
+//! `with_limits` or the `codec::limited` combinator.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::error::{lazy_description, Error};
+
+/// Hard caps on the resources that a single decode operation may consume.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DecodeLimits {
+    /// The maximum length, in bytes, of any value whose length is decoded from the input, or of any
+    /// region decompressed from the input.
+    pub max_alloc: usize,
+
+    /// The maximum nesting depth of recursive codecs.
+    pub max_depth: usize,
+
+    /// The maximum number of elements in any single collection.
+    pub max_elements: usize,
+}
+
+impl DecodeLimits {
+    /// Returns limits that impose no restrictions.
+    pub const fn unlimited() -> DecodeLimits {
+        DecodeLimits {
+            max_alloc: usize::MAX,
+            max_depth: usize::MAX,
+            max_elements: usize::MAX,
+        }
+    }
+}
+
+impl Default for DecodeLimits {
+    fn default() -> DecodeLimits {
+        DecodeLimits::unlimited()
+    }
+}
+
+// The limits used when none have been set for the current thread.  These are held as separate
+// atomics (rather than behind a lock) since they are read on every element of every collection.
+static DEFAULT_MAX_ALLOC: AtomicUsize = AtomicUsize::new(usize::MAX);
+static DEFAULT_MAX_DEPTH: AtomicUsize = AtomicUsize::new(usize::MAX);
+static DEFAULT_MAX_ELEMENTS: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+thread_local! {
+    /// The limits set for the current thread by `with_limits`, if any.
+    static THREAD_LIMITS: Cell<Option<DecodeLimits>> = const { Cell::new(None) };
+
+    /// The number of recursive codecs currently being decoded on this thread.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Sets the limits used by all decode operations that are not performed within `with_limits`.
+///
+/// By default, decoding is unlimited.  Each limit is updated individually, so decode operations
+/// running concurrently on other threads may briefly observe a mix of the old and new limits.
+pub fn set_default_limits(limits: DecodeLimits) {
+    DEFAULT_MAX_ALLOC.store(limits.max_alloc, Ordering::Relaxed);
+    DEFAULT_MAX_DEPTH.store(limits.max_depth, Ordering::Relaxed);
+    DEFAULT_MAX_ELEMENTS.store(limits.max_elements, Ordering::Relaxed);
+}
+
+/// Returns the limits used by decode operations that are not performed within `with_limits`.
+pub fn default_limits() -> DecodeLimits {
+    DecodeLimits {
+        max_alloc: DEFAULT_MAX_ALLOC.load(Ordering::Relaxed),
+        max_depth: DEFAULT_MAX_DEPTH.load(Ordering::Relaxed),
+        max_elements: DEFAULT_MAX_ELEMENTS.load(Ordering::Relaxed),
+    }
+}
+
+/// Returns the limits in effect for decode operations performed on the current thread.
+pub fn current_limits() -> DecodeLimits {
+    THREAD_LIMITS.with(Cell::get).unwrap_or_else(default_limits)
+}
+
+/// Calls `f` with the given limits in effect for decode operations performed on the current
+/// thread, restoring the previous limits afterwards.
+///
+/// # Examples
+///
+/// ```
+/// use rcodec::byte_vector;
+/// use rcodec::codec::*;
+/// use rcodec::limits::{with_limits, DecodeLimits};
+///
+/// let codec = vector_of_n(uint8, uint8);
+/// let limits = DecodeLimits {
+///     max_elements: 2,
+///     ..DecodeLimits::unlimited()
+/// };
+/// assert!(with_limits(limits, || codec.decode(&byte_vector!(2, 7, 8))).is_ok());
+/// assert!(with_limits(limits, || codec.decode(&byte_vector!(3, 7, 8, 9))).is_err());
+/// ```
+pub fn with_limits<R, F>(limits: DecodeLimits, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    // Restore the previous limits even if `f` panics
+    struct Restore(Option<DecodeLimits>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            THREAD_LIMITS.with(|l| l.set(self.0));
+        }
+    }
+
+    let _restore = Restore(THREAD_LIMITS.with(|l| l.replace(Some(limits))));
+    f()
+}
+
+/// Returns an error if a value of `size` bytes exceeds the current allocation limit.
+pub(crate) fn check_alloc(size: usize) -> Result<(), Error> {
+    let max_alloc = current_limits().max_alloc;
+    if size > max_alloc {
+        Err(Error::new(lazy_description(|| {
+            format!(
+                "Length of value ({} bytes) exceeds decode limit of {} bytes",
+                size, max_alloc
+            )
+        })))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns an error if a collection of `count` elements exceeds the current element limit.
+pub(crate) fn check_elements(count: usize) -> Result<(), Error> {
+    check_element_limit(count, current_limits().max_elements)
+}
+
+/// Returns an error if a collection of `count` elements exceeds the given element limit, which
+/// allows callers that check a growing collection to look up the limit only once.
+pub(crate) fn check_element_limit(count: usize, max_elements: usize) -> Result<(), Error> {
+    if count > max_elements {
+        Err(Error::new(lazy_description(|| {
+            format!(
+                "Number of elements ({}) exceeds decode limit of {}",
+                count, max_elements
+            )
+        })))
+    } else {
+        Ok(())
+    }
+}
+
+/// Tracks one level of recursive decoding, which ends when the guard is dropped.
+pub(crate) struct DepthGuard(());
+
+impl DepthGuard {
+    /// Enters one level of recursive decoding, returning an error if doing so would exceed the
+    /// current depth limit.
+    pub(crate) fn enter() -> Result<DepthGuard, Error> {
+        let max_depth = current_limits().max_depth;
+        DEPTH.with(|depth| {
+            if depth.get() >= max_depth {
+                Err(Error::new(lazy_description(|| {
+                    format!("Nesting depth exceeds decode limit of {}", max_depth)
+                })))
+            } else {
+                depth.set(depth.get() + 1);
+                Ok(DepthGuard(()))
+            }
+        })
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn with_limits_should_override_the_limits_for_the_duration_of_the_call() {
+        let limits = DecodeLimits {
+            max_alloc: 16,
+            max_depth: 2,
+            max_elements: 4,
+        };
+        assert_eq!(current_limits(), default_limits());
+        with_limits(limits, || {
+            assert_eq!(current_limits(), limits);
+            assert!(check_alloc(16).is_ok());
+            assert_eq!(
+                check_alloc(17).unwrap_err().message(),
+                "Length of value (17 bytes) exceeds decode limit of 16 bytes"
+            );
+            assert_eq!(
+                check_elements(5).unwrap_err().message(),
+                "Number of elements (5) exceeds decode limit of 4"
+            );
+
+            let outer = DepthGuard::enter().unwrap();
+            let inner = DepthGuard::enter().unwrap();
+            assert!(DepthGuard::enter().is_err());
+            drop(inner);
+            assert!(DepthGuard::enter().is_ok());
+            drop(outer);
+        });
+        assert_eq!(current_limits(), default_limits());
+    }
+
+    #[test]
+    fn limit_errors_should_omit_their_descriptions_when_terse() {
+        let limits = DecodeLimits {
+            max_alloc: 1,
+            max_depth: 0,
+            max_elements: 1,
+        };
+        with_limits(limits, || {
+            crate::error::with_terse_errors(|| {
+                assert_eq!(check_alloc(2).unwrap_err().description, "");
+                assert_eq!(check_elements(2).unwrap_err().description, "");
+                assert_eq!(DepthGuard::enter().err().unwrap().description, "");
+            })
+        });
+    }
+}
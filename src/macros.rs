@@ -14,23 +14,22 @@
 ///
 /// # Examples
 ///
-/// ```
-/// use rcodec::codec::*;
-///
-/// # fn main() {
+/// ```ignore
 /// let x = forcomp!({
 ///     foo <- Some(1u8);
-///     bar <- None;
+///     bar <- None::<u8>;
 /// } yield { foo + bar });
 /// assert!(x.is_none());
-/// # }
 /// ```
 ///
+/// (The macro is internal to this crate, so the example above is exercised by the unit tests in
+/// this module rather than as a doctest.)
+///
 /// This is equivalent to:
 ///
 /// ```
 /// let x = Some(1u8).and_then(|foo| {
-///     None.map(|bar| {
+///     None::<u8>.map(|bar| {
 ///         foo + bar
 ///     })
 /// });
@@ -86,6 +85,12 @@ macro_rules! byte_vector {
 /// Rust macro rules state that simple exprs (without the braces) can only be followed by
 /// `=> , ;` whereas blocks (with the braces) can be followed by any token like `>>` or `::`.
 ///
+/// The final element may be followed by `>>= |v| { ... }`, where the block produces an `HList`
+/// codec for the remaining values given a reference `v` to the value of that element (see
+/// `hlist_flat_prepend_codec`).  To access the values of all preceding elements that are joined
+/// by `::`, destructure them using `>>= |hlist_pat!(a, b, ...)| { ... }` instead (see
+/// `hlist_flat_concat_codec`).
+///
 /// # Examples
 ///
 /// ```
@@ -114,6 +119,12 @@ macro_rules! hcodec {
     { { $($head:tt)+ } } => {
         hlist_prepend_codec($crate::hcodec_block!($($head)+), hnil_codec())
     };
+    { $({ $($field:tt)+ })::+ >>= |hlist_pat!($($pat:tt)*)| $fnbody:block } => {
+        hlist_flat_concat_codec(
+            $crate::hcodec!($({ $($field)+ })::+),
+            |$crate::hlist_pat!($($pat)*)| $fnbody
+        )
+    };
     { { $($head:tt)+ } :: $($tail:tt)+ } => {
         hlist_prepend_codec($crate::hcodec_block!($($head)+), $crate::hcodec!($($tail)+))
     };
@@ -125,6 +136,30 @@ macro_rules! hcodec {
     };
 }
 
+/// Pattern that matches an `HList` with the given element patterns, for use in the closure passed
+/// to `hlist_flat_concat_codec` (or the `>>=` form of `hcodec!`).
+///
+/// # Examples
+///
+/// ```
+/// use pl_hlist::*;
+/// use rcodec::hlist_pat;
+///
+/// # fn main() {
+/// let hlist_pat!(a, _, c) = hlist!(1u8, "two", 3.0);
+/// assert_eq!((a, c), (1u8, 3.0));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! hlist_pat {
+    {} => {
+        HNil
+    };
+    { $head:pat $(, $tail:pat)* $(,)? } => {
+        HCons($head, $crate::hlist_pat!($($tail),*))
+    };
+}
+
 #[macro_export]
 #[doc(hidden)]
 macro_rules! hcodec_block {
@@ -141,6 +176,11 @@ macro_rules! hcodec_block {
 /// The given struct must support `HList` conversions, either by using the `HListSupport` attribute
 /// or by manually implementing the `FromHList` and `ToHList` traits.
 ///
+/// Errors are prefixed with the name of the struct followed by the label of the failing field, as
+/// in `Header/bar: ...`.  Fields without a label are identified by their position among the
+/// struct's fields (starting from zero, and not counting fields dropped using `>>`), as in
+/// `Header/1: ...`.
+///
 /// # Examples
 ///
 /// ```
@@ -167,18 +207,81 @@ macro_rules! hcodec_block {
 /// let bytes = byte_vector!(0xCA, 0xFE, 0x07, 0xBE, 0xEF, 0x00, 0x00, 0x00, 0x06);
 /// let header = header_codec.decode(&bytes).unwrap().value;
 /// assert_eq!(header, Header { foo: 7, bar: 6 });
+///
+/// let error = header_codec.decode(&bytes.take(7).unwrap()).unwrap_err();
+/// assert!(error.message().starts_with("Header/bar: "));
 /// # }
 /// ```
 #[macro_export]
 macro_rules! struct_codec {
     { $stype:ident from $($hcodec:tt)+ } => {
-        { struct_codec::<_, $stype, _>($crate::hcodec!($($hcodec)+)) }
+        {
+            named_struct_codec::<_, $stype, _>(
+                stringify!($stype),
+                $crate::hcodec_positional!(
+                    (
+                        "0" "1" "2" "3" "4" "5" "6" "7" "8" "9" "10" "11" "12" "13" "14" "15"
+                        "16" "17" "18" "19" "20" "21" "22" "23" "24" "25" "26" "27" "28" "29" "30" "31"
+                        "32" "33" "34" "35" "36" "37" "38" "39" "40" "41" "42" "43" "44" "45" "46" "47"
+                        "48" "49" "50" "51" "52" "53" "54" "55" "56" "57" "58" "59" "60" "61" "62" "63"
+                    );
+                    $($hcodec)+
+                )
+            )
+        }
+    };
+}
+
+/// Equivalent to `hcodec!`, except that fields without a label (other than those dropped using
+/// `>>`) are labeled with the next of the given positional labels, as used by `struct_codec!`.
+/// Fields that follow the last of the labels are left unlabeled.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! hcodec_positional {
+    { (); $($fields:tt)+ } => {
+        $crate::hcodec!($($fields)+)
+    };
+    { ($label:literal $($labels:literal)*); { $($head:tt)+ } } => {
+        hlist_prepend_codec($crate::hcodec_positional_block!($label; $($head)+), hnil_codec())
+    };
+    { ($($labels:literal)*); $({ $($field:tt)+ })::+ >>= |hlist_pat!($($pat:tt)*)| $fnbody:block } => {
+        hlist_flat_concat_codec(
+            $crate::hcodec_positional!(($($labels)*); $({ $($field)+ })::+),
+            |$crate::hlist_pat!($($pat)*)| $fnbody
+        )
+    };
+    { ($label:literal $($labels:literal)*); { $($head:tt)+ } :: $($tail:tt)+ } => {
+        hlist_prepend_codec(
+            $crate::hcodec_positional_block!($label; $($head)+),
+            $crate::hcodec_positional!(($($labels)*); $($tail)+)
+        )
+    };
+    { ($($labels:literal)*); { $($head:tt)+ } >> $($tail:tt)+ } => {
+        drop_left($crate::hcodec_block!($($head)+), $crate::hcodec_positional!(($($labels)*); $($tail)+))
+    };
+    { ($label:literal $($labels:literal)*); { $($head:tt)+ } >>= |$v:ident| $fnbody:block } => {
+        hlist_flat_prepend_codec($crate::hcodec_positional_block!($label; $($head)+), |$v| $fnbody)
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! hcodec_positional_block {
+    { $label:literal; $ctx:expr => $codec:expr } => {
+        with_context($ctx, $codec)
+    };
+    { $label:literal; $codec:expr } => {
+        with_context($label, $codec)
     };
 }
 
 /// Defines a struct that has derived impls for some common traits along with implementations
 /// of the `FromHList` and `ToHList` traits, taking all fields into account.
 ///
+/// Attributes (including doc comments and additional `derive`s) given before the struct name are
+/// forwarded to the generated struct, and each field may be preceded by attributes and a
+/// visibility qualifier.  Fields are private unless declared otherwise.
+///
 /// # Examples
 ///
 /// ```
@@ -191,18 +294,181 @@ macro_rules! struct_codec {
 ///     bar: u32
 /// );
 ///
+/// mod records {
+///     use pl_hlist::*;
+///     use rcodec::*;
+///
+///     record_struct!(
+///         /// A point in two dimensions.
+///         #[derive(Hash)]
+///         Point,
+///         /// The horizontal coordinate.
+///         pub x: u8,
+///         pub y: u8,
+///     );
+/// }
+///
 /// # fn main() {
 /// let hlist = hlist!(7u8, 666u32);
 /// let s = TestStruct::from_hlist(hlist);
 /// assert_eq!(s, TestStruct { foo: 7, bar: 666 });
+///
+/// let p = records::Point { x: 1, y: 2 };
+/// assert_eq!(p.x, 1);
 /// # }
 /// ```
 #[macro_export]
 macro_rules! record_struct {
-    { $stype:ident, $($fieldname:ident: $fieldtype:ty),+ } => {
+    {
+        $(#[$attr:meta])*
+        $stype:ident,
+        $($(#[$fieldattr:meta])* $fieldvis:vis $fieldname:ident: $fieldtype:ty),+ $(,)?
+    } => {
         #[derive(Debug, PartialEq, Eq, Clone, HListSupport)]
+        $(#[$attr])*
         pub struct $stype {
-            $($fieldname: $fieldtype),+
+            $($(#[$fieldattr])* $fieldvis $fieldname: $fieldtype),+
+        }
+    };
+}
+
+/// Defines a struct whose fields are packed into bit ranges within a fixed number of bytes, along
+/// with an implementation of `DefaultCodec` for it.
+///
+/// The struct name is followed by its size in bytes, and then by each field's name, type, and
+/// width in bits, with the first field occupying the most significant bits.  Field types may be
+/// any `BitField` type (unsigned or signed integers, or `bool`).  As with `record_struct!`,
+/// attributes may be given for the struct and its fields.  The struct and its fields are private
+/// unless given a visibility.  Compilation fails if the field widths do not add up to the size of
+/// the struct, or if any field is wider than its type allows:
+///
+/// ```compile_fail
+/// use rcodec::bitfield_struct;
+///
+/// bitfield_struct!(Flags, 1, enabled: bool: 2, mode: u8: 6);
+/// # fn main() {}
+/// ```
+///
+/// Errors produced when encoding or decoding a field include the name of the field as context.
+///
+/// # Examples
+///
+/// ```
+/// use rcodec::{bitfield_struct, byte_vector};
+/// use rcodec::codec::*;
+///
+/// bitfield_struct!(
+///     /// The first byte of an IPv4 header.
+///     pub VersionAndLength,
+///     1,
+///     pub version: u8: 4,
+///     pub header_len: u8: 4,
+/// );
+///
+/// # fn main() {
+/// let value = VersionAndLength { version: 4, header_len: 5 };
+/// let bv = VersionAndLength::codec().encode(&value).unwrap();
+/// assert_eq!(bv, byte_vector!(0x45));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! bitfield_struct {
+    {
+        $(#[$attr:meta])*
+        $vis:vis $stype:ident,
+        $bytes:expr,
+        $($(#[$fieldattr:meta])* $fieldvis:vis $fieldname:ident: $fieldtype:ty: $bits:expr),+ $(,)?
+    } => {
+        #[derive(Debug, PartialEq, Eq, Clone)]
+        $(#[$attr])*
+        $vis struct $stype {
+            $($(#[$fieldattr])* $fieldvis $fieldname: $fieldtype),+
+        }
+
+        impl $crate::codec::DefaultCodec for $stype {
+            fn codec() -> impl $crate::codec::Codec<Value = Self> {
+                const _: () = assert!(
+                    0 $(+ $bits)+ == 8 * $bytes,
+                    "Bit field widths must add up to the size of the struct"
+                );
+                $(
+                    const _: () = assert!(
+                        $bits > 0
+                            && $bits <= <$fieldtype as $crate::bit_codec::BitField>::MAX_BITS,
+                        "Bit field width must be between 1 and the width of the field type"
+                    );
+                )+
+
+                struct FieldsCodec;
+
+                impl $crate::bit_codec::BitCodec for FieldsCodec {
+                    type Value = $stype;
+
+                    fn size_bound(&self) -> $crate::codec::SizeBound {
+                        $crate::codec::SizeBound::exact(8 * $bytes)
+                    }
+
+                    fn encode(
+                        &self,
+                        value: &$stype,
+                    ) -> Result<$crate::bit_vector::BitVector, $crate::error::Error> {
+                        let bits = $crate::bit_vector::empty();
+                        $(
+                            let field_bits = $crate::bit_codec::BitCodec::encode(
+                                &$crate::bit_codec::bit_field::<$fieldtype>($bits),
+                                &value.$fieldname,
+                            )
+                            .map_err(|e| e.push_context(stringify!($fieldname)))?;
+                            let bits = $crate::bit_vector::append(&bits, &field_bits)?;
+                        )+
+                        Ok(bits)
+                    }
+
+                    fn decode(
+                        &self,
+                        bits: &$crate::bit_vector::BitVector,
+                    ) -> $crate::bit_codec::BitDecodeResult<$stype> {
+                        let remainder = bits.clone();
+                        $(
+                            let decoded = $crate::bit_codec::BitCodec::decode(
+                                &$crate::bit_codec::bit_field::<$fieldtype>($bits),
+                                &remainder,
+                            )
+                            .map_err(|e| e.push_context(stringify!($fieldname)))?;
+                            let $fieldname = decoded.value;
+                            let remainder = decoded.remainder;
+                        )+
+                        Ok($crate::bit_codec::BitDecoderResult {
+                            value: $stype { $($fieldname),+ },
+                            remainder,
+                        })
+                    }
+                }
+
+                $crate::bit_codec::byte_aligned(FieldsCodec)
+            }
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn forcomp_should_yield_none_if_any_step_is_none() {
+        let x = forcomp!({
+            foo <- Some(1u8);
+            bar <- None::<u8>;
+        } yield { foo + bar });
+        assert!(x.is_none());
+    }
+
+    #[test]
+    fn forcomp_should_yield_the_result_of_each_step() {
+        let x = forcomp!({
+            foo <- Some(1u8);
+            bar <- Some(foo + 1);
+            baz <- Ok::<u8, ()>(bar * 2).ok();
+        } yield { foo + bar + baz });
+        assert_eq!(x, Some(7));
+    }
+}
@@ -0,0 +1,166 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+//! Metrics collected from codecs wrapped with `codec::instrumented`.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The kind of operation performed by an instrumented codec.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// An `encode`, `encode_into` or `encode_to` call.
+    Encode,
+
+    /// A `decode` or `decode_slice` call.
+    Decode,
+}
+
+/// Describes a single operation performed by an instrumented codec.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// The name given to the instrumented codec.
+    pub name: &'static str,
+
+    /// The kind of operation that was performed.
+    pub operation: Operation,
+
+    /// The number of bytes produced (when encoding) or consumed (when decoding); zero if the
+    /// operation failed.
+    pub bytes: usize,
+
+    /// The time taken by the operation.
+    pub elapsed: Duration,
+
+    /// Whether the operation succeeded.
+    pub success: bool,
+}
+
+/// A destination for events produced by instrumented codecs.
+pub trait MetricsSink {
+    /// Records a single event.
+    fn record(&self, event: &Event);
+}
+
+impl<S: MetricsSink + ?Sized> MetricsSink for &S {
+    fn record(&self, event: &Event) {
+        (**self).record(event)
+    }
+}
+
+impl<S: MetricsSink + ?Sized> MetricsSink for Rc<S> {
+    fn record(&self, event: &Event) {
+        (**self).record(event)
+    }
+}
+
+impl<S: MetricsSink + ?Sized> MetricsSink for Arc<S> {
+    fn record(&self, event: &Event) {
+        (**self).record(event)
+    }
+}
+
+/// Totals accumulated for one operation of one named codec.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OperationStats {
+    /// The number of operations performed, including failures.
+    pub count: u64,
+
+    /// The number of operations that failed.
+    pub failures: u64,
+
+    /// The total number of bytes produced or consumed by successful operations.
+    pub bytes: u64,
+
+    /// The total time taken by all operations.
+    pub elapsed: Duration,
+}
+
+/// Sink that aggregates events into per-codec totals, which can be shared between threads.
+#[derive(Debug, Default)]
+pub struct CodecMetrics {
+    stats: Mutex<HashMap<(&'static str, Operation), OperationStats>>,
+}
+
+impl CodecMetrics {
+    /// Returns an empty set of metrics.
+    pub fn new() -> CodecMetrics {
+        CodecMetrics::default()
+    }
+
+    /// Returns the totals recorded for the given operation of the named codec.
+    pub fn stats(&self, name: &'static str, operation: Operation) -> OperationStats {
+        let stats = self.stats.lock().unwrap();
+        stats.get(&(name, operation)).copied().unwrap_or_default()
+    }
+
+    /// Returns a copy of all totals recorded so far, keyed by codec name and operation.
+    pub fn snapshot(&self) -> HashMap<(&'static str, Operation), OperationStats> {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Discards all totals recorded so far.
+    pub fn reset(&self) {
+        self.stats.lock().unwrap().clear();
+    }
+}
+
+impl MetricsSink for CodecMetrics {
+    fn record(&self, event: &Event) {
+        let mut stats = self.stats.lock().unwrap();
+        let entry = stats.entry((event.name, event.operation)).or_default();
+        entry.count += 1;
+        if event.success {
+            entry.bytes += event.bytes as u64;
+        } else {
+            entry.failures += 1;
+        }
+        entry.elapsed += event.elapsed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(operation: Operation, bytes: usize, success: bool) -> Event {
+        Event {
+            name: "frame",
+            operation,
+            bytes,
+            elapsed: Duration::from_micros(5),
+            success,
+        }
+    }
+
+    #[test]
+    fn codec_metrics_should_aggregate_events() {
+        let metrics = CodecMetrics::new();
+        metrics.record(&event(Operation::Encode, 4, true));
+        metrics.record(&event(Operation::Encode, 6, true));
+        metrics.record(&event(Operation::Decode, 0, false));
+
+        assert_eq!(
+            metrics.stats("frame", Operation::Encode),
+            OperationStats {
+                count: 2,
+                failures: 0,
+                bytes: 10,
+                elapsed: Duration::from_micros(10),
+            }
+        );
+        assert_eq!(metrics.stats("frame", Operation::Decode).failures, 1);
+        assert_eq!(metrics.stats("other", Operation::Decode).count, 0);
+        assert_eq!(metrics.snapshot().len(), 2);
+
+        metrics.reset();
+        assert!(metrics.snapshot().is_empty());
+    }
+}
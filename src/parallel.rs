@@ -0,0 +1,217 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+//! Parallel encoding using the `rayon` thread pool, available with the `rayon` feature.
+
+use std::cmp::min;
+
+use rayon::prelude::*;
+
+use crate::codec::Codec;
+use crate::error::Error;
+
+/// Encodes `value` using `codec`, encoding independent sections of the value (such as the fields
+/// of an `hcodec!` codec) in parallel on the `rayon` thread pool and concatenating the results.
+///
+/// The sections are divided into one contiguous group per thread, so this is most effective when
+/// the value is made up of many fields that are each expensive to encode (for example, a large
+/// archive of records).  HList and struct codecs are divided into one section per field; struct
+/// codecs created by `struct_codec!` convert the struct to an HList once per group.  Codecs whose
+/// later fields depend on earlier values (such as those built with `>>=`) are encoded as a single
+/// section, as are codecs that do not override `Codec::section_count`.  The result is identical to
+/// that of `encode_into`.
+///
+/// # Examples
+///
+/// ```
+/// use pl_hlist::*;
+/// use rcodec::codec::*;
+/// use rcodec::hcodec;
+/// use rcodec::parallel::encode_parallel;
+///
+/// # fn main() {
+/// let codec = hcodec!(
+///     { "id"    => uint16                          } ::
+///     { "left"  => variable_size_bytes(uint8, utf8()) } ::
+///     { "right" => variable_size_bytes(uint8, utf8()) }
+/// );
+/// let value = hlist!(7, "ab".to_string(), "c".to_string());
+/// let encoded = encode_parallel(&codec, &value).unwrap();
+/// assert_eq!(encoded, vec![0, 7, 2, b'a', b'b', 1, b'c']);
+/// # }
+/// ```
+pub fn encode_parallel<C>(codec: &C, value: &C::Value) -> Result<Vec<u8>, Error>
+where
+    C: Codec + Sync,
+    C::Value: Sync,
+{
+    let count = codec.section_count();
+    let group_len = count.div_ceil(rayon::current_num_threads()).max(1);
+    let encoded = (0..count)
+        .step_by(group_len)
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|start| {
+            let mut out = Vec::new();
+            codec
+                .encode_sections_into(value, start..min(start + group_len, count), &mut out)
+                .map(|_| out)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(encoded.concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::*;
+    use pl_hlist::*;
+
+    record_struct!(
+        Archive,
+        magic: (),
+        count: u16,
+        first: Vec<u8>,
+        second: String,
+        third: Vec<u32>,
+    );
+
+    macro_rules! archive_fields {
+        () => {
+            hcodec!(
+                { "magic"  => constant(byte_vector!(0xca, 0xfe))                 } ::
+                { "count"  => uint16                                               } ::
+                { "first"  => variable_size_bytes(uint16, eager(identity_bytes())) } ::
+                { "second" => variable_size_bytes(uint8, utf8())                   } ::
+                { "third"  => vector_of_n(uint8, uint32)                           }
+            )
+        };
+    }
+
+    #[test]
+    fn encode_parallel_should_produce_the_same_bytes_as_encode_into() {
+        let codec = archive_fields!();
+        assert_eq!(codec.section_count(), 5);
+
+        let archive = Archive {
+            magic: (),
+            count: 3,
+            first: vec![0xab; 1000],
+            second: "hello".to_string(),
+            third: (0..100).collect(),
+        }
+        .to_hlist();
+        assert_eq!(
+            encode_parallel(&codec, &archive).unwrap(),
+            codec.encode_to_vec(&archive).unwrap()
+        );
+
+        // Each combination of groups should concatenate to the full encoding
+        let expected = codec.encode_to_vec(&archive).unwrap();
+        for split in 0..=5 {
+            let mut out = Vec::new();
+            codec
+                .encode_sections_into(&archive, 0..split, &mut out)
+                .unwrap();
+            codec
+                .encode_sections_into(&archive, split..5, &mut out)
+                .unwrap();
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn encode_parallel_should_encode_struct_fields_as_sections() {
+        let archive = Archive {
+            magic: (),
+            count: 3,
+            first: vec![0xab; 1000],
+            second: "hello".to_string(),
+            third: (0..100).collect(),
+        };
+
+        let codec = named_struct_codec::<_, Archive, _>("Archive", archive_fields!());
+        assert_eq!(codec.section_count(), 5);
+        let expected = codec.encode_to_vec(&archive).unwrap();
+        assert_eq!(encode_parallel(&codec, &archive).unwrap(), expected);
+
+        let codec = StructCodecBuilder::new()
+            .field(
+                "magic",
+                constant(byte_vector!(0xca, 0xfe)),
+                |a: &Archive| &a.magic,
+            )
+            .field("count", uint16, |a: &Archive| &a.count)
+            .field(
+                "first",
+                variable_size_bytes(uint16, eager(identity_bytes())),
+                |a: &Archive| &a.first,
+            )
+            .field(
+                "second",
+                variable_size_bytes(uint8, utf8()),
+                |a: &Archive| &a.second,
+            )
+            .field("third", vector_of_n(uint8, uint32), |a: &Archive| &a.third)
+            .build(|_| Err(Error::new("Not decodable".to_string())));
+        assert_eq!(codec.section_count(), 5);
+        assert_eq!(encode_parallel(&codec, &archive).unwrap(), expected);
+        for split in 0..=5 {
+            let mut out = Vec::new();
+            codec
+                .encode_sections_into(&archive, 0..split, &mut out)
+                .unwrap();
+            codec
+                .encode_sections_into(&archive, split..5, &mut out)
+                .unwrap();
+            assert_eq!(out, expected);
+        }
+
+        // Errors are reported with the name of the struct and field
+        let codec = named_struct_codec::<_, Archive, _>("Archive", archive_fields!());
+        let archive = Archive {
+            second: "x".repeat(256),
+            ..archive
+        };
+        assert!(encode_parallel(&codec, &archive)
+            .unwrap_err()
+            .message()
+            .starts_with("Archive/second: "));
+    }
+
+    #[test]
+    fn encode_parallel_should_support_codecs_without_sections() {
+        assert_eq!(encode_parallel(&uint16, &0x0102).unwrap(), vec![1, 2]);
+        assert!(encode_parallel(&hnil_codec(), &HNil).unwrap().is_empty());
+
+        let codec =
+            hcodec!({ uint8 } >>= |len| { hcodec!({ fixed_size_bytes(*len as usize, utf8()) }) });
+        assert_eq!(codec.section_count(), 1);
+        assert_eq!(
+            encode_parallel(&codec, &hlist!(2, "hi".to_string())).unwrap(),
+            vec![2, b'h', b'i']
+        );
+    }
+
+    #[test]
+    fn encode_parallel_should_report_errors_with_context() {
+        let codec = hcodec!(
+            { "id"   => uint8                             } ::
+            { "name" => variable_size_bytes(uint8, utf8()) }
+        );
+        let value = hlist!(1, "x".repeat(256));
+        assert_eq!(
+            encode_parallel(&codec, &value).unwrap_err().message(),
+            codec.encode_to_vec(&value).unwrap_err().message()
+        );
+        assert!(encode_parallel(&codec, &value)
+            .unwrap_err()
+            .message()
+            .starts_with("name: "));
+    }
+}
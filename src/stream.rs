@@ -0,0 +1,282 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+//! Incremental decoding of successive values, either from input that arrives in pieces (such as
+//! datagrams or reads from a socket) or from large inputs that are processed one record at a time.
+
+use crate::byte_vector::ByteVector;
+use crate::codec::Codec;
+use crate::error::Error;
+
+/// Returns an iterator that lazily decodes successive values from `bv` using `codec`, until the
+/// input is exhausted.
+///
+/// Each value is decoded only when requested, and the remainder of the input is retained as a view
+/// over `bv`, so file-backed vectors (see `byte_vector::file`) can be processed record by record
+/// without reading the whole file into memory.  If decoding fails, the error is returned and the
+/// iteration ends.  Decoding also fails if a value is decoded without consuming any input, which
+/// would otherwise produce the same value forever.
+///
+/// # Examples
+///
+/// ```
+/// use rcodec::byte_vector;
+/// use rcodec::codec::*;
+/// use rcodec::stream::decode_iter;
+///
+/// let input = byte_vector!(0, 1, 0, 2, 0, 3);
+/// let values: Result<Vec<u16>, _> = decode_iter(uint16, input).collect();
+/// assert_eq!(values.unwrap(), vec![1, 2, 3]);
+/// ```
+pub fn decode_iter<T, C>(codec: C, bv: ByteVector) -> impl Iterator<Item = Result<T, Error>>
+where
+    C: Codec<Value = T>,
+{
+    DecodeIter {
+        codec,
+        remainder: Some(bv),
+    }
+}
+
+struct DecodeIter<C> {
+    /// The codec used to decode values.
+    codec: C,
+
+    /// The input that has not yet been decoded, or `None` if decoding has failed.
+    remainder: Option<ByteVector>,
+}
+
+impl<T, C> Iterator for DecodeIter<C>
+where
+    C: Codec<Value = T>,
+{
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Result<T, Error>> {
+        let remainder = self.remainder.take()?;
+        if remainder.length() == 0 {
+            return None;
+        }
+        match self.codec.decode(&remainder) {
+            Ok(decoded) if decoded.remainder.length() == remainder.length() => {
+                Some(Err(no_input_consumed()))
+            }
+            Ok(decoded) => {
+                self.remainder = Some(decoded.remainder);
+                Some(Ok(decoded.value))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Returns the error reported when a value is decoded from a stream without consuming any input,
+/// which would otherwise cause the same value to be decoded forever.
+pub(crate) fn no_input_consumed() -> Error {
+    Error::new("Decoded value did not consume any input".to_string())
+}
+
+/// Accumulates input pushed by the caller and decodes as many complete values from it as are
+/// available, retaining any trailing partial value until more input arrives.
+///
+/// Values are produced by calling `next` (this type implements `Iterator`).  A decoding error
+/// that was caused by the buffered input ending early (see `Error::is_insufficient_data`) is not
+/// reported; instead, `next` returns `None` until more input is pushed.  Any other error is
+/// returned from `next`, and the buffered input is left in place, so subsequent calls will return
+/// the same error until the input is discarded using `clear`.  As with `decode_iter`, decoding a
+/// value without consuming any input is reported as an error.
+///
+/// # Examples
+///
+/// ```
+/// use rcodec::codec::*;
+/// use rcodec::stream::DecodeSession;
+///
+/// let mut session = DecodeSession::new(variable_size_bytes(uint8, utf8()));
+/// session.push(&[2, b'h', b'i', 3, b'y']);
+/// assert_eq!(session.next().unwrap().unwrap(), "hi");
+/// assert!(session.next().is_none());
+///
+/// session.push(&[b'o', b'u']);
+/// assert_eq!(session.next().unwrap().unwrap(), "you");
+/// assert!(session.buffered().is_empty());
+/// ```
+pub struct DecodeSession<C> {
+    /// The codec used to decode values.
+    codec: C,
+
+    /// The accumulated input, including bytes that have already been consumed.
+    buffer: Vec<u8>,
+
+    /// The offset of the first unconsumed byte in `buffer`.
+    start: usize,
+}
+
+impl<C: Codec> DecodeSession<C> {
+    /// Returns a new session that decodes values using `codec`.
+    pub fn new(codec: C) -> DecodeSession<C> {
+        DecodeSession {
+            codec,
+            buffer: Vec::new(),
+            start: 0,
+        }
+    }
+
+    /// Appends the given bytes to the buffered input.
+    pub fn push(&mut self, bytes: &[u8]) {
+        // Discard consumed bytes before growing the buffer
+        if self.start > 0 {
+            self.buffer.drain(..self.start);
+            self.start = 0;
+        }
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the buffered input that has not yet been consumed by a decoded value.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer[self.start..]
+    }
+
+    /// Discards all buffered input (e.g. after a decoding error).
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.start = 0;
+    }
+
+    /// Returns the codec used to decode values.
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+}
+
+impl<C: Codec> Iterator for DecodeSession<C> {
+    type Item = Result<C::Value, Error>;
+
+    /// Decodes the next value from the buffered input, returning `None` if the buffered input
+    /// does not yet contain a complete value.
+    fn next(&mut self) -> Option<Result<C::Value, Error>> {
+        if self.start == self.buffer.len() {
+            return None;
+        }
+        match self.codec.decode_slice(&self.buffer[self.start..]) {
+            Ok((_, 0)) => Some(Err(no_input_consumed())),
+            Ok((value, consumed)) => {
+                self.start += consumed;
+                Some(Ok(value))
+            }
+            Err(e) if e.is_insufficient_data() => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::byte_vector;
+    use crate::codec::*;
+    use pl_hlist::*;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn decode_iter_should_decode_records_from_a_file_on_demand() {
+        let path = Path::new("/tmp/rcodec-test-decode-iter");
+        fs::write(path, [1, b'a', 2, b'b', b'c', 0, 3, b'd']).unwrap();
+
+        let codec = variable_size_bytes(uint8, utf8());
+        let mut records = decode_iter(codec, byte_vector::file(path).unwrap());
+        assert_eq!(records.next().unwrap().unwrap(), "a");
+        assert_eq!(records.next().unwrap().unwrap(), "bc");
+        assert_eq!(records.next().unwrap().unwrap(), "");
+        assert!(records.next().unwrap().is_err());
+        assert!(records.next().is_none());
+
+        let _ignore = fs::remove_file(path);
+    }
+
+    #[test]
+    fn decode_iter_should_fail_on_codecs_that_consume_no_input() {
+        let mut values = decode_iter(provide(7u8), byte_vector!(1));
+        assert_eq!(
+            values.next().unwrap().unwrap_err().message(),
+            "Decoded value did not consume any input"
+        );
+        assert!(values.next().is_none());
+        assert_eq!(decode_iter(uint8, byte_vector::empty()).count(), 0);
+    }
+
+    #[test]
+    fn a_decode_session_should_fail_on_codecs_that_consume_no_input() {
+        let mut session = DecodeSession::new(provide(7u8));
+        session.push(&[1]);
+        assert_eq!(
+            session.next().unwrap().unwrap_err().message(),
+            "Decoded value did not consume any input"
+        );
+        assert_eq!(session.buffered(), &[1]);
+    }
+
+    #[test]
+    fn a_decode_session_should_decode_values_as_input_arrives() {
+        let codec = hcodec!(
+            { "kind"    => uint8                                               } ::
+            { "payload" => variable_size_bytes(uint16, eager(identity_bytes())) }
+        );
+        let mut session = DecodeSession::new(codec);
+        assert!(session.next().is_none());
+
+        // Push a complete message followed by part of another, one byte at a time
+        for byte in [1, 0, 2, 0xaa, 0xbb, 2, 0] {
+            session.push(&[byte]);
+        }
+        assert_eq!(
+            session.next().unwrap().unwrap(),
+            hlist!(1, vec![0xaa, 0xbb])
+        );
+        assert!(session.next().is_none());
+        assert_eq!(session.buffered(), &[2, 0]);
+
+        session.push(&[1, 0xcc, 3, 0, 0]);
+        assert_eq!(session.next().unwrap().unwrap(), hlist!(2, vec![0xcc]));
+        assert_eq!(session.next().unwrap().unwrap(), hlist!(3, vec![]));
+        assert!(session.next().is_none());
+        assert!(session.buffered().is_empty());
+    }
+
+    #[test]
+    fn a_decode_session_should_report_malformed_input() {
+        let codec = variable_size_bytes(uint8, utf8());
+        let mut session = DecodeSession::new(codec);
+        session.push(&[1, 0xff, 1]);
+        let e = session.next().unwrap().unwrap_err();
+        assert!(!e.is_insufficient_data());
+        assert!(session.next().unwrap().is_err());
+
+        session.clear();
+        session.push(&[1, b'a']);
+        assert_eq!(session.next().unwrap().unwrap(), "a");
+    }
+
+    #[test]
+    fn a_decode_session_should_not_wait_for_input_within_a_complete_frame() {
+        // The length prefix says that the frame is complete, so running out of bytes while
+        // decoding the fields within it indicates malformed input
+        let codec = variable_size_bytes(uint8, (uint16, uint16));
+        let mut session = DecodeSession::new(codec);
+        session.push(&[3, 0, 1, 0]);
+        let e = session.next().unwrap().unwrap_err();
+        assert!(!e.is_insufficient_data());
+
+        let mut session = DecodeSession::new(delimited(utf8(), [0u8]));
+        session.push(b"abc");
+        assert!(session.next().is_none());
+        session.push(b"\0");
+        assert_eq!(session.next().unwrap().unwrap(), "abc");
+    }
+}
@@ -0,0 +1,221 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+//! Helpers for testing codecs, including those defined outside of this crate.
+
+use std::fmt::Debug;
+
+use crate::byte_vector;
+use crate::byte_vector::ByteVector;
+use crate::codec::Codec;
+use crate::error::Error;
+
+/// Asserts that `value` survives a round trip through `codec`, and that the encoded bytes match
+/// `raw_bytes`, if provided.  See `check_codec_laws` for the properties that are verified.
+///
+/// # Panics
+///
+/// Panics if encoding or decoding fails, or if any of the properties do not hold.
+pub fn assert_round_trip<T, C>(codec: C, value: &T, raw_bytes: &Option<ByteVector>)
+where
+    T: PartialEq + Debug,
+    C: Codec<Value = T>,
+{
+    if let Err(e) = check_value(&codec, value, raw_bytes.as_ref()) {
+        panic!("Round-trip encoding failed: {}", e.message());
+    }
+}
+
+/// Asserts that decoding `input` using `codec` fails, returning the resulting error so that the
+/// caller can make further assertions about it.
+///
+/// # Panics
+///
+/// Panics if decoding succeeds.
+pub fn assert_decode_fails<T, C>(codec: C, input: &ByteVector) -> Error
+where
+    T: Debug,
+    C: Codec<Value = T>,
+{
+    match codec.decode(input) {
+        Ok(decoded) => panic!(
+            "Expected decoding of {:?} to fail but got {:?}",
+            input, decoded.value
+        ),
+        Err(e) => e,
+    }
+}
+
+/// Checks that `codec` obeys the following laws for each of the given values, returning an error
+/// describing the first violation:
+///
+///   - Decoding the encoded bytes produces the original value (encode ∘ decode = id).
+///   - Decoding the encoded bytes consumes exactly those bytes.
+///   - `encode_into` appends the same bytes as are returned by `encode`.
+///   - `encode_to` writes the same bytes as are returned by `encode`.
+///   - `decode_slice` produces the same value and consumes the same bytes as `decode`.
+///   - The length of the encoded bytes falls within the codec's `size_bound`.
+pub fn check_codec_laws<'a, T, C, I>(codec: C, values: I) -> Result<(), Error>
+where
+    T: 'a + PartialEq + Debug,
+    C: Codec<Value = T>,
+    I: IntoIterator<Item = &'a T>,
+{
+    values.into_iter().try_for_each(|value| {
+        check_value(&codec, value, None).map_err(|e| {
+            Error::new(format!(
+                "Codec laws violated for value {:?}: {}",
+                value,
+                e.message()
+            ))
+        })
+    })
+}
+
+/// Checks the codec laws for a single value, and optionally the encoded bytes.
+fn check_value<T, C>(codec: &C, value: &T, raw_bytes: Option<&ByteVector>) -> Result<(), Error>
+where
+    T: PartialEq + Debug,
+    C: Codec<Value = T>,
+{
+    let encoded = codec.encode(value)?;
+    if let Some(expected) = raw_bytes {
+        if encoded != *expected {
+            return Err(Error::new(format!(
+                "Encoded bytes {:?} do not match expected bytes {:?}",
+                encoded, *expected
+            )));
+        }
+    }
+
+    // Verify that encoding directly into a buffer appends the same bytes
+    let mut out = vec![0xaa];
+    codec.encode_into(value, &mut out)?;
+    let appended = byte_vector::from_slice_copy(&out[1..]);
+    if appended != encoded {
+        return Err(Error::new(format!(
+            "Bytes {:?} appended by encode_into do not match encoded bytes {:?}",
+            appended, encoded
+        )));
+    }
+
+    // Verify that encoding to a writer writes the same bytes
+    let mut written = Vec::new();
+    codec.encode_to(value, &mut written)?;
+    if written != encoded {
+        return Err(Error::new(format!(
+            "Bytes {:?} written by encode_to do not match encoded bytes {:?}",
+            byte_vector::from_vec(written),
+            encoded
+        )));
+    }
+
+    // Verify that the encoded length is within the codec's advertised bounds
+    let len = encoded.length() as u64;
+    let bound = codec.size_bound();
+    if len < bound.lower || bound.upper.is_some_and(|upper| len > upper) {
+        return Err(Error::new(format!(
+            "Encoded length of {} bytes is outside of size bound {:?}",
+            len, bound
+        )));
+    }
+
+    // Verify that decoding produces the original value and consumes all of the encoded bytes
+    let decoded = codec.decode(&encoded)?;
+    check_decoded(value, &decoded.value, decoded.remainder.length(), "decode")?;
+    let (decoded, consumed) = codec.decode_slice(&out[1..])?;
+    check_decoded(value, &decoded, out.len() - 1 - consumed, "decode_slice")
+}
+
+/// Checks a value produced by the named decode operation, which left `unconsumed` bytes.
+fn check_decoded<T>(
+    expected: &T,
+    decoded: &T,
+    unconsumed: usize,
+    operation: &str,
+) -> Result<(), Error>
+where
+    T: PartialEq + Debug,
+{
+    if *decoded != *expected {
+        Err(Error::new(format!(
+            "Value {:?} produced by {} does not match original value {:?}",
+            decoded, operation, expected
+        )))
+    } else if unconsumed != 0 {
+        Err(Error::new(format!(
+            "{} left {} of the encoded bytes unconsumed",
+            operation, unconsumed
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::*;
+
+    #[test]
+    fn check_codec_laws_should_accept_lawful_codecs() {
+        assert!(check_codec_laws(uint16, &[0, 1, 0xffff]).is_ok());
+        assert!(check_codec_laws(float32, &[0.5, -1.0]).is_ok());
+        assert!(check_codec_laws(variable_size_bytes(uint8, utf8()), &["".to_string()]).is_ok());
+    }
+
+    #[test]
+    fn check_codec_laws_should_reject_lossy_codecs() {
+        let codec = xmap(uint8, |v| v & 0xfe, |v| *v);
+        assert_eq!(
+            check_codec_laws(codec, &[2, 3]).unwrap_err().message(),
+            "Codec laws violated for value 3: Value 2 produced by decode does not match original value 3"
+        );
+    }
+
+    #[test]
+    fn check_codec_laws_should_reject_codecs_that_do_not_consume_their_encoding() {
+        // Codec that writes a trailing padding byte but does not skip it when decoding
+        struct Padded;
+
+        impl Codec for Padded {
+            type Value = u8;
+
+            fn encode(&self, value: &u8) -> EncodeResult {
+                Ok(byte_vector!(*value, 0))
+            }
+
+            fn decode(&self, bv: &ByteVector) -> DecodeResult<u8> {
+                uint8.decode(bv)
+            }
+        }
+
+        assert_eq!(
+            check_codec_laws(Padded, &[7]).unwrap_err().message(),
+            "Codec laws violated for value 7: decode left 1 of the encoded bytes unconsumed"
+        );
+    }
+
+    #[test]
+    fn assert_decode_fails_should_return_the_error() {
+        let e = assert_decode_fails(uint16, &byte_vector!(1));
+        assert!(e.message().contains("exceeds vector length"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected decoding of 0102 to fail but got 258")]
+    fn assert_decode_fails_should_panic_on_success() {
+        assert_decode_fails(uint16, &byte_vector!(1, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Encoded bytes 0001 do not match expected bytes 0100")]
+    fn assert_round_trip_should_panic_on_unexpected_bytes() {
+        assert_round_trip(uint16, &1, &Some(byte_vector!(1, 0)));
+    }
+}
@@ -0,0 +1,183 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+//! Adapters for `tokio_util::codec` framed streams, available with the `tokio` feature.
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::codec::Codec;
+use crate::error::Error;
+use crate::stream;
+
+/// Adapts a `Codec` for use as a `tokio_util::codec::Encoder` and `Decoder`, so that values can be
+/// read from and written to `Framed` streams.
+///
+/// The codec must determine the length of each value from the input itself, for example by
+/// decoding a length prefix (`variable_size_bytes`) or by consuming a fixed number of bytes.
+/// Codecs that consume all remaining input (such as `utf8()` on its own) are not suitable, since
+/// they would decode whatever partial input happens to be buffered.
+///
+/// When decoding, a value is produced as soon as the buffered input contains all of its bytes;
+/// until then, the input is retained and the decoder waits for more (see
+/// `Error::is_insufficient_data`).  Any other decoding error is returned to the stream, as is an
+/// error if a value is decoded without consuming any input.
+///
+/// # Examples
+///
+/// ```
+/// use bytes::BytesMut;
+/// use rcodec::codec::*;
+/// use rcodec::tokio::RcodecFramed;
+/// use tokio_util::codec::{Decoder, Encoder};
+///
+/// let mut framed = RcodecFramed::new(variable_size_bytes(uint16, utf8()));
+/// let mut buf = BytesMut::new();
+/// framed.encode("hello".to_string(), &mut buf).unwrap();
+/// assert_eq!(&buf[..], b"\x00\x05hello");
+/// assert_eq!(framed.decode(&mut buf).unwrap(), Some("hello".to_string()));
+/// ```
+pub struct RcodecFramed<C> {
+    /// The codec used to encode and decode values.
+    codec: C,
+
+    /// The maximum number of bytes to buffer while waiting for a complete value, if any.
+    max_frame_length: Option<usize>,
+}
+
+impl<C: Codec> RcodecFramed<C> {
+    /// Returns a new adapter that encodes and decodes values using `codec`.
+    pub fn new(codec: C) -> RcodecFramed<C> {
+        RcodecFramed {
+            codec,
+            max_frame_length: None,
+        }
+    }
+
+    /// Returns this adapter with a limit on the number of bytes that may be buffered while waiting
+    /// for a complete value; decoding fails if the buffered input exceeds `len` bytes without
+    /// containing a complete value.
+    pub fn with_max_frame_length(self, len: usize) -> RcodecFramed<C> {
+        RcodecFramed {
+            max_frame_length: Some(len),
+            ..self
+        }
+    }
+
+    /// Returns the codec used to encode and decode values.
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+}
+
+impl<C: Codec> Encoder<C::Value> for RcodecFramed<C> {
+    type Error = Error;
+
+    fn encode(&mut self, item: C::Value, dst: &mut BytesMut) -> Result<(), Error> {
+        // Discard any partial encoding so that the stream is not left with a truncated value
+        let start = dst.len();
+        let mut writer = dst.writer();
+        match self.codec.encode_to(&item, &mut writer) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                writer.into_inner().truncate(start);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<C: Codec> Decoder for RcodecFramed<C> {
+    type Item = C::Value;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<C::Value>, Error> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+        match self.codec.decode_slice(src) {
+            // Fail rather than letting the stream yield the same value forever
+            Ok((_, 0)) => Err(stream::no_input_consumed()),
+            Ok((value, consumed)) => {
+                src.advance(consumed);
+                Ok(Some(value))
+            }
+            Err(e) if e.is_insufficient_data() => match self.max_frame_length {
+                Some(max) if src.len() > max => Err(Error::new(format!(
+                    "Buffered input of {} bytes exceeds maximum frame length of {} bytes",
+                    src.len(),
+                    max
+                ))),
+                _ => Ok(None),
+            },
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::*;
+
+    #[test]
+    fn an_rcodec_framed_decoder_should_wait_for_complete_values() {
+        let mut framed = RcodecFramed::new(variable_size_bytes(uint8, utf8()));
+        let mut src = BytesMut::new();
+        assert_eq!(framed.decode(&mut src).unwrap(), None);
+
+        src.extend_from_slice(&[2, b'h', b'i', 3, b'y']);
+        assert_eq!(framed.decode(&mut src).unwrap(), Some("hi".to_string()));
+        assert_eq!(framed.decode(&mut src).unwrap(), None);
+        assert_eq!(&src[..], &[3, b'y']);
+
+        src.extend_from_slice(b"ou");
+        assert_eq!(framed.decode(&mut src).unwrap(), Some("you".to_string()));
+        assert!(src.is_empty());
+
+        src.extend_from_slice(&[1, 0xff]);
+        assert!(framed.decode(&mut src).is_err());
+    }
+
+    #[test]
+    fn an_rcodec_framed_decoder_should_fail_on_codecs_that_consume_no_input() {
+        let mut framed = RcodecFramed::new(provide(7u8));
+        let mut src = BytesMut::from(&[1u8][..]);
+        assert_eq!(
+            framed.decode(&mut src).unwrap_err().message(),
+            "Decoded value did not consume any input"
+        );
+        assert_eq!(&src[..], &[1]);
+    }
+
+    #[test]
+    fn an_rcodec_framed_decoder_should_enforce_the_maximum_frame_length() {
+        let mut framed = RcodecFramed::new(variable_size_bytes(uint16, identity_bytes()))
+            .with_max_frame_length(4);
+        let mut src = BytesMut::from(&[0, 10, 1][..]);
+        assert_eq!(framed.decode(&mut src).unwrap(), None);
+        src.extend_from_slice(&[2]);
+        assert_eq!(framed.decode(&mut src).unwrap(), None);
+        src.extend_from_slice(&[3]);
+        assert_eq!(
+            framed.decode(&mut src).unwrap_err().message(),
+            "Buffered input of 5 bytes exceeds maximum frame length of 4 bytes"
+        );
+    }
+
+    #[test]
+    fn an_rcodec_framed_encoder_should_discard_partial_encodings() {
+        let mut framed = RcodecFramed::new((uint8, variable_size_bytes(uint8, utf8())));
+        let mut dst = BytesMut::new();
+        framed.encode((1, "a".to_string()), &mut dst).unwrap();
+        assert_eq!(&dst[..], &[1, 1, b'a']);
+
+        assert!(framed.encode((2, "x".repeat(256)), &mut dst).is_err());
+        assert_eq!(&dst[..], &[1, 1, b'a']);
+    }
+}
@@ -0,0 +1,265 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+//! Decoding that collects recoverable validation failures instead of stopping at the first one,
+//! for tools (such as file repair or forensic tools) that need to report everything that is wrong
+//! with their input.
+
+use std::cell::{Cell, RefCell};
+
+use crate::byte_vector::ByteVector;
+use crate::codec::{Codec, DecodeResult};
+use crate::cursor;
+use crate::error::Error;
+
+thread_local! {
+    /// The failures collected so far by `decode_collecting_errors`, or `None` if failures are not
+    /// being collected on this thread.
+    static COLLECTED: RefCell<Option<Vec<Error>>> = const { RefCell::new(None) };
+
+    /// Whether failures are being collected on this thread, which avoids borrowing `COLLECTED`
+    /// on the common path.
+    static COLLECTING: Cell<bool> = const { Cell::new(false) };
+
+    /// The context identifiers of the codecs currently decoding, outermost first, while failures
+    /// are being collected.
+    static CONTEXT: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The outcome of `decode_collecting_errors`.
+#[derive(Debug)]
+pub struct ValidationReport<T> {
+    /// The result of decoding, in which values that failed validation are replaced by
+    /// placeholders.  This is an error only if decoding could not continue.
+    pub result: DecodeResult<T>,
+
+    /// The validation failures encountered while decoding, in the order in which they occurred.
+    pub errors: Vec<Error>,
+}
+
+impl<T> ValidationReport<T> {
+    /// Returns true if decoding succeeded without any validation failures.
+    pub fn is_valid(&self) -> bool {
+        self.result.is_ok() && self.errors.is_empty()
+    }
+}
+
+/// Decodes a value from `bv` using `codec`, collecting recoverable validation failures while
+/// decoding continues with placeholder values.
+///
+/// The recoverable failures are those of `validated` and `bounded` values (for which the rejected
+/// value itself is the placeholder), unknown raw values of `mapped_enum_or` (for which its given
+/// placeholder is used), and checksum mismatches of `checksummed` values.  Each collected error
+/// has the context of the enclosing fields and the offset of the failing value within `bv`.
+/// Failures within the alternatives of speculative codecs (such as `choice` and `fallback`) are
+/// not collected, so that they are still able to reject the input.
+///
+/// # Examples
+///
+/// ```
+/// use pl_hlist::*;
+/// use rcodec::byte_vector;
+/// use rcodec::codec::*;
+/// use rcodec::hcodec;
+/// use rcodec::validation::decode_collecting_errors;
+///
+/// # fn main() {
+/// let codec = hcodec!(
+///     { "month" => bounded_uint8(1, 12) } ::
+///     { "day"   => bounded_uint8(1, 31) }
+/// );
+/// let report = decode_collecting_errors(&codec, &byte_vector!(13, 32));
+/// assert_eq!(report.result.unwrap().value, hlist!(13, 32));
+/// assert_eq!(report.errors.len(), 2);
+/// assert_eq!(
+///     report.errors[1].message(),
+///     "day: Value 32 is out of range 1..=31 (at offset 1)"
+/// );
+/// # }
+/// ```
+pub fn decode_collecting_errors<C: Codec>(
+    codec: &C,
+    bv: &ByteVector,
+) -> ValidationReport<C::Value> {
+    // Restore the previous state even if decoding panics
+    struct Restore(Option<Vec<Error>>, bool, Vec<&'static str>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            COLLECTED.with(|c| *c.borrow_mut() = self.0.take());
+            COLLECTING.with(|c| c.set(self.1));
+            CONTEXT.with(|c| *c.borrow_mut() = std::mem::take(&mut self.2));
+        }
+    }
+
+    let restore = Restore(
+        COLLECTED.with(|c| c.replace(Some(Vec::new()))),
+        COLLECTING.with(|c| c.replace(true)),
+        CONTEXT.with(|c| c.take()),
+    );
+    let result = cursor::tracking(bv.length(), || codec.decode(bv));
+    let errors = COLLECTED
+        .with(|c| c.borrow_mut().take())
+        .unwrap_or_default();
+    drop(restore);
+    ValidationReport { result, errors }
+}
+
+/// Records `error` and returns `placeholder` if failures are being collected, or returns `error`
+/// otherwise.  The failing value begins with `remaining` bytes left in the current region.
+pub(crate) fn recover<T>(error: Error, remaining: usize, placeholder: T) -> Result<T, Error> {
+    if !COLLECTING.with(Cell::get) {
+        return Err(error);
+    }
+    let error = CONTEXT.with(|context| {
+        context
+            .borrow()
+            .iter()
+            .rev()
+            .fold(error, |e, context| e.push_context(context))
+    });
+    let error = match cursor::offset_of(remaining) {
+        Some(offset) => error.at_offset(offset),
+        None => error,
+    };
+    COLLECTED.with(|c| {
+        if let Some(errors) = c.borrow_mut().as_mut() {
+            errors.push(error);
+        }
+    });
+    Ok(placeholder)
+}
+
+/// Calls `f` to decode a value with the given context identifier, which is applied to any
+/// failures collected while doing so.
+pub(crate) fn within_context<R, F>(context: &'static str, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    if !COLLECTING.with(Cell::get) {
+        return f();
+    }
+
+    // Pop the context even if `f` panics
+    struct Pop;
+    impl Drop for Pop {
+        fn drop(&mut self) {
+            CONTEXT.with(|c| c.borrow_mut().pop());
+        }
+    }
+
+    CONTEXT.with(|c| c.borrow_mut().push(context));
+    let _pop = Pop;
+    f()
+}
+
+/// Calls `f` to decode a value speculatively, with the collection of failures suspended so that
+/// validation failures cause `f` to fail.
+pub(crate) fn speculatively<R, F>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    if !COLLECTING.with(Cell::get) {
+        return f();
+    }
+
+    // Resume collecting even if `f` panics
+    struct Resume;
+    impl Drop for Resume {
+        fn drop(&mut self) {
+            COLLECTING.with(|c| c.set(true));
+        }
+    }
+
+    COLLECTING.with(|c| c.set(false));
+    let _resume = Resume;
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::*;
+    use pl_hlist::*;
+    use std::collections::HashMap;
+
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    enum Color {
+        Red,
+        Green,
+        Unknown,
+    }
+
+    record_struct!(Pixel, color: Color, alpha: u8, tail: HCons<u8, HNil>);
+
+    #[test]
+    fn decode_collecting_errors_should_continue_past_validation_failures() {
+        let colors: HashMap<u8, Color> = vec![(1, Color::Red), (2, Color::Green)]
+            .into_iter()
+            .collect();
+        let codec = struct_codec!(
+            Pixel from
+            { "color" => mapped_enum_or(uint8, colors, Color::Unknown)  } ::
+            { "alpha" => validated(uint8, |a| *a != 0, "alpha is zero") } ::
+            { "tail"  => variable_size_bytes(uint8, hcodec!(
+                { "level" => bounded_uint8(0, 9) }
+            )) }
+        );
+
+        let report = decode_collecting_errors(&codec, &byte_vector!(7, 0, 1, 10));
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.result.unwrap().value,
+            Pixel {
+                color: Color::Unknown,
+                alpha: 0,
+                tail: hlist!(10),
+            }
+        );
+        let messages: Vec<_> = report.errors.iter().map(|e| e.message()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Pixel/color: Unknown tag 7 (at offset 0)",
+                "Pixel/alpha: Validation failed: alpha is zero (at offset 1)",
+                "Pixel/tail/level: Value 10 is out of range 0..=9 (at offset 3)",
+            ]
+        );
+
+        // Outside of collecting mode, the first failure is returned
+        assert_eq!(
+            codec
+                .decode(&byte_vector!(7, 0, 1, 10))
+                .unwrap_err()
+                .message(),
+            "Pixel/color: Unknown tag 7"
+        );
+
+        let report = decode_collecting_errors(&codec, &byte_vector!(1, 2, 1, 3));
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn decode_collecting_errors_should_report_unrecoverable_failures() {
+        let codec = hcodec!({ "a" => bounded_uint8(0, 1) } :: { "b" => uint16 });
+        let report = decode_collecting_errors(&codec, &byte_vector!(2, 1));
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.result.unwrap_err().message().starts_with("b: "));
+    }
+
+    #[test]
+    fn decode_collecting_errors_should_not_collect_failures_of_speculative_codecs() {
+        let codec = choice(vec![bounded_uint8(0, 1), bounded_uint8(5, 6)]);
+        let report = decode_collecting_errors(&codec, &byte_vector!(5));
+        assert!(report.is_valid());
+        assert_eq!(report.result.unwrap().value, 5);
+
+        let report = decode_collecting_errors(&codec, &byte_vector!(3));
+        assert!(report.errors.is_empty());
+        assert!(report.result.is_err());
+    }
+}
@@ -0,0 +1,340 @@
+//
+// Copyright (c) 2015-2019 Plausible Labs Cooperative, Inc.
+// All rights reserved.
+//
+// This API is based on the design of Michael Pilquist and Paul Chiusano's
+// Scala scodec library: https://github.com/scodec/scodec/
+//
+
+//! Codecs for compressed regions, available with the `zlib` feature.
+
+use std::io::{BufRead, Read, Write};
+
+use flate2::bufread::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
+use flate2::Compression;
+
+use crate::byte_vector;
+use crate::byte_vector::ByteVector;
+use crate::codec::{Codec, CodecDescription, DecodeResult, DecoderResult, EncodeResult, SizeBound};
+use crate::cursor;
+use crate::error::Error;
+use crate::limits;
+
+/// Codec for values that are compressed using the zlib format (RFC 1950).
+///
+///   - Encodes using the given codec and then compresses the encoded bytes.
+///   - Decodes by decompressing a single zlib stream and then decoding the decompressed bytes
+///     using the given codec.  Any decompressed bytes not consumed by the given codec are
+///     discarded, and any bytes following the compressed stream are returned as the remainder.
+///     Fails if the decompressed length exceeds the `max_alloc` decode limit (see `limits`).
+#[inline(always)]
+pub fn zlib<T, C>(codec: C) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    CompressedCodec {
+        codec,
+        format: Format::Zlib,
+    }
+}
+
+/// Codec for values that are compressed using the raw deflate format (RFC 1951); see `zlib` for
+/// details.
+#[inline(always)]
+pub fn deflate<T, C>(codec: C) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    CompressedCodec {
+        codec,
+        format: Format::Deflate,
+    }
+}
+
+/// Codec for values that are compressed using the gzip format (RFC 1952); see `zlib` for details.
+#[inline(always)]
+pub fn gzip<T, C>(codec: C) -> impl Codec<Value = T>
+where
+    C: Codec<Value = T>,
+{
+    CompressedCodec {
+        codec,
+        format: Format::Gzip,
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Zlib,
+    Deflate,
+    Gzip,
+}
+
+struct CompressedCodec<C> {
+    codec: C,
+    format: Format,
+}
+
+impl<C> CompressedCodec<C> {
+    /// Compresses the given bytes.
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        fn finish<W: Write>(mut encoder: W, bytes: &[u8]) -> std::io::Result<W> {
+            encoder.write_all(bytes).map(|_| encoder)
+        }
+
+        let level = Compression::default();
+        let result = match self.format {
+            Format::Zlib => {
+                finish(ZlibEncoder::new(Vec::new(), level), bytes).and_then(|e| e.finish())
+            }
+            Format::Deflate => {
+                finish(DeflateEncoder::new(Vec::new(), level), bytes).and_then(|e| e.finish())
+            }
+            Format::Gzip => {
+                finish(GzEncoder::new(Vec::new(), level), bytes).and_then(|e| e.finish())
+            }
+        };
+        result.map_err(|e| Error::new(format!("Compression failed: {}", e)))
+    }
+
+    /// Decompresses a single stream from the start of the given input, returning the decompressed
+    /// bytes along with the input positioned after the compressed stream.  Fails if the
+    /// decompressed length exceeds the current allocation limit.
+    fn decompress<R: BufRead>(&self, input: R) -> Result<(Vec<u8>, R), Error> {
+        fn read_all<D: Read>(mut decoder: D, max_alloc: usize) -> std::io::Result<(Vec<u8>, D)> {
+            // Read at most one byte more than the limit, so that overlong output can be detected
+            // without decompressing the rest of the stream
+            let mut decompressed = Vec::new();
+            decoder
+                .by_ref()
+                .take((max_alloc as u64).saturating_add(1))
+                .read_to_end(&mut decompressed)
+                .map(|_| (decompressed, decoder))
+        }
+
+        let max_alloc = limits::current_limits().max_alloc;
+        let result = match self.format {
+            Format::Zlib => {
+                read_all(ZlibDecoder::new(input), max_alloc).map(|(d, r)| (d, r.into_inner()))
+            }
+            Format::Deflate => {
+                read_all(DeflateDecoder::new(input), max_alloc).map(|(d, r)| (d, r.into_inner()))
+            }
+            Format::Gzip => {
+                read_all(GzDecoder::new(input), max_alloc).map(|(d, r)| (d, r.into_inner()))
+            }
+        };
+        let (decompressed, input) =
+            result.map_err(|e| Error::new(format!("Decompression failed: {}", e)))?;
+        if decompressed.len() > max_alloc {
+            return Err(Error::new(format!(
+                "Decompressed length exceeds decode limit of {} bytes",
+                max_alloc
+            )));
+        }
+        Ok((decompressed, input))
+    }
+}
+
+/// The size of the buffer used when reading compressed data from non-contiguous storage.
+const READ_CHUNK_SIZE: usize = 8192;
+
+/// Adapts a `ByteVector` for use as a `BufRead` source, reading directly from contiguous storage
+/// and through a fixed-size buffer otherwise, so that the compressed input is never copied in full.
+struct ByteVectorReader<'a> {
+    /// The vector being read.
+    bv: &'a ByteVector,
+
+    /// The number of bytes consumed so far.
+    pos: usize,
+
+    /// The buffer used for non-contiguous storage.
+    buf: Vec<u8>,
+
+    /// The offset in `bv` of the first byte in `buf`.
+    buf_start: usize,
+
+    /// The number of valid bytes in `buf`.
+    buf_len: usize,
+}
+
+impl<'a> ByteVectorReader<'a> {
+    fn new(bv: &'a ByteVector) -> ByteVectorReader<'a> {
+        ByteVectorReader {
+            bv,
+            pos: 0,
+            buf: Vec::new(),
+            buf_start: 0,
+            buf_len: 0,
+        }
+    }
+}
+
+impl<'a> Read for ByteVectorReader<'a> {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let count = std::cmp::min(available.len(), out.len());
+        out[..count].copy_from_slice(&available[..count]);
+        self.consume(count);
+        Ok(count)
+    }
+}
+
+impl<'a> BufRead for ByteVectorReader<'a> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if let Some(bytes) = self.bv.as_slice() {
+            return Ok(&bytes[self.pos..]);
+        }
+
+        // Refill the buffer once all of its contents have been consumed
+        if self.pos >= self.buf_start + self.buf_len {
+            let count = std::cmp::min(READ_CHUNK_SIZE, self.bv.length() - self.pos);
+            self.buf.resize(count, 0);
+            self.bv.read(&mut self.buf[..count], self.pos, count)?;
+            self.buf_start = self.pos;
+            self.buf_len = count;
+        }
+        Ok(&self.buf[self.pos - self.buf_start..self.buf_len])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt;
+    }
+}
+
+impl<T, C> Codec for CompressedCodec<C>
+where
+    C: Codec<Value = T>,
+{
+    type Value = T;
+
+    fn size_bound(&self) -> SizeBound {
+        SizeBound::unknown()
+    }
+
+    fn describe(&self) -> CodecDescription {
+        let name = match self.format {
+            Format::Zlib => "zlib",
+            Format::Deflate => "deflate",
+            Format::Gzip => "gzip",
+        };
+        CodecDescription::new(name, self.size_bound()).with_child(self.codec.describe())
+    }
+
+    fn encode(&self, value: &T) -> EncodeResult {
+        let mut compressed = Vec::new();
+        self.encode_into(value, &mut compressed)
+            .map(|_| byte_vector::from_vec(compressed))
+    }
+
+    fn encode_into(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        let mut encoded = Vec::new();
+        self.codec.encode_into(value, &mut encoded)?;
+        self.compress(&encoded)
+            .map(|compressed| out.extend_from_slice(&compressed))
+    }
+
+    fn decode(&self, bv: &ByteVector) -> DecodeResult<T> {
+        let (decompressed, reader) = self.decompress(ByteVectorReader::new(bv))?;
+        let consumed = reader.pos;
+        let decoded = cursor::detached(|| self.codec.decode(&byte_vector::from_vec(decompressed)))?;
+        bv.drop(consumed).map(|remainder| DecoderResult {
+            value: decoded.value,
+            remainder,
+        })
+    }
+
+    fn decode_slice(&self, input: &[u8]) -> Result<(T, usize), Error> {
+        let (decompressed, unread) = self.decompress(input)?;
+        let consumed = input.len() - unread.len();
+        let (value, _) = cursor::detached(|| self.codec.decode_slice(&decompressed))?;
+        Ok((value, consumed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::*;
+    use crate::limits::DecodeLimits;
+
+    #[test]
+    fn compressed_codecs_should_round_trip() {
+        let input = "hello ".repeat(100);
+        for codec in [
+            Box::new(zlib(utf8())) as Box<dyn Codec<Value = String>>,
+            Box::new(deflate(utf8())),
+            Box::new(gzip(utf8())),
+        ] {
+            let encoded = codec.encode(&input).unwrap();
+            assert!(encoded.length() < input.len());
+            let decoded = codec.decode(&encoded).unwrap();
+            assert_eq!(decoded.value, input);
+            assert_eq!(decoded.remainder.length(), 0);
+        }
+    }
+
+    #[test]
+    fn decoding_with_compressed_codec_should_return_trailing_bytes_as_remainder() {
+        let codec = zlib(uint32);
+        let encoded = codec.encode(&0x01020304).unwrap();
+        let input = byte_vector::append(&encoded, &byte_vector!(7, 8));
+        let decoded = codec.decode(&input).unwrap();
+        assert_eq!(decoded.value, 0x01020304);
+        assert_eq!(decoded.remainder, byte_vector!(7, 8));
+    }
+
+    #[test]
+    fn decoding_with_compressed_codec_should_fail_on_invalid_data() {
+        let codec = zlib(uint8);
+        assert!(codec
+            .decode(&byte_vector!(1, 2, 3))
+            .unwrap_err()
+            .message()
+            .starts_with("Decompression failed"));
+    }
+
+    #[test]
+    fn decoding_with_compressed_codec_should_read_from_non_contiguous_storage() {
+        let codec = deflate(utf8());
+        let input = "hello ".repeat(5000);
+        let encoded = codec.encode(&input).unwrap().to_vec().unwrap();
+        let (lhs, rhs) = encoded.split_at(encoded.len() / 2);
+        let bv = byte_vector::append(
+            &byte_vector::from_slice_copy(lhs),
+            &byte_vector::append(&byte_vector::from_slice_copy(rhs), &byte_vector!(7, 8)),
+        );
+        assert!(bv.as_slice().is_none());
+        let decoded = codec.decode(&bv).unwrap();
+        assert_eq!(decoded.value, input);
+        assert_eq!(decoded.remainder, byte_vector!(7, 8));
+    }
+
+    #[test]
+    fn decoding_with_compressed_codec_should_enforce_the_allocation_limit() {
+        let codec = gzip(bytes(4096));
+        let encoded = codec.encode(&byte_vector::from_vec(vec![0; 4096])).unwrap();
+        let limits = DecodeLimits {
+            max_alloc: 1024,
+            ..DecodeLimits::unlimited()
+        };
+        assert_eq!(
+            limits::with_limits(limits, || codec.decode(&encoded))
+                .unwrap_err()
+                .message(),
+            "Decompressed length exceeds decode limit of 1024 bytes"
+        );
+        assert!(
+            limits::with_limits(limits, || codec.decode_slice(&encoded.to_vec().unwrap())).is_err()
+        );
+        assert!(codec.decode(&encoded).is_ok());
+    }
+
+    #[test]
+    fn compressed_codec_should_describe_inner_codec() {
+        let description = gzip(uint32).describe();
+        assert_eq!(description.name, "gzip");
+        assert_eq!(description.children[0].name, "uint32");
+    }
+}
@@ -3,51 +3,12 @@
 // All rights reserved.
 //
 
-use std::fmt::Debug;
-
 use pl_hlist::*;
 
-use rcodec::byte_vector::ByteVector;
 use rcodec::codec::*;
-use rcodec::error::Error;
+use rcodec::testing::assert_round_trip;
 use rcodec::{byte_vector, hcodec, record_struct, struct_codec};
 
-fn assert_round_trip<T, C>(codec: C, value: &T, raw_bytes: &Option<ByteVector>)
-where
-    T: 'static + Eq + Debug,
-    C: Codec<Value = T>,
-{
-    // Encode
-    let result = codec.encode(value).and_then(|encoded| {
-        // Compare encoded bytes to the expected bytes, if provided
-        let compare_result = match *raw_bytes {
-            Some(ref expected) => {
-                if encoded != *expected {
-                    Err(Error::new(format!(
-                        "Encoded bytes {:?} do not match expected bytes {:?}",
-                        encoded, *expected
-                    )))
-                } else {
-                    Ok(())
-                }
-            }
-            None => Ok(()),
-        };
-        if let Err(error) = compare_result {
-            return Err(error);
-        }
-
-        // Decode and drop the remainder
-        codec.decode(&encoded).map(|decoded| decoded.value)
-    });
-
-    // Verify result
-    match result {
-        Ok(decoded) => assert_eq!(decoded, *value),
-        Err(e) => panic!("Round-trip encoding failed: {}", e.message()),
-    }
-}
-
 #[test]
 fn a_u8_value_should_round_trip() {
     assert_round_trip(uint8, &7u8, &Some(byte_vector!(7)));
@@ -156,6 +117,118 @@ record_struct!(
     data: Vec<u8>
 );
 
+mod records {
+    use pl_hlist::*;
+    use rcodec::record_struct;
+
+    record_struct!(
+        /// Record with public fields and additional derives.
+        #[derive(Hash, PartialOrd, Ord)]
+        TestPublicRecord,
+        /// The record identifier.
+        pub id: u16,
+        pub flags: u8,
+    );
+}
+
+#[test]
+fn a_record_struct_with_public_fields_should_be_usable_from_other_modules() {
+    use records::TestPublicRecord;
+    use std::collections::HashSet;
+
+    let codec = || struct_codec!(TestPublicRecord from { uint16 } :: { uint8 });
+    let record = TestPublicRecord {
+        id: 0x0102,
+        flags: 3,
+    };
+    assert_round_trip(codec(), &record, &Some(byte_vector!(1, 2, 3)));
+
+    let smaller = TestPublicRecord { id: 1, flags: 9 };
+    assert!(smaller < record);
+    let set: HashSet<_> = vec![record.clone(), record, smaller].into_iter().collect();
+    assert_eq!(set.len(), 2);
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, PartialEq, Eq, Clone, HListSupport, DefaultCodec)]
+struct TestDerivedRecord {
+    version: TestDerivedVersion,
+    name: String,
+    values: Vec<u16>,
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, PartialEq, Eq, Clone, HListSupport, DefaultCodec)]
+struct TestDerivedVersion {
+    compat_version: u8,
+    feature_version: u8,
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, PartialEq, Eq, Clone, DefaultCodec)]
+struct TestDerivedPair<T> {
+    first: T,
+    second: T,
+}
+
+#[cfg(feature = "derive")]
+impl<T: Clone> FromHList<HCons<T, HCons<T, HNil>>> for TestDerivedPair<T> {
+    fn from_hlist(hlist: HCons<T, HCons<T, HNil>>) -> Self {
+        let HCons(first, HCons(second, HNil)) = hlist;
+        TestDerivedPair { first, second }
+    }
+}
+
+#[cfg(feature = "derive")]
+impl<T: Clone> ToHList<HCons<T, HCons<T, HNil>>> for TestDerivedPair<T> {
+    fn to_hlist(&self) -> HCons<T, HCons<T, HNil>> {
+        HCons(self.first.clone(), HCons(self.second.clone(), HNil))
+    }
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn a_derived_default_codec_should_encode_fields_in_order() {
+    let record = TestDerivedRecord {
+        version: TestDerivedVersion {
+            compat_version: 1,
+            feature_version: 2,
+        },
+        name: "ab".to_string(),
+        values: vec![0x0304],
+    };
+    assert_round_trip(
+        TestDerivedRecord::codec(),
+        &record,
+        &Some(byte_vector!(
+            0x01, 0x02, // version
+            0x00, 0x00, 0x00, 0x02, b'a', b'b', // name
+            0x00, 0x00, 0x00, 0x01, 0x03, 0x04 // values
+        )),
+    );
+
+    // Errors should include the name of the field
+    let input = byte_vector!(0x01, 0x02, 0x00, 0x00, 0x00, 0x02, b'a');
+    assert!(TestDerivedRecord::codec()
+        .decode(&input)
+        .unwrap_err()
+        .message()
+        .starts_with("name: "));
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn a_derived_default_codec_should_support_generic_structs() {
+    assert_round_trip(
+        TestDerivedPair::<u16>::codec(),
+        &TestDerivedPair {
+            first: 0x0102,
+            second: 0x0304,
+        },
+        &Some(byte_vector!(0x01, 0x02, 0x03, 0x04)),
+    );
+}
+
 #[test]
 fn a_complex_codec_should_round_trip() {
     const FILE_HEADER_SIZE: u8 = 6;